@@ -0,0 +1,264 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+
+use crate::{
+    auth::AuthFailure,
+    db::{
+        adjustment::{ReasonCode, Refund},
+        payment::Payment,
+        OperationError,
+    },
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+pub mod refund;
+pub mod refund_list;
+pub mod void;
+
+/// Parses the reason code out of the request.
+fn parse_reason(reason: &str) -> Result<ReasonCode, AdjustmentFailure> {
+    ReasonCode::parse(reason).ok_or_else(|| {
+        AdjustmentFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: String::from(
+                "reason must be one of entry_error, guest_cancelled, kitchen_error, quality_complaint, overcharge or other",
+            ),
+        })
+    })
+}
+
+/// Checks the free text note which may come along the reason code.
+fn validate_note(note: &Option<String>) -> Result<(), AdjustmentFailure> {
+    if note.as_ref().is_some_and(|n| n.len() > 300) {
+        return Err(AdjustmentFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: String::from("note must be at most 300 characters"),
+        }));
+    }
+    Ok(())
+}
+
+/// performs simple request validation to make check some bounds.
+fn validate_table_number(table_number: u32) -> Result<(), AdjustmentFailure> {
+    if !(1..=100).contains(&table_number) {
+        return Err(AdjustmentFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: String::from("table_number must be in range of 1 to 100"),
+        }));
+    }
+    Ok(())
+}
+
+/// Takes the amount to refund from the Payments, newest first, so money goes back the way it came in last.
+/// Returns the Payments along with what is refunded from each of them, the leftover is not refundable.
+fn allocate_refund(amount: i64, payments: &[Payment], refunds: &[Refund]) -> Vec<(Payment, i64)> {
+    let mut left = amount;
+    let mut allocation = vec![];
+    for payment in payments.iter().rev() {
+        if left <= 0 {
+            break;
+        }
+        let refunded: i64 = refunds
+            .iter()
+            .filter(|refund| refund.payment_id == payment.payment_id)
+            .map(|refund| refund.amount)
+            .sum();
+        let take = left.min(payment.amount - refunded);
+        if take > 0 {
+            allocation.push((payment.clone(), take));
+            left -= take;
+        }
+    }
+    allocation
+}
+
+#[derive(Serialize, Deserialize)]
+struct RefundData {
+    refund_id: i64,
+    session_id: i64,
+    payment_id: i64,
+    order_id: Option<i64>,
+    method: String,
+    amount: i64,
+    reason: String,
+    note: Option<String>,
+    reference: Option<String>,
+    staff_id: Option<i64>,
+    created_at: String,
+}
+
+impl From<Refund> for RefundData {
+    fn from(refund: Refund) -> Self {
+        Self {
+            refund_id: refund.refund_id,
+            session_id: refund.session_id,
+            payment_id: refund.payment_id,
+            order_id: refund.order_id,
+            method: refund.method.as_str().to_string(),
+            amount: refund.amount,
+            reason: refund.reason.as_str().to_string(),
+            note: refund.note,
+            reference: refund.reference,
+            staff_id: refund.staff_id,
+            created_at: refund
+                .created_at
+                .format(&Rfc3339)
+                .unwrap_or("---".to_string()),
+        }
+    }
+}
+
+/// How much of what was paid has been given back.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct RefundSummary {
+    paid: i64,
+    refunded: i64,
+    refundable: i64,
+}
+
+impl RefundSummary {
+    fn new(payments: &[Payment], refunds: &[Refund]) -> Self {
+        let paid = payments.iter().map(|p| p.amount).sum();
+        let refunded = refunds.iter().map(|r| r.amount).sum();
+        Self {
+            paid,
+            refunded,
+            refundable: paid - refunded,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    table_number: u32,
+}
+
+#[derive(Debug)]
+enum AdjustmentFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    Conflict(BadRequestBody),
+    Declined(BadRequestBody),
+    GatewayUnavailable(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for AdjustmentFailure {
+    fn from(e: AuthFailure) -> Self {
+        AdjustmentFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for AdjustmentFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to void or refund")
+    }
+}
+
+impl ResponseError for AdjustmentFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            AdjustmentFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            AdjustmentFailure::Auth(e) => e.status_code(),
+            AdjustmentFailure::Conflict(_) => StatusCode::CONFLICT,
+            AdjustmentFailure::Declined(_) => StatusCode::PAYMENT_REQUIRED,
+            AdjustmentFailure::GatewayUnavailable(_) => StatusCode::BAD_GATEWAY,
+            AdjustmentFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            AdjustmentFailure::InvalidInput(r)
+            | AdjustmentFailure::Conflict(r)
+            | AdjustmentFailure::Declined(r)
+            | AdjustmentFailure::GatewayUnavailable(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            AdjustmentFailure::Auth(e) => e.error_response(),
+            AdjustmentFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::payment::PaymentMethod;
+
+    fn payment(payment_id: i64, method: PaymentMethod, amount: i64) -> Payment {
+        Payment {
+            payment_id,
+            ..Payment::new(7, 3, method, amount, None)
+        }
+    }
+
+    #[test]
+    fn test_allocate_refund_newest_first() {
+        let payments = vec![
+            payment(1, PaymentMethod::Cash, 50_000),
+            payment(2, PaymentMethod::Card, 30_000),
+        ];
+        let refunds = vec![Refund::new(
+            7,
+            2,
+            PaymentMethod::Card,
+            10_000,
+            ReasonCode::Overcharge,
+        )];
+
+        let allocation = allocate_refund(45_000, &payments, &refunds);
+
+        let allocation: Vec<(i64, i64)> = allocation
+            .into_iter()
+            .map(|(payment, amount)| (payment.payment_id, amount))
+            .collect();
+        assert_eq!(allocation, vec![(2, 20_000), (1, 25_000)]);
+    }
+
+    #[test]
+    fn test_allocate_refund_more_than_paid() {
+        let payments = vec![payment(1, PaymentMethod::Cash, 50_000)];
+
+        let allocation = allocate_refund(80_000, &payments, &[]);
+
+        assert_eq!(allocation.len(), 1);
+        assert_eq!(allocation[0].1, 50_000);
+        assert!(allocate_refund(10_000, &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_summary() {
+        let payments = vec![
+            payment(1, PaymentMethod::Cash, 50_000),
+            payment(2, PaymentMethod::Qris, 30_000),
+        ];
+        let refunds = vec![Refund::new(
+            7,
+            1,
+            PaymentMethod::Cash,
+            12_000,
+            ReasonCode::QualityComplaint,
+        )];
+        assert_eq!(
+            RefundSummary::new(&payments, &refunds),
+            RefundSummary {
+                paid: 80_000,
+                refunded: 12_000,
+                refundable: 68_000,
+            }
+        );
+    }
+}
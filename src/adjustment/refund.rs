@@ -0,0 +1,585 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    bill::{load_bill, split_orders},
+    db::{
+        self,
+        adjustment::{ReasonCode, Refund},
+        payment::{Payment, PaymentMethod},
+        staff::StaffRole,
+    },
+    order::BadRequestBody,
+    payment::gateway::{Gateway, GatewayError, RefundRequest},
+};
+
+use super::{
+    allocate_refund, parse_reason, validate_note, validate_table_number, AdjustmentFailure,
+    PathParams, RefundData, RefundSummary,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    order_id: Option<i64>,
+    amount: Option<i64>,
+    reason: String,
+    note: Option<String>,
+}
+
+/// The input data to refund an Order or (a part of) the bill of a Table.
+struct Input {
+    table_number: u32,
+    session_id: Option<u32>,
+    order_id: Option<i64>,
+    amount: Option<i64>,
+    reason: ReasonCode,
+    note: Option<String>,
+}
+
+impl Input {
+    fn new(
+        path_params: PathParams,
+        query_params: QueryParams,
+        rb: RequestBody,
+    ) -> Result<Self, AdjustmentFailure> {
+        Ok(Self {
+            table_number: path_params.table_number,
+            session_id: query_params.session_id,
+            order_id: rb.order_id,
+            amount: rb.amount,
+            reason: parse_reason(&rb.reason)?,
+            note: rb.note,
+        })
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, AdjustmentFailure> {
+        validate_table_number(self.table_number)?;
+        validate_note(&self.note)?;
+        if self.amount.is_some_and(|amount| amount < 1) {
+            return Err(AdjustmentFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("amount must be positive"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    session_id: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    refunds: Vec<RefundData>,
+    summary: RefundSummary,
+}
+
+/// Gives card and QRIS money back through the gateway, cash is handed out at the counter.
+async fn give_back(
+    gateway: &dyn Gateway,
+    payment: &Payment,
+    amount: i64,
+) -> Result<Option<String>, AdjustmentFailure> {
+    if payment.method == PaymentMethod::Cash {
+        return Ok(None);
+    }
+    let request = RefundRequest {
+        method: payment.method,
+        capture_reference: payment.reference.clone().unwrap_or("".to_string()),
+        amount,
+    };
+    match gateway.refund(request).await {
+        Ok(capture) => Ok(Some(capture.reference)),
+        Err(GatewayError::Declined(reason)) => Err(AdjustmentFailure::Declined(BadRequestBody {
+            error: true,
+            message: format!("the refund was declined: {}", reason),
+        })),
+        Err(GatewayError::Unavailable(reason)) => {
+            log::error!("payment gateway is unavailable: {}", reason);
+            Err(AdjustmentFailure::GatewayUnavailable(BadRequestBody {
+                error: true,
+                message: String::from("the payment gateway is unavailable, please try again"),
+            }))
+        }
+    }
+}
+
+/// Refunds an Order or (a part of) the bill after payment, manager only.
+/// An Order is refunded its share of the bill, the money goes back through the latest Payments first.
+#[post("/refund")]
+#[allow(clippy::too_many_arguments)]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    payment_repository: web::Data<dyn db::payment::Repository>,
+    adjustment_repository: web::Data<dyn db::adjustment::Repository>,
+    gateway: web::Data<dyn Gateway>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+    query_params: web::Query<QueryParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, AdjustmentFailure> {
+    let identity = identity.require(StaffRole::Manager)?;
+    let input = Input::new(
+        path_params.into_inner(),
+        query_params.into_inner(),
+        request_body.into_inner(),
+    )?
+    .validate()?;
+
+    // pass session_id to refund a past session, paid bills are usually closed already:
+    let bill = match load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        input.table_number as i32,
+        input.session_id.map(|v| v as i64),
+    )
+    .await
+    .map_err(AdjustmentFailure::InternalServerError)?
+    {
+        Some(bill) => bill,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    let payments = payment_repository
+        .list_by_session(bill.session_id)
+        .await
+        .map_err(AdjustmentFailure::InternalServerError)?;
+    let mut refunds = adjustment_repository
+        .list_by_session(bill.session_id)
+        .await
+        .map_err(AdjustmentFailure::InternalServerError)?;
+    let summary = RefundSummary::new(&payments, &refunds);
+    if summary.refundable <= 0 {
+        return Err(AdjustmentFailure::Conflict(BadRequestBody {
+            error: true,
+            message: String::from("nothing has been paid that could be refunded"),
+        }));
+    }
+
+    // an Order can get back at most what it was billed, after discounts, service charge and tax:
+    let limit = match input.order_id {
+        Some(order_id) => {
+            let share = split_orders(&bill, &[vec![order_id]])
+                .map_err(|message| {
+                    AdjustmentFailure::InvalidInput(BadRequestBody {
+                        error: true,
+                        message,
+                    })
+                })?
+                .first()
+                .map(|part| part.total)
+                .unwrap_or(0);
+            let refunded: i64 = refunds
+                .iter()
+                .filter(|refund| refund.order_id == Some(order_id))
+                .map(|refund| refund.amount)
+                .sum();
+            if share - refunded <= 0 {
+                return Err(AdjustmentFailure::Conflict(BadRequestBody {
+                    error: true,
+                    message: format!("order {} has been refunded already", order_id),
+                }));
+            }
+            (share - refunded).min(summary.refundable)
+        }
+        None => summary.refundable,
+    };
+    let amount = input.amount.unwrap_or(limit);
+    if amount > limit {
+        return Err(AdjustmentFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: format!("amount exceeds the refundable amount of {}", limit),
+        }));
+    }
+
+    // every Refund is recorded before its money moves, so the gateway never gives back anything unrecorded,
+    // the Refund is deleted again when the gateway does not give the money back:
+    let mut recorded = vec![];
+    for (payment, part) in allocate_refund(amount, &payments, &refunds) {
+        let refund = Refund {
+            order_id: input.order_id,
+            note: input.note.clone(),
+            staff_id: Some(identity.0.staff_id),
+            ..Refund::new(
+                bill.session_id,
+                payment.payment_id,
+                payment.method,
+                part,
+                input.reason,
+            )
+        };
+        let mut refund = match adjustment_repository
+            .record_refund(refund)
+            .await
+            .map_err(AdjustmentFailure::InternalServerError)?
+        {
+            Some(refund) => refund,
+            None => {
+                return Err(AdjustmentFailure::Conflict(BadRequestBody {
+                    error: true,
+                    message: String::from("the payment has been refunded in the meantime"),
+                }));
+            }
+        };
+        match give_back(gateway.get_ref(), &payment, part).await {
+            Ok(Some(reference)) => {
+                if let Err(e) = adjustment_repository
+                    .set_refund_reference(refund.refund_id, reference.clone())
+                    .await
+                {
+                    // the money is back with the guests, only the reference is missing from the record:
+                    log::error!(
+                        "failed to keep reference {} of refund {}: {:?}",
+                        reference,
+                        refund.refund_id,
+                        e
+                    );
+                }
+                refund.reference = Some(reference);
+            }
+            Ok(None) => {}
+            Err(failure) => {
+                if let Err(e) = adjustment_repository.delete_refund(refund.refund_id).await {
+                    log::error!(
+                        "refund {} was not given back but is still recorded, it has to be deleted by hand: {:?}",
+                        refund.refund_id,
+                        e
+                    );
+                }
+                return Err(failure);
+            }
+        }
+        recorded.push(refund);
+    }
+
+    refunds.extend(recorded.iter().cloned());
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        refunds: recorded.into_iter().map(RefundData::from).collect(),
+        summary: RefundSummary::new(&payments, &refunds),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::{
+        db::{order::Order, session::Session, staff::Staff, OperationError},
+        payment::gateway::{Capture, MockGateway},
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    /// Two Orders of 50.000 and 30.000, billed 92.400 with 5% service charge and 10% tax.
+    fn order_repo() -> Arc<dyn db::order::Repository> {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .returning(|table_number, session_id| {
+                Ok(vec![
+                    Order {
+                        order_id: 1,
+                        session_id: Some(session_id),
+                        unit_price: 50_000,
+                        ..Order::new(table_number, 5, 10)
+                    },
+                    Order {
+                        order_id: 2,
+                        session_id: Some(session_id),
+                        unit_price: 30_000,
+                        ..Order::new(table_number, 6, 5)
+                    },
+                ])
+            });
+        Arc::new(order_repo)
+    }
+
+    fn session_repo() -> Arc<dyn db::session::Repository> {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_session()
+            .returning(|table_number, session_id| Ok(Some(Session::new(session_id, table_number))));
+        Arc::new(session_repo)
+    }
+
+    fn discount_repo() -> Arc<dyn db::discount::Repository> {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(discount_repo)
+    }
+
+    /// The bill was paid 50.000 in cash, then 42.400 by card.
+    fn payment_repo() -> Arc<dyn db::payment::Repository> {
+        let mut payment_repo = crate::db::payment::MockRepository::new();
+        payment_repo
+            .expect_list_by_session()
+            .returning(|session_id| {
+                Ok(vec![
+                    Payment {
+                        payment_id: 1,
+                        ..Payment::new(session_id, 3, PaymentMethod::Cash, 50_000, None)
+                    },
+                    Payment {
+                        payment_id: 2,
+                        ..Payment::new(
+                            session_id,
+                            3,
+                            PaymentMethod::Card,
+                            42_400,
+                            Some("cap-2".to_string()),
+                        )
+                    },
+                ])
+            });
+        Arc::new(payment_repo)
+    }
+
+    fn gateway() -> Arc<dyn Gateway> {
+        let mut gateway = MockGateway::new();
+        gateway
+            .expect_refund()
+            .withf(|request| request.capture_reference == "cap-2")
+            .returning(|_| {
+                Ok(Capture {
+                    reference: "ref-9".to_string(),
+                })
+            });
+        Arc::new(gateway)
+    }
+
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: refunding an order.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let adjustment_repo = crate::db::adjustment::MockRepository::new();
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_adjustment_repo))
+                .app_data(Data::from(gateway()))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/refund?session_id=7")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .set_json(serde_json::json!({"order_id": 2, "reason": "quality_complaint"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a paid bill.
+    /// when: refunding an order.
+    /// then: its share of the bill goes back through the card payment.
+    async fn test_success() {
+        let mut adjustment_repo = crate::db::adjustment::MockRepository::new();
+        adjustment_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        adjustment_repo
+            .expect_record_refund()
+            .once()
+            .returning(|refund| {
+                Ok(Some(Refund {
+                    refund_id: 4,
+                    ..refund
+                }))
+            });
+        adjustment_repo
+            .expect_set_refund_reference()
+            .withf(|refund_id, reference| *refund_id == 4 && reference == "ref-9")
+            .once()
+            .returning(|_, _| Ok(()));
+        adjustment_repo.expect_delete_refund().never();
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_adjustment_repo))
+                .app_data(Data::from(gateway()))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/refund?session_id=7")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(
+                serde_json::json!({"order_id": 2, "reason": "quality_complaint", "note": "cold"}),
+            )
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.refunds.len(), 1);
+        let refund = &response_body.refunds[0];
+        assert_eq!(refund.payment_id, 2);
+        assert_eq!(refund.method, "card");
+        assert_eq!(refund.amount, 34_650);
+        assert_eq!(refund.reason, "quality_complaint");
+        assert_eq!(refund.reference, Some("ref-9".to_string()));
+        assert_eq!(refund.staff_id, Some(1));
+        assert_eq!(response_body.summary.refunded, 34_650);
+        assert_eq!(response_body.summary.refundable, 57_750);
+    }
+
+    #[actix_web::test]
+    /// given: the gateway declines to give back the card payment.
+    /// when: refunding an order.
+    /// then: response status code is 402 and the recorded refund is deleted again.
+    async fn test_declined() {
+        let mut adjustment_repo = crate::db::adjustment::MockRepository::new();
+        adjustment_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        adjustment_repo
+            .expect_record_refund()
+            .once()
+            .returning(|refund| {
+                Ok(Some(Refund {
+                    refund_id: 4,
+                    ..refund
+                }))
+            });
+        adjustment_repo.expect_set_refund_reference().never();
+        adjustment_repo
+            .expect_delete_refund()
+            .withf(|refund_id| *refund_id == 4)
+            .once()
+            .returning(|_| Ok(()));
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+        let mut gateway = MockGateway::new();
+        gateway
+            .expect_refund()
+            .once()
+            .returning(|_| Err(GatewayError::Declined("capture expired".to_string())));
+        let arc_gateway: Arc<dyn Gateway> = Arc::new(gateway);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_adjustment_repo))
+                .app_data(Data::from(arc_gateway))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/refund?session_id=7")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"order_id": 2, "reason": "quality_complaint"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    }
+
+    #[actix_web::test]
+    /// given: a paid bill.
+    /// when: refunding more than what was paid.
+    /// then: response status code is 400.
+    async fn test_exceeds_paid() {
+        let mut adjustment_repo = crate::db::adjustment::MockRepository::new();
+        adjustment_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        adjustment_repo.expect_record_refund().never();
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_adjustment_repo))
+                .app_data(Data::from(gateway()))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/refund?session_id=7")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"amount": 100000, "reason": "overcharge"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: refunding the bill.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut adjustment_repo = crate::db::adjustment::MockRepository::new();
+        adjustment_repo
+            .expect_list_by_session()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_adjustment_repo))
+                .app_data(Data::from(gateway()))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/refund?session_id=7")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"reason": "other"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,220 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::db;
+
+use super::{validate_table_number, AdjustmentFailure, PathParams, RefundData, RefundSummary};
+
+/// The input data to list the Refunds of a Table.
+struct Input {
+    table_number: u32,
+    session_id: Option<u32>,
+}
+
+impl Input {
+    fn new(path_params: PathParams, query_params: QueryParams) -> Self {
+        Self {
+            table_number: path_params.table_number,
+            session_id: query_params.session_id,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, AdjustmentFailure> {
+        validate_table_number(self.table_number)?;
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    session_id: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    refunds: Vec<RefundData>,
+    summary: RefundSummary,
+}
+
+/// Lists the Refunds of a Table session along with what is left to refund.
+#[get("/refund")]
+async fn handler(
+    session_repository: web::Data<dyn db::session::Repository>,
+    payment_repository: web::Data<dyn db::payment::Repository>,
+    adjustment_repository: web::Data<dyn db::adjustment::Repository>,
+    path_params: web::Path<PathParams>,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, AdjustmentFailure> {
+    let input = Input::new(path_params.into_inner(), query_params.into_inner()).validate()?;
+
+    let session_id = match input.session_id {
+        Some(session_id) => session_id as i64,
+        None => match session_repository
+            .get_active_session(input.table_number as i32)
+            .await
+            .map_err(AdjustmentFailure::InternalServerError)?
+        {
+            Some(session) => session.session_id,
+            None => return Ok(HttpResponse::NotFound().body("".to_string())),
+        },
+    };
+
+    let payments = payment_repository
+        .list_by_session(session_id)
+        .await
+        .map_err(AdjustmentFailure::InternalServerError)?;
+    let refunds = adjustment_repository
+        .list_by_session(session_id)
+        .await
+        .map_err(AdjustmentFailure::InternalServerError)?;
+    let summary = RefundSummary::new(&payments, &refunds);
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        refunds: refunds.into_iter().map(RefundData::from).collect(),
+        summary,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        adjustment::{ReasonCode, Refund},
+        payment::{Payment, PaymentMethod},
+        OperationError,
+    };
+
+    fn payment_repo() -> Arc<dyn db::payment::Repository> {
+        let mut payment_repo = crate::db::payment::MockRepository::new();
+        payment_repo
+            .expect_list_by_session()
+            .returning(|session_id| {
+                Ok(vec![Payment {
+                    payment_id: 1,
+                    ..Payment::new(session_id, 3, PaymentMethod::Cash, 92_400, None)
+                }])
+            });
+        Arc::new(payment_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a table without an open session.
+    /// when: listing the refunds of a table.
+    /// then: response status code is 404.
+    async fn test_no_active_session() {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|_| Ok(None));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+        let adjustment_repo = crate::db::adjustment::MockRepository::new();
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_adjustment_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/table/3/refund").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: a past session with a refund.
+    /// when: listing its refunds.
+    /// then: the refund is listed along with what is left to refund.
+    async fn test_success() {
+        let session_repo = crate::db::session::MockRepository::new();
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+        let mut adjustment_repo = crate::db::adjustment::MockRepository::new();
+        adjustment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|session_id| {
+                Ok(vec![Refund {
+                    refund_id: 4,
+                    order_id: Some(2),
+                    ..Refund::new(
+                        session_id,
+                        1,
+                        PaymentMethod::Cash,
+                        34_650,
+                        ReasonCode::KitchenError,
+                    )
+                }])
+            });
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_adjustment_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/refund?session_id=7")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.refunds.len(), 1);
+        assert_eq!(response_body.refunds[0].session_id, 7);
+        assert_eq!(response_body.refunds[0].reason, "kitchen_error");
+        assert_eq!(
+            response_body.summary,
+            RefundSummary {
+                paid: 92_400,
+                refunded: 34_650,
+                refundable: 57_750,
+            }
+        );
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: listing the refunds of a table.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let session_repo = crate::db::session::MockRepository::new();
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+        let mut adjustment_repo = crate::db::adjustment::MockRepository::new();
+        adjustment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_adjustment_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/refund?session_id=7")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,389 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::StaffIdentity,
+    bill::{load_bill, Bill},
+    db::{self, adjustment::ReasonCode, order::Void, staff::StaffRole},
+    order::BadRequestBody,
+};
+
+use super::{parse_reason, validate_note, validate_table_number, AdjustmentFailure, PathParams};
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    order_id: Option<i64>,
+    reason: String,
+    note: Option<String>,
+}
+
+/// The input data to void an Order or the whole bill of a Table.
+struct Input {
+    table_number: u32,
+    order_id: Option<i64>,
+    reason: ReasonCode,
+    note: Option<String>,
+}
+
+impl Input {
+    fn new(path_params: PathParams, rb: RequestBody) -> Result<Self, AdjustmentFailure> {
+        Ok(Self {
+            table_number: path_params.table_number,
+            order_id: rb.order_id,
+            reason: parse_reason(&rb.reason)?,
+            note: rb.note,
+        })
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, AdjustmentFailure> {
+        validate_table_number(self.table_number)?;
+        validate_note(&self.note)?;
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    voided_order_ids: Vec<i64>,
+    bill: Bill,
+}
+
+/// Voids an Order, or every Order on the bill when no order_id is given, manager only.
+/// Voids are only possible before payment, paid bills are refunded instead.
+#[post("/void")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    payment_repository: web::Data<dyn db::payment::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, AdjustmentFailure> {
+    let identity = identity.require(StaffRole::Manager)?;
+    let input = Input::new(path_params.into_inner(), request_body.into_inner())?.validate()?;
+    let table_number = input.table_number as i32;
+
+    let bill = match load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        table_number,
+        None,
+    )
+    .await
+    .map_err(AdjustmentFailure::InternalServerError)?
+    {
+        Some(bill) => bill,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    match input.order_id {
+        Some(order_id) if !bill.lines.iter().any(|line| line.order_id == order_id) => {
+            return Err(AdjustmentFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: format!("order {} is not on the bill", order_id),
+            }));
+        }
+        None if bill.lines.is_empty() => {
+            return Err(AdjustmentFailure::Conflict(BadRequestBody {
+                error: true,
+                message: format!("the bill of table {} has nothing to void", table_number),
+            }));
+        }
+        _ => {}
+    }
+
+    let payments = payment_repository
+        .list_by_session(bill.session_id)
+        .await
+        .map_err(AdjustmentFailure::InternalServerError)?;
+    if !payments.is_empty() {
+        return Err(AdjustmentFailure::Conflict(BadRequestBody {
+            error: true,
+            message: String::from("the bill has payments already, refund instead"),
+        }));
+    }
+
+    let void = Void {
+        reason: input.reason,
+        note: input.note,
+        staff_id: identity.0.staff_id,
+        at: OffsetDateTime::now_utc(),
+    };
+    let voided_order_ids = order_repository
+        .void_orders(table_number, bill.session_id, input.order_id, void)
+        .await
+        .map_err(AdjustmentFailure::InternalServerError)?;
+    if voided_order_ids.is_empty() {
+        // a payment or another void got in first:
+        return Err(AdjustmentFailure::Conflict(BadRequestBody {
+            error: true,
+            message: String::from("the bill has changed in the meantime, please try again"),
+        }));
+    }
+
+    let bill = load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        table_number,
+        Some(bill.session_id),
+    )
+    .await
+    .map_err(AdjustmentFailure::InternalServerError)?
+    .unwrap_or(bill);
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        voided_order_ids,
+        bill,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        order::Order,
+        payment::{Payment, PaymentMethod},
+        session::Session,
+        staff::Staff,
+        OperationError,
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    fn session_repo() -> Arc<dyn db::session::Repository> {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        session_repo
+            .expect_get_session()
+            .returning(|table_number, session_id| Ok(Some(Session::new(session_id, table_number))));
+        Arc::new(session_repo)
+    }
+
+    fn discount_repo() -> Arc<dyn db::discount::Repository> {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(discount_repo)
+    }
+
+    fn payment_repo(payments: Vec<Payment>) -> Arc<dyn db::payment::Repository> {
+        let mut payment_repo = crate::db::payment::MockRepository::new();
+        payment_repo
+            .expect_list_by_session()
+            .returning(move |_| Ok(payments.clone()));
+        Arc::new(payment_repo)
+    }
+
+    /// Two Orders on table 3, voids are kept in memory so the bill reflects them.
+    fn order_repo(void_result: Result<(), ()>) -> Arc<dyn db::order::Repository> {
+        let orders = Arc::new(Mutex::new(vec![
+            Order {
+                order_id: 1,
+                session_id: Some(7),
+                unit_price: 35_000,
+                ..Order::new(3, 5, 10)
+            },
+            Order {
+                order_id: 2,
+                session_id: Some(7),
+                unit_price: 18_000,
+                ..Order::new(3, 6, 5)
+            },
+        ]));
+        let orders_list = orders.clone();
+
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .returning(move |_, _| {
+                Ok(orders_list
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|order| Order {
+                        name: order.name.clone(),
+                        modifiers: order.modifiers.clone(),
+                        note: order.note.clone(),
+                        void_note: order.void_note.clone(),
+                        ..*order
+                    })
+                    .collect())
+            });
+        order_repo
+            .expect_void_orders()
+            .returning(move |_, _, order_id, void| {
+                if void_result.is_err() {
+                    return Err(OperationError::OtherError);
+                }
+                let mut orders = orders.lock().unwrap();
+                let mut voided = vec![];
+                for order in orders.iter_mut() {
+                    if order.voided_at.is_none() && order_id.is_none_or(|id| id == order.order_id) {
+                        order.voided_at = Some(void.at);
+                        order.void_reason = Some(void.reason);
+                        order.voided_by = Some(void.staff_id);
+                        voided.push(order.order_id);
+                    }
+                }
+                Ok(voided)
+            });
+        Arc::new(order_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: voiding an order.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo(Ok(()))))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/void")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .set_json(serde_json::json!({"order_id": 2, "reason": "entry_error"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: an unknown reason code.
+    /// when: voiding an order.
+    /// then: response status code is 400.
+    async fn test_invalid_reason() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo(Ok(()))))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/void")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"order_id": 2, "reason": "because"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a manager access token and a bill without payments.
+    /// when: voiding an order.
+    /// then: the order is off the bill and listed as voided.
+    async fn test_success() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo(Ok(()))))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/void")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(
+                serde_json::json!({"order_id": 2, "reason": "entry_error", "note": "wrong table"}),
+            )
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.voided_order_ids, vec![2]);
+        assert_eq!(response_body.bill.lines.len(), 1);
+        assert_eq!(response_body.bill.voided[0].order_id, 2);
+        assert_eq!(response_body.bill.subtotal, 35_000);
+    }
+
+    #[actix_web::test]
+    /// given: a bill which has been paid (in part).
+    /// when: voiding the bill.
+    /// then: response status code is 409.
+    async fn test_paid_bill() {
+        let payments = vec![Payment::new(7, 3, PaymentMethod::Cash, 20_000, None)];
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo(Ok(()))))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo(payments)))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/void")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"reason": "guest_cancelled"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: voiding an order.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo(Err(()))))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/void")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"order_id": 1, "reason": "kitchen_error"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
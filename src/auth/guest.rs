@@ -0,0 +1,154 @@
+use std::env;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use time::{Duration, OffsetDateTime};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A verified guest token, it lets guests order for the Table it is bound to.
+#[derive(Debug, PartialEq)]
+pub struct GuestClaims {
+    pub table_number: i32,
+    pub expires_at: OffsetDateTime,
+}
+
+/// Issues and verifies guest tokens which are printed as a QR code on the Table.
+///
+/// A token is `<payload>.<signature>` where the payload is `<table_number>.<expires_at unix>`,
+/// both parts are base64url encoded and the signature is an HMAC-SHA256 of the payload.
+pub struct GuestTokenSigner {
+    secret: Vec<u8>,
+    ttl: Duration,
+}
+
+impl GuestTokenSigner {
+    pub fn new(secret: Vec<u8>, ttl: Duration) -> Self {
+        Self { secret, ttl }
+    }
+
+    /// Reads the secret from `GUEST_TOKEN_SECRET` and the lifetime in minutes from `GUEST_TOKEN_TTL`.
+    /// Without a secret a random one is generated, which only works for a single server instance.
+    pub fn from_env() -> Self {
+        const DEFAULT_TTL: i64 = 180;
+        let ttl = match env::var("GUEST_TOKEN_TTL").ok() {
+            Some(v) => v.parse().unwrap_or(DEFAULT_TTL),
+            None => DEFAULT_TTL,
+        };
+        let secret = match env::var("GUEST_TOKEN_SECRET") {
+            Ok(v) if !v.is_empty() => v.into_bytes(),
+            _ => {
+                log::warn!(
+                    "GUEST_TOKEN_SECRET is not set, guest tokens will not survive a restart"
+                );
+                let mut secret = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut secret);
+                secret
+            }
+        };
+        Self::new(secret, Duration::minutes(ttl))
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length")
+    }
+
+    /// Issues a token for the Table which expires after the configured lifetime.
+    pub fn issue(&self, table_number: i32, now: OffsetDateTime) -> (String, OffsetDateTime) {
+        let expires_at = now + self.ttl;
+        let payload = format!("{}.{}", table_number, expires_at.unix_timestamp());
+        let mut mac = self.mac();
+        mac.update(payload.as_bytes());
+        let signature = mac.finalize().into_bytes();
+        let token = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        );
+        (token, expires_at)
+    }
+
+    /// Verifies the signature and the expiry of a token, returns None if either is not valid.
+    pub fn verify(&self, token: &str, now: OffsetDateTime) -> Option<GuestClaims> {
+        let (payload, signature) = token.split_once('.')?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).ok()?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+
+        let mut mac = self.mac();
+        mac.update(&payload);
+        mac.verify_slice(&signature).ok()?;
+
+        let payload = String::from_utf8(payload).ok()?;
+        let (table_number, expires_at) = payload.split_once('.')?;
+        let expires_at = OffsetDateTime::from_unix_timestamp(expires_at.parse().ok()?).ok()?;
+        if expires_at <= now {
+            return None;
+        }
+        Some(GuestClaims {
+            table_number: table_number.parse().ok()?,
+            expires_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> GuestTokenSigner {
+        GuestTokenSigner::new(b"sukab".to_vec(), Duration::minutes(30))
+    }
+
+    #[test]
+    /// given: a freshly issued token.
+    /// when: verifying the token.
+    /// then: it is bound to the Table it was issued for.
+    fn test_roundtrip() {
+        let now = OffsetDateTime::now_utc();
+        let (token, expires_at) = signer().issue(7, now);
+        let claims = signer().verify(&token, now).unwrap();
+        assert_eq!(claims.table_number, 7);
+        assert_eq!(
+            claims.expires_at.unix_timestamp(),
+            expires_at.unix_timestamp()
+        );
+    }
+
+    #[test]
+    /// given: a token past its lifetime.
+    /// when: verifying the token.
+    /// then: it is rejected.
+    fn test_expired() {
+        let now = OffsetDateTime::now_utc();
+        let (token, _) = signer().issue(7, now);
+        assert!(signer()
+            .verify(&token, now + Duration::minutes(31))
+            .is_none());
+    }
+
+    #[test]
+    /// given: a token whose Table was changed by the guest.
+    /// when: verifying the token.
+    /// then: the signature does not match and it is rejected.
+    fn test_tampered() {
+        let now = OffsetDateTime::now_utc();
+        let (token, expires_at) = signer().issue(7, now);
+        let (_, signature) = token.split_once('.').unwrap();
+        let forged_payload = URL_SAFE_NO_PAD.encode(format!("8.{}", expires_at.unix_timestamp()));
+        let forged = format!("{}.{}", forged_payload, signature);
+        assert!(signer().verify(&forged, now).is_none());
+    }
+
+    #[test]
+    /// given: a token signed with another secret.
+    /// when: verifying the token.
+    /// then: it is rejected.
+    fn test_other_secret() {
+        let now = OffsetDateTime::now_utc();
+        let (token, _) =
+            GuestTokenSigner::new(b"other".to_vec(), Duration::minutes(30)).issue(7, now);
+        assert!(signer().verify(&token, now).is_none());
+    }
+}
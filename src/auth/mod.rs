@@ -0,0 +1,251 @@
+use std::{fmt, future::Future, pin::Pin};
+
+use actix_web::{
+    body::BoxBody, dev::Payload, http::StatusCode, web, FromRequest, HttpRequest, HttpResponse,
+    HttpResponseBuilder, ResponseError,
+};
+
+use time::OffsetDateTime;
+
+use crate::{
+    db::{
+        self,
+        staff::{Staff, StaffRole},
+        OperationError,
+    },
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+use self::guest::{GuestClaims, GuestTokenSigner};
+
+pub mod guest;
+
+/// Reads the token from an `Authorization: <scheme> <token>` header.
+fn authorization_token(req: &HttpRequest, scheme: &str) -> Option<String> {
+    req.headers()
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix(scheme))
+        .and_then(|v| v.strip_prefix(' '))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Reads the token from an `Authorization: Bearer <token>` header.
+fn bearer_token(req: &HttpRequest) -> Option<String> {
+    authorization_token(req, "Bearer")
+}
+
+/// Looks up the Staff member owning the token of the request, if the request carries one.
+async fn authenticate(
+    token: Option<String>,
+    staff_repository: Option<web::Data<dyn db::staff::Repository>>,
+) -> Result<Option<Staff>, AuthFailure> {
+    let token = match token {
+        Some(token) => token,
+        None => return Ok(None),
+    };
+    let invalid = || {
+        AuthFailure::Unauthorized(BadRequestBody {
+            error: true,
+            message: String::from("the staff access token is not valid"),
+        })
+    };
+    let staff_repository = staff_repository.ok_or_else(invalid)?;
+    match staff_repository.get_by_token(token).await {
+        Ok(Some(staff)) => Ok(Some(staff)),
+        Ok(None) => Err(invalid()),
+        Err(e) => Err(AuthFailure::InternalServerError(e)),
+    }
+}
+
+/// The Staff member who sent the request, authenticated by their access token.
+pub struct StaffIdentity(pub Staff);
+
+impl StaffIdentity {
+    /// Ensures the Staff member has the given role, managers are allowed to do everything.
+    pub fn require(self, role: StaffRole) -> Result<Self, AuthFailure> {
+        match self.0.role {
+            StaffRole::Manager => Ok(self),
+            r if r == role => Ok(self),
+            _ => Err(AuthFailure::Forbidden(BadRequestBody {
+                error: true,
+                message: String::from("this action is not allowed for your role"),
+            })),
+        }
+    }
+}
+
+impl FromRequest for StaffIdentity {
+    type Error = AuthFailure;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let token = bearer_token(req);
+        let staff_repository = req
+            .app_data::<web::Data<dyn db::staff::Repository>>()
+            .cloned();
+
+        Box::pin(async move {
+            match authenticate(token, staff_repository).await? {
+                Some(staff) => Ok(StaffIdentity(staff)),
+                None => Err(AuthFailure::Unauthorized(BadRequestBody {
+                    error: true,
+                    message: String::from("a staff access token is required"),
+                })),
+            }
+        })
+    }
+}
+
+/// Like `StaffIdentity`, but lets anonymous requests through as None.
+/// A request carrying an invalid token is still rejected.
+pub struct OptionalStaffIdentity(pub Option<Staff>);
+
+impl FromRequest for OptionalStaffIdentity {
+    type Error = AuthFailure;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let token = bearer_token(req);
+        let staff_repository = req
+            .app_data::<web::Data<dyn db::staff::Repository>>()
+            .cloned();
+
+        Box::pin(async move {
+            authenticate(token, staff_repository)
+                .await
+                .map(OptionalStaffIdentity)
+        })
+    }
+}
+
+/// Who is allowed to place an Order:
+/// a Staff member via `Authorization: Bearer <token>`,
+/// or guests scanning the QR code of their Table via `Authorization: Guest <token>`.
+pub enum OrderingCredential {
+    Staff(Staff),
+    Guest(GuestClaims),
+}
+
+impl OrderingCredential {
+    /// Ensures the credential may order for the Table, guests only for the Table of their token.
+    pub fn allows_table(&self, table_number: i32) -> Result<(), AuthFailure> {
+        match self {
+            OrderingCredential::Guest(claims) if claims.table_number != table_number => {
+                Err(AuthFailure::Forbidden(BadRequestBody {
+                    error: true,
+                    message: format!("the guest token is not valid for table {}", table_number),
+                }))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Ensures the credential may see the Orders of the Table, guests only the Table of their token
+    /// and waiters only the Tables in the sections they work this shift.
+    pub async fn serves_table(
+        &self,
+        section_repository: &dyn db::section::Repository,
+        table_number: i32,
+    ) -> Result<(), AuthFailure> {
+        self.allows_table(table_number)?;
+        let staff = match self {
+            OrderingCredential::Staff(staff) if staff.role == StaffRole::Waiter => staff,
+            _ => return Ok(()),
+        };
+        let section_id = section_repository
+            .get_table_section(table_number)
+            .await
+            .map_err(AuthFailure::InternalServerError)?;
+        let assigned = section_repository
+            .list_assigned_sections(staff.staff_id, OffsetDateTime::now_utc())
+            .await
+            .map_err(AuthFailure::InternalServerError)?;
+        match section_id {
+            Some(section_id) if assigned.contains(&section_id) => Ok(()),
+            _ => Err(AuthFailure::Forbidden(BadRequestBody {
+                error: true,
+                message: format!(
+                    "you are not assigned to the section of table {}",
+                    table_number
+                ),
+            })),
+        }
+    }
+}
+
+impl FromRequest for OrderingCredential {
+    type Error = AuthFailure;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let staff_token = bearer_token(req);
+        let staff_repository = req
+            .app_data::<web::Data<dyn db::staff::Repository>>()
+            .cloned();
+        let guest_claims = authorization_token(req, "Guest").map(|token| {
+            req.app_data::<web::Data<GuestTokenSigner>>()
+                .and_then(|signer| signer.verify(&token, OffsetDateTime::now_utc()))
+        });
+
+        Box::pin(async move {
+            match guest_claims {
+                Some(Some(claims)) => return Ok(OrderingCredential::Guest(claims)),
+                Some(None) => {
+                    return Err(AuthFailure::Unauthorized(BadRequestBody {
+                        error: true,
+                        message: String::from("the guest token is not valid or has expired"),
+                    }))
+                }
+                None => {}
+            }
+            match authenticate(staff_token, staff_repository).await? {
+                Some(staff) => Ok(OrderingCredential::Staff(staff)),
+                None => Err(AuthFailure::Unauthorized(BadRequestBody {
+                    error: true,
+                    message: String::from("a staff access token or a guest token is required"),
+                })),
+            }
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum AuthFailure {
+    Unauthorized(BadRequestBody),
+    Forbidden(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl fmt::Display for AuthFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to authenticate")
+    }
+}
+
+impl ResponseError for AuthFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            AuthFailure::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AuthFailure::Forbidden(_) => StatusCode::FORBIDDEN,
+            AuthFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            AuthFailure::Unauthorized(r) | AuthFailure::Forbidden(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            AuthFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, get, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{self, OperationError},
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+use super::{load_bill, Bill};
+
+/// The input data to get the Bill of a Table.
+struct Input {
+    table_number: u32,
+    session_id: Option<u32>,
+}
+
+impl Input {
+    fn new(path_params: PathParams, query_params: QueryParams) -> Self {
+        Self {
+            table_number: path_params.table_number,
+            session_id: query_params.session_id,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, DetailFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(DetailFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    table_number: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    session_id: Option<u32>,
+}
+
+#[derive(Debug)]
+enum DetailFailure {
+    InvalidInput(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl fmt::Display for DetailFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to get bill")
+    }
+}
+
+impl ResponseError for DetailFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            DetailFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            DetailFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            DetailFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            DetailFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    bill: Bill,
+}
+
+#[get("/bill")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    path_params: web::Path<PathParams>,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, DetailFailure> {
+    let input = Input::new(path_params.into_inner(), query_params.into_inner()).validate()?;
+
+    // pass session_id to reprint the bill of a past session:
+    let bill = load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        input.table_number as i32,
+        input.session_id.map(|v| v as i64),
+    )
+    .await
+    .map_err(DetailFailure::InternalServerError)?;
+
+    match bill {
+        Some(bill) => Ok(HttpResponse::Ok().json(SuccessResponseBody { bill })),
+        None => Ok(HttpResponse::NotFound().body("".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{order::Order, session::Session};
+
+    fn discount_repo() -> Arc<dyn db::discount::Repository> {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(discount_repo)
+    }
+
+    #[actix_web::test]
+    /// given: zero table_id.
+    /// when: getting the bill of a table.
+    /// then: response status code is 400.
+    async fn test_invalid_table_id() {
+        let order_repo = crate::db::order::MockRepository::new();
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let session_repo = crate::db::session::MockRepository::new();
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/table/0/bill").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a table with an open session and two orders.
+    /// when: getting the bill of a table.
+    /// then: response contains the line items and the totals.
+    async fn test_success() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .once()
+            .returning(|table_number, session_id| {
+                Ok(vec![
+                    Order {
+                        order_id: 1,
+                        session_id: Some(session_id),
+                        quantity: 2,
+                        unit_price: 35_000,
+                        ..Order::new(table_number, 5, 10)
+                    },
+                    Order {
+                        order_id: 2,
+                        session_id: Some(session_id),
+                        unit_price: 18_000,
+                        ..Order::new(table_number, 6, 10)
+                    },
+                ])
+            });
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/table/3/bill").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        let bill = response_body.bill;
+        assert_eq!(bill.session_id, 7);
+        assert_eq!(bill.lines.len(), 2);
+        assert_eq!(bill.subtotal, 88_000);
+        assert_eq!(bill.total, bill.subtotal + bill.service_charge + bill.tax);
+    }
+
+    #[actix_web::test]
+    /// given: a past session settled without service charge and tax, unlike the current configuration.
+    /// when: reprinting the bill of that session.
+    /// then: the bill is computed with the configuration it was settled with.
+    async fn test_past_session_settled_config() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .withf(|table_number, session_id| *table_number == 3 && *session_id == 5)
+            .once()
+            .returning(|table_number, session_id| {
+                Ok(vec![Order {
+                    order_id: 1,
+                    session_id: Some(session_id),
+                    unit_price: 35_000,
+                    ..Order::new(table_number, 5, 10)
+                }])
+            });
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_session()
+            .once()
+            .returning(|table_number, session_id| {
+                Ok(Some(Session {
+                    closed_at: Some(time::OffsetDateTime::now_utc()),
+                    service_charge_rate: Some(0),
+                    tax_rate: Some(0),
+                    tax_mode: Some("exclusive".to_string()),
+                    rounding_unit: Some(1),
+                    rounding_mode: Some("half_up".to_string()),
+                    ..Session::new(session_id, table_number)
+                }))
+            });
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/bill?session_id=5")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        let bill = response_body.bill;
+        assert_eq!(bill.session_id, 5);
+        assert_eq!(bill.service_charge, 0);
+        assert_eq!(bill.tax, 0);
+        assert_eq!(bill.total, 35_000);
+    }
+
+    #[actix_web::test]
+    /// given: a table without an open session.
+    /// when: getting the bill of a table.
+    /// then: response status code is 404.
+    async fn test_no_active_session() {
+        let order_repo = crate::db::order::MockRepository::new();
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|_| Ok(None));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/table/3/bill").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: getting the bill of a past session.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_session()
+            .returning(|table_number, session_id| Ok(Some(Session::new(session_id, table_number))));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/bill?session_id=5")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
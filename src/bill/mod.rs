@@ -0,0 +1,564 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{
+        self,
+        discount::{Discount, DiscountKind},
+        order::Order,
+        OperationError,
+    },
+    tax::{apply_rate, TaxConfig, TaxMode},
+};
+
+pub mod detail;
+pub mod split;
+
+/// A single billed Order, priced with the Menu price at the time of ordering.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillLine {
+    pub order_id: i64,
+    pub menu_id: i32,
+    pub name: String,
+    pub seat_number: Option<i32>,
+    pub quantity: i32,
+    pub unit_price: i64,
+    pub amount: i64,
+    /// the line level discounts of this Order.
+    pub discount: i64,
+}
+
+impl BillLine {
+    fn from_order(order: &Order) -> Self {
+        Self {
+            order_id: order.order_id,
+            menu_id: order.menu_id,
+            name: order.name.clone().unwrap_or("".to_string()),
+            seat_number: order.seat_number,
+            quantity: order.quantity,
+            unit_price: order.unit_price,
+            amount: order.unit_price * order.quantity as i64,
+            discount: 0,
+        }
+    }
+}
+
+/// What a Table session owes, every amount is in minor units of the currency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bill {
+    pub session_id: i64,
+    pub table_number: i32,
+    pub lines: Vec<BillLine>,
+    pub subtotal: i64,
+    /// the line level and the bill level discounts together.
+    pub discount: i64,
+    pub service_charge: i64,
+    pub tax: i64,
+    /// what the total was rounded by to the rounding unit of the currency.
+    pub rounding: i64,
+    pub total: i64,
+    /// whether the prices already include the service charge and the tax,
+    /// in which case they are only broken out of the total.
+    pub tax_inclusive: bool,
+    /// the voided Orders, kept for the records but not charged.
+    pub voided: Vec<BillLine>,
+}
+
+/// How much a Discount takes off the amount, never more than the amount itself.
+fn discount_amount(amount: i64, discount: &Discount) -> i64 {
+    let value = match discount.kind {
+        DiscountKind::Percent => apply_rate(amount, discount.value),
+        DiscountKind::Fixed => discount.value,
+    };
+    value.clamp(0, amount.max(0))
+}
+
+impl Bill {
+    /// Sums up the Orders and takes off the line level discounts, then the bill level discounts.
+    /// Voided Orders are left out along with their discounts.
+    /// The service charge and the tax are assessed on the discounted subtotal, see [`TaxConfig::assess`].
+    pub fn compute(
+        table_number: i32,
+        session_id: i64,
+        orders: &[Order],
+        discounts: &[Discount],
+        tax_config: TaxConfig,
+    ) -> Self {
+        let (voided, orders): (Vec<&Order>, Vec<&Order>) =
+            orders.iter().partition(|order| order.voided_at.is_some());
+        let mut lines: Vec<BillLine> = orders.into_iter().map(BillLine::from_order).collect();
+        for discount in discounts {
+            let line = lines
+                .iter_mut()
+                .find(|line| Some(line.order_id) == discount.order_id);
+            if let Some(line) = line {
+                line.discount += discount_amount(line.amount - line.discount, discount);
+            }
+        }
+        let subtotal: i64 = lines.iter().map(|line| line.amount).sum();
+        let line_discount: i64 = lines.iter().map(|line| line.discount).sum();
+
+        // discounts stack, each one is taken from what is left by the previous ones:
+        let bill_discount = discounts
+            .iter()
+            .filter(|discount| discount.order_id.is_none())
+            .fold(0, |taken, discount| {
+                taken + discount_amount(subtotal - line_discount - taken, discount)
+            });
+
+        let discount = line_discount + bill_discount;
+        let assessment = tax_config.assess(subtotal - discount);
+        Self {
+            session_id,
+            table_number,
+            lines,
+            subtotal,
+            discount,
+            service_charge: assessment.service_charge,
+            tax: assessment.tax,
+            rounding: assessment.rounding,
+            total: assessment.total,
+            tax_inclusive: tax_config.mode == TaxMode::Inclusive,
+            voided: voided.into_iter().map(BillLine::from_order).collect(),
+        }
+    }
+
+    /// What a part owes given its shares, the service charge and the tax are only added on top when exclusive.
+    fn part_total(&self, taxable: i64, service_charge: i64, tax: i64, rounding: i64) -> i64 {
+        match self.tax_inclusive {
+            true => taxable + rounding,
+            false => taxable + service_charge + tax + rounding,
+        }
+    }
+}
+
+/// A sub-bill, the parts of a split always add up to the Bill it was split from.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SplitPart {
+    pub seat_number: Option<i32>,
+    pub lines: Vec<BillLine>,
+    pub subtotal: i64,
+    pub discount: i64,
+    pub service_charge: i64,
+    pub tax: i64,
+    pub rounding: i64,
+    pub total: i64,
+}
+
+/// Divides an amount proportionally to the weights using the largest remainder method,
+/// so the shares always add up to the amount. Equal weights are used when they are all zero.
+fn allocate(amount: i64, weights: &[i64]) -> Vec<i64> {
+    if amount < 0 {
+        return allocate(-amount, weights).into_iter().map(|v| -v).collect();
+    }
+    let weights: Vec<i64> = match weights.iter().sum::<i64>() {
+        0 => vec![1; weights.len()],
+        _ => weights.to_vec(),
+    };
+    let total_weight: i64 = weights.iter().sum();
+    if total_weight == 0 {
+        return vec![];
+    }
+
+    let mut shares: Vec<i64> = weights.iter().map(|w| amount * w / total_weight).collect();
+    let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+    // the earlier part wins a tie, so the result is stable:
+    by_remainder.sort_by_key(|&i| std::cmp::Reverse(amount * weights[i] % total_weight));
+    let leftover = amount - shares.iter().sum::<i64>();
+    for &i in by_remainder.iter().take(leftover as usize) {
+        shares[i] += 1;
+    }
+    shares
+}
+
+/// Splits the Bill into N equal parts, the first parts absorb the leftover minor units.
+pub fn split_even(bill: &Bill, parts: usize) -> Vec<SplitPart> {
+    let weights = vec![1; parts];
+    let subtotals = allocate(bill.subtotal, &weights);
+    let discounts = allocate(bill.discount, &weights);
+    let service_charges = allocate(bill.service_charge, &weights);
+    let taxes = allocate(bill.tax, &weights);
+    let roundings = allocate(bill.rounding, &weights);
+    (0..parts)
+        .map(|i| SplitPart {
+            seat_number: None,
+            lines: vec![],
+            subtotal: subtotals[i],
+            discount: discounts[i],
+            service_charge: service_charges[i],
+            tax: taxes[i],
+            rounding: roundings[i],
+            total: bill.part_total(
+                subtotals[i] - discounts[i],
+                service_charges[i],
+                taxes[i],
+                roundings[i],
+            ),
+        })
+        .collect()
+}
+
+/// Builds one part per group of lines. Line level discounts stay with their lines, while the bill
+/// level discounts, the service charge, the tax and the rounding are shared proportionally to what each part owes.
+fn split_lines(bill: &Bill, groups: Vec<(Option<i32>, Vec<BillLine>)>) -> Vec<SplitPart> {
+    let subtotals: Vec<i64> = groups
+        .iter()
+        .map(|(_, lines)| lines.iter().map(|line| line.amount).sum())
+        .collect();
+    let line_discounts: Vec<i64> = groups
+        .iter()
+        .map(|(_, lines)| lines.iter().map(|line| line.discount).sum())
+        .collect();
+    let nets: Vec<i64> = (0..groups.len())
+        .map(|i| subtotals[i] - line_discounts[i])
+        .collect();
+    let bill_discounts = allocate(bill.discount - line_discounts.iter().sum::<i64>(), &nets);
+    let discounts: Vec<i64> = (0..groups.len())
+        .map(|i| line_discounts[i] + bill_discounts[i])
+        .collect();
+    let taxables: Vec<i64> = (0..groups.len())
+        .map(|i| subtotals[i] - discounts[i])
+        .collect();
+    let service_charges = allocate(bill.service_charge, &taxables);
+    let taxes = allocate(bill.tax, &taxables);
+    let roundings = allocate(bill.rounding, &taxables);
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(i, (seat_number, lines))| SplitPart {
+            seat_number,
+            lines,
+            subtotal: subtotals[i],
+            discount: discounts[i],
+            service_charge: service_charges[i],
+            tax: taxes[i],
+            rounding: roundings[i],
+            total: bill.part_total(taxables[i], service_charges[i], taxes[i], roundings[i]),
+        })
+        .collect()
+}
+
+/// Splits the Bill by groups of order IDs, every ID has to be on the Bill and in one group only.
+pub fn split_orders(bill: &Bill, groups: &[Vec<i64>]) -> Result<Vec<SplitPart>, String> {
+    let mut seen = HashSet::new();
+    let mut line_groups = vec![];
+    for group in groups {
+        let mut lines = vec![];
+        for order_id in group {
+            if !seen.insert(*order_id) {
+                return Err(format!("order {} is in more than one group", order_id));
+            }
+            match bill.lines.iter().find(|line| line.order_id == *order_id) {
+                Some(line) => lines.push(line.clone()),
+                None => return Err(format!("order {} is not on the bill", order_id)),
+            }
+        }
+        line_groups.push((None, lines));
+    }
+
+    let rest: Vec<BillLine> = bill
+        .lines
+        .iter()
+        .filter(|line| !seen.contains(&line.order_id))
+        .cloned()
+        .collect();
+    if !rest.is_empty() {
+        line_groups.push((None, rest));
+    }
+    Ok(split_lines(bill, line_groups))
+}
+
+/// Splits the Bill by seat in ascending order, orders without a seat come last as a shared part.
+pub fn split_seats(bill: &Bill) -> Vec<SplitPart> {
+    let mut seats: Vec<Option<i32>> = bill.lines.iter().map(|line| line.seat_number).collect();
+    seats.sort_by_key(|seat| (seat.is_none(), *seat));
+    seats.dedup();
+
+    let line_groups = seats
+        .into_iter()
+        .map(|seat| {
+            let lines = bill
+                .lines
+                .iter()
+                .filter(|line| line.seat_number == seat)
+                .cloned()
+                .collect();
+            (seat, lines)
+        })
+        .collect();
+    split_lines(bill, line_groups)
+}
+
+/// Builds the Bill of a Table session, defaults to the session of the guests currently seated.
+/// A session which is checked out already is billed with the tax configuration it was settled with.
+/// Returns None when the Table has no such session.
+pub async fn load_bill(
+    order_repository: &dyn db::order::Repository,
+    session_repository: &dyn db::session::Repository,
+    discount_repository: &dyn db::discount::Repository,
+    table_number: i32,
+    session_id: Option<i64>,
+) -> Result<Option<Bill>, OperationError> {
+    build_bill(
+        order_repository,
+        session_repository,
+        discount_repository,
+        table_number,
+        session_id,
+        None,
+    )
+    .await
+}
+
+/// Builds the Bill of the guests currently seated on a Table as it would be with one more Discount given,
+/// so the Discount can be checked before it is stored. Returns None when the Table has no active session.
+pub async fn preview_discount(
+    order_repository: &dyn db::order::Repository,
+    session_repository: &dyn db::session::Repository,
+    discount_repository: &dyn db::discount::Repository,
+    table_number: i32,
+    discount: &Discount,
+) -> Result<Option<Bill>, OperationError> {
+    build_bill(
+        order_repository,
+        session_repository,
+        discount_repository,
+        table_number,
+        None,
+        Some(discount),
+    )
+    .await
+}
+
+async fn build_bill(
+    order_repository: &dyn db::order::Repository,
+    session_repository: &dyn db::session::Repository,
+    discount_repository: &dyn db::discount::Repository,
+    table_number: i32,
+    session_id: Option<i64>,
+    extra_discount: Option<&Discount>,
+) -> Result<Option<Bill>, OperationError> {
+    let session = match session_id {
+        Some(session_id) => {
+            session_repository
+                .get_session(table_number, session_id)
+                .await?
+        }
+        None => session_repository.get_active_session(table_number).await?,
+    };
+    let session = match session {
+        Some(session) => session,
+        None => return Ok(None),
+    };
+    let orders = order_repository
+        .list_all_by_session(table_number, session.session_id)
+        .await?;
+    let mut discounts = discount_repository
+        .list_by_session(session.session_id)
+        .await?;
+    discounts.extend(extra_discount.cloned());
+    Ok(Some(Bill::compute(
+        table_number,
+        session.session_id,
+        &orders,
+        &discounts,
+        session.tax_config().unwrap_or_else(TaxConfig::from_env),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tax::RoundingMode;
+
+    fn order(order_id: i64, quantity: i32, unit_price: i64) -> Order {
+        Order {
+            order_id,
+            quantity,
+            unit_price,
+            ..Order::new(3, 1, 5)
+        }
+    }
+
+    fn tax_config(mode: TaxMode, rounding_unit: i64) -> TaxConfig {
+        TaxConfig {
+            service_charge: 500,
+            tax: 1_000,
+            mode,
+            rounding_unit,
+            rounding_mode: RoundingMode::HalfUp,
+        }
+    }
+
+    #[test]
+    fn test_compute() {
+        let orders = vec![order(1, 2, 35_000), order(2, 1, 18_000)];
+        let rates = tax_config(TaxMode::Exclusive, 1);
+
+        let bill = Bill::compute(3, 7, &orders, &[], rates);
+
+        assert_eq!(bill.lines.len(), 2);
+        assert_eq!(bill.lines[0].amount, 70_000);
+        assert_eq!(bill.subtotal, 88_000);
+        assert_eq!(bill.service_charge, 4_400);
+        assert_eq!(bill.tax, 9_240);
+        assert_eq!(bill.total, 101_640);
+    }
+
+    #[test]
+    fn test_compute_empty() {
+        let bill = Bill::compute(3, 7, &[], &[], tax_config(TaxMode::Exclusive, 1));
+        assert!(bill.lines.is_empty());
+        assert_eq!(bill.total, 0);
+    }
+
+    #[test]
+    fn test_compute_discounts() {
+        let orders = vec![order(1, 2, 35_000), order(2, 1, 18_000)];
+        let discounts = vec![
+            // half off the second order:
+            Discount::new(7, Some(2), DiscountKind::Percent, 5_000),
+            Discount::new(7, None, DiscountKind::Fixed, 10_000),
+            // more than what is left on the order:
+            Discount::new(7, Some(2), DiscountKind::Fixed, 50_000),
+        ];
+        let rates = tax_config(TaxMode::Exclusive, 1);
+
+        let bill = Bill::compute(3, 7, &orders, &discounts, rates);
+
+        assert_eq!(bill.lines[0].discount, 0);
+        assert_eq!(bill.lines[1].discount, 18_000);
+        assert_eq!(bill.subtotal, 88_000);
+        assert_eq!(bill.discount, 28_000);
+        assert_eq!(bill.service_charge, 3_000);
+        assert_eq!(bill.tax, 6_300);
+        assert_eq!(bill.total, 69_300);
+    }
+
+    #[test]
+    fn test_compute_voided() {
+        let orders = vec![
+            order(1, 2, 35_000),
+            Order {
+                voided_at: Some(time::OffsetDateTime::now_utc()),
+                ..order(2, 1, 18_000)
+            },
+        ];
+        // the discount of the voided order goes with it:
+        let discounts = vec![Discount::new(7, Some(2), DiscountKind::Fixed, 5_000)];
+
+        let bill = Bill::compute(3, 7, &orders, &discounts, tax_config(TaxMode::Exclusive, 1));
+
+        assert_eq!(bill.lines.len(), 1);
+        assert_eq!(bill.voided.len(), 1);
+        assert_eq!(bill.voided[0].order_id, 2);
+        assert_eq!(bill.subtotal, 70_000);
+        assert_eq!(bill.discount, 0);
+        assert_eq!(bill.total, 80_850);
+    }
+
+    fn seated_order(order_id: i64, seat_number: Option<i32>, unit_price: i64) -> Order {
+        Order {
+            order_id,
+            session_id: Some(7),
+            seat_number,
+            unit_price,
+            ..Order::new(3, 1, 5)
+        }
+    }
+
+    #[test]
+    fn test_compute_inclusive_rounding() {
+        let orders = vec![order(1, 1, 115_549)];
+
+        let bill = Bill::compute(3, 7, &orders, &[], tax_config(TaxMode::Inclusive, 100));
+
+        assert!(bill.tax_inclusive);
+        assert_eq!(bill.subtotal, 115_549);
+        assert_eq!(bill.service_charge, 5_002);
+        assert_eq!(bill.tax, 10_504);
+        assert_eq!(bill.rounding, -49);
+        assert_eq!(bill.total, 115_500);
+    }
+
+    fn bill() -> Bill {
+        bill_with(tax_config(TaxMode::Exclusive, 1))
+    }
+
+    fn bill_with(tax_config: TaxConfig) -> Bill {
+        let orders = vec![
+            seated_order(1, Some(2), 35_000),
+            seated_order(2, Some(1), 18_000),
+            seated_order(3, None, 33_333),
+        ];
+        let discounts = vec![
+            Discount::new(7, Some(1), DiscountKind::Percent, 1_000),
+            Discount::new(7, None, DiscountKind::Fixed, 5_000),
+        ];
+        Bill::compute(3, 7, &orders, &discounts, tax_config)
+    }
+
+    fn assert_adds_up(bill: &Bill, parts: &[SplitPart]) {
+        assert_eq!(parts.iter().map(|p| p.subtotal).sum::<i64>(), bill.subtotal);
+        assert_eq!(parts.iter().map(|p| p.discount).sum::<i64>(), bill.discount);
+        assert_eq!(
+            parts.iter().map(|p| p.service_charge).sum::<i64>(),
+            bill.service_charge
+        );
+        assert_eq!(parts.iter().map(|p| p.tax).sum::<i64>(), bill.tax);
+        assert_eq!(parts.iter().map(|p| p.rounding).sum::<i64>(), bill.rounding);
+        assert_eq!(parts.iter().map(|p| p.total).sum::<i64>(), bill.total);
+    }
+
+    #[test]
+    fn test_allocate() {
+        assert_eq!(allocate(100, &[1, 1, 1]), vec![34, 33, 33]);
+        assert_eq!(allocate(10, &[1, 3]), vec![3, 7]);
+        assert_eq!(allocate(7, &[0, 0]), vec![4, 3]);
+        assert_eq!(allocate(0, &[5, 5]), vec![0, 0]);
+        assert_eq!(allocate(-10, &[1, 3]), vec![-3, -7]);
+    }
+
+    #[test]
+    fn test_split_even() {
+        let bill = bill();
+        let parts = split_even(&bill, 3);
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].total - parts[2].total <= 3);
+        assert_adds_up(&bill, &parts);
+    }
+
+    #[test]
+    fn test_split_orders() {
+        let bill = bill();
+        let parts = split_orders(&bill, &[vec![1], vec![2]]).unwrap();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].subtotal, 35_000);
+        assert!(parts[0].discount >= 3_500);
+        assert_eq!(parts[2].lines[0].order_id, 3);
+        assert_adds_up(&bill, &parts);
+
+        assert!(split_orders(&bill, &[vec![1], vec![1]]).is_err());
+        assert!(split_orders(&bill, &[vec![9]]).is_err());
+    }
+
+    #[test]
+    fn test_split_seats() {
+        let bill = bill();
+        let parts = split_seats(&bill);
+        let seats: Vec<Option<i32>> = parts.iter().map(|p| p.seat_number).collect();
+        assert_eq!(seats, vec![Some(1), Some(2), None]);
+        assert_adds_up(&bill, &parts);
+    }
+
+    #[test]
+    fn test_split_inclusive_rounding() {
+        let bill = bill_with(tax_config(TaxMode::Inclusive, 100));
+        assert_ne!(bill.rounding, 0);
+        assert_eq!(bill.total % 100, 0);
+        assert_adds_up(&bill, &split_even(&bill, 3));
+        assert_adds_up(&bill, &split_seats(&bill));
+        assert_adds_up(&bill, &split_orders(&bill, &[vec![2]]).unwrap());
+    }
+}
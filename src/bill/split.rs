@@ -0,0 +1,309 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, post, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{self, OperationError},
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+use super::{load_bill, split_even, split_orders, split_seats, Bill, SplitPart};
+
+/// How the guests want to split the bill.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SplitMode {
+    /// N equal parts.
+    Even { parts: u32 },
+    /// one part per group of order IDs, orders left out of every group make up one more part.
+    Orders { groups: Vec<Vec<i64>> },
+    /// one part per seat, orders without a seat make up one shared part.
+    Seat,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    #[serde(flatten)]
+    mode: SplitMode,
+    session_id: Option<u32>,
+}
+
+/// The input data to split the Bill of a Table.
+struct Input {
+    table_number: u32,
+    mode: SplitMode,
+    session_id: Option<u32>,
+}
+
+impl Input {
+    fn new(rb: RequestBody, path_params: PathParams) -> Self {
+        Self {
+            table_number: path_params.table_number,
+            mode: rb.mode,
+            session_id: rb.session_id,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, SplitFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(invalid_input("table_number must be in range of 1 to 100"));
+        }
+        match &self.mode {
+            SplitMode::Even { parts } if *parts < 2 || *parts > 20 => {
+                Err(invalid_input("parts must be in range of 2 to 20"))
+            }
+            SplitMode::Orders { groups }
+                if groups.is_empty() || groups.iter().any(|g| g.is_empty()) =>
+            {
+                Err(invalid_input(
+                    "groups must contain at least one non-empty group",
+                ))
+            }
+            _ => Ok(self),
+        }
+    }
+}
+
+fn invalid_input(message: &str) -> SplitFailure {
+    SplitFailure::InvalidInput(BadRequestBody {
+        error: true,
+        message: message.to_string(),
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    table_number: u32,
+}
+
+#[derive(Debug)]
+enum SplitFailure {
+    InvalidInput(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl fmt::Display for SplitFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to split bill")
+    }
+}
+
+impl ResponseError for SplitFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            SplitFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            SplitFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            SplitFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            SplitFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    bill: Bill,
+    parts: Vec<SplitPart>,
+}
+
+#[post("/bill/split")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    path_params: web::Path<PathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, SplitFailure> {
+    let input = Input::new(request_body.into_inner(), path_params.into_inner()).validate()?;
+
+    let bill = load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        input.table_number as i32,
+        input.session_id.map(|v| v as i64),
+    )
+    .await
+    .map_err(SplitFailure::InternalServerError)?;
+    let bill = match bill {
+        Some(bill) => bill,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    let parts = match &input.mode {
+        SplitMode::Even { parts } => split_even(&bill, *parts as usize),
+        SplitMode::Orders { groups } => {
+            split_orders(&bill, groups).map_err(|message| invalid_input(&message))?
+        }
+        SplitMode::Seat => split_seats(&bill),
+    };
+    Ok(HttpResponse::Ok().json(SuccessResponseBody { bill, parts }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{order::Order, session::Session};
+
+    fn discount_repo() -> Arc<dyn db::discount::Repository> {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(discount_repo)
+    }
+
+    fn order(order_id: i64, seat_number: Option<i32>, unit_price: i64) -> Order {
+        Order {
+            order_id,
+            session_id: Some(7),
+            seat_number,
+            unit_price,
+            ..Order::new(3, 1, 5)
+        }
+    }
+
+    fn app_data() -> (
+        Arc<dyn db::order::Repository>,
+        Arc<dyn db::session::Repository>,
+    ) {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .returning(|_, _| Ok(vec![order(1, Some(2), 35_000), order(2, Some(1), 18_000)]));
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        (Arc::new(order_repo), Arc::new(session_repo))
+    }
+
+    #[actix_web::test]
+    /// given: a single part.
+    /// when: splitting the bill evenly.
+    /// then: response status code is 400.
+    async fn test_invalid_parts() {
+        let (arc_order_repo, arc_session_repo) = app_data();
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/bill/split")
+            .set_json(serde_json::json!({"mode": "even", "parts": 1}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a group with an order which is not on the bill.
+    /// when: splitting the bill by orders.
+    /// then: response status code is 400.
+    async fn test_unknown_order() {
+        let (arc_order_repo, arc_session_repo) = app_data();
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/bill/split")
+            .set_json(serde_json::json!({"mode": "orders", "groups": [[1], [99]]}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: orders on two seats.
+    /// when: splitting the bill by seat.
+    /// then: response contains one part per seat adding up to the bill.
+    async fn test_success() {
+        let (arc_order_repo, arc_session_repo) = app_data();
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/bill/split")
+            .set_json(serde_json::json!({"mode": "seat"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.parts.len(), 2);
+        assert_eq!(response_body.parts[0].seat_number, Some(1));
+        let total: i64 = response_body.parts.iter().map(|p| p.total).sum();
+        assert_eq!(total, response_body.bill.total);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: splitting the bill evenly.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let order_repo = crate::db::order::MockRepository::new();
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/bill/split")
+            .set_json(serde_json::json!({"mode": "even", "parts": 2}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,66 @@
+use std::{env, process::ExitCode};
+
+use sukab_resto::db::adjustment::AdjustmentRepository;
+use sukab_resto::db::create_conn_pool;
+use sukab_resto::db::report::ReportRepository;
+use sukab_resto::report::{date_from_str, load_z_report, today};
+
+const USAGE: &str = "usage: admin z-report [YYYY-MM-DD] [--json]";
+
+fn set_global_logger() {
+    let rust_log_flag = "RUST_LOG";
+    match env::var(rust_log_flag) {
+        Ok(_) => {}
+        Err(_) => env::set_var(rust_log_flag, "warn"),
+    };
+    env_logger::init();
+}
+
+/// Prints the end-of-day report of a business day, today in the outlet time by default.
+async fn z_report(args: &[String]) -> ExitCode {
+    let json = args.iter().any(|arg| arg == "--json");
+    let date = match args.iter().find(|arg| !arg.starts_with("--")) {
+        Some(date) => match date_from_str(date) {
+            Some(date) => date,
+            None => {
+                eprintln!("date must be formatted as YYYY-MM-DD\n{}", USAGE);
+                return ExitCode::from(2);
+            }
+        },
+        None => today(),
+    };
+
+    let db_conn_pool = create_conn_pool();
+    let report_repo = ReportRepository::new(db_conn_pool.clone());
+    let adjustment_repo = AdjustmentRepository::new(db_conn_pool);
+    let report = match load_z_report(&report_repo, &adjustment_repo, date).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("failed to build the Z-report: {:?}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match json {
+        true => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        ),
+        false => print!("{}", report.to_text()),
+    }
+    ExitCode::SUCCESS
+}
+
+#[actix_web::main]
+async fn main() -> ExitCode {
+    set_global_logger();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("z-report") => z_report(&args[1..]).await,
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -35,6 +35,10 @@ impl UrlBuilder {
         self
     }
 
+    fn session_url(&self) -> String {
+        format!("{}/table/{}/session", self.base_url, self.table_number)
+    }
+
     fn url(&self) -> String {
         let url = format!("{}/table/{}/order", self.base_url, self.table_number);
         match self.order_id {
@@ -64,13 +68,52 @@ fn get_random_range_inclusive(min: i32, max: i32) -> i32 {
     rr.gen_range(min..=max)
 }
 
+fn send_open_session(table_number: i32) {
+    let url = UrlBuilder::new(table_number).session_url();
+
+    let client = reqwest::blocking::ClientBuilder::default().build().unwrap();
+
+    // a conflict only means the table has been seated already, which is fine for us:
+    let response = client.post(url).send();
+    match response {
+        Ok(v) => {
+            log::info!(
+                "open session on Table Number {}, status {:?}",
+                table_number,
+                v.status()
+            );
+        }
+        Err(e) => {
+            log::error!(
+                "open session on Table Number {}, failure {:?}",
+                table_number,
+                e
+            );
+        }
+    }
+}
+
+/// the access token of the waiter who places the orders.
+fn get_staff_token() -> String {
+    env::var("CLIENT_STAFF_TOKEN").unwrap_or("dev-waiter-token".to_string())
+}
+
+/// the access token of the manager who looks up and deletes the orders.
+fn get_manager_token() -> String {
+    env::var("CLIENT_MANAGER_TOKEN").unwrap_or("dev-manager-token".to_string())
+}
+
 fn send_create_order(table_number: i32, menu_id: i32) -> Result<i32, ()> {
     let url = UrlBuilder::new(table_number).url();
 
     let client = reqwest::blocking::ClientBuilder::default().build().unwrap();
     let req_body = CreateRequest { menu_id };
 
-    let response = client.post(url).json(&req_body).send();
+    let response = client
+        .post(url)
+        .bearer_auth(get_staff_token())
+        .json(&req_body)
+        .send();
     match response {
         Ok(v) => {
             let stat_code = v.status();
@@ -94,7 +137,7 @@ fn send_delete_order(table_number: i32, order_id: i32) {
 
     let client = reqwest::blocking::ClientBuilder::default().build().unwrap();
 
-    let response = client.delete(url).send();
+    let response = client.delete(url).bearer_auth(get_manager_token()).send();
     match response {
         Ok(v) => {
             log::info!("delete order ID {}, status {:?}", order_id, v.status());
@@ -108,7 +151,8 @@ fn send_delete_order(table_number: i32, order_id: i32) {
 fn send_detail_order(table_number: i32, order_id: i32) {
     let url = UrlBuilder::new(table_number).order_id(order_id).url();
 
-    let response = reqwest::blocking::get(url);
+    let client = reqwest::blocking::ClientBuilder::default().build().unwrap();
+    let response = client.get(url).bearer_auth(get_manager_token()).send();
     match response {
         Ok(v) => {
             let stat_code = v.status();
@@ -128,7 +172,9 @@ fn send_detail_order(table_number: i32, order_id: i32) {
 fn send_list_orders(table_number: i32) {
     let url = UrlBuilder::new(table_number).url();
 
-    let response = reqwest::blocking::get(url);
+    // the manager sees every Table, waiters only those of the sections they work:
+    let client = reqwest::blocking::ClientBuilder::default().build().unwrap();
+    let response = client.get(url).bearer_auth(get_manager_token()).send();
     match response {
         Ok(v) => {
             let stat_code = v.status();
@@ -142,13 +188,15 @@ fn send_list_orders(table_number: i32) {
         Err(e) => {
             log::error!(
                 "list orders by Table Number {}, failure {:?}",
-                table_number, e
+                table_number,
+                e
             );
         }
     }
 }
 
 fn send_request(table_number: i32, menu_id: i32) {
+    send_open_session(table_number);
     let order_id = send_create_order(table_number, menu_id).unwrap();
     send_list_orders(table_number);
     send_detail_order(table_number, order_id);
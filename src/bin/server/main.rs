@@ -1,11 +1,36 @@
 use std::{env, sync::Arc};
 
 use actix_web::{middleware::Logger, web, App, HttpServer};
-use sukab_resto::db::create_conn_pool;
+use sukab_resto::auth::guest::GuestTokenSigner;
+use sukab_resto::db::adjustment::{AdjustmentRepository, Repository as AdjustmentRepositoryTrait};
+use sukab_resto::db::discount::{DiscountRepository, Repository as DiscountRepositoryTrait};
 use sukab_resto::db::menu::{MenuRepository, Repository as MenuRepositoryTrait};
 use sukab_resto::db::order::{OrderRepository, Repository as OrderRepositoryTrait};
-use log;
-use sukab_resto::order::service;
+use sukab_resto::db::payment::{PaymentRepository, Repository as PaymentRepositoryTrait};
+use sukab_resto::db::price_rule::{PriceRuleRepository, Repository as PriceRuleRepositoryTrait};
+use sukab_resto::db::report::{ReportRepository, Repository as ReportRepositoryTrait};
+use sukab_resto::db::reservation::{
+    Repository as ReservationRepositoryTrait, ReservationRepository,
+};
+use sukab_resto::db::section::{Repository as SectionRepositoryTrait, SectionRepository};
+use sukab_resto::db::session::{Repository as SessionRepositoryTrait, SessionRepository};
+use sukab_resto::db::staff::{Repository as StaffRepositoryTrait, StaffRepository};
+use sukab_resto::db::station::{Repository as StationRepositoryTrait, StationRepository};
+use sukab_resto::db::table::{Repository as TableRepositoryTrait, TableRepository};
+use sukab_resto::db::ticket::{Repository as TicketRepositoryTrait, TicketRepository};
+use sukab_resto::db::waitlist::{Repository as WaitlistRepositoryTrait, WaitlistRepository};
+use sukab_resto::db::{create_conn_pool, pg_config};
+use sukab_resto::event::{listener, EventBus};
+use sukab_resto::kitchen::{
+    capacity::CapacityConfig,
+    printer::{self, PrintConfig},
+    priority::PriorityConfig,
+    scheduler,
+};
+use sukab_resto::order::{floor_service, service};
+use sukab_resto::payment::gateway::{FakeGateway, Gateway};
+use sukab_resto::receipt::sink::{self, Sink};
+use sukab_resto::{discount, event, kitchen, pricing, report, reservation, section, waitlist};
 
 /// get host:port pair for our HTTP server.
 fn get_host_port() -> (String, u16) {
@@ -39,6 +64,24 @@ async fn main() -> std::io::Result<()> {
     );
 
     let host_port = get_host_port();
+    let guest_token_signer = web::Data::new(GuestTokenSigner::from_env());
+    let event_bus = web::Data::new(EventBus::from_env());
+    let capacity_config = web::Data::new(CapacityConfig::from_env());
+    let priority_config = web::Data::new(PriorityConfig::from_env());
+    // the Order events are written along with the Orders, rebroadcast them to the local subscribers:
+    actix_web::rt::spawn(listener::run(pg_config(), event_bus.get_ref().clone()));
+    // every instance runs the scheduler, the Orders are locked while being marked so none is marked twice:
+    let scheduler_order_repo: Arc<dyn OrderRepositoryTrait> =
+        Arc::new(OrderRepository::new(db_conn_pool.clone()));
+    actix_web::rt::spawn(scheduler::run(
+        scheduler_order_repo,
+        capacity_config.get_ref().clone(),
+        scheduler::interval_from_env(),
+    ));
+    // every instance prints as well, a ticket is claimed by one of them at a time:
+    let printer_ticket_repo: Arc<dyn TicketRepositoryTrait> =
+        Arc::new(TicketRepository::new(db_conn_pool.clone()));
+    actix_web::rt::spawn(printer::run(printer_ticket_repo, PrintConfig::from_env()));
 
     let server = HttpServer::new(move || {
         let logger = Logger::default();
@@ -46,11 +89,67 @@ async fn main() -> std::io::Result<()> {
         let arc_order_repo: Arc<dyn OrderRepositoryTrait> = Arc::new(order_repo);
         let menu_repo = MenuRepository::new(db_conn_pool.clone());
         let arc_menu_repo: Arc<dyn MenuRepositoryTrait> = Arc::new(menu_repo);
+        let session_repo = SessionRepository::new(db_conn_pool.clone());
+        let arc_session_repo: Arc<dyn SessionRepositoryTrait> = Arc::new(session_repo);
+        let table_repo = TableRepository::new(db_conn_pool.clone());
+        let arc_table_repo: Arc<dyn TableRepositoryTrait> = Arc::new(table_repo);
+        let reservation_repo = ReservationRepository::new(db_conn_pool.clone());
+        let arc_reservation_repo: Arc<dyn ReservationRepositoryTrait> = Arc::new(reservation_repo);
+        let waitlist_repo = WaitlistRepository::new(db_conn_pool.clone());
+        let arc_waitlist_repo: Arc<dyn WaitlistRepositoryTrait> = Arc::new(waitlist_repo);
+        let staff_repo = StaffRepository::new(db_conn_pool.clone());
+        let arc_staff_repo: Arc<dyn StaffRepositoryTrait> = Arc::new(staff_repo);
+        let section_repo = SectionRepository::new(db_conn_pool.clone());
+        let arc_section_repo: Arc<dyn SectionRepositoryTrait> = Arc::new(section_repo);
+        let station_repo = StationRepository::new(db_conn_pool.clone());
+        let arc_station_repo: Arc<dyn StationRepositoryTrait> = Arc::new(station_repo);
+        let ticket_repo = TicketRepository::new(db_conn_pool.clone());
+        let arc_ticket_repo: Arc<dyn TicketRepositoryTrait> = Arc::new(ticket_repo);
+        let payment_repo = PaymentRepository::new(db_conn_pool.clone());
+        let arc_payment_repo: Arc<dyn PaymentRepositoryTrait> = Arc::new(payment_repo);
+        let discount_repo = DiscountRepository::new(db_conn_pool.clone());
+        let arc_discount_repo: Arc<dyn DiscountRepositoryTrait> = Arc::new(discount_repo);
+        let price_rule_repo = PriceRuleRepository::new(db_conn_pool.clone());
+        let arc_price_rule_repo: Arc<dyn PriceRuleRepositoryTrait> = Arc::new(price_rule_repo);
+        let adjustment_repo = AdjustmentRepository::new(db_conn_pool.clone());
+        let arc_adjustment_repo: Arc<dyn AdjustmentRepositoryTrait> = Arc::new(adjustment_repo);
+        let report_repo = ReportRepository::new(db_conn_pool.clone());
+        let arc_report_repo: Arc<dyn ReportRepositoryTrait> = Arc::new(report_repo);
+        let arc_gateway: Arc<dyn Gateway> = Arc::new(FakeGateway);
+        let arc_receipt_sink: Arc<dyn Sink> = Arc::from(sink::from_env());
         App::new()
             .wrap(logger)
             .app_data(web::Data::from(arc_order_repo))
             .app_data(web::Data::from(arc_menu_repo))
+            .app_data(web::Data::from(arc_session_repo))
+            .app_data(web::Data::from(arc_table_repo))
+            .app_data(web::Data::from(arc_reservation_repo))
+            .app_data(web::Data::from(arc_waitlist_repo))
+            .app_data(web::Data::from(arc_staff_repo))
+            .app_data(web::Data::from(arc_section_repo))
+            .app_data(web::Data::from(arc_station_repo))
+            .app_data(web::Data::from(arc_ticket_repo))
+            .app_data(web::Data::from(arc_payment_repo))
+            .app_data(web::Data::from(arc_discount_repo))
+            .app_data(web::Data::from(arc_price_rule_repo))
+            .app_data(web::Data::from(arc_adjustment_repo))
+            .app_data(web::Data::from(arc_report_repo))
+            .app_data(web::Data::from(arc_gateway))
+            .app_data(web::Data::from(arc_receipt_sink))
+            .app_data(guest_token_signer.clone())
+            .app_data(event_bus.clone())
+            .app_data(capacity_config.clone())
+            .app_data(priority_config.clone())
             .service(service())
+            .service(floor_service())
+            .service(reservation::service())
+            .service(waitlist::service())
+            .service(section::service())
+            .service(discount::service())
+            .service(pricing::service())
+            .service(report::service())
+            .service(kitchen::service())
+            .service(event::service())
     })
     .bind(host_port.clone())?
     .run();
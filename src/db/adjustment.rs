@@ -0,0 +1,332 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+use time::OffsetDateTime;
+
+use super::{payment::PaymentMethod, OperationError};
+
+#[automock]
+#[async_trait]
+/// Adjustment repository abstraction, covers refunds and the void and refund totals of reports.
+/// Voiding itself is done on the Orders, see the Order repository.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// Store the Refund unless the refunds of its Payment would exceed the Payment amount,
+    /// returns None in that case.
+    async fn record_refund(&self, data: Refund) -> Result<Option<Refund>, OperationError>;
+    /// Set the reference of the payment gateway on a Refund once its money went back.
+    async fn set_refund_reference(
+        &self,
+        refund_id: i64,
+        reference: String,
+    ) -> Result<(), OperationError>;
+    /// Delete a Refund whose money could not be given back.
+    async fn delete_refund(&self, refund_id: i64) -> Result<(), OperationError>;
+    /// List the Refunds of a Table session, oldest first.
+    async fn list_by_session(&self, session_id: i64) -> Result<Vec<Refund>, OperationError>;
+    /// Sum up voided Orders and Refunds made within `[from, to)` by kind and reason.
+    async fn summarize(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<AdjustmentTotal>, OperationError>;
+}
+
+/// Why an Order was voided or money was refunded.
+#[derive(Debug, Clone, Copy, PartialEq, ToSql, FromSql)]
+#[postgres(name = "adjustment_reason")]
+pub enum ReasonCode {
+    /// the order was keyed in by mistake.
+    #[postgres(name = "entry_error")]
+    EntryError,
+    #[postgres(name = "guest_cancelled")]
+    GuestCancelled,
+    #[postgres(name = "kitchen_error")]
+    KitchenError,
+    #[postgres(name = "quality_complaint")]
+    QualityComplaint,
+    /// the guests paid more than they should have.
+    #[postgres(name = "overcharge")]
+    Overcharge,
+    #[postgres(name = "other")]
+    Other,
+}
+
+impl ReasonCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::EntryError => "entry_error",
+            Self::GuestCancelled => "guest_cancelled",
+            Self::KitchenError => "kitchen_error",
+            Self::QualityComplaint => "quality_complaint",
+            Self::Overcharge => "overcharge",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn parse(v: &str) -> Option<Self> {
+        match v {
+            "entry_error" => Some(Self::EntryError),
+            "guest_cancelled" => Some(Self::GuestCancelled),
+            "kitchen_error" => Some(Self::KitchenError),
+            "quality_complaint" => Some(Self::QualityComplaint),
+            "overcharge" => Some(Self::Overcharge),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+}
+
+/// Represents money given back to the guests against a single Payment, the way they paid.
+#[derive(FromRow, Clone)]
+pub struct Refund {
+    pub refund_id: i64,
+    pub session_id: i64,
+    pub payment_id: i64,
+    /// the refunded Order, None refunds (a part of) the whole bill.
+    pub order_id: Option<i64>,
+    pub method: PaymentMethod,
+    /// in minor units of the currency.
+    pub amount: i64,
+    pub reason: ReasonCode,
+    pub note: Option<String>,
+    /// the refund reference of the payment gateway, cash has none.
+    pub reference: Option<String>,
+    /// the manager who approved the Refund.
+    pub staff_id: Option<i64>,
+    pub created_at: OffsetDateTime,
+}
+
+impl Refund {
+    /// Create a new Refund entity to be stored later.
+    pub fn new(
+        session_id: i64,
+        payment_id: i64,
+        method: PaymentMethod,
+        amount: i64,
+        reason: ReasonCode,
+    ) -> Self {
+        Self {
+            refund_id: 0,
+            session_id,
+            payment_id,
+            order_id: None,
+            method,
+            amount,
+            reason,
+            note: None,
+            reference: None,
+            staff_id: None,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+/// How many voids or refunds were made for a reason and their amount.
+#[derive(FromRow, Debug, Clone, PartialEq)]
+pub struct AdjustmentTotal {
+    /// either `void` or `refund`.
+    pub kind: String,
+    pub reason: ReasonCode,
+    pub count: i64,
+    /// in minor units of the currency, voids count the Order amount before discounts.
+    pub amount: i64,
+}
+
+#[derive(Clone)]
+// Concrete implementation of adjustment repository
+// which uses PostgreSQL as its datastore.
+pub struct AdjustmentRepository {
+    db_pool: Pool,
+}
+
+impl AdjustmentRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl AdjustmentRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for AdjustmentRepository {
+    async fn record_refund(&self, data: Refund) -> Result<Option<Refund>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToCreate)?;
+
+        // same lock as payments of the session, so concurrent refunds can't give back more than was paid:
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&data.session_id])
+            .await
+            .map_err(OperationError::FailedToCreate)?;
+
+        let query = "INSERT INTO refunds (session_id, payment_id, order_id, method, amount, reason, note, reference, staff_id, created_at) SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9, $10 WHERE (SELECT COALESCE(SUM(amount), 0)::bigint FROM refunds WHERE payment_id = $2) + $5 <= (SELECT amount FROM payments WHERE payment_id = $2 AND session_id = $1) RETURNING refund_id";
+        let inserted = tx
+            .query_opt(
+                query,
+                &[
+                    &data.session_id,
+                    &data.payment_id,
+                    &data.order_id,
+                    &data.method,
+                    &data.amount,
+                    &data.reason,
+                    &data.note,
+                    &data.reference,
+                    &data.staff_id,
+                    &data.created_at,
+                ],
+            )
+            .await
+            .map_err(OperationError::FailedToCreate)?;
+        tx.commit().await.map_err(OperationError::FailedToCreate)?;
+
+        Ok(inserted.map(|row| {
+            let refund_id: i64 = row.try_get("refund_id").unwrap_or(0);
+            Refund { refund_id, ..data }
+        }))
+    }
+
+    async fn set_refund_reference(
+        &self,
+        refund_id: i64,
+        reference: String,
+    ) -> Result<(), OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "UPDATE refunds SET reference = $2 WHERE refund_id = $1";
+        conn.execute(query, &[&refund_id, &reference])
+            .await
+            .map(|_| ())
+            .map_err(OperationError::FailedToUpdate)
+    }
+
+    async fn delete_refund(&self, refund_id: i64) -> Result<(), OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "DELETE FROM refunds WHERE refund_id = $1";
+        conn.execute(query, &[&refund_id])
+            .await
+            .map(|_| ())
+            .map_err(OperationError::FailedToDelete)
+    }
+
+    async fn list_by_session(&self, session_id: i64) -> Result<Vec<Refund>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM refunds WHERE session_id = $1 ORDER BY created_at, refund_id";
+        conn.query(query, &[&session_id])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Refund::try_from_row(row).ok())
+                    .collect::<Vec<Refund>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn summarize(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<AdjustmentTotal>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT 'void' AS kind, void_reason AS reason, COUNT(*) AS count, COALESCE(SUM(unit_price * quantity), 0)::bigint AS amount FROM orders WHERE voided_at >= $1 AND voided_at < $2 GROUP BY void_reason UNION ALL SELECT 'refund' AS kind, reason, COUNT(*) AS count, COALESCE(SUM(amount), 0)::bigint AS amount FROM refunds WHERE created_at >= $1 AND created_at < $2 GROUP BY reason ORDER BY kind DESC, reason";
+        conn.query(query, &[&from, &to])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| AdjustmentTotal::try_from_row(row).ok())
+                    .collect::<Vec<AdjustmentTotal>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        create_conn_pool,
+        payment::{Payment, PaymentRepository, Repository as _},
+    };
+
+    #[actix_web::test]
+    #[ignore = "needs a PostgreSQL database with the schema imported"]
+    /// given: a card payment of 50000.
+    /// when: refunding it against the database.
+    /// then: the refund within the payment is stored, the one exceeding what is left of it is not,
+    /// the stored one takes the gateway reference and can be deleted again.
+    async fn test_record_refund() {
+        let db_pool = create_conn_pool();
+        let repo = AdjustmentRepository::new(db_pool.clone());
+        let session_id =
+            i64::MAX - (OffsetDateTime::now_utc().unix_timestamp_nanos() % 1_000_000) as i64;
+        let payment = PaymentRepository::new(db_pool.clone())
+            .record_payment(
+                Payment::new(
+                    session_id,
+                    3,
+                    PaymentMethod::Card,
+                    50000,
+                    Some("cap-1".to_string()),
+                ),
+                50000,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        let first = repo
+            .record_refund(Refund::new(
+                session_id,
+                payment.payment_id,
+                PaymentMethod::Card,
+                30000,
+                ReasonCode::Overcharge,
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(first.refund_id > 0);
+        let over = repo
+            .record_refund(Refund::new(
+                session_id,
+                payment.payment_id,
+                PaymentMethod::Card,
+                30000,
+                ReasonCode::Overcharge,
+            ))
+            .await
+            .unwrap();
+        assert!(over.is_none());
+        repo.set_refund_reference(first.refund_id, "ref-1".to_string())
+            .await
+            .unwrap();
+        let refunds = repo.list_by_session(session_id).await.unwrap();
+        assert_eq!(refunds.len(), 1);
+        assert_eq!(refunds[0].reference, Some("ref-1".to_string()));
+        repo.delete_refund(first.refund_id).await.unwrap();
+        assert!(repo.list_by_session(session_id).await.unwrap().is_empty());
+
+        let conn = db_pool.get().await.unwrap();
+        conn.execute("DELETE FROM refunds WHERE session_id = $1", &[&session_id])
+            .await
+            .unwrap();
+        conn.execute("DELETE FROM payments WHERE session_id = $1", &[&session_id])
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,274 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+use time::OffsetDateTime;
+
+use super::OperationError;
+
+#[automock]
+#[async_trait]
+/// Discount and promo code repository abstraction.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// Store a Discount given on a Table session.
+    async fn add_discount(&self, data: Discount) -> Result<Discount, OperationError>;
+    /// List the Discounts given on a Table session, oldest first.
+    async fn list_by_session(&self, session_id: i64) -> Result<Vec<Discount>, OperationError>;
+    /// Store a new promo code, returns None when the code is taken already.
+    async fn create_promo_code(&self, data: PromoCode) -> Result<Option<PromoCode>, OperationError>;
+    /// Get a promo code by its code.
+    async fn get_promo_code(&self, code: String) -> Result<Option<PromoCode>, OperationError>;
+    /// Use up one redemption of the promo code and store its Discount on the session,
+    /// returns None when the code is out of its validity window or has no uses left at `at`.
+    async fn redeem_promo_code(
+        &self,
+        code: String,
+        session_id: i64,
+        at: OffsetDateTime,
+    ) -> Result<Option<Discount>, OperationError>;
+}
+
+/// How the value of a Discount is applied.
+#[derive(Debug, Clone, Copy, PartialEq, ToSql, FromSql)]
+#[postgres(name = "discount_kind")]
+pub enum DiscountKind {
+    /// the value is in basis points (1% = 100) of the discounted amount.
+    #[postgres(name = "percent")]
+    Percent,
+    /// the value is an amount in minor units of the currency.
+    #[postgres(name = "fixed")]
+    Fixed,
+}
+
+impl DiscountKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Percent => "percent",
+            Self::Fixed => "fixed",
+        }
+    }
+
+    pub fn parse(v: &str) -> Option<Self> {
+        match v {
+            "percent" => Some(Self::Percent),
+            "fixed" => Some(Self::Fixed),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a Discount given on a single Order (line level) or on the whole bill of a session.
+#[derive(FromRow, Clone)]
+pub struct Discount {
+    pub discount_id: i64,
+    pub session_id: i64,
+    /// the discounted Order, None discounts the whole bill.
+    pub order_id: Option<i64>,
+    pub kind: DiscountKind,
+    pub value: i64,
+    pub promo_code: Option<String>,
+    pub reason: Option<String>,
+    /// the manager who gave the Discount, promo codes have none.
+    pub staff_id: Option<i64>,
+    pub created_at: OffsetDateTime,
+}
+
+impl Discount {
+    /// Create a new Discount entity to be stored later.
+    pub fn new(session_id: i64, order_id: Option<i64>, kind: DiscountKind, value: i64) -> Self {
+        Self {
+            discount_id: 0,
+            session_id,
+            order_id,
+            kind,
+            value,
+            promo_code: None,
+            reason: None,
+            staff_id: None,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+/// Represents a promo code which gives a bill level Discount within its validity window.
+#[derive(FromRow, Clone)]
+pub struct PromoCode {
+    pub code: String,
+    pub kind: DiscountKind,
+    pub value: i64,
+    pub starts_at: OffsetDateTime,
+    pub ends_at: OffsetDateTime,
+    /// how many times the code can be redeemed, None is unlimited.
+    pub max_uses: Option<i32>,
+    pub used_count: i32,
+    pub created_at: OffsetDateTime,
+}
+
+impl PromoCode {
+    /// Create a new promo code entity to be stored later.
+    pub fn new(
+        code: String,
+        kind: DiscountKind,
+        value: i64,
+        starts_at: OffsetDateTime,
+        ends_at: OffsetDateTime,
+        max_uses: Option<i32>,
+    ) -> Self {
+        Self {
+            code,
+            kind,
+            value,
+            starts_at,
+            ends_at,
+            max_uses,
+            used_count: 0,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+#[derive(Clone)]
+// Concrete implementation of discount repository
+// which uses PostgreSQL as its datastore.
+pub struct DiscountRepository {
+    db_pool: Pool,
+}
+
+impl DiscountRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl DiscountRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for DiscountRepository {
+    async fn add_discount(&self, data: Discount) -> Result<Discount, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "INSERT INTO discounts (discount_id, session_id, order_id, kind, value, promo_code, reason, staff_id, created_at) VALUES (DEFAULT, $1, $2, $3, $4, $5, $6, $7, $8) RETURNING discount_id";
+        conn.query_one(
+            query,
+            &[
+                &data.session_id,
+                &data.order_id,
+                &data.kind,
+                &data.value,
+                &data.promo_code,
+                &data.reason,
+                &data.staff_id,
+                &data.created_at,
+            ],
+        )
+        .await
+        .map(|row| {
+            let discount_id: i64 = row.try_get("discount_id").unwrap_or(0);
+            Discount { discount_id, ..data }
+        })
+        .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn list_by_session(&self, session_id: i64) -> Result<Vec<Discount>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM discounts WHERE session_id = $1 ORDER BY created_at, discount_id";
+        conn.query(query, &[&session_id])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Discount::try_from_row(row).ok())
+                    .collect::<Vec<Discount>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn create_promo_code(&self, data: PromoCode) -> Result<Option<PromoCode>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "INSERT INTO promo_codes (code, kind, value, starts_at, ends_at, max_uses, used_count, created_at) VALUES ($1, $2, $3, $4, $5, $6, 0, $7) ON CONFLICT (code) DO NOTHING RETURNING *";
+        conn.query_opt(
+            query,
+            &[
+                &data.code,
+                &data.kind,
+                &data.value,
+                &data.starts_at,
+                &data.ends_at,
+                &data.max_uses,
+                &data.created_at,
+            ],
+        )
+        .await
+        .map(|row| row.and_then(|r| PromoCode::try_from_row(&r).ok()))
+        .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn get_promo_code(&self, code: String) -> Result<Option<PromoCode>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM promo_codes WHERE code = $1";
+        conn.query_opt(query, &[&code])
+            .await
+            .map(|row| row.and_then(|r| PromoCode::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn redeem_promo_code(
+        &self,
+        code: String,
+        session_id: i64,
+        at: OffsetDateTime,
+    ) -> Result<Option<Discount>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        // the usage limit is checked and bumped in one statement, so concurrent redemptions can't overuse it:
+        let query = "UPDATE promo_codes SET used_count = used_count + 1 WHERE code = $1 AND starts_at <= $2 AND ends_at > $2 AND (max_uses IS NULL OR used_count < max_uses) RETURNING *";
+        let promo_code = match tx
+            .query_opt(query, &[&code, &at])
+            .await
+            .map_err(OperationError::FailedToUpdate)?
+            .and_then(|r| PromoCode::try_from_row(&r).ok())
+        {
+            Some(promo_code) => promo_code,
+            None => return Ok(None),
+        };
+
+        let data = Discount {
+            promo_code: Some(promo_code.code),
+            created_at: at,
+            ..Discount::new(session_id, None, promo_code.kind, promo_code.value)
+        };
+        let query = "INSERT INTO discounts (discount_id, session_id, order_id, kind, value, promo_code, reason, staff_id, created_at) VALUES (DEFAULT, $1, NULL, $2, $3, $4, NULL, NULL, $5) RETURNING discount_id";
+        let row = tx
+            .query_one(
+                query,
+                &[
+                    &data.session_id,
+                    &data.kind,
+                    &data.value,
+                    &data.promo_code,
+                    &data.created_at,
+                ],
+            )
+            .await
+            .map_err(OperationError::FailedToCreate)?;
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+
+        let discount_id: i64 = row.try_get("discount_id").unwrap_or(0);
+        Ok(Some(Discount { discount_id, ..data }))
+    }
+}
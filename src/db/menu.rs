@@ -11,6 +11,8 @@ use super::OperationError;
 /// Use this trait as dependency to make the usecase function be easy testable via mocks.
 pub trait Repository {
     async fn get_by_id(&self, id: i64) -> Result<Menu, OperationError>;
+    /// List every Menu, ordered by its ID.
+    async fn list_all(&self) -> Result<Vec<Menu>, OperationError>;
 }
 
 #[derive(FromRow)]
@@ -18,12 +20,21 @@ pub struct Menu {
     #[from_row(rename = "menu_id")]
     pub id: i64,
     pub name: String,
+    /// the current price in minor units of the currency.
+    pub price: i64,
+    /// groups similar Menus, price rules may target a whole category.
+    pub category: Option<String>,
 }
 
 #[cfg(test)]
 impl Menu {
     pub fn new(id: i64, name: String) -> Self {
-        Self { id, name }
+        Self {
+            id,
+            name,
+            price: 0,
+            category: None,
+        }
     }
 }
 
@@ -52,7 +63,24 @@ impl Repository for MenuRepository {
                 conn.query_one(query, &[&id])
                     .await
                     .map(|row| Menu::from_row(&row))
-                    .map_err(|e| OperationError::FailedToCreate(e))
+                    .map_err(OperationError::FailedToCreate)
+            }
+        }
+    }
+
+    async fn list_all(&self) -> Result<Vec<Menu>, OperationError> {
+        match self.db_pool.get().await {
+            Err(e) => Err(OperationError::FailedToConnect(e)),
+            Ok(conn) => {
+                let query = "SELECT * FROM menus ORDER BY menu_id";
+                conn.query(query, &[])
+                    .await
+                    .map(|rows| {
+                        rows.iter()
+                            .filter_map(|row| Menu::try_from_row(row).ok())
+                            .collect::<Vec<Menu>>()
+                    })
+                    .map_err(OperationError::FailedToGetDetail)
             }
         }
     }
@@ -3,8 +3,21 @@ use std::env;
 use deadpool_postgres::{Manager, ManagerConfig, Pool, PoolError, RecyclingMethod};
 use tokio_postgres::{Error, NoTls};
 
+pub mod adjustment;
+pub mod discount;
 pub mod menu;
 pub mod order;
+pub mod payment;
+pub mod price_rule;
+pub mod report;
+pub mod reservation;
+pub mod section;
+pub mod session;
+pub mod staff;
+pub mod station;
+pub mod table;
+pub mod ticket;
+pub mod waitlist;
 
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -13,10 +26,12 @@ pub enum OperationError {
     FailedToCreate(Error),
     FailedToGetDetail(Error),
     FailedToDelete(Error),
+    FailedToUpdate(Error),
     OtherError,
 }
 
-pub fn create_conn_pool() -> Pool {
+/// Reads where the PostgreSQL database is and how to log into it from the env vars.
+pub fn pg_config() -> tokio_postgres::Config {
     let mut pg_config = tokio_postgres::Config::new();
     pg_config.host(
         env::var("PG_HOST")
@@ -40,7 +55,11 @@ pub fn create_conn_pool() -> Pool {
             .unwrap_or("sukab_restaurant".to_string())
             .as_str(),
     );
+    pg_config
+}
 
+pub fn create_conn_pool() -> Pool {
+    let pg_config = pg_config();
     let mgr_cfg = ManagerConfig {
         recycling_method: RecyclingMethod::Fast,
     };
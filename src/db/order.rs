@@ -2,32 +2,136 @@ use async_trait::async_trait;
 use deadpool_postgres::{GenericClient, Object, Pool};
 use mockall::automock;
 use postgres_from_row::FromRow;
-use postgres_types::ToSql;
+use postgres_types::{FromSql, ToSql};
 use time::OffsetDateTime;
 
-use super::OperationError;
+use crate::event::{OrderEvent, OrderEventKind, ORDER_EVENTS_CHANNEL};
+
+use super::{adjustment::ReasonCode, OperationError};
 
 #[automock]
 #[async_trait]
 /// Order repository abstraction.
+/// Every write announces what happened to the Orders as an `OrderEvent`, committed along with the write.
 /// Use this trait as dependency to make the usecase function be easy testable via mocks.
 pub trait Repository {
-    /// Store the Order entity into the datastore.
+    /// Store the Order entity into the datastore, an Order waiting for a course which is fired by then
+    /// goes to the kitchen right away along with its course, without the kitchen capacity.
     async fn create_order(&self, data: Order) -> Result<Order, OperationError>;
-    /// List Orders by Table number.
-    async fn list_by_table(
+    /// List Orders by Table number which belong to a single Table session.
+    async fn list_by_session(
+        &self,
+        table_number: i32,
+        session_id: i64,
+        page: i64,
+        limit: i64,
+    ) -> Result<Vec<Order>, OperationError>;
+    /// List every Order of a Table session, oldest first, to be put on the bill.
+    async fn list_all_by_session(
         &self,
         table_number: i32,
+        session_id: i64,
+    ) -> Result<Vec<Order>, OperationError>;
+    /// List Orders of every open Table session across the floor,
+    /// optionally narrowed down to Tables within the given sections.
+    async fn list_by_sections(
+        &self,
+        section_ids: Option<Vec<i64>>,
         page: i64,
         limit: i64,
     ) -> Result<Vec<Order>, OperationError>;
+    /// List the Orders still to be cooked, those of every open Table session which are cooking and not voided,
+    /// VIP Orders first then rush ones, each soonest due (`started_at` plus `eta` or `cook_time` minutes) first,
+    /// optionally narrowed down to the Tables of a section and to the Menus routed to a station.
+    async fn list_kitchen_queue(
+        &self,
+        section_id: Option<i64>,
+        station_id: Option<i64>,
+    ) -> Result<Vec<Order>, OperationError>;
     /// Get Order detail by its ID and table_number.
     async fn get_order_detail(
         &self,
         table_number: i32,
         order_id: i64,
     ) -> Result<Option<Order>, OperationError>;
-    /// Delete an Order.
+    /// Void Orders of a Table session, a single one or every Order still on the bill when `order_id` is None.
+    /// Nothing is voided once the session has Payments, those have to be refunded instead.
+    /// Returns the IDs of the voided Orders.
+    async fn void_orders(
+        &self,
+        table_number: i32,
+        session_id: i64,
+        order_id: Option<i64>,
+        void: Void,
+    ) -> Result<Vec<i64>, OperationError>;
+    /// Mark the cooking Orders which are due by `now` as ready, at most `limit` of them,
+    /// the soonest due first. Orders being marked by another server instance at the same time are skipped,
+    /// so every Order is marked exactly once however many instances run this.
+    /// Returns the Orders marked as ready.
+    async fn mark_ready(
+        &self,
+        now: OffsetDateTime,
+        limit: i64,
+    ) -> Result<Vec<Order>, OperationError>;
+    /// Count the Orders the kitchen has yet to get done, those cooking or held, which are not voided.
+    async fn count_outstanding(&self) -> Result<i64, OperationError>;
+    /// Start cooking the held Orders of open Table sessions, VIP then rush ones first and oldest first among those,
+    /// as long as neither `capacity` cooking Orders overall nor the capacity of their stations is reached.
+    /// Only one server instance releases at a time, the others release nothing meanwhile.
+    /// Returns the Orders which started cooking.
+    async fn release_held(
+        &self,
+        now: OffsetDateTime,
+        capacity: Option<i64>,
+    ) -> Result<Vec<Order>, OperationError>;
+    /// List the courses of a Table session which have been fired, in order.
+    async fn list_fired_courses(&self, session_id: i64) -> Result<Vec<i32>, OperationError>;
+    /// Fire a course of a Table session: its waiting Orders start cooking at `now`, their tickets are printed,
+    /// and the Orders placed for the course later go to the kitchen right away.
+    /// Returns the Orders which started cooking.
+    async fn fire_course(
+        &self,
+        table_number: i32,
+        session_id: i64,
+        course: i32,
+        staff_id: i64,
+        now: OffsetDateTime,
+    ) -> Result<Vec<Order>, OperationError>;
+    /// Change the priority of an Order which is not ready nor voided, along with the `eta` it comes with.
+    /// Returns None when there is no such Order still to be served.
+    async fn set_priority(
+        &self,
+        table_number: i32,
+        order_id: i64,
+        priority: OrderPriority,
+        eta: Option<i32>,
+    ) -> Result<Option<Order>, OperationError>;
+    /// Assign an Order still to be served to a cook, or to the cook on shift at `now` with the fewest
+    /// outstanding Orders when `cook_id` is None. A `claim` leaves an Order assigned to another cook as it is.
+    async fn assign_cook(
+        &self,
+        order_id: i64,
+        cook_id: Option<i64>,
+        claim: bool,
+        now: OffsetDateTime,
+    ) -> Result<CookOutcome, OperationError>;
+    /// Mark a cooking Order as ready at `now`, ahead of its due time, on behalf of a cook
+    /// who takes it over when it is not assigned yet. With no `cook_id` any Order is bumped as it is.
+    async fn bump(
+        &self,
+        order_id: i64,
+        cook_id: Option<i64>,
+        now: OffsetDateTime,
+    ) -> Result<CookOutcome, OperationError>;
+    /// List the workload of every cook: the Orders they have outstanding now,
+    /// and those they got done between `from` and `to`.
+    async fn list_cook_workloads(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<Vec<CookWorkload>, OperationError>;
+    /// Delete an Order which is not voided and whose bill has no payments yet, returns None otherwise.
     async fn delete_order(
         &self,
         table_number: i32,
@@ -35,31 +139,172 @@ pub trait Repository {
     ) -> Result<Option<i64>, OperationError>;
 }
 
+/// Where an Order is in the kitchen.
+#[derive(Debug, Clone, Copy, PartialEq, ToSql, FromSql)]
+#[postgres(name = "order_status")]
+pub enum OrderStatus {
+    #[postgres(name = "cooking")]
+    Cooking,
+    /// cooked and waiting to be served.
+    #[postgres(name = "ready")]
+    Ready,
+    /// waiting for room in the kitchen, its cook time has not started yet.
+    #[postgres(name = "held")]
+    Held,
+    /// waiting for its course to be fired, the kitchen has not seen it yet.
+    #[postgres(name = "waiting")]
+    Waiting,
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cooking => "cooking",
+            Self::Ready => "ready",
+            Self::Held => "held",
+            Self::Waiting => "waiting",
+        }
+    }
+}
+
+/// How urgently the kitchen should get an Order out.
+#[derive(Debug, Clone, Copy, PartialEq, ToSql, FromSql)]
+#[postgres(name = "order_priority")]
+pub enum OrderPriority {
+    #[postgres(name = "normal")]
+    Normal,
+    /// the guests are in a hurry, e.g. they have a train to catch.
+    #[postgres(name = "rush")]
+    Rush,
+    #[postgres(name = "vip")]
+    Vip,
+}
+
+impl OrderPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Rush => "rush",
+            Self::Vip => "vip",
+        }
+    }
+
+    pub fn parse(v: &str) -> Option<Self> {
+        match v {
+            "normal" => Some(Self::Normal),
+            "rush" => Some(Self::Rush),
+            "vip" => Some(Self::Vip),
+            _ => None,
+        }
+    }
+}
+
 /// Represents a single Order entity.
 #[derive(FromRow)]
 pub struct Order {
     pub order_id: i64,
     pub table_number: i32,
+    pub session_id: Option<i64>,
     pub menu_id: i32,
     pub cook_time: i32,
+    pub seat_number: Option<i32>,
+    pub quantity: i32,
+    /// the Menu price at the time of ordering, in minor units of the currency.
+    pub unit_price: i64,
     pub name: Option<String>,
+    /// how the guests want it changed, e.g. `no onion`, printed on the kitchen ticket.
+    pub modifiers: Vec<String>,
+    /// free text for the kitchen, printed on the kitchen ticket.
+    pub note: Option<String>,
+    /// the course the Order is served with, e.g. 1 for starters and 2 for mains,
+    /// None to send it to the kitchen right away.
+    pub course: Option<i32>,
+    pub status: OrderStatus,
+    pub priority: OrderPriority,
+    /// how many minutes the priority gives the kitchen instead of `cook_time`, None for `cook_time`.
+    pub eta: Option<i32>,
+    /// the cook the Order is assigned to.
+    pub cook_id: Option<i64>,
     pub created_at: OffsetDateTime,
+    /// when the kitchen started cooking, the Order is due `eta` or else `cook_time` minutes later.
+    /// Held Orders have not started yet.
+    pub started_at: Option<OffsetDateTime>,
+    /// set once the Order is cooked.
+    pub ready_at: Option<OffsetDateTime>,
+    /// set once the Order is voided, voided Orders stay for the records but are off the bill.
+    pub voided_at: Option<OffsetDateTime>,
+    pub void_reason: Option<ReasonCode>,
+    pub void_note: Option<String>,
+    /// the manager who voided the Order.
+    pub voided_by: Option<i64>,
 }
 
 impl Order {
     /// Create a new Order entity to be used later for creation/deletion.
     pub fn new(table_number: i32, menu_id: i32, cook_time: i32) -> Self {
+        let now = OffsetDateTime::now_utc();
         Self {
             order_id: 0,
             table_number,
+            session_id: None,
             menu_id,
             cook_time,
+            seat_number: None,
+            quantity: 1,
+            unit_price: 0,
             name: None,
-            created_at: OffsetDateTime::now_utc(),
+            modifiers: vec![],
+            note: None,
+            course: None,
+            status: OrderStatus::Cooking,
+            priority: OrderPriority::Normal,
+            eta: None,
+            cook_id: None,
+            created_at: now,
+            started_at: Some(now),
+            ready_at: None,
+            voided_at: None,
+            void_reason: None,
+            void_note: None,
+            voided_by: None,
         }
     }
 }
 
+/// What came of handing an Order to a cook.
+pub enum CookOutcome {
+    Done(Box<Order>),
+    /// no such Order still to be cooked.
+    NotFound,
+    /// the Order is assigned to this other cook.
+    Taken(i64),
+    /// no such cook, or no cook on shift to pick.
+    NoCook,
+}
+
+/// How much a cook has on their plate.
+#[derive(FromRow)]
+pub struct CookWorkload {
+    pub staff_id: i64,
+    pub name: String,
+    pub on_shift: bool,
+    /// the Orders assigned to the cook which are cooking or held, and how many portions they make.
+    pub outstanding: i64,
+    pub portions: i64,
+    /// the Orders the cook got done within the period, and how long they took on average.
+    pub done: i64,
+    pub average_minutes: Option<i64>,
+}
+
+/// Why and by whom Orders are voided.
+#[derive(Debug, Clone)]
+pub struct Void {
+    pub reason: ReasonCode,
+    pub note: Option<String>,
+    pub staff_id: i64,
+    pub at: OffsetDateTime,
+}
+
 #[derive(Clone)]
 // Concrete implementation of Order repository
 // which uses PostgreSQL as its datastore.
@@ -78,34 +323,126 @@ impl OrderRepository {
         self.db_pool
             .get()
             .await
-            .map_err(|e| OperationError::FailedToConnect(e))
+            .map_err(OperationError::FailedToConnect)
     }
 }
 
+/// Advisory lock key taken while releasing the held Orders, "held" in ASCII.
+/// It is used in the two keys form, which never meets the session ID keys of the payments.
+const RELEASE_HELD_LOCK: i32 = 0x68656c64;
+
+/// Advisory lock key taken while assigning Orders to cooks, "cook" in ASCII,
+/// so two Orders assigned at once never both go to the same least busy cook.
+const ASSIGN_COOK_LOCK: i32 = 0x636f6f6b;
+
+/// How many rows come before a page, pages count from 1 and 0 is taken as the first page.
+fn page_offset(page: i64, limit: i64) -> i64 {
+    (page.max(1) - 1) * limit
+}
+
+/// Queues the event on the Order events channel, PostgreSQL delivers it to the listeners
+/// of every server instance once the surrounding transaction commits, and drops it on rollback.
+async fn notify<C: GenericClient>(
+    client: &C,
+    event: &OrderEvent,
+) -> Result<(), tokio_postgres::Error> {
+    let payload = serde_json::to_string(event).unwrap_or_default();
+    client
+        .execute(
+            "SELECT pg_notify($1, $2)",
+            &[&ORDER_EVENTS_CHANNEL, &payload],
+        )
+        .await
+        .map(|_| ())
+}
+
+/// Queues the kitchen tickets of the Orders which started cooking, one for every station their Menu
+/// is routed to, or a single one for the default kitchen printer when it is routed nowhere.
+/// The tickets are printed once the surrounding transaction commits, and never on rollback.
+async fn queue_tickets<C: GenericClient>(
+    client: &C,
+    order_ids: &[i64],
+    now: &OffsetDateTime,
+) -> Result<(), tokio_postgres::Error> {
+    let query = "INSERT INTO kitchen_tickets (order_id, station_id, queued_at, next_attempt_at) SELECT o.order_id, ms.station_id, $2, $2 FROM orders o LEFT JOIN menu_stations ms ON ms.menu_id = o.menu_id WHERE o.order_id = ANY($1)";
+    client.execute(query, &[&order_ids, now]).await.map(|_| ())
+}
+
 #[async_trait]
 impl Repository for OrderRepository {
-    async fn create_order(&self, data: Order) -> Result<Order, OperationError> {
-        let conn = self.get_conn().await?;
+    async fn create_order(&self, mut data: Order) -> Result<Order, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToCreate)?;
+
+        // the course may have been fired meanwhile, the session lock keeps a fire from slipping in until commit:
+        if let (OrderStatus::Waiting, Some(session_id), Some(course)) =
+            (data.status, data.session_id, data.course)
+        {
+            tx.execute("SELECT pg_advisory_xact_lock($1)", &[&session_id])
+                .await
+                .map_err(OperationError::FailedToCreate)?;
+            let fired = tx
+                .query_opt(
+                    "SELECT 1 FROM fired_courses WHERE session_id = $1 AND course = $2",
+                    &[&session_id, &course],
+                )
+                .await
+                .map_err(OperationError::FailedToCreate)?;
+            // it joins the course as if placed just before the fire, so it skips the kitchen capacity like the rest:
+            if fired.is_some() {
+                data.status = OrderStatus::Cooking;
+                data.started_at = Some(data.created_at);
+            }
+        }
 
         let insert_params: &[&(dyn ToSql + Sync)] = &[
             &data.menu_id,
             &data.table_number,
             &data.cook_time,
             &data.created_at,
+            &data.session_id,
+            &data.quantity,
+            &data.unit_price,
+            &data.seat_number,
+            &data.status,
+            &data.started_at,
+            &data.modifiers,
+            &data.note,
+            &data.course,
+            &data.priority,
+            &data.eta,
         ];
-        let query = "INSERT INTO orders (order_id, menu_id, table_number, cook_time, created_at) VALUES (DEFAULT, $1, $2, $3, $4) RETURNING order_id";
-        conn.query_one(query, insert_params)
+        let query = "INSERT INTO orders (order_id, menu_id, table_number, cook_time, created_at, session_id, quantity, unit_price, seat_number, status, started_at, modifiers, note, course, priority, eta) VALUES (DEFAULT, $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) RETURNING order_id";
+        let order = tx
+            .query_one(query, insert_params)
             .await
             .map(|row| {
                 let order_id: i64 = row.try_get("order_id").unwrap_or(0);
                 Order { order_id, ..data }
             })
-            .map_err(|e| OperationError::FailedToCreate(e))
+            .map_err(OperationError::FailedToCreate)?;
+        if order.status == OrderStatus::Cooking {
+            queue_tickets(&tx, &[order.order_id], &order.created_at)
+                .await
+                .map_err(OperationError::FailedToCreate)?;
+        }
+        notify(
+            &tx,
+            &OrderEvent::from_order(OrderEventKind::Created, &order),
+        )
+        .await
+        .map_err(OperationError::FailedToCreate)?;
+        tx.commit().await.map_err(OperationError::FailedToCreate)?;
+        Ok(order)
     }
 
-    async fn list_by_table(
+    async fn list_by_session(
         &self,
         table_number: i32,
+        session_id: i64,
         page: i64,
         limit: i64,
     ) -> Result<Vec<Order>, OperationError> {
@@ -117,15 +454,73 @@ impl Repository for OrderRepository {
             v => v * limit,
         };
 
-        let query = "SELECT o.*, m.* FROM orders o INNER JOIN menus m ON o.menu_id = m.menu_id WHERE table_number = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3";
-        conn.query(query, &[&table_number, &limit, &offset])
+        let query = "SELECT o.*, m.* FROM orders o INNER JOIN menus m ON o.menu_id = m.menu_id WHERE table_number = $1 AND session_id = $2 ORDER BY created_at DESC LIMIT $3 OFFSET $4";
+        conn.query(query, &[&table_number, &session_id, &limit, &offset])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .map(|row| Order::try_from_row(row).unwrap_or(Order::new(0, 0, 0)))
+                    .collect::<Vec<Order>>()
+            })
+            .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn list_all_by_session(
+        &self,
+        table_number: i32,
+        session_id: i64,
+    ) -> Result<Vec<Order>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT o.*, m.* FROM orders o INNER JOIN menus m ON o.menu_id = m.menu_id WHERE table_number = $1 AND session_id = $2 ORDER BY created_at ASC, order_id ASC";
+        conn.query(query, &[&table_number, &session_id])
             .await
             .map(|rows| {
                 rows.iter()
                     .map(|row| Order::try_from_row(row).unwrap_or(Order::new(0, 0, 0)))
                     .collect::<Vec<Order>>()
             })
-            .map_err(|e| OperationError::FailedToCreate(e))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn list_by_sections(
+        &self,
+        section_ids: Option<Vec<i64>>,
+        page: i64,
+        limit: i64,
+    ) -> Result<Vec<Order>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let offset = page_offset(page, limit);
+
+        let query = "SELECT o.*, m.* FROM orders o INNER JOIN menus m ON o.menu_id = m.menu_id INNER JOIN table_sessions s ON s.session_id = o.session_id AND s.closed_at IS NULL INNER JOIN dining_tables t ON t.table_number = o.table_number WHERE ($1::bigint[] IS NULL OR t.section_id = ANY($1)) ORDER BY o.created_at DESC LIMIT $2 OFFSET $3";
+        conn.query(query, &[&section_ids, &limit, &offset])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .map(|row| Order::try_from_row(row).unwrap_or(Order::new(0, 0, 0)))
+                    .collect::<Vec<Order>>()
+            })
+            .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn list_kitchen_queue(
+        &self,
+        section_id: Option<i64>,
+        station_id: Option<i64>,
+    ) -> Result<Vec<Order>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        // open sessions come from their partial index, their pending Orders from orders_session_id_pending_index:
+        let query = "SELECT o.*, m.* FROM orders o INNER JOIN menus m ON o.menu_id = m.menu_id INNER JOIN table_sessions s ON s.session_id = o.session_id AND s.closed_at IS NULL INNER JOIN dining_tables t ON t.table_number = o.table_number WHERE o.voided_at IS NULL AND o.status = 'cooking' AND ($1::bigint IS NULL OR t.section_id = $1) AND ($2::bigint IS NULL OR EXISTS (SELECT 1 FROM menu_stations ms WHERE ms.menu_id = o.menu_id AND ms.station_id = $2)) ORDER BY o.priority DESC, o.started_at + COALESCE(o.eta, o.cook_time) * interval '1 minute', o.order_id";
+        conn.query(query, &[&section_id, &station_id])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Order::try_from_row(row).ok())
+                    .collect::<Vec<Order>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
     }
 
     async fn get_order_detail(
@@ -139,10 +534,401 @@ impl Repository for OrderRepository {
         conn.query_opt(query, &[&table_number, &order_id])
             .await
             .map(|row| match row {
-                Some(r) => Order::try_from_row(&r).map(|o| Some(o)).unwrap_or(None),
+                Some(r) => Order::try_from_row(&r).map(Some).unwrap_or(None),
                 None => None,
             })
-            .map_err(|e| OperationError::FailedToGetDetail(e))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn void_orders(
+        &self,
+        table_number: i32,
+        session_id: i64,
+        order_id: Option<i64>,
+        void: Void,
+    ) -> Result<Vec<i64>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        // same lock as payments of the session, so a payment can't slip in while voiding:
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&session_id])
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        let query = "UPDATE orders SET voided_at = $4, void_reason = $5, void_note = $6, voided_by = $7 WHERE table_number = $1 AND session_id = $2 AND ($3::bigint IS NULL OR order_id = $3) AND voided_at IS NULL AND NOT EXISTS (SELECT 1 FROM payments WHERE session_id = $2) RETURNING order_id, menu_id";
+        let voided = tx
+            .query(
+                query,
+                &[
+                    &table_number,
+                    &session_id,
+                    &order_id,
+                    &void.at,
+                    &void.reason,
+                    &void.note,
+                    &void.staff_id,
+                ],
+            )
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| {
+                        Some((row.try_get("order_id").ok()?, row.try_get("menu_id").ok()?))
+                    })
+                    .collect::<Vec<(i64, i32)>>()
+            })
+            .map_err(OperationError::FailedToUpdate)?;
+        // the tickets of voided orders are never printed, they are settled here rather than left pending:
+        let order_ids: Vec<i64> = voided.iter().map(|(order_id, _)| *order_id).collect();
+        tx.execute(
+            "UPDATE kitchen_tickets SET status = 'failed', last_error = 'voided' WHERE order_id = ANY($1) AND status = 'pending'",
+            &[&order_ids],
+        )
+        .await
+        .map_err(OperationError::FailedToUpdate)?;
+        for (order_id, menu_id) in voided.iter() {
+            let event = OrderEvent {
+                session_id: Some(session_id),
+                menu_id: Some(*menu_id),
+                ..OrderEvent::new(OrderEventKind::Cancelled, *order_id, table_number)
+            };
+            notify(&tx, &event)
+                .await
+                .map_err(OperationError::FailedToUpdate)?;
+        }
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+        Ok(order_ids)
+    }
+
+    async fn mark_ready(
+        &self,
+        now: OffsetDateTime,
+        limit: i64,
+    ) -> Result<Vec<Order>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        // the due rows are locked until commit, another instance skips them instead of marking them twice:
+        let query = "WITH due AS (SELECT order_id FROM orders WHERE status = 'cooking' AND voided_at IS NULL AND started_at + COALESCE(eta, cook_time) * interval '1 minute' <= $1 ORDER BY started_at + COALESCE(eta, cook_time) * interval '1 minute', order_id LIMIT $2 FOR UPDATE SKIP LOCKED), marked AS (UPDATE orders o SET status = 'ready', ready_at = $1 FROM due WHERE o.order_id = due.order_id RETURNING o.*) SELECT o.*, m.* FROM marked o INNER JOIN menus m ON o.menu_id = m.menu_id ORDER BY o.started_at + COALESCE(o.eta, o.cook_time) * interval '1 minute', o.order_id";
+        let orders = tx
+            .query(query, &[&now, &limit])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Order::try_from_row(row).ok())
+                    .collect::<Vec<Order>>()
+            })
+            .map_err(OperationError::FailedToUpdate)?;
+        for order in orders.iter() {
+            notify(
+                &tx,
+                &OrderEvent::from_order(OrderEventKind::StatusChanged, order),
+            )
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        }
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+        Ok(orders)
+    }
+
+    async fn count_outstanding(&self) -> Result<i64, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT COUNT(*) AS outstanding FROM orders WHERE status IN ('cooking', 'held') AND voided_at IS NULL";
+        conn.query_one(query, &[])
+            .await
+            .map(|row| row.try_get("outstanding").unwrap_or(0))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn release_held(
+        &self,
+        now: OffsetDateTime,
+        capacity: Option<i64>,
+    ) -> Result<Vec<Order>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        // the free room is counted once for every held Order, releasing from two instances at once would overfill it:
+        let locked: bool = tx
+            .query_one(
+                "SELECT pg_try_advisory_xact_lock($1, 0) AS locked",
+                &[&RELEASE_HELD_LOCK],
+            )
+            .await
+            .map(|row| row.try_get("locked").unwrap_or(false))
+            .map_err(OperationError::FailedToUpdate)?;
+        if !locked {
+            return Ok(vec![]);
+        }
+
+        // every held Order takes a place in line overall and at each of its stations,
+        // it is released when its place fits the free room everywhere. An Order which doesn't fit
+        // still takes its place, so the room is underfilled rather than overfilled until the next round:
+        let query = "WITH cooking AS (SELECT order_id, menu_id FROM orders WHERE status = 'cooking' AND voided_at IS NULL), held AS (SELECT o.order_id, o.menu_id, ROW_NUMBER() OVER (ORDER BY o.priority DESC, o.created_at, o.order_id) AS place FROM orders o INNER JOIN table_sessions s ON s.session_id = o.session_id AND s.closed_at IS NULL WHERE o.status = 'held' AND o.voided_at IS NULL), station_room AS (SELECT st.station_id, st.capacity - (SELECT COUNT(*) FROM cooking c INNER JOIN menu_stations mc ON mc.menu_id = c.menu_id WHERE mc.station_id = st.station_id) AS room FROM stations st WHERE st.capacity IS NOT NULL), blocked AS (SELECT p.order_id FROM (SELECT h.order_id, ms.station_id, ROW_NUMBER() OVER (PARTITION BY ms.station_id ORDER BY h.place) AS place FROM held h INNER JOIN menu_stations ms ON ms.menu_id = h.menu_id) p INNER JOIN station_room r ON r.station_id = p.station_id WHERE p.place > r.room), released AS (UPDATE orders o SET status = 'cooking', started_at = $1 FROM held h WHERE o.order_id = h.order_id AND ($2::bigint IS NULL OR h.place <= $2 - (SELECT COUNT(*) FROM cooking)) AND h.order_id NOT IN (SELECT order_id FROM blocked) RETURNING o.*) SELECT o.*, m.* FROM released o INNER JOIN menus m ON o.menu_id = m.menu_id ORDER BY o.created_at, o.order_id";
+        let orders = tx
+            .query(query, &[&now, &capacity])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Order::try_from_row(row).ok())
+                    .collect::<Vec<Order>>()
+            })
+            .map_err(OperationError::FailedToUpdate)?;
+        let order_ids: Vec<i64> = orders.iter().map(|o| o.order_id).collect();
+        queue_tickets(&tx, &order_ids, &now)
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        for order in orders.iter() {
+            notify(
+                &tx,
+                &OrderEvent::from_order(OrderEventKind::StatusChanged, order),
+            )
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        }
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+        Ok(orders)
+    }
+
+    async fn list_fired_courses(&self, session_id: i64) -> Result<Vec<i32>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT course FROM fired_courses WHERE session_id = $1 ORDER BY course";
+        conn.query(query, &[&session_id])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| row.try_get("course").ok())
+                    .collect::<Vec<i32>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn fire_course(
+        &self,
+        table_number: i32,
+        session_id: i64,
+        course: i32,
+        staff_id: i64,
+        now: OffsetDateTime,
+    ) -> Result<Vec<Order>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        // same lock as the Orders placed for a course, so none is left waiting for a course already fired:
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&session_id])
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        // firing twice keeps the first fire, the Orders waiting since are sent along anyway:
+        tx.execute(
+            "INSERT INTO fired_courses (session_id, course, fired_at, fired_by) VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING",
+            &[&session_id, &course, &now, &staff_id],
+        )
+        .await
+        .map_err(OperationError::FailedToUpdate)?;
+
+        let query = "WITH fired AS (UPDATE orders SET status = 'cooking', started_at = $4 WHERE table_number = $1 AND session_id = $2 AND course = $3 AND status = 'waiting' AND voided_at IS NULL RETURNING *) SELECT o.*, m.* FROM fired o INNER JOIN menus m ON o.menu_id = m.menu_id ORDER BY o.created_at, o.order_id";
+        let orders = tx
+            .query(query, &[&table_number, &session_id, &course, &now])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Order::try_from_row(row).ok())
+                    .collect::<Vec<Order>>()
+            })
+            .map_err(OperationError::FailedToUpdate)?;
+        let order_ids: Vec<i64> = orders.iter().map(|o| o.order_id).collect();
+        queue_tickets(&tx, &order_ids, &now)
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        for order in orders.iter() {
+            notify(
+                &tx,
+                &OrderEvent::from_order(OrderEventKind::StatusChanged, order),
+            )
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        }
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+        Ok(orders)
+    }
+
+    async fn set_priority(
+        &self,
+        table_number: i32,
+        order_id: i64,
+        priority: OrderPriority,
+        eta: Option<i32>,
+    ) -> Result<Option<Order>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        let query = "WITH changed AS (UPDATE orders SET priority = $3, eta = $4 WHERE table_number = $1 AND order_id = $2 AND status <> 'ready' AND voided_at IS NULL RETURNING *) SELECT o.*, m.* FROM changed o INNER JOIN menus m ON o.menu_id = m.menu_id";
+        let order = tx
+            .query_opt(query, &[&table_number, &order_id, &priority, &eta])
+            .await
+            .map_err(OperationError::FailedToUpdate)?
+            .and_then(|row| Order::try_from_row(&row).ok());
+        if let Some(order) = &order {
+            notify(
+                &tx,
+                &OrderEvent::from_order(OrderEventKind::PriorityChanged, order),
+            )
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        }
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+        Ok(order)
+    }
+
+    async fn assign_cook(
+        &self,
+        order_id: i64,
+        cook_id: Option<i64>,
+        claim: bool,
+        now: OffsetDateTime,
+    ) -> Result<CookOutcome, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        tx.execute("SELECT pg_advisory_xact_lock($1, 0)", &[&ASSIGN_COOK_LOCK])
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        let current: Option<i64> = match tx
+            .query_opt(
+                "SELECT cook_id FROM orders WHERE order_id = $1 AND status <> 'ready' AND voided_at IS NULL FOR UPDATE",
+                &[&order_id],
+            )
+            .await
+            .map_err(OperationError::FailedToUpdate)?
+        {
+            Some(row) => row.try_get("cook_id").unwrap_or(None),
+            None => return Ok(CookOutcome::NotFound),
+        };
+        if let Some(other) = current.filter(|other| claim && Some(*other) != cook_id) {
+            return Ok(CookOutcome::Taken(other));
+        }
+
+        let cook = match cook_id {
+            Some(cook_id) => tx
+                .query_opt(
+                    "SELECT staff_id FROM staff WHERE staff_id = $1 AND role = 'cook'",
+                    &[&cook_id],
+                )
+                .await,
+            // the least busy cook on shift, counting the Orders assigned to them which are still to be cooked:
+            None => tx
+                .query_opt(
+                    "SELECT st.staff_id FROM staff st WHERE st.role = 'cook' AND EXISTS (SELECT 1 FROM shift_assignments a WHERE a.staff_id = st.staff_id AND a.starts_at <= $1 AND a.ends_at > $1) ORDER BY (SELECT COUNT(*) FROM orders o WHERE o.cook_id = st.staff_id AND o.status IN ('cooking', 'held') AND o.voided_at IS NULL), st.staff_id LIMIT 1",
+                    &[&now],
+                )
+                .await,
+        }
+        .map_err(OperationError::FailedToUpdate)?;
+        let cook_id: i64 = match cook {
+            Some(row) => row
+                .try_get("staff_id")
+                .map_err(OperationError::FailedToUpdate)?,
+            None => return Ok(CookOutcome::NoCook),
+        };
+
+        let query = "WITH assigned AS (UPDATE orders SET cook_id = $2 WHERE order_id = $1 RETURNING *) SELECT o.*, m.* FROM assigned o INNER JOIN menus m ON o.menu_id = m.menu_id";
+        let row = tx
+            .query_one(query, &[&order_id, &cook_id])
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        let order = Order::try_from_row(&row).map_err(OperationError::FailedToUpdate)?;
+        notify(
+            &tx,
+            &OrderEvent::from_order(OrderEventKind::Assigned, &order),
+        )
+        .await
+        .map_err(OperationError::FailedToUpdate)?;
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+        Ok(CookOutcome::Done(Box::new(order)))
+    }
+
+    async fn bump(
+        &self,
+        order_id: i64,
+        cook_id: Option<i64>,
+        now: OffsetDateTime,
+    ) -> Result<CookOutcome, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        let current: Option<i64> = match tx
+            .query_opt(
+                "SELECT cook_id FROM orders WHERE order_id = $1 AND status = 'cooking' AND voided_at IS NULL FOR UPDATE",
+                &[&order_id],
+            )
+            .await
+            .map_err(OperationError::FailedToUpdate)?
+        {
+            Some(row) => row.try_get("cook_id").unwrap_or(None),
+            None => return Ok(CookOutcome::NotFound),
+        };
+        if let (Some(cook_id), Some(other)) = (cook_id, current) {
+            if other != cook_id {
+                return Ok(CookOutcome::Taken(other));
+            }
+        }
+
+        let query = "WITH bumped AS (UPDATE orders SET status = 'ready', ready_at = $3, cook_id = COALESCE(cook_id, $2) WHERE order_id = $1 RETURNING *) SELECT o.*, m.* FROM bumped o INNER JOIN menus m ON o.menu_id = m.menu_id";
+        let row = tx
+            .query_one(query, &[&order_id, &cook_id, &now])
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        let order = Order::try_from_row(&row).map_err(OperationError::FailedToUpdate)?;
+        notify(
+            &tx,
+            &OrderEvent::from_order(OrderEventKind::StatusChanged, &order),
+        )
+        .await
+        .map_err(OperationError::FailedToUpdate)?;
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+        Ok(CookOutcome::Done(Box::new(order)))
+    }
+
+    async fn list_cook_workloads(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<Vec<CookWorkload>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT st.staff_id, st.name, EXISTS (SELECT 1 FROM shift_assignments a WHERE a.staff_id = st.staff_id AND a.starts_at <= $3 AND a.ends_at > $3) AS on_shift, COUNT(o.order_id) FILTER (WHERE o.status IN ('cooking', 'held')) AS outstanding, COALESCE(SUM(o.quantity) FILTER (WHERE o.status IN ('cooking', 'held')), 0) AS portions, COUNT(o.order_id) FILTER (WHERE o.status = 'ready') AS done, (AVG(EXTRACT(EPOCH FROM o.ready_at - o.started_at)) FILTER (WHERE o.status = 'ready') / 60)::bigint AS average_minutes FROM staff st LEFT JOIN orders o ON o.cook_id = st.staff_id AND o.voided_at IS NULL AND (o.status IN ('cooking', 'held') OR (o.status = 'ready' AND o.ready_at >= $1 AND o.ready_at < $2)) WHERE st.role = 'cook' GROUP BY st.staff_id, st.name ORDER BY st.staff_id";
+        conn.query(query, &[&from, &to, &now])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| CookWorkload::try_from_row(row).ok())
+                    .collect::<Vec<CookWorkload>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
     }
 
     async fn delete_order(
@@ -150,16 +936,130 @@ impl Repository for OrderRepository {
         table_number: i32,
         order_id: i64,
     ) -> Result<Option<i64>, OperationError> {
-        let conn = self.get_conn().await?;
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToDelete)?;
 
-        let query = "DELETE FROM orders WHERE table_number = $1 AND order_id = $2";
-        let query_result = conn.execute(query, &[&table_number, &order_id]).await;
-        match query_result {
-            Ok(affected_rows) => match affected_rows {
-                0 => Ok(None),
-                _ => Ok(Some(order_id)),
-            },
-            Err(e) => Err(OperationError::FailedToDelete(e)),
+        let session_id: Option<i64> = tx
+            .query_opt(
+                "SELECT session_id FROM orders WHERE table_number = $1 AND order_id = $2",
+                &[&table_number, &order_id],
+            )
+            .await
+            .map_err(OperationError::FailedToDelete)?
+            .and_then(|row| row.try_get("session_id").ok());
+        if let Some(session_id) = session_id {
+            // same lock as payments of the session, so a payment can't slip in while deleting:
+            tx.execute("SELECT pg_advisory_xact_lock($1)", &[&session_id])
+                .await
+                .map_err(OperationError::FailedToDelete)?;
         }
+
+        let query = "DELETE FROM orders WHERE table_number = $1 AND order_id = $2 AND voided_at IS NULL AND NOT EXISTS (SELECT 1 FROM payments p WHERE p.session_id = orders.session_id) RETURNING session_id, menu_id";
+        let deleted = tx
+            .query_opt(query, &[&table_number, &order_id])
+            .await
+            .map_err(OperationError::FailedToDelete)?;
+        let row = match deleted {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        // a deleted Order is no longer cooked, its tickets which are not out yet go with it:
+        tx.execute(
+            "DELETE FROM kitchen_tickets WHERE order_id = $1 AND status <> 'printed'",
+            &[&order_id],
+        )
+        .await
+        .map_err(OperationError::FailedToDelete)?;
+        let event = OrderEvent {
+            session_id: row.try_get("session_id").unwrap_or(None),
+            menu_id: row.try_get("menu_id").ok(),
+            ..OrderEvent::new(OrderEventKind::Cancelled, order_id, table_number)
+        };
+        notify(&tx, &event)
+            .await
+            .map_err(OperationError::FailedToDelete)?;
+        tx.commit().await.map_err(OperationError::FailedToDelete)?;
+        Ok(Some(order_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::create_conn_pool;
+
+    #[test]
+    fn test_page_offset() {
+        assert_eq!(page_offset(0, 20), 0);
+        assert_eq!(page_offset(1, 20), 0);
+        // the second page starts right after the first one:
+        assert_eq!(page_offset(2, 20), 20);
+        assert_eq!(page_offset(3, 5), 10);
+    }
+
+    #[actix_web::test]
+    #[ignore = "needs a PostgreSQL database with the schema imported"]
+    /// given: an Order whose kitchen ticket is still waiting to be printed.
+    /// when: voiding the Order against the database.
+    /// then: its ticket is failed as voided, in the same go.
+    async fn test_void_orders() {
+        let db_pool = create_conn_pool();
+        let repo = OrderRepository::new(db_pool.clone());
+        let conn = db_pool.get().await.unwrap();
+        // a table number no dining table has, so the test doesn't clash with real orders:
+        let table_number = 9_000 + (OffsetDateTime::now_utc().nanosecond() % 1_000) as i32;
+        let session_id: i64 = -(table_number as i64);
+
+        let order_id: i64 = conn
+            .query_one(
+                "INSERT INTO orders (table_number, session_id, menu_id, created_at) VALUES ($1, $2, 1, now()) RETURNING order_id",
+                &[&table_number, &session_id],
+            )
+            .await
+            .unwrap()
+            .get("order_id");
+        conn.execute(
+            "INSERT INTO kitchen_tickets (order_id, queued_at, next_attempt_at) VALUES ($1, now(), now())",
+            &[&order_id],
+        )
+        .await
+        .unwrap();
+
+        let void = Void {
+            reason: ReasonCode::EntryError,
+            note: None,
+            staff_id: 1,
+            at: OffsetDateTime::now_utc(),
+        };
+        let voided = repo
+            .void_orders(table_number, session_id, Some(order_id), void)
+            .await
+            .unwrap();
+        assert_eq!(voided, vec![order_id]);
+        let ticket = conn
+            .query_one(
+                "SELECT status::text AS status, last_error FROM kitchen_tickets WHERE order_id = $1",
+                &[&order_id],
+            )
+            .await
+            .unwrap();
+        assert_eq!(ticket.get::<_, String>("status"), "failed");
+        assert_eq!(
+            ticket.get::<_, Option<String>>("last_error"),
+            Some("voided".to_string())
+        );
+
+        conn.execute(
+            "DELETE FROM kitchen_tickets WHERE order_id = $1",
+            &[&order_id],
+        )
+        .await
+        .unwrap();
+        conn.execute("DELETE FROM orders WHERE order_id = $1", &[&order_id])
+            .await
+            .unwrap();
     }
 }
@@ -0,0 +1,224 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+use time::OffsetDateTime;
+
+use super::OperationError;
+
+#[automock]
+#[async_trait]
+/// Payment repository abstraction.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// Store the Payment unless the payments of its session would exceed `bill_total`,
+    /// returns None in that case.
+    async fn record_payment(
+        &self,
+        data: Payment,
+        bill_total: i64,
+    ) -> Result<Option<Payment>, OperationError>;
+    /// List the Payments of a Table session, oldest first.
+    async fn list_by_session(&self, session_id: i64) -> Result<Vec<Payment>, OperationError>;
+}
+
+/// How the guests paid.
+#[derive(Debug, Clone, Copy, PartialEq, ToSql, FromSql)]
+#[postgres(name = "payment_method")]
+pub enum PaymentMethod {
+    #[postgres(name = "cash")]
+    Cash,
+    #[postgres(name = "card")]
+    Card,
+    #[postgres(name = "qris")]
+    Qris,
+}
+
+impl PaymentMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cash => "cash",
+            Self::Card => "card",
+            Self::Qris => "qris",
+        }
+    }
+
+    pub fn parse(v: &str) -> Option<Self> {
+        match v {
+            "cash" => Some(Self::Cash),
+            "card" => Some(Self::Card),
+            "qris" => Some(Self::Qris),
+            _ => None,
+        }
+    }
+}
+
+/// Represents money received against the bill of a Table session.
+#[derive(FromRow, Clone)]
+pub struct Payment {
+    pub payment_id: i64,
+    pub session_id: i64,
+    pub table_number: i32,
+    pub method: PaymentMethod,
+    /// in minor units of the currency.
+    pub amount: i64,
+    /// what the guests gave on top of the bill, it is not part of the bill.
+    pub tip: i64,
+    /// the capture reference of the payment gateway, cash has none.
+    pub reference: Option<String>,
+    /// the Staff member who took the payment.
+    pub staff_id: Option<i64>,
+    pub created_at: OffsetDateTime,
+}
+
+impl Payment {
+    /// Create a new Payment entity to be stored later.
+    pub fn new(
+        session_id: i64,
+        table_number: i32,
+        method: PaymentMethod,
+        amount: i64,
+        reference: Option<String>,
+    ) -> Self {
+        Self {
+            payment_id: 0,
+            session_id,
+            table_number,
+            method,
+            amount,
+            tip: 0,
+            reference,
+            staff_id: None,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+#[derive(Clone)]
+// Concrete implementation of payment repository
+// which uses PostgreSQL as its datastore.
+pub struct PaymentRepository {
+    db_pool: Pool,
+}
+
+impl PaymentRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl PaymentRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for PaymentRepository {
+    async fn record_payment(
+        &self,
+        data: Payment,
+        bill_total: i64,
+    ) -> Result<Option<Payment>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToCreate)?;
+
+        // serializes concurrent payments of the same session, so they can't overpay the bill together:
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&data.session_id])
+            .await
+            .map_err(OperationError::FailedToCreate)?;
+
+        let query = "INSERT INTO payments (session_id, table_number, method, amount, reference, created_at, tip, staff_id) SELECT $1, $2, $3, $4, $5, $6, $8, $9 WHERE (SELECT COALESCE(SUM(amount), 0)::bigint FROM payments WHERE session_id = $1) + $4 <= $7 RETURNING payment_id";
+        let inserted = tx
+            .query_opt(
+                query,
+                &[
+                    &data.session_id,
+                    &data.table_number,
+                    &data.method,
+                    &data.amount,
+                    &data.reference,
+                    &data.created_at,
+                    &bill_total,
+                    &data.tip,
+                    &data.staff_id,
+                ],
+            )
+            .await
+            .map_err(OperationError::FailedToCreate)?;
+        tx.commit().await.map_err(OperationError::FailedToCreate)?;
+
+        Ok(inserted.map(|row| {
+            let payment_id: i64 = row.try_get("payment_id").unwrap_or(0);
+            Payment { payment_id, ..data }
+        }))
+    }
+
+    async fn list_by_session(&self, session_id: i64) -> Result<Vec<Payment>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM payments WHERE session_id = $1 ORDER BY created_at, payment_id";
+        conn.query(query, &[&session_id])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Payment::try_from_row(row).ok())
+                    .collect::<Vec<Payment>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::create_conn_pool;
+
+    #[actix_web::test]
+    #[ignore = "needs a PostgreSQL database with the schema imported"]
+    /// given: a bill of 100000 of which 60000 is paid already.
+    /// when: recording the payments against the database.
+    /// then: the payment which fits the bill is stored, the one overpaying it is not.
+    async fn test_record_payment() {
+        let db_pool = create_conn_pool();
+        let repo = PaymentRepository::new(db_pool.clone());
+        let session_id =
+            i64::MAX - (OffsetDateTime::now_utc().unix_timestamp_nanos() % 1_000_000) as i64;
+
+        let first = repo
+            .record_payment(
+                Payment::new(session_id, 3, PaymentMethod::Cash, 60000, None),
+                100000,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(first.payment_id > 0);
+        let over = repo
+            .record_payment(
+                Payment::new(session_id, 3, PaymentMethod::Cash, 50000, None),
+                100000,
+            )
+            .await
+            .unwrap();
+        assert!(over.is_none());
+        let payments = repo.list_by_session(session_id).await.unwrap();
+        assert_eq!(payments.len(), 1);
+        assert_eq!(payments[0].payment_id, first.payment_id);
+
+        db_pool
+            .get()
+            .await
+            .unwrap()
+            .execute("DELETE FROM payments WHERE session_id = $1", &[&session_id])
+            .await
+            .unwrap();
+    }
+}
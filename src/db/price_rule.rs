@@ -0,0 +1,193 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+use time::OffsetDateTime;
+
+use super::OperationError;
+
+#[automock]
+#[async_trait]
+/// Price rule repository abstraction.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// Store a new price rule.
+    async fn create_rule(&self, data: PriceRule) -> Result<PriceRule, OperationError>;
+    /// List the active price rules, oldest first.
+    async fn list_active(&self) -> Result<Vec<PriceRule>, OperationError>;
+    /// List the active price rules which may apply on the Table, oldest first.
+    /// Zone rules of other sections are left out, None leaves out every zone rule.
+    async fn list_applicable(
+        &self,
+        table_number: Option<i32>,
+    ) -> Result<Vec<PriceRule>, OperationError>;
+    /// Deactivate a price rule, returns false when there is no active rule with the ID.
+    async fn deactivate_rule(&self, rule_id: i64) -> Result<bool, OperationError>;
+}
+
+/// How a price rule adjusts the Menu price.
+#[derive(Debug, Clone, Copy, PartialEq, ToSql, FromSql)]
+#[postgres(name = "price_adjustment_kind")]
+pub enum AdjustmentKind {
+    /// the value is in basis points (1% = 100) of the price.
+    #[postgres(name = "percent")]
+    Percent,
+    /// the value is an amount in minor units of the currency.
+    #[postgres(name = "fixed")]
+    Fixed,
+}
+
+impl AdjustmentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Percent => "percent",
+            Self::Fixed => "fixed",
+        }
+    }
+
+    pub fn parse(v: &str) -> Option<Self> {
+        match v {
+            "percent" => Some(Self::Percent),
+            "fixed" => Some(Self::Fixed),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a rule which adjusts Menu prices within a weekly time window,
+/// e.g. a happy hour (negative value) or a surcharge in a zone of the floor (positive value).
+#[derive(FromRow, Clone)]
+pub struct PriceRule {
+    pub rule_id: i64,
+    pub name: String,
+    /// the Menu the rule applies to, None applies to every Menu.
+    pub menu_id: Option<i64>,
+    /// the Menu category the rule applies to, None applies to every category.
+    pub category: Option<String>,
+    /// the floor section (zone) the rule applies to, None applies to every section.
+    pub section_id: Option<i64>,
+    /// ISO days of the week (Monday = 1) the window opens on, empty opens every day.
+    pub days: Vec<i32>,
+    /// minutes after local midnight, the window wraps past midnight when it starts after it ends.
+    pub start_minute: i32,
+    pub end_minute: i32,
+    pub kind: AdjustmentKind,
+    pub value: i64,
+    pub active: bool,
+    pub created_at: OffsetDateTime,
+}
+
+impl PriceRule {
+    /// Create a new price rule entity to be stored later, it applies all day to every Menu.
+    pub fn new(name: String, kind: AdjustmentKind, value: i64) -> Self {
+        Self {
+            rule_id: 0,
+            name,
+            menu_id: None,
+            category: None,
+            section_id: None,
+            days: vec![],
+            start_minute: 0,
+            end_minute: 1440,
+            kind,
+            value,
+            active: true,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+#[derive(Clone)]
+// Concrete implementation of price rule repository
+// which uses PostgreSQL as its datastore.
+pub struct PriceRuleRepository {
+    db_pool: Pool,
+}
+
+impl PriceRuleRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl PriceRuleRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for PriceRuleRepository {
+    async fn create_rule(&self, data: PriceRule) -> Result<PriceRule, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "INSERT INTO price_rules (rule_id, name, menu_id, category, section_id, days, start_minute, end_minute, kind, value, active, created_at) VALUES (DEFAULT, $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING rule_id";
+        conn.query_one(
+            query,
+            &[
+                &data.name,
+                &data.menu_id,
+                &data.category,
+                &data.section_id,
+                &data.days,
+                &data.start_minute,
+                &data.end_minute,
+                &data.kind,
+                &data.value,
+                &data.active,
+                &data.created_at,
+            ],
+        )
+        .await
+        .map(|row| {
+            let rule_id: i64 = row.try_get("rule_id").unwrap_or(0);
+            PriceRule { rule_id, ..data }
+        })
+        .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn list_active(&self) -> Result<Vec<PriceRule>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM price_rules WHERE active ORDER BY rule_id";
+        conn.query(query, &[])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| PriceRule::try_from_row(row).ok())
+                    .collect::<Vec<PriceRule>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn list_applicable(
+        &self,
+        table_number: Option<i32>,
+    ) -> Result<Vec<PriceRule>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM price_rules WHERE active AND (section_id IS NULL OR section_id = (SELECT section_id FROM dining_tables WHERE table_number = $1)) ORDER BY rule_id";
+        conn.query(query, &[&table_number])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| PriceRule::try_from_row(row).ok())
+                    .collect::<Vec<PriceRule>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn deactivate_rule(&self, rule_id: i64) -> Result<bool, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "UPDATE price_rules SET active = false WHERE rule_id = $1 AND active";
+        conn.execute(query, &[&rule_id])
+            .await
+            .map(|affected| affected > 0)
+            .map_err(OperationError::FailedToUpdate)
+    }
+}
@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use time::OffsetDateTime;
+
+use super::{
+    discount::Discount, order::Order, payment::PaymentMethod, session::Session, staff::StaffRole,
+    OperationError,
+};
+
+#[automock]
+#[async_trait]
+/// Report repository abstraction, reads what the end-of-day reports are computed from.
+/// Sales belong to the business day their Table session was opened in,
+/// money belongs to the business day it changed hands in.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// List the Table sessions opened within `[from, to)`.
+    async fn list_sessions(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<Session>, OperationError>;
+    /// List every Order, voided ones included, of the Table sessions opened within `[from, to)`.
+    async fn list_orders(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<Order>, OperationError>;
+    /// List the Discounts of the Table sessions opened within `[from, to)`.
+    async fn list_discounts(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<Discount>, OperationError>;
+    /// Sum up the Payments received within `[from, to)` by method.
+    async fn payment_totals(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<MethodTotal>, OperationError>;
+    /// Sum up the Refunds given within `[from, to)` by method.
+    async fn refund_totals(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<MethodTotal>, OperationError>;
+    /// List the Staff who were assigned to work within `[from, to)`,
+    /// along with how long they worked within it.
+    async fn list_shift_work(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<ShiftWork>, OperationError>;
+}
+
+/// How many Payments or Refunds were made by a method and their amount.
+#[derive(FromRow, Debug, Clone, PartialEq)]
+pub struct MethodTotal {
+    pub method: PaymentMethod,
+    pub count: i64,
+    /// in minor units of the currency.
+    pub amount: i64,
+    /// what was given on top of the bills, Refunds have none.
+    pub tip: i64,
+}
+
+/// How long a Staff member worked within a period, out of their shift assignments.
+#[derive(FromRow, Debug, Clone, PartialEq)]
+pub struct ShiftWork {
+    pub staff_id: i64,
+    pub name: String,
+    pub role: StaffRole,
+    pub minutes: i64,
+}
+
+#[derive(Clone)]
+// Concrete implementation of report repository
+// which uses PostgreSQL as its datastore.
+pub struct ReportRepository {
+    db_pool: Pool,
+}
+
+impl ReportRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl ReportRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for ReportRepository {
+    async fn list_sessions(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<Session>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM table_sessions WHERE opened_at >= $1 AND opened_at < $2 ORDER BY session_id";
+        conn.query(query, &[&from, &to])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Session::try_from_row(row).ok())
+                    .collect::<Vec<Session>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn list_orders(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<Order>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT o.*, m.* FROM orders o INNER JOIN menus m ON o.menu_id = m.menu_id INNER JOIN table_sessions s ON s.session_id = o.session_id WHERE s.opened_at >= $1 AND s.opened_at < $2 ORDER BY o.session_id, o.created_at, o.order_id";
+        conn.query(query, &[&from, &to])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Order::try_from_row(row).ok())
+                    .collect::<Vec<Order>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn list_discounts(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<Discount>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT d.* FROM discounts d INNER JOIN table_sessions s ON s.session_id = d.session_id WHERE s.opened_at >= $1 AND s.opened_at < $2 ORDER BY d.session_id, d.created_at, d.discount_id";
+        conn.query(query, &[&from, &to])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Discount::try_from_row(row).ok())
+                    .collect::<Vec<Discount>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn payment_totals(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<MethodTotal>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT method, COUNT(*) AS count, COALESCE(SUM(amount), 0)::bigint AS amount, COALESCE(SUM(tip), 0)::bigint AS tip FROM payments WHERE created_at >= $1 AND created_at < $2 GROUP BY method ORDER BY method";
+        conn.query(query, &[&from, &to])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| MethodTotal::try_from_row(row).ok())
+                    .collect::<Vec<MethodTotal>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn refund_totals(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<MethodTotal>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT method, COUNT(*) AS count, COALESCE(SUM(amount), 0)::bigint AS amount, 0::bigint AS tip FROM refunds WHERE created_at >= $1 AND created_at < $2 GROUP BY method ORDER BY method";
+        conn.query(query, &[&from, &to])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| MethodTotal::try_from_row(row).ok())
+                    .collect::<Vec<MethodTotal>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn list_shift_work(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<ShiftWork>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        // assignments are clipped to the period, a shift across midnight counts on both days:
+        let query = "SELECT st.staff_id, st.name, st.role, (SUM(EXTRACT(EPOCH FROM LEAST(a.ends_at, $2) - GREATEST(a.starts_at, $1))) / 60)::bigint AS minutes FROM shift_assignments a INNER JOIN staff st ON st.staff_id = a.staff_id WHERE a.starts_at < $2 AND a.ends_at > $1 GROUP BY st.staff_id, st.name, st.role ORDER BY st.staff_id";
+        conn.query(query, &[&from, &to])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| ShiftWork::try_from_row(row).ok())
+                    .collect::<Vec<ShiftWork>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+}
@@ -0,0 +1,389 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+use time::OffsetDateTime;
+
+use super::{
+    session::{self, SeatOutcome},
+    OperationError,
+};
+
+#[automock]
+#[async_trait]
+/// Reservation repository abstraction.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// Store the Reservation entity into the datastore.
+    async fn create_reservation(&self, data: Reservation) -> Result<Reservation, OperationError>;
+    /// Get Reservation detail by its ID.
+    async fn get_reservation(
+        &self,
+        reservation_id: i64,
+    ) -> Result<Option<Reservation>, OperationError>;
+    /// Replace the booking details of a Reservation which is still booked.
+    async fn update_reservation(
+        &self,
+        data: Reservation,
+    ) -> Result<Option<Reservation>, OperationError>;
+    /// Cancel a Reservation which is still booked.
+    async fn cancel_reservation(
+        &self,
+        reservation_id: i64,
+    ) -> Result<Option<Reservation>, OperationError>;
+    /// Open a session on the Table of a booked Reservation and mark the Reservation as seated into it,
+    /// both or neither.
+    async fn seat_reservation(
+        &self,
+        reservation_id: i64,
+    ) -> Result<SeatOutcome<Reservation>, OperationError>;
+    /// List active Reservations on a Table that overlap the time range,
+    /// the Reservation being modified can be excluded via `exclude_id`.
+    async fn list_conflicts(
+        &self,
+        table_number: i32,
+        from: OffsetDateTime,
+        until: OffsetDateTime,
+        exclude_id: i64,
+    ) -> Result<Vec<Reservation>, OperationError>;
+    /// List booked Reservations which start within the given time range.
+    async fn list_starting(
+        &self,
+        from: OffsetDateTime,
+        until: OffsetDateTime,
+    ) -> Result<Vec<Reservation>, OperationError>;
+}
+
+/// Lifecycle of a Reservation.
+#[derive(Debug, Clone, Copy, PartialEq, ToSql, FromSql)]
+#[postgres(name = "reservation_status")]
+pub enum ReservationStatus {
+    #[postgres(name = "booked")]
+    Booked,
+    #[postgres(name = "seated")]
+    Seated,
+    #[postgres(name = "cancelled")]
+    Cancelled,
+}
+
+impl ReservationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Booked => "booked",
+            Self::Seated => "seated",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Represents a single Reservation entity.
+#[derive(FromRow, Clone)]
+pub struct Reservation {
+    pub reservation_id: i64,
+    pub table_number: i32,
+    pub party_size: i32,
+    pub contact_name: String,
+    pub contact_phone: String,
+    pub reserved_from: OffsetDateTime,
+    pub reserved_until: OffsetDateTime,
+    pub status: ReservationStatus,
+    pub session_id: Option<i64>,
+    pub created_at: OffsetDateTime,
+}
+
+impl Reservation {
+    /// Create a new booked Reservation entity to be stored later.
+    pub fn new(
+        table_number: i32,
+        party_size: i32,
+        contact_name: String,
+        contact_phone: String,
+        reserved_from: OffsetDateTime,
+        reserved_until: OffsetDateTime,
+    ) -> Self {
+        Self {
+            reservation_id: 0,
+            table_number,
+            party_size,
+            contact_name,
+            contact_phone,
+            reserved_from,
+            reserved_until,
+            status: ReservationStatus::Booked,
+            session_id: None,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+#[derive(Clone)]
+// Concrete implementation of Reservation repository
+// which uses PostgreSQL as its datastore.
+pub struct ReservationRepository {
+    db_pool: Pool,
+}
+
+impl ReservationRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl ReservationRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for ReservationRepository {
+    async fn create_reservation(&self, data: Reservation) -> Result<Reservation, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "INSERT INTO reservations (reservation_id, table_number, party_size, contact_name, contact_phone, reserved_from, reserved_until, status, created_at) VALUES (DEFAULT, $1, $2, $3, $4, $5, $6, $7, $8) RETURNING reservation_id";
+        conn.query_one(
+            query,
+            &[
+                &data.table_number,
+                &data.party_size,
+                &data.contact_name,
+                &data.contact_phone,
+                &data.reserved_from,
+                &data.reserved_until,
+                &data.status,
+                &data.created_at,
+            ],
+        )
+        .await
+        .map(|row| {
+            let reservation_id: i64 = row.try_get("reservation_id").unwrap_or(0);
+            Reservation {
+                reservation_id,
+                ..data
+            }
+        })
+        .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn get_reservation(
+        &self,
+        reservation_id: i64,
+    ) -> Result<Option<Reservation>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM reservations WHERE reservation_id = $1";
+        conn.query_opt(query, &[&reservation_id])
+            .await
+            .map(|row| row.and_then(|r| Reservation::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn update_reservation(
+        &self,
+        data: Reservation,
+    ) -> Result<Option<Reservation>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "UPDATE reservations SET table_number = $2, party_size = $3, contact_name = $4, contact_phone = $5, reserved_from = $6, reserved_until = $7 WHERE reservation_id = $1 AND status = 'booked' RETURNING *";
+        conn.query_opt(
+            query,
+            &[
+                &data.reservation_id,
+                &data.table_number,
+                &data.party_size,
+                &data.contact_name,
+                &data.contact_phone,
+                &data.reserved_from,
+                &data.reserved_until,
+            ],
+        )
+        .await
+        .map(|row| row.and_then(|r| Reservation::try_from_row(&r).ok()))
+        .map_err(OperationError::FailedToUpdate)
+    }
+
+    async fn cancel_reservation(
+        &self,
+        reservation_id: i64,
+    ) -> Result<Option<Reservation>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "UPDATE reservations SET status = 'cancelled' WHERE reservation_id = $1 AND status = 'booked' RETURNING *";
+        conn.query_opt(query, &[&reservation_id])
+            .await
+            .map(|row| row.and_then(|r| Reservation::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToUpdate)
+    }
+
+    async fn seat_reservation(
+        &self,
+        reservation_id: i64,
+    ) -> Result<SeatOutcome<Reservation>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        let booked = tx
+            .query_opt(
+                "SELECT table_number FROM reservations WHERE reservation_id = $1 AND status = 'booked' FOR UPDATE",
+                &[&reservation_id],
+            )
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        let table_number: i32 = match booked {
+            Some(row) => row.try_get("table_number").unwrap_or(0),
+            None => return Ok(SeatOutcome::Gone),
+        };
+        let session = match session::open_in(&tx, table_number)
+            .await
+            .map_err(OperationError::FailedToCreate)?
+        {
+            Some(session) => session,
+            None => return Ok(SeatOutcome::Occupied),
+        };
+
+        let query = "UPDATE reservations SET status = 'seated', session_id = $2 WHERE reservation_id = $1 RETURNING *";
+        let reservation = tx
+            .query_opt(query, &[&reservation_id, &session.session_id])
+            .await
+            .map(|row| row.and_then(|r| Reservation::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToUpdate)?;
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+        Ok(match reservation {
+            Some(reservation) => SeatOutcome::Seated(reservation),
+            None => SeatOutcome::Gone,
+        })
+    }
+
+    async fn list_conflicts(
+        &self,
+        table_number: i32,
+        from: OffsetDateTime,
+        until: OffsetDateTime,
+        exclude_id: i64,
+    ) -> Result<Vec<Reservation>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM reservations WHERE table_number = $1 AND status <> 'cancelled' AND reserved_from < $3 AND reserved_until > $2 AND reservation_id <> $4";
+        conn.query(query, &[&table_number, &from, &until, &exclude_id])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Reservation::try_from_row(row).ok())
+                    .collect::<Vec<Reservation>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn list_starting(
+        &self,
+        from: OffsetDateTime,
+        until: OffsetDateTime,
+    ) -> Result<Vec<Reservation>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM reservations WHERE status = 'booked' AND reserved_from BETWEEN $1 AND $2 ORDER BY reserved_from";
+        conn.query(query, &[&from, &until])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Reservation::try_from_row(row).ok())
+                    .collect::<Vec<Reservation>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+    use tokio_postgres::error::SqlState;
+
+    use super::*;
+    use crate::{
+        db::{create_conn_pool, session::Repository as _},
+        tax::TaxConfig,
+    };
+
+    #[actix_web::test]
+    #[ignore = "needs a PostgreSQL database with the schema imported"]
+    /// given: a booked Reservation on a free Table.
+    /// when: seating it against the database, then booking the same slot again.
+    /// then: it is seated into a fresh session once, and the overlapping booking is refused.
+    async fn test_seat_reservation() {
+        let db_pool = create_conn_pool();
+        let repo = ReservationRepository::new(db_pool.clone());
+        let sessions = session::SessionRepository::new(db_pool.clone());
+        // a table number no dining table has, so the test doesn't clash with real bookings:
+        let table_number = 9_000 + (OffsetDateTime::now_utc().nanosecond() % 1_000) as i32;
+        let from = OffsetDateTime::now_utc() + Duration::days(400);
+        let reservation = Reservation::new(
+            table_number,
+            2,
+            "Sukab".to_string(),
+            "+62 812 0000 0000".to_string(),
+            from,
+            from + Duration::hours(2),
+        );
+
+        let booked = repo.create_reservation(reservation.clone()).await.unwrap();
+        match repo.create_reservation(reservation).await {
+            Err(OperationError::FailedToCreate(e)) => {
+                assert_eq!(e.code(), Some(&SqlState::EXCLUSION_VIOLATION))
+            }
+            _ => panic!("the overlapping booking must be refused"),
+        }
+
+        // the table is still occupied, the reservation stays booked:
+        let previous = sessions.open_session(table_number).await.unwrap().unwrap();
+        assert!(matches!(
+            repo.seat_reservation(booked.reservation_id).await.unwrap(),
+            SeatOutcome::Occupied
+        ));
+        let unchanged = repo
+            .get_reservation(booked.reservation_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(unchanged.status, ReservationStatus::Booked);
+        sessions
+            .close_session(previous.session_id, TaxConfig::from_env())
+            .await
+            .unwrap();
+
+        let seated = match repo.seat_reservation(booked.reservation_id).await.unwrap() {
+            SeatOutcome::Seated(reservation) => reservation,
+            _ => panic!("the reservation must be seated"),
+        };
+        let session = sessions
+            .get_active_session(table_number)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(seated.session_id, Some(session.session_id));
+        assert!(matches!(
+            repo.seat_reservation(booked.reservation_id).await.unwrap(),
+            SeatOutcome::Gone
+        ));
+
+        let conn = db_pool.get().await.unwrap();
+        conn.execute(
+            "DELETE FROM reservations WHERE reservation_id = $1",
+            &[&booked.reservation_id],
+        )
+        .await
+        .unwrap();
+        conn.execute(
+            "DELETE FROM table_sessions WHERE table_number = $1",
+            &[&table_number],
+        )
+        .await
+        .unwrap();
+    }
+}
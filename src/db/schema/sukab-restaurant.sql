@@ -2,7 +2,15 @@
 create database sukab_restaurant;
 -- end: create database
 
+-- begin: create extensions
+create extension if not exists btree_gist;
+-- end: create extensions
+
 -- begin: create tables
+create type public.adjustment_reason as enum ('entry_error', 'guest_cancelled', 'kitchen_error', 'quality_complaint', 'overcharge', 'other');
+create type public.order_status as enum ('cooking', 'ready', 'held', 'waiting');
+create type public.order_priority as enum ('normal', 'rush', 'vip');
+
 create table public.orders
 (
     order_id     bigserial
@@ -10,33 +18,384 @@ create table public.orders
             primary key,
     menu_id      integer,
     table_number integer,
+    session_id   bigint,
     cook_time    integer,
-    created_at   timestamp with time zone
+    quantity     integer not null default 1,
+    unit_price   bigint  not null default 0,
+    seat_number  integer,
+    modifiers    varchar(50)[] not null default '{}',
+    note         varchar(300),
+    course       integer,
+    status       public.order_status not null default 'cooking',
+    priority     public.order_priority not null default 'normal',
+    eta          integer,
+    cook_id      bigint,
+    created_at   timestamp with time zone,
+    started_at   timestamp with time zone,
+    ready_at     timestamp with time zone,
+    voided_at    timestamp with time zone,
+    void_reason  public.adjustment_reason,
+    void_note    varchar(300),
+    voided_by    bigint
 );
 
 create index orders_table_number_order_id_index
     on public.orders (table_number, order_id);
 
+create index orders_table_number_session_id_index
+    on public.orders (table_number, session_id);
+
+create index orders_voided_at_index
+    on public.orders (voided_at)
+    where voided_at is not null;
+
+create index orders_session_id_pending_index
+    on public.orders (session_id)
+    where voided_at is null;
+
+create index orders_cook_id_index
+    on public.orders (cook_id)
+    where cook_id is not null;
+
+create index orders_outstanding_index
+    on public.orders (status, started_at)
+    where status in ('cooking', 'held') and voided_at is null;
+
+create table public.fired_courses
+(
+    session_id bigint  not null,
+    course     integer not null,
+    fired_at   timestamp with time zone,
+    fired_by   bigint,
+    constraint fired_courses_pk
+        primary key (session_id, course)
+);
+
 create table public.menus
 (
     menu_id bigserial
         constraint menus_pk
             primary key,
-    name    varchar(300)
+    name     varchar(300),
+    price    bigint not null default 0,
+    category varchar(50)
+);
+
+create table public.table_sessions
+(
+    session_id   bigserial
+        constraint table_sessions_pk
+            primary key,
+    table_number integer,
+    opened_at           timestamp with time zone,
+    closed_at           timestamp with time zone,
+    service_charge_rate bigint,
+    tax_rate            bigint,
+    tax_mode            varchar(20),
+    rounding_unit       bigint,
+    rounding_mode       varchar(20)
+);
+
+create unique index table_sessions_table_number_active_index
+    on public.table_sessions (table_number)
+    where closed_at is null;
+
+create index table_sessions_opened_at_index
+    on public.table_sessions (opened_at);
+
+create table public.dining_tables
+(
+    table_number integer
+        constraint dining_tables_pk
+            primary key,
+    capacity     integer,
+    section_id   bigint
+);
+
+create index dining_tables_section_id_index
+    on public.dining_tables (section_id);
+
+create table public.sections
+(
+    section_id bigserial
+        constraint sections_pk
+            primary key,
+    name       varchar(100)
 );
+
+create table public.stations
+(
+    station_id bigserial
+        constraint stations_pk
+            primary key,
+    name       varchar(100),
+    capacity   integer,
+    printer    varchar(300)
+);
+
+create table public.menu_stations
+(
+    menu_id    bigint not null,
+    station_id bigint not null,
+    constraint menu_stations_pk
+        primary key (menu_id, station_id)
+);
+
+create index menu_stations_station_id_index
+    on public.menu_stations (station_id);
+
+create type public.ticket_status as enum ('pending', 'printed', 'failed');
+
+create table public.kitchen_tickets
+(
+    ticket_id       bigserial
+        constraint kitchen_tickets_pk
+            primary key,
+    order_id        bigint not null,
+    station_id      bigint,
+    status          public.ticket_status not null default 'pending',
+    reprint         boolean not null default false,
+    attempts        integer not null default 0,
+    last_error      varchar(300),
+    queued_at       timestamp with time zone,
+    next_attempt_at timestamp with time zone,
+    printed_at      timestamp with time zone
+);
+
+create index kitchen_tickets_pending_index
+    on public.kitchen_tickets (next_attempt_at)
+    where status = 'pending';
+
+create index kitchen_tickets_order_id_index
+    on public.kitchen_tickets (order_id);
+
+create type public.staff_role as enum ('waiter', 'cook', 'manager');
+
+create table public.staff
+(
+    staff_id bigserial
+        constraint staff_pk
+            primary key,
+    name     varchar(300),
+    role     public.staff_role,
+    token    varchar(100)
+        constraint staff_token_unique
+            unique
+);
+
+create table public.shift_assignments
+(
+    assignment_id bigserial
+        constraint shift_assignments_pk
+            primary key,
+    section_id    bigint,
+    staff_id      bigint,
+    starts_at     timestamp with time zone,
+    ends_at       timestamp with time zone
+);
+
+create index shift_assignments_staff_id_starts_at_index
+    on public.shift_assignments (staff_id, starts_at);
+
+create type public.reservation_status as enum ('booked', 'seated', 'cancelled');
+
+create table public.reservations
+(
+    reservation_id bigserial
+        constraint reservations_pk
+            primary key,
+    table_number   integer,
+    party_size     integer,
+    contact_name   varchar(300),
+    contact_phone  varchar(50),
+    reserved_from  timestamp with time zone,
+    reserved_until timestamp with time zone,
+    status         public.reservation_status,
+    session_id     bigint,
+    created_at     timestamp with time zone,
+    -- safety net against double booking when two requests race past the availability check:
+    constraint reservations_no_overlap
+        exclude using gist (table_number with =, tstzrange(reserved_from, reserved_until) with &&)
+        where (status <> 'cancelled')
+);
+
+create type public.waitlist_status as enum ('waiting', 'called', 'seated', 'no_show');
+
+create table public.waitlist
+(
+    entry_id      bigserial
+        constraint waitlist_pk
+            primary key,
+    party_size    integer,
+    contact_name  varchar(300),
+    contact_phone varchar(50),
+    status        public.waitlist_status,
+    table_number  integer,
+    session_id    bigint,
+    created_at    timestamp with time zone,
+    called_at     timestamp with time zone
+);
+
+create index waitlist_status_created_at_index
+    on public.waitlist (status, created_at);
+
+create type public.payment_method as enum ('cash', 'card', 'qris');
+
+create table public.payments
+(
+    payment_id   bigserial
+        constraint payments_pk
+            primary key,
+    session_id   bigint not null,
+    table_number integer,
+    method       public.payment_method,
+    amount       bigint not null,
+    tip          bigint not null default 0,
+    reference    varchar(100),
+    staff_id     bigint,
+    created_at   timestamp with time zone
+);
+
+create index payments_session_id_index
+    on public.payments (session_id);
+
+create index payments_created_at_index
+    on public.payments (created_at);
+
+create type public.discount_kind as enum ('percent', 'fixed');
+
+create table public.promo_codes
+(
+    code       varchar(50)
+        constraint promo_codes_pk
+            primary key,
+    kind       public.discount_kind,
+    value      bigint  not null,
+    starts_at  timestamp with time zone,
+    ends_at    timestamp with time zone,
+    max_uses   integer,
+    used_count integer not null default 0,
+    created_at timestamp with time zone
+);
+
+create table public.discounts
+(
+    discount_id bigserial
+        constraint discounts_pk
+            primary key,
+    session_id  bigint not null,
+    order_id    bigint,
+    kind        public.discount_kind,
+    value       bigint not null,
+    promo_code  varchar(50),
+    reason      varchar(300),
+    staff_id    bigint,
+    created_at  timestamp with time zone
+);
+
+create index discounts_session_id_index
+    on public.discounts (session_id);
+
+create type public.price_adjustment_kind as enum ('percent', 'fixed');
+
+create table public.price_rules
+(
+    rule_id      bigserial
+        constraint price_rules_pk
+            primary key,
+    name         varchar(100),
+    menu_id      bigint,
+    category     varchar(50),
+    section_id   bigint,
+    days         integer[] not null default '{}',
+    start_minute integer   not null,
+    end_minute   integer   not null,
+    kind         public.price_adjustment_kind,
+    value        bigint    not null,
+    active       boolean   not null default true,
+    created_at   timestamp with time zone
+);
+
+create table public.refunds
+(
+    refund_id  bigserial
+        constraint refunds_pk
+            primary key,
+    session_id bigint not null,
+    payment_id bigint not null,
+    order_id   bigint,
+    method     public.payment_method,
+    amount     bigint not null,
+    reason     public.adjustment_reason,
+    note       varchar(300),
+    reference  varchar(100),
+    staff_id   bigint,
+    created_at timestamp with time zone
+);
+
+create index refunds_session_id_index
+    on public.refunds (session_id);
+
+create index refunds_created_at_index
+    on public.refunds (created_at);
 -- end: create tables
 
 -- begin: master data for menus table
 
-INSERT INTO public.menus (menu_id, name) VALUES (1, 'ちゃづけ');
-INSERT INTO public.menus (menu_id, name) VALUES (2, 'らーめん');
-INSERT INTO public.menus (menu_id, name) VALUES (3, '弁当');
-INSERT INTO public.menus (menu_id, name) VALUES (4, '牛丼');
-INSERT INTO public.menus (menu_id, name) VALUES (5, '焼き鳥');
-INSERT INTO public.menus (menu_id, name) VALUES (6, '枝豆');
-INSERT INTO public.menus (menu_id, name) VALUES (7, '刺身');
-INSERT INTO public.menus (menu_id, name) VALUES (8, 'うどん');
-INSERT INTO public.menus (menu_id, name) VALUES (9, 'Nasi Goreng');
-INSERT INTO public.menus (menu_id, name) VALUES (10, 'Rendang');
+INSERT INTO public.menus (menu_id, name, price, category) VALUES (1, 'ちゃづけ', 28000, 'rice');
+INSERT INTO public.menus (menu_id, name, price, category) VALUES (2, 'らーめん', 45000, 'noodle');
+INSERT INTO public.menus (menu_id, name, price, category) VALUES (3, '弁当', 52000, 'rice');
+INSERT INTO public.menus (menu_id, name, price, category) VALUES (4, '牛丼', 48000, 'rice');
+INSERT INTO public.menus (menu_id, name, price, category) VALUES (5, '焼き鳥', 35000, 'side');
+INSERT INTO public.menus (menu_id, name, price, category) VALUES (6, '枝豆', 18000, 'side');
+INSERT INTO public.menus (menu_id, name, price, category) VALUES (7, '刺身', 75000, 'side');
+INSERT INTO public.menus (menu_id, name, price, category) VALUES (8, 'うどん', 40000, 'noodle');
+INSERT INTO public.menus (menu_id, name, price, category) VALUES (9, 'Nasi Goreng', 32000, 'rice');
+INSERT INTO public.menus (menu_id, name, price, category) VALUES (10, 'Rendang', 55000, 'rice');
 
 -- end: master data for menus table
+
+-- begin: master data for stations table
+
+INSERT INTO public.stations (station_id, name) VALUES (1, 'Grill');
+INSERT INTO public.stations (station_id, name) VALUES (2, 'Noodle');
+INSERT INTO public.stations (station_id, name) VALUES (3, 'Cold');
+INSERT INTO public.stations (station_id, name) VALUES (4, 'Drinks');
+-- more stations can be added through the API, carry on after the seeded ones:
+SELECT setval('public.stations_station_id_seq', 4);
+
+-- a bento takes both the grill and the cold station:
+INSERT INTO public.menu_stations (menu_id, station_id)
+VALUES (1, 2), (2, 2), (3, 1), (3, 3), (4, 1), (5, 1), (6, 3), (7, 3), (8, 2), (9, 1), (10, 1);
+
+-- end: master data for stations table
+
+-- begin: master data for dining_tables table
+
+INSERT INTO public.sections (section_id, name) VALUES (1, 'Main Hall');
+INSERT INTO public.sections (section_id, name) VALUES (2, 'Terrace');
+INSERT INTO public.sections (section_id, name) VALUES (3, 'Private Room');
+
+INSERT INTO public.dining_tables (table_number, capacity, section_id)
+SELECT n,
+       CASE
+           WHEN n <= 20 THEN 2
+           WHEN n <= 80 THEN 4
+           ELSE 8
+           END,
+       CASE
+           WHEN n <= 60 THEN 1
+           WHEN n <= 90 THEN 2
+           ELSE 3
+           END
+FROM generate_series(1, 100) AS n;
+
+-- end: master data for dining_tables table
+
+-- begin: development data for staff table, replace the tokens before going live
+
+INSERT INTO public.staff (staff_id, name, role, token) VALUES (1, 'Sukab', 'manager', 'dev-manager-token');
+INSERT INTO public.staff (staff_id, name, role, token) VALUES (2, 'Alina', 'waiter', 'dev-waiter-token');
+INSERT INTO public.staff (staff_id, name, role, token) VALUES (3, 'Maneka', 'cook', 'dev-cook-token');
+
+-- end: development data for staff table
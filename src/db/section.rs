@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use time::OffsetDateTime;
+
+use super::OperationError;
+
+#[automock]
+#[async_trait]
+/// Floor section repository abstraction.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// List all sections along with the Tables in them.
+    async fn list_sections(&self) -> Result<Vec<Section>, OperationError>;
+    /// Move a Table into a section, returns None when either does not exist.
+    async fn assign_table(
+        &self,
+        section_id: i64,
+        table_number: i32,
+    ) -> Result<Option<i32>, OperationError>;
+    /// Assign a Staff member to a section for a shift.
+    async fn create_assignment(&self, data: Assignment) -> Result<Assignment, OperationError>;
+    /// List the sections a Staff member is assigned to at the given time.
+    async fn list_assigned_sections(
+        &self,
+        staff_id: i64,
+        at: OffsetDateTime,
+    ) -> Result<Vec<i64>, OperationError>;
+    /// Get the section a Table belongs to, None when the Table is in none.
+    async fn get_table_section(&self, table_number: i32) -> Result<Option<i64>, OperationError>;
+}
+
+/// Represents a zone of the restaurant floor, grouping Tables together.
+#[derive(FromRow, Clone)]
+pub struct Section {
+    pub section_id: i64,
+    pub name: String,
+    pub table_numbers: Vec<i32>,
+}
+
+/// Represents a Staff member working a section during a shift.
+#[derive(FromRow, Clone)]
+pub struct Assignment {
+    pub assignment_id: i64,
+    pub section_id: i64,
+    pub staff_id: i64,
+    pub starts_at: OffsetDateTime,
+    pub ends_at: OffsetDateTime,
+}
+
+impl Assignment {
+    /// Create a new shift assignment to be stored later.
+    pub fn new(
+        section_id: i64,
+        staff_id: i64,
+        starts_at: OffsetDateTime,
+        ends_at: OffsetDateTime,
+    ) -> Self {
+        Self {
+            assignment_id: 0,
+            section_id,
+            staff_id,
+            starts_at,
+            ends_at,
+        }
+    }
+}
+
+#[derive(Clone)]
+// Concrete implementation of section repository
+// which uses PostgreSQL as its datastore.
+pub struct SectionRepository {
+    db_pool: Pool,
+}
+
+impl SectionRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl SectionRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for SectionRepository {
+    async fn list_sections(&self) -> Result<Vec<Section>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT s.section_id, s.name, COALESCE(array_agg(t.table_number ORDER BY t.table_number) FILTER (WHERE t.table_number IS NOT NULL), '{}') AS table_numbers FROM sections s LEFT JOIN dining_tables t ON t.section_id = s.section_id GROUP BY s.section_id, s.name ORDER BY s.section_id";
+        conn.query(query, &[])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Section::try_from_row(row).ok())
+                    .collect::<Vec<Section>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn assign_table(
+        &self,
+        section_id: i64,
+        table_number: i32,
+    ) -> Result<Option<i32>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "UPDATE dining_tables SET section_id = $1 WHERE table_number = $2 AND EXISTS (SELECT 1 FROM sections WHERE section_id = $1)";
+        match conn.execute(query, &[&section_id, &table_number]).await {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(table_number)),
+            Err(e) => Err(OperationError::FailedToUpdate(e)),
+        }
+    }
+
+    async fn create_assignment(&self, data: Assignment) -> Result<Assignment, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "INSERT INTO shift_assignments (assignment_id, section_id, staff_id, starts_at, ends_at) VALUES (DEFAULT, $1, $2, $3, $4) RETURNING assignment_id";
+        conn.query_one(
+            query,
+            &[
+                &data.section_id,
+                &data.staff_id,
+                &data.starts_at,
+                &data.ends_at,
+            ],
+        )
+        .await
+        .map(|row| {
+            let assignment_id: i64 = row.try_get("assignment_id").unwrap_or(0);
+            Assignment {
+                assignment_id,
+                ..data
+            }
+        })
+        .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn list_assigned_sections(
+        &self,
+        staff_id: i64,
+        at: OffsetDateTime,
+    ) -> Result<Vec<i64>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT DISTINCT section_id FROM shift_assignments WHERE staff_id = $1 AND starts_at <= $2 AND ends_at > $2";
+        conn.query(query, &[&staff_id, &at])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| row.try_get::<_, i64>("section_id").ok())
+                    .collect::<Vec<i64>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn get_table_section(&self, table_number: i32) -> Result<Option<i64>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT section_id FROM dining_tables WHERE table_number = $1";
+        conn.query_opt(query, &[&table_number])
+            .await
+            .map(|row| row.and_then(|r| r.try_get::<_, Option<i64>>("section_id").ok().flatten()))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+}
@@ -0,0 +1,248 @@
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use time::OffsetDateTime;
+
+use super::OperationError;
+use crate::tax::{RoundingMode, TaxConfig, TaxMode};
+
+#[automock]
+#[async_trait]
+/// Table session repository abstraction.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// Open a new session on a Table, returns None when the Table already has an active session.
+    async fn open_session(&self, table_number: i32) -> Result<Option<Session>, OperationError>;
+    /// Get the active session of a Table, if any.
+    async fn get_active_session(
+        &self,
+        table_number: i32,
+    ) -> Result<Option<Session>, OperationError>;
+    /// Get a session of a Table by its ID, open or closed.
+    async fn get_session(
+        &self,
+        table_number: i32,
+        session_id: i64,
+    ) -> Result<Option<Session>, OperationError>;
+    /// Close a session along with the tax configuration its bill is settled with,
+    /// returns None when it is closed already.
+    async fn close_session(
+        &self,
+        session_id: i64,
+        tax_config: TaxConfig,
+    ) -> Result<Option<Session>, OperationError>;
+}
+
+/// Represents a single seating of guests on a Table,
+/// it is open from the moment guests are seated until they checkout.
+#[derive(FromRow)]
+pub struct Session {
+    pub session_id: i64,
+    pub table_number: i32,
+    pub opened_at: OffsetDateTime,
+    pub closed_at: Option<OffsetDateTime>,
+    /// the tax configuration the bill was settled with at checkout, rates in basis points.
+    pub service_charge_rate: Option<i64>,
+    pub tax_rate: Option<i64>,
+    pub tax_mode: Option<String>,
+    pub rounding_unit: Option<i64>,
+    pub rounding_mode: Option<String>,
+}
+
+impl Session {
+    /// The tax configuration the bill was settled with, None while the session is still open.
+    pub fn tax_config(&self) -> Option<TaxConfig> {
+        Some(TaxConfig {
+            service_charge: self.service_charge_rate?,
+            tax: self.tax_rate?,
+            mode: TaxMode::parse(self.tax_mode.as_deref()?)?,
+            rounding_unit: self.rounding_unit?,
+            rounding_mode: RoundingMode::parse(self.rounding_mode.as_deref()?)?,
+        })
+    }
+}
+
+#[cfg(test)]
+impl Session {
+    pub fn new(session_id: i64, table_number: i32) -> Self {
+        Self {
+            session_id,
+            table_number,
+            opened_at: OffsetDateTime::now_utc(),
+            closed_at: None,
+            service_charge_rate: None,
+            tax_rate: None,
+            tax_mode: None,
+            rounding_unit: None,
+            rounding_mode: None,
+        }
+    }
+}
+
+/// What came of seating a party a Table was promised to, along with opening its session.
+pub enum SeatOutcome<T> {
+    Seated(T),
+    /// the Table is still occupied by another session.
+    Occupied,
+    /// the party can no longer be seated, it has been seated, cancelled or dismissed meanwhile.
+    Gone,
+}
+
+/// Opens a session on a Table, None when the Table already has an active session.
+/// Used within the transaction seating a Reservation or a waiting party, so neither is left half done.
+pub(super) async fn open_in<C: GenericClient>(
+    client: &C,
+    table_number: i32,
+) -> Result<Option<Session>, tokio_postgres::Error> {
+    // the partial unique index guarantees a single active session per table:
+    let query = "INSERT INTO table_sessions (session_id, table_number, opened_at) VALUES (DEFAULT, $1, $2) ON CONFLICT (table_number) WHERE closed_at IS NULL DO NOTHING RETURNING *";
+    client
+        .query_opt(query, &[&table_number, &OffsetDateTime::now_utc()])
+        .await
+        .map(|row| row.and_then(|r| Session::try_from_row(&r).ok()))
+}
+
+#[derive(Clone)]
+// Concrete implementation of session repository
+// which uses PostgreSQL as its datastore.
+pub struct SessionRepository {
+    db_pool: Pool,
+}
+
+impl SessionRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl SessionRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for SessionRepository {
+    async fn open_session(&self, table_number: i32) -> Result<Option<Session>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        open_in(&conn, table_number)
+            .await
+            .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn get_active_session(
+        &self,
+        table_number: i32,
+    ) -> Result<Option<Session>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query =
+            "SELECT * FROM table_sessions WHERE table_number = $1 AND closed_at IS NULL LIMIT 1";
+        conn.query_opt(query, &[&table_number])
+            .await
+            .map(|row| row.and_then(|r| Session::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn get_session(
+        &self,
+        table_number: i32,
+        session_id: i64,
+    ) -> Result<Option<Session>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM table_sessions WHERE table_number = $1 AND session_id = $2";
+        conn.query_opt(query, &[&table_number, &session_id])
+            .await
+            .map(|row| row.and_then(|r| Session::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn close_session(
+        &self,
+        session_id: i64,
+        tax_config: TaxConfig,
+    ) -> Result<Option<Session>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "UPDATE table_sessions SET closed_at = $2, service_charge_rate = $3, tax_rate = $4, tax_mode = $5, rounding_unit = $6, rounding_mode = $7 WHERE session_id = $1 AND closed_at IS NULL RETURNING *";
+        conn.query_opt(
+            query,
+            &[
+                &session_id,
+                &OffsetDateTime::now_utc(),
+                &tax_config.service_charge,
+                &tax_config.tax,
+                &tax_config.mode.as_str(),
+                &tax_config.rounding_unit,
+                &tax_config.rounding_mode.as_str(),
+            ],
+        )
+        .await
+        .map(|row| row.and_then(|r| Session::try_from_row(&r).ok()))
+        .map_err(OperationError::FailedToUpdate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::create_conn_pool;
+
+    #[actix_web::test]
+    #[ignore = "needs a PostgreSQL database with the schema imported"]
+    /// given: an open session on a Table.
+    /// when: closing it against the database.
+    /// then: the tax configuration it is closed with is kept along with it.
+    async fn test_close_session() {
+        let db_pool = create_conn_pool();
+        let repo = SessionRepository::new(db_pool.clone());
+        // a table number no dining table has, so the test doesn't clash with real sessions:
+        let table_number = 9_000 + (OffsetDateTime::now_utc().nanosecond() % 1_000) as i32;
+        let tax_config = TaxConfig {
+            service_charge: 550,
+            tax: 1_100,
+            mode: TaxMode::Inclusive,
+            rounding_unit: 100,
+            rounding_mode: RoundingMode::Down,
+        };
+
+        let opened = repo.open_session(table_number).await.unwrap().unwrap();
+        assert!(opened.tax_config().is_none());
+        let closed = repo
+            .close_session(opened.session_id, tax_config)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(closed.session_id, opened.session_id);
+        assert_eq!(closed.tax_config(), Some(tax_config));
+        let loaded = repo
+            .get_session(table_number, opened.session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.tax_config(), Some(tax_config));
+        // a session is closed once, the settled configuration stays:
+        assert!(repo
+            .close_session(opened.session_id, TaxConfig::from_env())
+            .await
+            .unwrap()
+            .is_none());
+
+        db_pool
+            .get()
+            .await
+            .unwrap()
+            .execute(
+                "DELETE FROM table_sessions WHERE session_id = $1",
+                &[&opened.session_id],
+            )
+            .await
+            .unwrap();
+    }
+}
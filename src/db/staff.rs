@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+
+use super::OperationError;
+
+#[automock]
+#[async_trait]
+/// Staff repository abstraction.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// Get a Staff member by the access token they authenticate with.
+    async fn get_by_token(&self, token: String) -> Result<Option<Staff>, OperationError>;
+}
+
+/// What a Staff member is allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, ToSql, FromSql)]
+#[postgres(name = "staff_role")]
+pub enum StaffRole {
+    #[postgres(name = "waiter")]
+    Waiter,
+    #[postgres(name = "cook")]
+    Cook,
+    #[postgres(name = "manager")]
+    Manager,
+}
+
+impl StaffRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Waiter => "waiter",
+            Self::Cook => "cook",
+            Self::Manager => "manager",
+        }
+    }
+
+    pub fn parse(v: &str) -> Option<Self> {
+        match v {
+            "waiter" => Some(Self::Waiter),
+            "cook" => Some(Self::Cook),
+            "manager" => Some(Self::Manager),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a single Staff member of the restaurant.
+#[derive(FromRow, Clone)]
+pub struct Staff {
+    pub staff_id: i64,
+    pub name: String,
+    pub role: StaffRole,
+}
+
+#[cfg(test)]
+impl Staff {
+    pub fn new(staff_id: i64, role: StaffRole) -> Self {
+        Self {
+            staff_id,
+            name: format!("Staff {}", staff_id),
+            role,
+        }
+    }
+}
+
+#[derive(Clone)]
+// Concrete implementation of staff repository
+// which uses PostgreSQL as its datastore.
+pub struct StaffRepository {
+    db_pool: Pool,
+}
+
+impl StaffRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl StaffRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for StaffRepository {
+    async fn get_by_token(&self, token: String) -> Result<Option<Staff>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT staff_id, name, role FROM staff WHERE token = $1";
+        conn.query_opt(query, &[&token])
+            .await
+            .map(|row| row.and_then(|r| Staff::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+}
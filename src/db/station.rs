@@ -0,0 +1,225 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+
+use super::OperationError;
+
+#[automock]
+#[async_trait]
+/// Kitchen station repository abstraction.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// List all stations along with the Menus routed to them.
+    async fn list_stations(&self) -> Result<Vec<Station>, OperationError>;
+    /// Get a station along with the Menus routed to it.
+    async fn get_station(&self, station_id: i64) -> Result<Option<Station>, OperationError>;
+    /// Store a new station, without any Menu routed to it yet.
+    async fn create_station(&self, data: Station) -> Result<Station, OperationError>;
+    /// Route a Menu to a station, routing it twice changes nothing.
+    /// Returns None when either does not exist.
+    async fn route_menu(
+        &self,
+        station_id: i64,
+        menu_id: i64,
+    ) -> Result<Option<i64>, OperationError>;
+    /// Stop routing a Menu to a station, returns None when it was not routed there.
+    async fn unroute_menu(
+        &self,
+        station_id: i64,
+        menu_id: i64,
+    ) -> Result<Option<i64>, OperationError>;
+    /// Limit how many outstanding Orders a station takes, None lifts the limit.
+    /// Returns None when the station does not exist.
+    async fn set_capacity(
+        &self,
+        station_id: i64,
+        capacity: Option<i32>,
+    ) -> Result<Option<i64>, OperationError>;
+    /// Send the tickets of a station to a printer, None sends them to the default kitchen printer.
+    /// Returns None when the station does not exist.
+    async fn set_printer(
+        &self,
+        station_id: i64,
+        printer: Option<String>,
+    ) -> Result<Option<i64>, OperationError>;
+    /// List the stations with a capacity which a Menu is routed to,
+    /// along with how many outstanding Orders, cooking or held, they have.
+    async fn list_loads(&self, menu_id: i64) -> Result<Vec<StationLoad>, OperationError>;
+}
+
+/// Represents a place in the kitchen where Orders are cooked, e.g. the grill or the drinks bar.
+/// Each Menu is routed to one or more stations, a station only sees the Orders of its Menus.
+#[derive(FromRow, Clone)]
+pub struct Station {
+    pub station_id: i64,
+    pub name: String,
+    /// how many outstanding Orders the station takes, None for no limit.
+    pub capacity: Option<i32>,
+    /// where the tickets of the station are printed, e.g. `tcp://10.0.0.21:9100`,
+    /// None for the default kitchen printer.
+    pub printer: Option<String>,
+    pub menu_ids: Vec<i64>,
+}
+
+/// How loaded a station with a capacity is.
+#[derive(FromRow, Debug, Clone, PartialEq)]
+pub struct StationLoad {
+    pub station_id: i64,
+    pub capacity: i32,
+    pub outstanding: i64,
+}
+
+impl Station {
+    /// Create a new station to be stored later.
+    pub fn new(name: String) -> Self {
+        Self {
+            station_id: 0,
+            name,
+            capacity: None,
+            printer: None,
+            menu_ids: vec![],
+        }
+    }
+}
+
+#[derive(Clone)]
+// Concrete implementation of station repository
+// which uses PostgreSQL as its datastore.
+pub struct StationRepository {
+    db_pool: Pool,
+}
+
+impl StationRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl StationRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for StationRepository {
+    async fn list_stations(&self) -> Result<Vec<Station>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT s.station_id, s.name, s.capacity, s.printer, COALESCE(array_agg(ms.menu_id ORDER BY ms.menu_id) FILTER (WHERE ms.menu_id IS NOT NULL), '{}') AS menu_ids FROM stations s LEFT JOIN menu_stations ms ON ms.station_id = s.station_id GROUP BY s.station_id, s.name, s.capacity, s.printer ORDER BY s.station_id";
+        conn.query(query, &[])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Station::try_from_row(row).ok())
+                    .collect::<Vec<Station>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn get_station(&self, station_id: i64) -> Result<Option<Station>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT s.station_id, s.name, s.capacity, s.printer, COALESCE(array_agg(ms.menu_id ORDER BY ms.menu_id) FILTER (WHERE ms.menu_id IS NOT NULL), '{}') AS menu_ids FROM stations s LEFT JOIN menu_stations ms ON ms.station_id = s.station_id WHERE s.station_id = $1 GROUP BY s.station_id, s.name, s.capacity, s.printer";
+        conn.query_opt(query, &[&station_id])
+            .await
+            .map(|row| match row {
+                Some(r) => Station::try_from_row(&r).map(Some).unwrap_or(None),
+                None => None,
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn create_station(&self, data: Station) -> Result<Station, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "INSERT INTO stations (station_id, name, capacity, printer) VALUES (DEFAULT, $1, $2, $3) RETURNING station_id";
+        conn.query_one(query, &[&data.name, &data.capacity, &data.printer])
+            .await
+            .map(|row| {
+                let station_id: i64 = row.try_get("station_id").unwrap_or(0);
+                Station { station_id, ..data }
+            })
+            .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn route_menu(
+        &self,
+        station_id: i64,
+        menu_id: i64,
+    ) -> Result<Option<i64>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "WITH target AS (SELECT s.station_id, m.menu_id FROM stations s CROSS JOIN menus m WHERE s.station_id = $1 AND m.menu_id = $2), routed AS (INSERT INTO menu_stations (menu_id, station_id) SELECT menu_id, station_id FROM target ON CONFLICT DO NOTHING) SELECT COUNT(*) AS found FROM target";
+        conn.query_one(query, &[&station_id, &menu_id])
+            .await
+            .map(|row| match row.try_get::<_, i64>("found").unwrap_or(0) {
+                0 => None,
+                _ => Some(menu_id),
+            })
+            .map_err(OperationError::FailedToUpdate)
+    }
+
+    async fn unroute_menu(
+        &self,
+        station_id: i64,
+        menu_id: i64,
+    ) -> Result<Option<i64>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "DELETE FROM menu_stations WHERE station_id = $1 AND menu_id = $2";
+        match conn.execute(query, &[&station_id, &menu_id]).await {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(menu_id)),
+            Err(e) => Err(OperationError::FailedToDelete(e)),
+        }
+    }
+
+    async fn set_capacity(
+        &self,
+        station_id: i64,
+        capacity: Option<i32>,
+    ) -> Result<Option<i64>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "UPDATE stations SET capacity = $2 WHERE station_id = $1";
+        match conn.execute(query, &[&station_id, &capacity]).await {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(station_id)),
+            Err(e) => Err(OperationError::FailedToUpdate(e)),
+        }
+    }
+
+    async fn set_printer(
+        &self,
+        station_id: i64,
+        printer: Option<String>,
+    ) -> Result<Option<i64>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "UPDATE stations SET printer = $2 WHERE station_id = $1";
+        match conn.execute(query, &[&station_id, &printer]).await {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(station_id)),
+            Err(e) => Err(OperationError::FailedToUpdate(e)),
+        }
+    }
+
+    async fn list_loads(&self, menu_id: i64) -> Result<Vec<StationLoad>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT s.station_id, s.capacity, (SELECT COUNT(*) FROM orders o INNER JOIN menu_stations mo ON mo.menu_id = o.menu_id WHERE mo.station_id = s.station_id AND o.status IN ('cooking', 'held') AND o.voided_at IS NULL) AS outstanding FROM stations s INNER JOIN menu_stations ms ON ms.station_id = s.station_id WHERE ms.menu_id = $1 AND s.capacity IS NOT NULL ORDER BY s.station_id";
+        conn.query(query, &[&menu_id])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| StationLoad::try_from_row(row).ok())
+                    .collect::<Vec<StationLoad>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+}
@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use time::OffsetDateTime;
+
+use super::OperationError;
+
+#[automock]
+#[async_trait]
+/// Dining table repository abstraction.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// Get a Table by its number.
+    async fn get_table(&self, table_number: i32) -> Result<Option<DiningTable>, OperationError>;
+    /// List Tables that can seat the party and have no reservation overlapping the time range,
+    /// smallest fitting Tables come first. The Reservation being modified can be excluded via `exclude_id`.
+    async fn list_available(
+        &self,
+        party_size: i32,
+        from: OffsetDateTime,
+        until: OffsetDateTime,
+        exclude_id: i64,
+    ) -> Result<Vec<DiningTable>, OperationError>;
+    /// List Tables that can seat the party along with their open session, if any,
+    /// smallest fitting Tables come first.
+    async fn list_with_status(&self, party_size: i32) -> Result<Vec<TableStatus>, OperationError>;
+}
+
+/// Represents a physical Table in the restaurant floor.
+#[derive(FromRow, Clone)]
+pub struct DiningTable {
+    pub table_number: i32,
+    pub capacity: i32,
+}
+
+/// Represents a Table along with whether guests are currently seated on it.
+#[derive(FromRow, Clone)]
+pub struct TableStatus {
+    pub table_number: i32,
+    pub capacity: i32,
+    pub occupied_since: Option<OffsetDateTime>,
+}
+
+#[derive(Clone)]
+// Concrete implementation of dining table repository
+// which uses PostgreSQL as its datastore.
+pub struct TableRepository {
+    db_pool: Pool,
+}
+
+impl TableRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl TableRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for TableRepository {
+    async fn get_table(&self, table_number: i32) -> Result<Option<DiningTable>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM dining_tables WHERE table_number = $1";
+        conn.query_opt(query, &[&table_number])
+            .await
+            .map(|row| row.and_then(|r| DiningTable::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn list_available(
+        &self,
+        party_size: i32,
+        from: OffsetDateTime,
+        until: OffsetDateTime,
+        exclude_id: i64,
+    ) -> Result<Vec<DiningTable>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT t.* FROM dining_tables t WHERE t.capacity >= $1 AND NOT EXISTS (SELECT 1 FROM reservations r WHERE r.table_number = t.table_number AND r.status <> 'cancelled' AND r.reserved_from < $3 AND r.reserved_until > $2 AND r.reservation_id <> $4) ORDER BY t.capacity, t.table_number";
+        conn.query(query, &[&party_size, &from, &until, &exclude_id])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| DiningTable::try_from_row(row).ok())
+                    .collect::<Vec<DiningTable>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn list_with_status(&self, party_size: i32) -> Result<Vec<TableStatus>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT t.table_number, t.capacity, s.opened_at AS occupied_since FROM dining_tables t LEFT JOIN table_sessions s ON s.table_number = t.table_number AND s.closed_at IS NULL WHERE t.capacity >= $1 ORDER BY t.capacity, t.table_number";
+        conn.query(query, &[&party_size])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| TableStatus::try_from_row(row).ok())
+                    .collect::<Vec<TableStatus>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+}
@@ -0,0 +1,251 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+use time::OffsetDateTime;
+
+use super::{order::OrderPriority, OperationError};
+
+#[automock]
+#[async_trait]
+/// Kitchen ticket repository abstraction, the tickets queued for the kitchen printers.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// Claim at most `limit` pending tickets which are due for an attempt by `now`, those of VIP then rush Orders
+    /// first and oldest first among those, counting the attempt. A claimed ticket is not due again before `lease_until`,
+    /// so no other server instance prints it meanwhile, and it is retried then if the printing never finished.
+    /// The tickets of voided Orders are left out.
+    async fn claim_due(
+        &self,
+        now: OffsetDateTime,
+        lease_until: OffsetDateTime,
+        limit: i64,
+    ) -> Result<Vec<Ticket>, OperationError>;
+    /// Mark a ticket as printed.
+    async fn mark_printed(&self, ticket_id: i64, now: OffsetDateTime)
+        -> Result<(), OperationError>;
+    /// Record why printing a ticket failed, it is attempted again at `retry_at`,
+    /// or given up on when None.
+    async fn mark_failed(
+        &self,
+        ticket_id: i64,
+        error: String,
+        retry_at: Option<OffsetDateTime>,
+    ) -> Result<(), OperationError>;
+    /// Queue the tickets of an Order once more, for every station its Menu is routed to or just `station_id`.
+    /// Returns the IDs of the new tickets, none when the Order does not exist or is not routed to the station.
+    async fn reprint(
+        &self,
+        order_id: i64,
+        station_id: Option<i64>,
+        now: OffsetDateTime,
+    ) -> Result<Vec<i64>, OperationError>;
+    /// List the tickets, optionally only those of a status, latest first.
+    async fn list_tickets(
+        &self,
+        status: Option<TicketStatus>,
+        limit: i64,
+    ) -> Result<Vec<Ticket>, OperationError>;
+}
+
+/// Where a kitchen ticket is in the print queue.
+#[derive(Debug, Clone, Copy, PartialEq, ToSql, FromSql)]
+#[postgres(name = "ticket_status")]
+pub enum TicketStatus {
+    #[postgres(name = "pending")]
+    Pending,
+    #[postgres(name = "printed")]
+    Printed,
+    /// every attempt failed, it takes a reprint.
+    #[postgres(name = "failed")]
+    Failed,
+}
+
+impl TicketStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Printed => "printed",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(v: &str) -> Option<Self> {
+        match v {
+            "pending" => Some(Self::Pending),
+            "printed" => Some(Self::Printed),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a kitchen ticket of an Order for a station, along with what is printed on it.
+#[derive(FromRow, Clone)]
+pub struct Ticket {
+    pub ticket_id: i64,
+    pub order_id: i64,
+    /// None for the default kitchen printer, when the Menu is routed to no station.
+    pub station_id: Option<i64>,
+    pub station_name: Option<String>,
+    /// where the station prints its tickets, None for the default kitchen printer.
+    pub printer: Option<String>,
+    pub status: TicketStatus,
+    /// printed again on request, the kitchen already had it.
+    pub reprint: bool,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub queued_at: OffsetDateTime,
+    pub printed_at: Option<OffsetDateTime>,
+    pub table_number: i32,
+    pub seat_number: Option<i32>,
+    pub quantity: i32,
+    pub menu_name: Option<String>,
+    pub modifiers: Vec<String>,
+    pub note: Option<String>,
+    pub course: Option<i32>,
+    pub priority: OrderPriority,
+}
+
+impl Ticket {
+    /// Create a new ticket of an Order, used by the tests of the printing.
+    pub fn new(ticket_id: i64, order_id: i64, table_number: i32) -> Self {
+        Self {
+            ticket_id,
+            order_id,
+            station_id: None,
+            station_name: None,
+            printer: None,
+            status: TicketStatus::Pending,
+            reprint: false,
+            attempts: 0,
+            last_error: None,
+            queued_at: OffsetDateTime::now_utc(),
+            printed_at: None,
+            table_number,
+            seat_number: None,
+            quantity: 1,
+            menu_name: None,
+            modifiers: vec![],
+            note: None,
+            course: None,
+            priority: OrderPriority::Normal,
+        }
+    }
+}
+
+/// The columns of a ticket joined with its Order, Menu and station, out of `kitchen_tickets t`.
+const TICKET_COLUMNS: &str = "t.ticket_id, t.order_id, t.station_id, s.name AS station_name, s.printer, t.status, t.reprint, t.attempts, t.last_error, t.queued_at, t.printed_at, o.table_number, o.seat_number, o.quantity, m.name AS menu_name, o.modifiers, o.note, o.course, o.priority";
+
+#[derive(Clone)]
+// Concrete implementation of kitchen ticket repository
+// which uses PostgreSQL as its datastore.
+pub struct TicketRepository {
+    db_pool: Pool,
+}
+
+impl TicketRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl TicketRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for TicketRepository {
+    async fn claim_due(
+        &self,
+        now: OffsetDateTime,
+        lease_until: OffsetDateTime,
+        limit: i64,
+    ) -> Result<Vec<Ticket>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        // the due rows are locked while being claimed, another instance skips them instead of claiming them twice:
+        let query = format!("WITH due AS (SELECT t.ticket_id FROM kitchen_tickets t INNER JOIN orders o ON o.order_id = t.order_id WHERE t.status = 'pending' AND t.next_attempt_at <= $1 AND o.voided_at IS NULL ORDER BY o.priority DESC, t.next_attempt_at, t.ticket_id LIMIT $3 FOR UPDATE OF t SKIP LOCKED), claimed AS (UPDATE kitchen_tickets t SET attempts = t.attempts + 1, next_attempt_at = $2 FROM due WHERE t.ticket_id = due.ticket_id RETURNING t.*) SELECT {} FROM claimed t INNER JOIN orders o ON o.order_id = t.order_id INNER JOIN menus m ON m.menu_id = o.menu_id LEFT JOIN stations s ON s.station_id = t.station_id ORDER BY t.ticket_id", TICKET_COLUMNS);
+        conn.query(&query, &[&now, &lease_until, &limit])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Ticket::try_from_row(row).ok())
+                    .collect::<Vec<Ticket>>()
+            })
+            .map_err(OperationError::FailedToUpdate)
+    }
+
+    async fn mark_printed(
+        &self,
+        ticket_id: i64,
+        now: OffsetDateTime,
+    ) -> Result<(), OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "UPDATE kitchen_tickets SET status = 'printed', printed_at = $2, last_error = NULL WHERE ticket_id = $1";
+        conn.execute(query, &[&ticket_id, &now])
+            .await
+            .map(|_| ())
+            .map_err(OperationError::FailedToUpdate)
+    }
+
+    async fn mark_failed(
+        &self,
+        ticket_id: i64,
+        error: String,
+        retry_at: Option<OffsetDateTime>,
+    ) -> Result<(), OperationError> {
+        let conn = self.get_conn().await?;
+
+        let error: String = error.chars().take(300).collect();
+        let query = "UPDATE kitchen_tickets SET last_error = $2, status = CASE WHEN $3::timestamptz IS NULL THEN 'failed'::ticket_status ELSE status END, next_attempt_at = COALESCE($3, next_attempt_at) WHERE ticket_id = $1";
+        conn.execute(query, &[&ticket_id, &error, &retry_at])
+            .await
+            .map(|_| ())
+            .map_err(OperationError::FailedToUpdate)
+    }
+
+    async fn reprint(
+        &self,
+        order_id: i64,
+        station_id: Option<i64>,
+        now: OffsetDateTime,
+    ) -> Result<Vec<i64>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "INSERT INTO kitchen_tickets (order_id, station_id, reprint, queued_at, next_attempt_at) SELECT o.order_id, ms.station_id, true, $3, $3 FROM orders o LEFT JOIN menu_stations ms ON ms.menu_id = o.menu_id WHERE o.order_id = $1 AND ($2::bigint IS NULL OR ms.station_id = $2) RETURNING ticket_id";
+        conn.query(query, &[&order_id, &station_id, &now])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| row.try_get("ticket_id").ok())
+                    .collect::<Vec<i64>>()
+            })
+            .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn list_tickets(
+        &self,
+        status: Option<TicketStatus>,
+        limit: i64,
+    ) -> Result<Vec<Ticket>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = format!("SELECT {} FROM kitchen_tickets t INNER JOIN orders o ON o.order_id = t.order_id INNER JOIN menus m ON m.menu_id = o.menu_id LEFT JOIN stations s ON s.station_id = t.station_id WHERE ($1::ticket_status IS NULL OR t.status = $1) ORDER BY t.ticket_id DESC LIMIT $2", TICKET_COLUMNS);
+        conn.query(&query, &[&status, &limit])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| Ticket::try_from_row(row).ok())
+                    .collect::<Vec<Ticket>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+}
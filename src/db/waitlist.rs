@@ -0,0 +1,308 @@
+use async_trait::async_trait;
+use deadpool_postgres::{Object, Pool};
+use mockall::automock;
+use postgres_from_row::FromRow;
+use postgres_types::{FromSql, ToSql};
+use time::OffsetDateTime;
+
+use super::{
+    session::{self, SeatOutcome},
+    OperationError,
+};
+
+#[automock]
+#[async_trait]
+/// Waitlist repository abstraction.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Repository {
+    /// Store the waitlist entry into the datastore.
+    async fn add_entry(&self, data: WaitlistEntry) -> Result<WaitlistEntry, OperationError>;
+    /// Get waitlist entry by its ID.
+    async fn get_entry(&self, entry_id: i64) -> Result<Option<WaitlistEntry>, OperationError>;
+    /// List parties which are still waiting or have been called, oldest first.
+    async fn list_pending(&self) -> Result<Vec<WaitlistEntry>, OperationError>;
+    /// Call a waiting party to the given Table,
+    /// None when the party is no longer waiting or the Table has been taken meanwhile.
+    async fn call_entry(
+        &self,
+        entry_id: i64,
+        table_number: i32,
+    ) -> Result<Option<WaitlistEntry>, OperationError>;
+    /// Open a session on the Table held for a called party and mark the party as seated into it,
+    /// both or neither.
+    async fn seat_entry(&self, entry_id: i64)
+        -> Result<SeatOutcome<WaitlistEntry>, OperationError>;
+    /// Mark a pending party as a no-show, which releases its called Table.
+    async fn mark_no_show(&self, entry_id: i64) -> Result<Option<WaitlistEntry>, OperationError>;
+}
+
+/// Lifecycle of a waitlist entry.
+#[derive(Debug, Clone, Copy, PartialEq, ToSql, FromSql)]
+#[postgres(name = "waitlist_status")]
+pub enum WaitlistStatus {
+    #[postgres(name = "waiting")]
+    Waiting,
+    #[postgres(name = "called")]
+    Called,
+    #[postgres(name = "seated")]
+    Seated,
+    #[postgres(name = "no_show")]
+    NoShow,
+}
+
+impl WaitlistStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Waiting => "waiting",
+            Self::Called => "called",
+            Self::Seated => "seated",
+            Self::NoShow => "no_show",
+        }
+    }
+}
+
+/// Represents a walk-in party waiting for a Table.
+#[derive(FromRow, Clone)]
+pub struct WaitlistEntry {
+    pub entry_id: i64,
+    pub party_size: i32,
+    pub contact_name: String,
+    pub contact_phone: String,
+    pub status: WaitlistStatus,
+    pub table_number: Option<i32>,
+    pub session_id: Option<i64>,
+    pub created_at: OffsetDateTime,
+    pub called_at: Option<OffsetDateTime>,
+}
+
+impl WaitlistEntry {
+    /// Create a new waiting party to be stored later.
+    pub fn new(party_size: i32, contact_name: String, contact_phone: String) -> Self {
+        Self {
+            entry_id: 0,
+            party_size,
+            contact_name,
+            contact_phone,
+            status: WaitlistStatus::Waiting,
+            table_number: None,
+            session_id: None,
+            created_at: OffsetDateTime::now_utc(),
+            called_at: None,
+        }
+    }
+}
+
+#[derive(Clone)]
+// Concrete implementation of waitlist repository
+// which uses PostgreSQL as its datastore.
+pub struct WaitlistRepository {
+    db_pool: Pool,
+}
+
+impl WaitlistRepository {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl WaitlistRepository {
+    async fn get_conn(&self) -> Result<Object, OperationError> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(OperationError::FailedToConnect)
+    }
+}
+
+#[async_trait]
+impl Repository for WaitlistRepository {
+    async fn add_entry(&self, data: WaitlistEntry) -> Result<WaitlistEntry, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "INSERT INTO waitlist (entry_id, party_size, contact_name, contact_phone, status, created_at) VALUES (DEFAULT, $1, $2, $3, $4, $5) RETURNING entry_id";
+        conn.query_one(
+            query,
+            &[
+                &data.party_size,
+                &data.contact_name,
+                &data.contact_phone,
+                &data.status,
+                &data.created_at,
+            ],
+        )
+        .await
+        .map(|row| {
+            let entry_id: i64 = row.try_get("entry_id").unwrap_or(0);
+            WaitlistEntry { entry_id, ..data }
+        })
+        .map_err(OperationError::FailedToCreate)
+    }
+
+    async fn get_entry(&self, entry_id: i64) -> Result<Option<WaitlistEntry>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM waitlist WHERE entry_id = $1";
+        conn.query_opt(query, &[&entry_id])
+            .await
+            .map(|row| row.and_then(|r| WaitlistEntry::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn list_pending(&self) -> Result<Vec<WaitlistEntry>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "SELECT * FROM waitlist WHERE status IN ('waiting', 'called') ORDER BY created_at, entry_id";
+        conn.query(query, &[])
+            .await
+            .map(|rows| {
+                rows.iter()
+                    .filter_map(|row| WaitlistEntry::try_from_row(row).ok())
+                    .collect::<Vec<WaitlistEntry>>()
+            })
+            .map_err(OperationError::FailedToGetDetail)
+    }
+
+    async fn call_entry(
+        &self,
+        entry_id: i64,
+        table_number: i32,
+    ) -> Result<Option<WaitlistEntry>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        // concurrent calls to the same table wait for each other,
+        // so the later one sees the table already held and never promises it twice:
+        tx.execute(
+            "SELECT 1 FROM dining_tables WHERE table_number = $1 FOR UPDATE",
+            &[&table_number],
+        )
+        .await
+        .map_err(OperationError::FailedToUpdate)?;
+        let query = "UPDATE waitlist SET status = 'called', table_number = $2, called_at = $3 WHERE entry_id = $1 AND status = 'waiting' AND NOT EXISTS (SELECT 1 FROM waitlist WHERE status = 'called' AND table_number = $2) AND NOT EXISTS (SELECT 1 FROM table_sessions WHERE table_number = $2 AND closed_at IS NULL) RETURNING *";
+        let entry = tx
+            .query_opt(
+                query,
+                &[&entry_id, &table_number, &OffsetDateTime::now_utc()],
+            )
+            .await
+            .map(|row| row.and_then(|r| WaitlistEntry::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToUpdate)?;
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+        Ok(entry)
+    }
+
+    async fn seat_entry(
+        &self,
+        entry_id: i64,
+    ) -> Result<SeatOutcome<WaitlistEntry>, OperationError> {
+        let mut conn = self.get_conn().await?;
+        let tx = conn
+            .transaction()
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+
+        let called = tx
+            .query_opt(
+                "SELECT table_number FROM waitlist WHERE entry_id = $1 AND status = 'called' FOR UPDATE",
+                &[&entry_id],
+            )
+            .await
+            .map_err(OperationError::FailedToUpdate)?;
+        let table_number: i32 = match called.and_then(|row| row.try_get("table_number").ok()) {
+            Some(table_number) => table_number,
+            None => return Ok(SeatOutcome::Gone),
+        };
+        let session = match session::open_in(&tx, table_number)
+            .await
+            .map_err(OperationError::FailedToCreate)?
+        {
+            Some(session) => session,
+            None => return Ok(SeatOutcome::Occupied),
+        };
+
+        let query = "UPDATE waitlist SET status = 'seated', session_id = $2 WHERE entry_id = $1 RETURNING *";
+        let entry = tx
+            .query_opt(query, &[&entry_id, &session.session_id])
+            .await
+            .map(|row| row.and_then(|r| WaitlistEntry::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToUpdate)?;
+        tx.commit().await.map_err(OperationError::FailedToUpdate)?;
+        Ok(match entry {
+            Some(entry) => SeatOutcome::Seated(entry),
+            None => SeatOutcome::Gone,
+        })
+    }
+
+    async fn mark_no_show(&self, entry_id: i64) -> Result<Option<WaitlistEntry>, OperationError> {
+        let conn = self.get_conn().await?;
+
+        let query = "UPDATE waitlist SET status = 'no_show' WHERE entry_id = $1 AND status IN ('waiting', 'called') RETURNING *";
+        conn.query_opt(query, &[&entry_id])
+            .await
+            .map(|row| row.and_then(|r| WaitlistEntry::try_from_row(&r).ok()))
+            .map_err(OperationError::FailedToUpdate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::create_conn_pool;
+
+    #[actix_web::test]
+    #[ignore = "needs a PostgreSQL database with the schema imported"]
+    /// given: two waiting parties.
+    /// when: calling both to the same Table against the database.
+    /// then: only the first one is called, the Table is promised once.
+    async fn test_call_entry() {
+        let db_pool = create_conn_pool();
+        let repo = WaitlistRepository::new(db_pool.clone());
+        // a table number no dining table has, so the test doesn't clash with real parties:
+        let table_number = 9_000 + (OffsetDateTime::now_utc().nanosecond() % 1_000) as i32;
+
+        let first = repo
+            .add_entry(WaitlistEntry::new(
+                2,
+                "Sukab".to_string(),
+                "0812".to_string(),
+            ))
+            .await
+            .unwrap();
+        let second = repo
+            .add_entry(WaitlistEntry::new(
+                2,
+                "Alina".to_string(),
+                "0813".to_string(),
+            ))
+            .await
+            .unwrap();
+
+        let called = repo
+            .call_entry(first.entry_id, table_number)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(called.table_number, Some(table_number));
+        assert!(repo
+            .call_entry(second.entry_id, table_number)
+            .await
+            .unwrap()
+            .is_none());
+        let still_waiting = repo.get_entry(second.entry_id).await.unwrap().unwrap();
+        assert_eq!(still_waiting.status, WaitlistStatus::Waiting);
+
+        db_pool
+            .get()
+            .await
+            .unwrap()
+            .execute(
+                "DELETE FROM waitlist WHERE entry_id = ANY($1)",
+                &[&vec![first.entry_id, second.entry_id]],
+            )
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,426 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    bill::{load_bill, preview_discount, Bill},
+    db::{self, discount::Discount, staff::StaffRole},
+    order::BadRequestBody,
+};
+
+use super::{parse_kind, parse_value, DiscountData, DiscountFailure, TablePathParams};
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    kind: String,
+    /// in percent for `percent`, in minor units of the currency for `fixed`.
+    value: f64,
+    order_id: Option<i64>,
+    reason: Option<String>,
+}
+
+/// The input data to give a Discount on the bill of a Table.
+struct Input {
+    table_number: u32,
+    discount: Discount,
+}
+
+impl Input {
+    fn new(path_params: TablePathParams, rb: RequestBody) -> Result<Self, DiscountFailure> {
+        let kind = parse_kind(&rb.kind)?;
+        Ok(Self {
+            table_number: path_params.table_number,
+            discount: Discount {
+                reason: rb.reason,
+                ..Discount::new(0, rb.order_id, kind, parse_value(kind, rb.value)?)
+            },
+        })
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, DiscountFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(DiscountFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        if self.discount.reason.as_ref().is_some_and(|r| r.len() > 300) {
+            return Err(DiscountFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("reason must be at most 300 characters"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    discount: DiscountData,
+    bill: Bill,
+}
+
+/// Gives a Discount on a single Order or on the whole bill, manager only.
+#[post("/discount")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    payment_repository: web::Data<dyn db::payment::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<TablePathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, DiscountFailure> {
+    let identity = identity.require(StaffRole::Manager)?;
+    let input = Input::new(path_params.into_inner(), request_body.into_inner())?.validate()?;
+    let table_number = input.table_number as i32;
+
+    let bill = match preview_discount(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        table_number,
+        &input.discount,
+    )
+    .await
+    .map_err(DiscountFailure::InternalServerError)?
+    {
+        Some(bill) => bill,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    if let Some(order_id) = input.discount.order_id {
+        if !bill.lines.iter().any(|line| line.order_id == order_id) {
+            return Err(DiscountFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: format!("order {} is not on the bill", order_id),
+            }));
+        }
+    }
+
+    // what has been paid is not given back by a discount, the bill must still cover it:
+    let paid: i64 = payment_repository
+        .list_by_session(bill.session_id)
+        .await
+        .map_err(DiscountFailure::InternalServerError)?
+        .iter()
+        .map(|payment| payment.amount)
+        .sum();
+    if bill.total < paid {
+        return Err(DiscountFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!(
+                "the bill of table {} would come to {}, less than the {} already paid",
+                table_number, bill.total, paid
+            ),
+        }));
+    }
+
+    let discount = discount_repository
+        .add_discount(Discount {
+            session_id: bill.session_id,
+            staff_id: Some(identity.0.staff_id),
+            ..input.discount
+        })
+        .await
+        .map_err(DiscountFailure::InternalServerError)?;
+
+    let bill = load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        table_number,
+        Some(bill.session_id),
+    )
+    .await
+    .map_err(DiscountFailure::InternalServerError)?
+    .unwrap_or(bill);
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        discount: discount.into(),
+        bill,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        order::Order,
+        payment::{Payment, PaymentMethod},
+        session::Session,
+        staff::Staff,
+        OperationError,
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    fn order_repo() -> Arc<dyn db::order::Repository> {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .returning(|table_number, session_id| {
+                Ok(vec![Order {
+                    order_id: 1,
+                    session_id: Some(session_id),
+                    unit_price: 100_000,
+                    ..Order::new(table_number, 5, 10)
+                }])
+            });
+        Arc::new(order_repo)
+    }
+
+    /// Payment repository where `paid` has been paid on the bill already.
+    fn payment_repo(paid: i64) -> Arc<dyn db::payment::Repository> {
+        let mut payment_repo = crate::db::payment::MockRepository::new();
+        payment_repo
+            .expect_list_by_session()
+            .returning(move |session_id| {
+                Ok(vec![Payment::new(
+                    session_id,
+                    3,
+                    PaymentMethod::Cash,
+                    paid,
+                    None,
+                )])
+            });
+        Arc::new(payment_repo)
+    }
+
+    fn session_repo() -> Arc<dyn db::session::Repository> {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        session_repo
+            .expect_get_session()
+            .returning(|table_number, session_id| Ok(Some(Session::new(session_id, table_number))));
+        Arc::new(session_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: giving a discount.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let discount_repo = crate::db::discount::MockRepository::new();
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(payment_repo(0)))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/discount")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .set_json(serde_json::json!({"kind": "percent", "value": 10}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a percent discount of more than 100 percent.
+    /// when: giving a discount.
+    /// then: response status code is 400.
+    async fn test_invalid_percent() {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo.expect_add_discount().never();
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(payment_repo(0)))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/discount")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"kind": "percent", "value": 1000}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a discount on an order which is not on the bill.
+    /// when: giving a discount.
+    /// then: response status code is 400.
+    async fn test_unknown_order() {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        discount_repo.expect_add_discount().never();
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(payment_repo(0)))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/discount")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"kind": "fixed", "value": 5000, "order_id": 99}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a manager access token.
+    /// when: giving 10% off the bill.
+    /// then: the discount is stored and the bill reflects it.
+    async fn test_success() {
+        let stored: Arc<Mutex<Vec<Discount>>> = Arc::new(Mutex::new(vec![]));
+        let stored_list = stored.clone();
+        let stored_add = stored.clone();
+
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(move |_| Ok(stored_list.lock().unwrap().clone()));
+        discount_repo
+            .expect_add_discount()
+            .once()
+            .returning(move |discount| {
+                let discount = Discount {
+                    discount_id: 4,
+                    ..discount
+                };
+                stored_add.lock().unwrap().push(discount.clone());
+                Ok(discount)
+            });
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(payment_repo(0)))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/discount")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"kind": "percent", "value": 10, "reason": "birthday"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.discount.discount_id, 4);
+        assert_eq!(response_body.discount.session_id, 7);
+        assert_eq!(response_body.discount.staff_id, Some(1));
+        assert_eq!(response_body.discount.value, 10.0);
+        assert_eq!(response_body.bill.discount, 10_000);
+    }
+
+    #[actix_web::test]
+    /// given: part of the bill has been paid already.
+    /// when: giving a discount which brings the total below what has been paid.
+    /// then: response status code is 409 and the discount is not stored.
+    async fn test_below_paid() {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        discount_repo.expect_add_discount().never();
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(payment_repo(60_000)))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/discount")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"kind": "percent", "value": 50}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: giving a discount.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        discount_repo
+            .expect_add_discount()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(payment_repo(0)))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/discount")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"kind": "fixed", "value": 5000}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
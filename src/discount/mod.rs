@@ -0,0 +1,177 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    auth::AuthFailure,
+    db::{
+        discount::{Discount, DiscountKind, PromoCode},
+        OperationError,
+    },
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+pub mod apply;
+pub mod promo;
+pub mod redeem;
+
+fn format_time(dt: OffsetDateTime) -> String {
+    dt.format(&Rfc3339).unwrap_or("---".to_string())
+}
+
+/// Converts the value of a request to what is stored. Percentages are given in percent like the tax rates,
+/// e.g. 12.5, and stored in basis points; fixed amounts are whole minor units of the currency.
+fn parse_value(kind: DiscountKind, value: f64) -> Result<i64, DiscountFailure> {
+    let stored = match kind {
+        DiscountKind::Percent => (value * 100.0).round(),
+        DiscountKind::Fixed => value,
+    };
+    let valid = match kind {
+        DiscountKind::Percent => (1.0..=10_000.0).contains(&stored),
+        DiscountKind::Fixed => stored >= 1.0 && stored.fract() == 0.0,
+    };
+    match valid {
+        true => Ok(stored as i64),
+        false => Err(DiscountFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: String::from(
+                "value must be in range of 0.01 to 100 percent for percent, or a positive whole amount for fixed",
+            ),
+        })),
+    }
+}
+
+/// The stored value as given in requests, percentages in percent.
+fn display_value(kind: DiscountKind, value: i64) -> f64 {
+    match kind {
+        DiscountKind::Percent => value as f64 / 100.0,
+        DiscountKind::Fixed => value as f64,
+    }
+}
+
+/// Parses the kind of discount out of the request.
+fn parse_kind(kind: &str) -> Result<DiscountKind, DiscountFailure> {
+    DiscountKind::parse(kind).ok_or_else(|| {
+        DiscountFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: String::from("kind must be one of percent or fixed"),
+        })
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiscountData {
+    discount_id: i64,
+    session_id: i64,
+    order_id: Option<i64>,
+    kind: String,
+    /// in percent for `percent`, in minor units of the currency for `fixed`.
+    value: f64,
+    promo_code: Option<String>,
+    reason: Option<String>,
+    staff_id: Option<i64>,
+    created_at: String,
+}
+
+impl From<Discount> for DiscountData {
+    fn from(discount: Discount) -> Self {
+        Self {
+            discount_id: discount.discount_id,
+            session_id: discount.session_id,
+            order_id: discount.order_id,
+            kind: discount.kind.as_str().to_string(),
+            value: display_value(discount.kind, discount.value),
+            promo_code: discount.promo_code,
+            reason: discount.reason,
+            staff_id: discount.staff_id,
+            created_at: format_time(discount.created_at),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PromoCodeData {
+    code: String,
+    kind: String,
+    /// in percent for `percent`, in minor units of the currency for `fixed`.
+    value: f64,
+    starts_at: String,
+    ends_at: String,
+    max_uses: Option<i32>,
+    used_count: i32,
+}
+
+impl From<PromoCode> for PromoCodeData {
+    fn from(promo_code: PromoCode) -> Self {
+        Self {
+            code: promo_code.code,
+            kind: promo_code.kind.as_str().to_string(),
+            value: display_value(promo_code.kind, promo_code.value),
+            starts_at: format_time(promo_code.starts_at),
+            ends_at: format_time(promo_code.ends_at),
+            max_uses: promo_code.max_uses,
+            used_count: promo_code.used_count,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TablePathParams {
+    table_number: u32,
+}
+
+#[derive(Debug)]
+enum DiscountFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    Conflict(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for DiscountFailure {
+    fn from(e: AuthFailure) -> Self {
+        DiscountFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for DiscountFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to process discount")
+    }
+}
+
+impl ResponseError for DiscountFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            DiscountFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            DiscountFailure::Auth(e) => e.status_code(),
+            DiscountFailure::Conflict(_) => StatusCode::CONFLICT,
+            DiscountFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            DiscountFailure::InvalidInput(r) | DiscountFailure::Conflict(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            DiscountFailure::Auth(e) => e.error_response(),
+            DiscountFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+pub fn service() -> actix_web::Scope {
+    web::scope("/promo").service(promo::handler)
+}
@@ -0,0 +1,236 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, discount::PromoCode, staff::StaffRole},
+    order::BadRequestBody,
+};
+
+use super::{parse_kind, parse_value, DiscountFailure, PromoCodeData};
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    code: String,
+    kind: String,
+    /// in percent for `percent`, in minor units of the currency for `fixed`.
+    value: f64,
+    starts_at: String,
+    ends_at: String,
+    max_uses: Option<i32>,
+}
+
+/// The input data to create a promo code.
+struct Input {
+    promo_code: PromoCode,
+}
+
+fn parse_time(field: &str, v: &str) -> Result<OffsetDateTime, DiscountFailure> {
+    OffsetDateTime::parse(v, &Rfc3339).map_err(|_| {
+        DiscountFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: format!("{} must be an RFC3339 timestamp", field),
+        })
+    })
+}
+
+impl Input {
+    fn new(rb: RequestBody) -> Result<Self, DiscountFailure> {
+        let kind = parse_kind(&rb.kind)?;
+        Ok(Self {
+            promo_code: PromoCode::new(
+                rb.code.trim().to_uppercase(),
+                kind,
+                parse_value(kind, rb.value)?,
+                parse_time("starts_at", &rb.starts_at)?,
+                parse_time("ends_at", &rb.ends_at)?,
+                rb.max_uses,
+            ),
+        })
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, DiscountFailure> {
+        let code = &self.promo_code.code;
+        if code.is_empty()
+            || code.len() > 32
+            || !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(DiscountFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from(
+                    "code must be 1 to 32 characters of letters, digits or dashes",
+                ),
+            }));
+        }
+        if self.promo_code.ends_at <= self.promo_code.starts_at {
+            return Err(DiscountFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("ends_at must be after starts_at"),
+            }));
+        }
+        if self.promo_code.max_uses.is_some_and(|v| v < 1) {
+            return Err(DiscountFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("max_uses must be at least 1"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    data: PromoCodeData,
+}
+
+/// Creates a promo code, manager only.
+#[post("")]
+async fn handler(
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    identity: StaffIdentity,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, DiscountFailure> {
+    identity.require(StaffRole::Manager)?;
+    let input = Input::new(request_body.into_inner())?.validate()?;
+
+    let promo_code = discount_repository
+        .create_promo_code(input.promo_code)
+        .await
+        .map_err(DiscountFailure::InternalServerError)?
+        .ok_or_else(|| {
+            DiscountFailure::Conflict(BadRequestBody {
+                error: true,
+                message: String::from("promo code exists already"),
+            })
+        })?;
+
+    Ok(HttpResponse::Created().json(SuccessResponseBody {
+        data: promo_code.into(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::staff::Staff;
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Manager))));
+        Arc::new(staff_repo)
+    }
+
+    fn request_body(starts_at: &str, ends_at: &str) -> serde_json::Value {
+        serde_json::json!({
+            "code": "hemat-10",
+            "kind": "percent",
+            "value": 10,
+            "starts_at": starts_at,
+            "ends_at": ends_at,
+            "max_uses": 100,
+        })
+    }
+
+    #[actix_web::test]
+    /// given: a validity window which ends before it starts.
+    /// when: creating a promo code.
+    /// then: response status code is 400.
+    async fn test_invalid_window() {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo.expect_create_promo_code().never();
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/promo").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/promo")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(request_body("2026-02-01T00:00:00Z", "2026-01-01T00:00:00Z"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a code which exists already.
+    /// when: creating a promo code.
+    /// then: response status code is 409.
+    async fn test_duplicate() {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_create_promo_code()
+            .once()
+            .returning(|_| Ok(None));
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/promo").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/promo")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(request_body("2026-01-01T00:00:00Z", "2026-02-01T00:00:00Z"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: a valid promo code.
+    /// when: creating a promo code.
+    /// then: the code is stored in uppercase.
+    async fn test_success() {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_create_promo_code()
+            .withf(|data| {
+                data.code == "HEMAT-10" && data.value == 1000 && data.max_uses == Some(100)
+            })
+            .once()
+            .returning(|data| Ok(Some(data)));
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/promo").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/promo")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(request_body("2026-01-01T00:00:00Z", "2026-02-01T00:00:00Z"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.data.code, "HEMAT-10");
+        assert_eq!(response_body.data.value, 10.0);
+        assert_eq!(response_body.data.used_count, 0);
+    }
+}
@@ -0,0 +1,342 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::StaffIdentity,
+    bill::{load_bill, Bill},
+    db::{self, staff::StaffRole},
+    order::BadRequestBody,
+};
+
+use super::{DiscountData, DiscountFailure, TablePathParams};
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    code: String,
+}
+
+/// The input data to redeem a promo code on the bill of a Table.
+struct Input {
+    table_number: u32,
+    code: String,
+}
+
+impl Input {
+    fn new(path_params: TablePathParams, rb: RequestBody) -> Self {
+        Self {
+            table_number: path_params.table_number,
+            code: rb.code.trim().to_uppercase(),
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, DiscountFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(DiscountFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        if self.code.is_empty() {
+            return Err(DiscountFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("code must not be empty"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    discount: DiscountData,
+    bill: Bill,
+}
+
+fn conflict(message: &str) -> DiscountFailure {
+    DiscountFailure::Conflict(BadRequestBody {
+        error: true,
+        message: String::from(message),
+    })
+}
+
+/// Redeems a promo code as a Discount on the whole bill of the open session.
+#[post("/promo")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<TablePathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, DiscountFailure> {
+    identity.require(StaffRole::Waiter)?;
+    let input = Input::new(path_params.into_inner(), request_body.into_inner()).validate()?;
+    let table_number = input.table_number as i32;
+    let now = OffsetDateTime::now_utc();
+
+    let bill = match load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        table_number,
+        None,
+    )
+    .await
+    .map_err(DiscountFailure::InternalServerError)?
+    {
+        Some(bill) => bill,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    let promo_code = match discount_repository
+        .get_promo_code(input.code.clone())
+        .await
+        .map_err(DiscountFailure::InternalServerError)?
+    {
+        Some(promo_code) => promo_code,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+    if now < promo_code.starts_at || now >= promo_code.ends_at {
+        return Err(conflict("promo code is not valid at this time"));
+    }
+    if promo_code
+        .max_uses
+        .is_some_and(|max_uses| promo_code.used_count >= max_uses)
+    {
+        return Err(conflict("promo code has been used up"));
+    }
+
+    let discounts = discount_repository
+        .list_by_session(bill.session_id)
+        .await
+        .map_err(DiscountFailure::InternalServerError)?;
+    if discounts
+        .iter()
+        .any(|d| d.promo_code.as_deref() == Some(input.code.as_str()))
+    {
+        return Err(conflict("promo code has been applied to this bill already"));
+    }
+
+    // the checks above may race with other redemptions, the repository has the final say:
+    let discount = discount_repository
+        .redeem_promo_code(input.code, bill.session_id, now)
+        .await
+        .map_err(DiscountFailure::InternalServerError)?
+        .ok_or_else(|| conflict("promo code is no longer valid"))?;
+
+    let bill = load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        table_number,
+        Some(bill.session_id),
+    )
+    .await
+    .map_err(DiscountFailure::InternalServerError)?
+    .unwrap_or(bill);
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        discount: discount.into(),
+        bill,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use actix_web::{http::StatusCode, test, App};
+    use time::Duration;
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        discount::{Discount, DiscountKind, PromoCode},
+        order::Order,
+        session::Session,
+        staff::Staff,
+    };
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Waiter))));
+        Arc::new(staff_repo)
+    }
+
+    fn order_repo() -> Arc<dyn db::order::Repository> {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .returning(|table_number, session_id| {
+                Ok(vec![Order {
+                    order_id: 1,
+                    session_id: Some(session_id),
+                    unit_price: 100_000,
+                    ..Order::new(table_number, 5, 10)
+                }])
+            });
+        Arc::new(order_repo)
+    }
+
+    fn session_repo() -> Arc<dyn db::session::Repository> {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        session_repo
+            .expect_get_session()
+            .returning(|table_number, session_id| Ok(Some(Session::new(session_id, table_number))));
+        Arc::new(session_repo)
+    }
+
+    fn promo_code(max_uses: Option<i32>, used_count: i32) -> PromoCode {
+        let now = OffsetDateTime::now_utc();
+        PromoCode {
+            used_count,
+            ..PromoCode::new(
+                "HEMAT10".to_string(),
+                DiscountKind::Percent,
+                1000,
+                now - Duration::days(1),
+                now + Duration::days(1),
+                max_uses,
+            )
+        }
+    }
+
+    #[actix_web::test]
+    /// given: an unknown promo code.
+    /// when: redeeming a promo code.
+    /// then: response status code is 404.
+    async fn test_unknown_code() {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        discount_repo
+            .expect_get_promo_code()
+            .once()
+            .returning(|_| Ok(None));
+        discount_repo.expect_redeem_promo_code().never();
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/promo")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .set_json(serde_json::json!({"code": "nope"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: a promo code which has been used up.
+    /// when: redeeming a promo code.
+    /// then: response status code is 409.
+    async fn test_used_up() {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        discount_repo
+            .expect_get_promo_code()
+            .once()
+            .returning(|_| Ok(Some(promo_code(Some(5), 5))));
+        discount_repo.expect_redeem_promo_code().never();
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/promo")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .set_json(serde_json::json!({"code": "HEMAT10"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: a valid promo code written in lowercase.
+    /// when: redeeming a promo code.
+    /// then: the code is redeemed and the bill reflects its discount.
+    async fn test_success() {
+        let stored: Arc<Mutex<Vec<Discount>>> = Arc::new(Mutex::new(vec![]));
+        let stored_list = stored.clone();
+        let stored_redeem = stored.clone();
+
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(move |_| Ok(stored_list.lock().unwrap().clone()));
+        discount_repo
+            .expect_get_promo_code()
+            .withf(|code| code == "HEMAT10")
+            .once()
+            .returning(|_| Ok(Some(promo_code(None, 10))));
+        discount_repo
+            .expect_redeem_promo_code()
+            .once()
+            .returning(move |code, session_id, at| {
+                let discount = Discount {
+                    discount_id: 2,
+                    promo_code: Some(code),
+                    created_at: at,
+                    ..Discount::new(session_id, None, DiscountKind::Percent, 1000)
+                };
+                stored_redeem.lock().unwrap().push(discount.clone());
+                Ok(Some(discount))
+            });
+        let arc_discount_repo: Arc<dyn db::discount::Repository> = Arc::new(discount_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(arc_discount_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/promo")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .set_json(serde_json::json!({"code": "hemat10"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(
+            response_body.discount.promo_code,
+            Some("HEMAT10".to_string())
+        );
+        assert_eq!(response_body.discount.session_id, 7);
+        assert_eq!(response_body.bill.discount, 10_000);
+    }
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use futures_util::{stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio_postgres::{AsyncMessage, Config, NoTls, Notification};
+
+use super::{EventBus, OrderEvent, ORDER_EVENTS_CHANNEL};
+
+/// How long to wait before connecting again once the connection is lost.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Listens to the Order events channel and rebroadcasts the events to the local subscribers,
+/// so a subscriber sees the Orders written through any server instance.
+/// Runs for as long as the server does, reconnecting whenever the connection is lost.
+/// Events notified while disconnected are missed, subscribers reload the state they need after a gap.
+pub async fn run(pg_config: Config, event_bus: EventBus) {
+    loop {
+        match listen(&pg_config, &event_bus).await {
+            Ok(()) => log::warn!("order events listener connection closed, reconnecting"),
+            Err(e) => log::warn!("order events listener failed: {}, reconnecting", e),
+        }
+        actix_web::rt::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Listens on a single connection until it is closed.
+async fn listen(pg_config: &Config, event_bus: &EventBus) -> Result<(), tokio_postgres::Error> {
+    let (client, mut connection) = pg_config.connect(NoTls).await?;
+
+    // the connection has to be polled for the client to make progress,
+    // notifications arrive through it as well:
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let connection_task = actix_web::rt::spawn(async move {
+        let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+        while let Some(message) = messages.next().await {
+            match message {
+                Ok(AsyncMessage::Notification(notification)) => {
+                    if sender.send(notification).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    });
+
+    client
+        .batch_execute(&format!("LISTEN {}", ORDER_EVENTS_CHANNEL))
+        .await?;
+    log::info!("listening to order events");
+
+    while let Some(notification) = receiver.recv().await {
+        if let Some(event) = parse(&notification) {
+            event_bus.publish(event);
+        }
+    }
+    drop(client);
+    match connection_task.await {
+        Ok(result) => result,
+        Err(_) => Ok(()),
+    }
+}
+
+fn parse(notification: &Notification) -> Option<OrderEvent> {
+    match serde_json::from_str(notification.payload()) {
+        Ok(event) => Some(event),
+        Err(e) => {
+            log::debug!("skipping malformed order event: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use std::{env, fmt};
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tokio::sync::broadcast;
+
+use crate::{
+    auth::AuthFailure,
+    db::{self, order::Order, OperationError},
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+pub mod listener;
+pub mod socket;
+pub mod stream;
+
+/// The PostgreSQL channel the Order events are notified on.
+pub const ORDER_EVENTS_CHANNEL: &str = "order_events";
+
+/// What happened to an Order.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderEventKind {
+    Created,
+    StatusChanged,
+    Cancelled,
+    PriorityChanged,
+    Assigned,
+}
+
+impl OrderEventKind {
+    /// The event name pushed to subscribers.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "order_created",
+            Self::StatusChanged => "order_status_changed",
+            Self::Cancelled => "order_cancelled",
+            Self::PriorityChanged => "order_priority_changed",
+            Self::Assigned => "order_assigned",
+        }
+    }
+}
+
+/// Something which happened to an Order, pushed to the kitchen screens and the waiter tablets.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderEvent {
+    pub kind: OrderEventKind,
+    pub order_id: i64,
+    pub table_number: i32,
+    pub session_id: Option<i64>,
+    pub menu_id: Option<i32>,
+    /// the status the Order changed to.
+    pub status: Option<String>,
+    pub priority: Option<String>,
+    /// the cook the Order is assigned to.
+    pub cook_id: Option<i64>,
+    pub at: String,
+}
+
+impl OrderEvent {
+    pub fn new(kind: OrderEventKind, order_id: i64, table_number: i32) -> Self {
+        Self {
+            kind,
+            order_id,
+            table_number,
+            session_id: None,
+            menu_id: None,
+            status: None,
+            priority: None,
+            cook_id: None,
+            at: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or("---".to_string()),
+        }
+    }
+
+    pub fn from_order(kind: OrderEventKind, order: &Order) -> Self {
+        Self {
+            session_id: order.session_id,
+            menu_id: Some(order.menu_id),
+            status: Some(order.status.as_str().to_string()),
+            priority: Some(order.priority.as_str().to_string()),
+            cook_id: order.cook_id,
+            ..Self::new(kind, order.order_id, order.table_number)
+        }
+    }
+}
+
+/// Fans the Order events out to every subscriber of this server instance.
+/// Subscribers which fall too far behind miss the oldest events and are told how many.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<OrderEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Reads how many events a subscriber may fall behind from `EVENT_BUFFER`, or defer to predefined default.
+    pub fn from_env() -> Self {
+        let capacity = match env::var("EVENT_BUFFER").ok() {
+            Some(v) => v.parse().unwrap_or(256),
+            None => 256,
+        };
+        Self::new(capacity)
+    }
+
+    /// Pushes the event to the current subscribers, it is dropped when there are none.
+    pub fn publish(&self, event: OrderEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// What a subscriber asks for, passed as query params.
+#[derive(Debug, Serialize, Deserialize)]
+struct EventQuery {
+    table_number: Option<i32>,
+    station_id: Option<u32>,
+}
+
+/// Which events a subscriber is interested in, everything by default.
+#[derive(Debug)]
+struct EventFilter {
+    table_number: Option<i32>,
+    /// the Menus routed to the station the subscriber asked for.
+    menu_ids: Option<Vec<i64>>,
+}
+
+impl EventFilter {
+    /// Looks up the Menus of the station asked for, the routes are taken as they are when subscribing.
+    async fn load(
+        station_repository: &dyn db::station::Repository,
+        query: EventQuery,
+    ) -> Result<Self, EventFailure> {
+        let menu_ids = match query.station_id {
+            Some(station_id) => match station_repository
+                .get_station(station_id as i64)
+                .await
+                .map_err(EventFailure::InternalServerError)?
+            {
+                Some(station) => Some(station.menu_ids),
+                None => {
+                    return Err(EventFailure::InvalidInput(BadRequestBody {
+                        error: true,
+                        message: format!("station {} does not exist", station_id),
+                    }))
+                }
+            },
+            None => None,
+        };
+        Ok(Self {
+            table_number: query.table_number,
+            menu_ids,
+        })
+    }
+
+    fn matches(&self, event: &OrderEvent) -> bool {
+        let table_matches = match self.table_number {
+            Some(table_number) => event.table_number == table_number,
+            None => true,
+        };
+        let station_matches = match (&self.menu_ids, event.menu_id) {
+            (Some(menu_ids), Some(menu_id)) => menu_ids.contains(&(menu_id as i64)),
+            // an event without its Menu might concern any station:
+            (Some(_), None) => true,
+            (None, _) => true,
+        };
+        table_matches && station_matches
+    }
+}
+
+#[derive(Debug)]
+enum EventFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for EventFailure {
+    fn from(e: AuthFailure) -> Self {
+        EventFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for EventFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to subscribe to events")
+    }
+}
+
+impl ResponseError for EventFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            EventFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            EventFailure::Auth(e) => e.status_code(),
+            EventFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            EventFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            EventFailure::Auth(e) => e.error_response(),
+            EventFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+pub fn service() -> actix_web::Scope {
+    web::scope("/events")
+        .service(stream::handler)
+        .service(socket::handler)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let event = OrderEvent::new(OrderEventKind::Created, 1, 3);
+        assert!(EventFilter {
+            table_number: None,
+            menu_ids: None,
+        }
+        .matches(&event));
+        assert!(EventFilter {
+            table_number: Some(3),
+            menu_ids: None,
+        }
+        .matches(&event));
+        assert!(!EventFilter {
+            table_number: Some(4),
+            menu_ids: None,
+        }
+        .matches(&event));
+    }
+
+    #[test]
+    fn test_station_filter() {
+        let grill = EventFilter {
+            table_number: None,
+            menu_ids: Some(vec![4, 5]),
+        };
+        let yakitori = OrderEvent {
+            menu_id: Some(5),
+            ..OrderEvent::new(OrderEventKind::Created, 1, 3)
+        };
+        let udon = OrderEvent {
+            menu_id: Some(8),
+            ..OrderEvent::new(OrderEventKind::Created, 2, 3)
+        };
+        assert!(grill.matches(&yakitori));
+        assert!(!grill.matches(&udon));
+        // the Menu is unknown, let the station decide:
+        assert!(grill.matches(&OrderEvent::new(OrderEventKind::Cancelled, 3, 3)));
+
+        let grill_of_table_4 = EventFilter {
+            table_number: Some(4),
+            menu_ids: Some(vec![4, 5]),
+        };
+        assert!(!grill_of_table_4.matches(&yakitori));
+    }
+
+    #[test]
+    fn test_payload() {
+        let event = OrderEvent {
+            session_id: Some(7),
+            menu_id: Some(5),
+            ..OrderEvent::new(OrderEventKind::Cancelled, 1, 3)
+        };
+        let payload = serde_json::to_string(&event).unwrap();
+        // NOTIFY payloads must be shorter than 8000 bytes:
+        assert!(payload.len() < 8000);
+        assert!(payload.contains(r#""kind":"cancelled""#));
+        let parsed: OrderEvent = serde_json::from_str(&payload).unwrap();
+        assert_eq!(parsed, event);
+    }
+
+    #[test]
+    fn test_bus() {
+        let bus = EventBus::new(1);
+        // nobody listens yet, the event is dropped:
+        bus.publish(OrderEvent::new(OrderEventKind::Created, 1, 3));
+
+        let mut receiver = bus.subscribe();
+        bus.publish(OrderEvent::new(OrderEventKind::Created, 2, 3));
+        bus.publish(OrderEvent::new(OrderEventKind::Cancelled, 2, 3));
+        assert!(matches!(
+            receiver.try_recv(),
+            Err(broadcast::error::TryRecvError::Lagged(1))
+        ));
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.kind, OrderEventKind::Cancelled);
+        assert_eq!(event.kind.as_str(), "order_cancelled");
+    }
+}
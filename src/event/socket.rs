@@ -0,0 +1,140 @@
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use actix_ws::Message;
+use futures_util::StreamExt;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{auth::StaffIdentity, db, order::BadRequestBody};
+
+use super::{EventBus, EventFailure, EventFilter, EventQuery};
+
+/// Pushes the Order events over a WebSocket as JSON text messages,
+/// pass `table_number` to only get those of a Table and `station_id` to only get those of a kitchen station.
+#[get("/ws")]
+async fn handler(
+    event_bus: web::Data<EventBus>,
+    station_repository: web::Data<dyn db::station::Repository>,
+    _identity: StaffIdentity,
+    query_params: web::Query<EventQuery>,
+    req: HttpRequest,
+    body: web::Payload,
+) -> Result<HttpResponse, EventFailure> {
+    let filter = EventFilter::load(station_repository.get_ref(), query_params.into_inner()).await?;
+    let (response, mut session, mut messages) = actix_ws::handle(&req, body).map_err(|_| {
+        EventFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: String::from("a websocket upgrade request is required"),
+        })
+    })?;
+    let mut receiver = event_bus.subscribe();
+
+    actix_web::rt::spawn(async move {
+        loop {
+            tokio::select! {
+                event = receiver.recv() => {
+                    let text = match event {
+                        Ok(event) if filter.matches(&event) => {
+                            serde_json::to_string(&event).unwrap_or_default()
+                        }
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(missed)) => {
+                            format!("{{\"kind\":\"lagged\",\"missed\":{}}}", missed)
+                        }
+                        Err(RecvError::Closed) => break,
+                    };
+                    if session.text(text).await.is_err() {
+                        return;
+                    }
+                }
+                message = messages.next() => match message {
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            return;
+                        }
+                    }
+                    Some(Ok(Message::Close(reason))) => {
+                        let _ = session.close(reason).await;
+                        return;
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(_)) | None => break,
+                },
+            }
+        }
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::staff::{Staff, StaffRole};
+
+    fn station_repo() -> Arc<dyn db::station::Repository> {
+        Arc::new(crate::db::station::MockRepository::new())
+    }
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Waiter))));
+        Arc::new(staff_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a plain request without the websocket upgrade.
+    /// when: subscribing to the events over a websocket.
+    /// then: response status code is 400.
+    async fn test_not_upgraded() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new(EventBus::new(16)))
+                .app_data(Data::from(station_repo()))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/events").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/events/ws")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a websocket upgrade request.
+    /// when: subscribing to the events over a websocket.
+    /// then: the connection is switched to the websocket protocol.
+    async fn test_upgraded() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new(EventBus::new(16)))
+                .app_data(Data::from(station_repo()))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/events").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/events/ws?table_number=3")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .insert_header(("Upgrade", "websocket"))
+            .insert_header(("Connection", "Upgrade"))
+            .insert_header(("Sec-WebSocket-Version", "13"))
+            .insert_header(("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::SWITCHING_PROTOCOLS);
+    }
+}
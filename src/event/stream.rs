@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use actix_web::{
+    get,
+    http::header::{CACHE_CONTROL, CONTENT_TYPE},
+    rt::time::timeout,
+    web, HttpResponse,
+};
+use futures_util::stream;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{auth::StaffIdentity, db};
+
+use super::{EventBus, EventFailure, EventFilter, EventQuery, OrderEvent};
+
+/// How often an idle stream sends a comment, so proxies don't cut it.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Formats an event as a Server-Sent Events message.
+fn message(event: &OrderEvent) -> web::Bytes {
+    let data = serde_json::to_string(event).unwrap_or_default();
+    web::Bytes::from(format!(
+        "event: {}\ndata: {}\n\n",
+        event.kind.as_str(),
+        data
+    ))
+}
+
+/// Streams the Order events as Server-Sent Events, pass `table_number` to only get those of a Table
+/// and `station_id` to only get those of the Menus routed to a kitchen station.
+/// A `lagged` event tells a slow subscriber how many events it missed, so it can reload instead.
+#[get("")]
+async fn handler(
+    event_bus: web::Data<EventBus>,
+    station_repository: web::Data<dyn db::station::Repository>,
+    _identity: StaffIdentity,
+    query_params: web::Query<EventQuery>,
+) -> Result<HttpResponse, EventFailure> {
+    let filter = EventFilter::load(station_repository.get_ref(), query_params.into_inner()).await?;
+    let receiver = event_bus.subscribe();
+
+    let messages = stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+        loop {
+            let bytes = match timeout(KEEP_ALIVE, receiver.recv()).await {
+                Err(_) => web::Bytes::from_static(b": keep-alive\n\n"),
+                Ok(Ok(event)) if filter.matches(&event) => message(&event),
+                Ok(Ok(_)) => continue,
+                Ok(Err(RecvError::Lagged(missed))) => web::Bytes::from(format!(
+                    "event: lagged\ndata: {{\"missed\":{}}}\n\n",
+                    missed
+                )),
+                Ok(Err(RecvError::Closed)) => return None,
+            };
+            return Some((Ok::<_, actix_web::Error>(bytes), (receiver, filter)));
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .insert_header((CONTENT_TYPE, "text/event-stream"))
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        .streaming(messages))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::poll_fn, pin::pin, sync::Arc};
+
+    use actix_web::{body::MessageBody, http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::{
+        db::{
+            self,
+            staff::{Staff, StaffRole},
+            station::Station,
+        },
+        event::OrderEventKind,
+    };
+
+    fn station_repo() -> Arc<dyn db::station::Repository> {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_get_station()
+            .returning(|station_id| match station_id {
+                1 => Ok(Some(Station {
+                    station_id,
+                    menu_ids: vec![4, 5],
+                    ..Station::new("Grill".to_string())
+                })),
+                _ => Ok(None),
+            });
+        Arc::new(station_repo)
+    }
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Cook))));
+        Arc::new(staff_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a request without a staff access token.
+    /// when: subscribing to the events.
+    /// then: response status code is 401.
+    async fn test_unauthorized() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new(EventBus::new(16)))
+                .app_data(Data::from(station_repo()))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/events").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/events").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    /// given: a subscriber of table 3.
+    /// when: Orders are created on tables 4 and 3.
+    /// then: only the Order of table 3 is pushed.
+    async fn test_success() {
+        let event_bus = Data::new(EventBus::new(16));
+        let app = test::init_service(
+            App::new()
+                .app_data(event_bus.clone())
+                .app_data(Data::from(station_repo()))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/events").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/events?table_number=3")
+            .insert_header(("Authorization", "Bearer cook-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+
+        event_bus.publish(OrderEvent::new(OrderEventKind::Created, 1, 4));
+        event_bus.publish(OrderEvent::new(OrderEventKind::Created, 2, 3));
+        let mut body = pin!(resp.into_body());
+        let chunk = poll_fn(|cx| body.as_mut().poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        let chunk = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(chunk.starts_with("event: order_created\ndata: "));
+        assert!(chunk.contains("\"order_id\":2"));
+    }
+
+    #[actix_web::test]
+    /// given: a station which does not exist.
+    /// when: subscribing to the events of that station.
+    /// then: response status code is 400.
+    async fn test_unknown_station() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new(EventBus::new(16)))
+                .app_data(Data::from(station_repo()))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/events").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/events?station_id=9")
+            .insert_header(("Authorization", "Bearer cook-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a subscriber of the grill station.
+    /// when: an udon and a yakitori Order are created.
+    /// then: only the yakitori Order is pushed.
+    async fn test_station() {
+        let event_bus = Data::new(EventBus::new(16));
+        let app = test::init_service(
+            App::new()
+                .app_data(event_bus.clone())
+                .app_data(Data::from(station_repo()))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/events").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/events?station_id=1")
+            .insert_header(("Authorization", "Bearer cook-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        event_bus.publish(OrderEvent {
+            menu_id: Some(8),
+            ..OrderEvent::new(OrderEventKind::Created, 1, 3)
+        });
+        event_bus.publish(OrderEvent {
+            menu_id: Some(5),
+            ..OrderEvent::new(OrderEventKind::Created, 2, 3)
+        });
+        let mut body = pin!(resp.into_body());
+        let chunk = poll_fn(|cx| body.as_mut().poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        let chunk = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(chunk.contains("\"order_id\":2"));
+    }
+}
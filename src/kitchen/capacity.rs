@@ -0,0 +1,173 @@
+use std::env;
+
+use crate::db::station::StationLoad;
+
+/// What to do with a new Order once the kitchen is at capacity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverloadPolicy {
+    /// turn the Order down, the guests may try again after a while.
+    Reject,
+    /// take the Order, it just takes longer.
+    Extend,
+    /// take the Order but hold it until there is room in the kitchen.
+    Hold,
+}
+
+impl OverloadPolicy {
+    pub fn parse(v: &str) -> Option<Self> {
+        match v {
+            "reject" => Some(Self::Reject),
+            "extend" => Some(Self::Extend),
+            "hold" => Some(Self::Hold),
+            _ => None,
+        }
+    }
+}
+
+/// How many outstanding Orders the kitchen takes as a whole and what happens beyond,
+/// the capacity of each station is kept along with the station.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapacityConfig {
+    /// None for no limit.
+    pub capacity: Option<i64>,
+    pub policy: OverloadPolicy,
+    /// how long rejected guests are asked to wait before trying again, in seconds.
+    pub retry_after: u64,
+    /// how much longer an Order takes for every Order the kitchen is over capacity, in minutes.
+    pub delay: i32,
+}
+
+impl Default for CapacityConfig {
+    fn default() -> Self {
+        Self {
+            capacity: None,
+            policy: OverloadPolicy::Reject,
+            retry_after: 60,
+            delay: 2,
+        }
+    }
+}
+
+impl CapacityConfig {
+    /// Reads the config from `KITCHEN_CAPACITY`, `KITCHEN_OVERLOAD`, `KITCHEN_RETRY_AFTER`
+    /// and `KITCHEN_OVERLOAD_DELAY`, or defer to predefined defaults.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            capacity: env::var("KITCHEN_CAPACITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|v: &i64| *v > 0),
+            policy: env::var("KITCHEN_OVERLOAD")
+                .ok()
+                .and_then(|v| OverloadPolicy::parse(&v))
+                .unwrap_or(default.policy),
+            retry_after: env::var("KITCHEN_RETRY_AFTER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.retry_after),
+            delay: env::var("KITCHEN_OVERLOAD_DELAY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.delay),
+        }
+    }
+}
+
+/// Whether a new Order is let into the kitchen.
+#[derive(Debug, PartialEq)]
+pub enum Admission {
+    Accept,
+    /// accepted, with this many minutes on top of its cook time.
+    Extend(i32),
+    Hold,
+    /// rejected, try again after this many seconds.
+    Reject(u64),
+}
+
+/// Decides whether a new Order is let into the kitchen, out of the outstanding Orders of the whole kitchen
+/// and of the stations with a capacity which the Order is routed to.
+pub fn admit(config: &CapacityConfig, outstanding: i64, stations: &[StationLoad]) -> Admission {
+    // how many Orders over capacity the new one would be, at the most loaded place:
+    let over = config
+        .capacity
+        .map(|capacity| outstanding - capacity + 1)
+        .into_iter()
+        .chain(
+            stations
+                .iter()
+                .map(|station| station.outstanding - station.capacity as i64 + 1),
+        )
+        .max()
+        .unwrap_or(0);
+    if over <= 0 {
+        return Admission::Accept;
+    }
+    match config.policy {
+        OverloadPolicy::Reject => Admission::Reject(config.retry_after),
+        OverloadPolicy::Extend => Admission::Extend(config.delay.saturating_mul(over as i32)),
+        OverloadPolicy::Hold => Admission::Hold,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(station_id: i64, capacity: i32, outstanding: i64) -> StationLoad {
+        StationLoad {
+            station_id,
+            capacity,
+            outstanding,
+        }
+    }
+
+    #[test]
+    fn test_admit_within_capacity() {
+        let config = CapacityConfig {
+            capacity: Some(10),
+            ..CapacityConfig::default()
+        };
+        assert_eq!(admit(&config, 9, &[load(1, 3, 2)]), Admission::Accept);
+        // no limit at all:
+        assert_eq!(
+            admit(&CapacityConfig::default(), 500, &[]),
+            Admission::Accept
+        );
+    }
+
+    #[test]
+    fn test_admit_over_capacity() {
+        let config = CapacityConfig {
+            capacity: Some(10),
+            retry_after: 30,
+            ..CapacityConfig::default()
+        };
+        assert_eq!(admit(&config, 10, &[]), Admission::Reject(30));
+        // the kitchen has room but the grill doesn't:
+        assert_eq!(admit(&config, 4, &[load(1, 3, 3)]), Admission::Reject(30));
+
+        let config = CapacityConfig {
+            policy: OverloadPolicy::Extend,
+            delay: 2,
+            ..config
+        };
+        // 3 over at the grill, 1 over in the whole kitchen:
+        assert_eq!(
+            admit(&config, 10, &[load(1, 3, 5), load(3, 8, 1)]),
+            Admission::Extend(6)
+        );
+
+        let config = CapacityConfig {
+            policy: OverloadPolicy::Hold,
+            ..config
+        };
+        assert_eq!(admit(&config, 12, &[]), Admission::Hold);
+    }
+
+    #[test]
+    fn test_policy_parse() {
+        assert_eq!(OverloadPolicy::parse("hold"), Some(OverloadPolicy::Hold));
+        assert_eq!(OverloadPolicy::parse("queue"), None);
+    }
+}
@@ -0,0 +1,152 @@
+use actix_web::{put, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::{cook_response, KitchenFailure};
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    order_id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RequestBody {
+    /// None to pick the cook on shift with the fewest outstanding Orders.
+    cook_id: Option<u32>,
+}
+
+/// Assigns an Order still to be served to a cook, or to the least busy cook on shift when no cook is given,
+/// manager only. An Order assigned to another cook already is handed over.
+#[put("/order/{order_id}/cook")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, KitchenFailure> {
+    identity.require(StaffRole::Manager)?;
+    let order_id = path_params.into_inner().order_id as i64;
+    let cook_id = request_body.into_inner().cook_id.map(|v| v as i64);
+
+    let outcome = order_repository
+        .assign_cook(order_id, cook_id, false, OffsetDateTime::now_utc())
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+    cook_response(order_id, outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::{
+        db::{
+            order::{CookOutcome, Order},
+            staff::Staff,
+            OperationError,
+        },
+        kitchen::CookOrderData,
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        order_repo: crate::db::order::MockRepository,
+        role: StaffRole,
+        cook_id: Option<u32>,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/kitchen/order/12/cook")
+            .insert_header(("Authorization", "Bearer some-token"))
+            .set_json(RequestBody { cook_id })
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a cook access token.
+    /// when: assigning an Order to a cook.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_assign_cook().never();
+        let resp = call(order_repo, StaffRole::Cook, Some(4)).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: no cook in particular.
+    /// when: assigning an Order to the least busy cook.
+    /// then: the cook on shift with the fewest Orders gets it.
+    async fn test_auto_assign() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_assign_cook()
+            .withf(|order_id, cook_id, claim, _| *order_id == 12 && cook_id.is_none() && !claim)
+            .once()
+            .returning(|order_id, _, _, _| {
+                Ok(CookOutcome::Done(Box::new(Order {
+                    order_id,
+                    cook_id: Some(4),
+                    ..Order::new(3, 5, 10)
+                })))
+            });
+        let resp = call(order_repo, StaffRole::Manager, None).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let response_body: CookOrderData = test::read_body_json(resp).await;
+        assert_eq!(response_body.order_id, 12);
+        assert_eq!(response_body.cook_id, Some(4));
+    }
+
+    #[actix_web::test]
+    /// given: no cook on shift.
+    /// when: assigning an Order to the least busy cook.
+    /// then: response status code is 409.
+    async fn test_no_cook() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_assign_cook()
+            .once()
+            .returning(|_, _, _, _| Ok(CookOutcome::NoCook));
+        let resp = call(order_repo, StaffRole::Manager, None).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: assigning an Order to a cook.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_assign_cook()
+            .once()
+            .returning(|_, _, _, _| Err(OperationError::OtherError));
+        let resp = call(order_repo, StaffRole::Manager, Some(4)).await;
+        assert!(resp.status().is_server_error());
+    }
+}
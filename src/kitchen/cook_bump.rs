@@ -0,0 +1,161 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::{cook_response, KitchenFailure};
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    order_id: u32,
+}
+
+/// Bumps a cooking Order off the kitchen queue as ready once the cook is done with it, ahead of its due time.
+/// Cooks bump their own Orders and those no cook has yet, which become theirs; managers bump any Order.
+#[post("/order/{order_id}/bump")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, KitchenFailure> {
+    let identity = identity.require(StaffRole::Cook)?;
+    let order_id = path_params.into_inner().order_id as i64;
+    let cook_id = match identity.0.role {
+        StaffRole::Manager => None,
+        _ => Some(identity.0.staff_id),
+    };
+
+    let outcome = order_repository
+        .bump(order_id, cook_id, OffsetDateTime::now_utc())
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+    cook_response(order_id, outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::{
+        db::{
+            order::{CookOutcome, Order, OrderStatus},
+            staff::Staff,
+            OperationError,
+        },
+        kitchen::CookOrderData,
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(4, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        order_repo: crate::db::order::MockRepository,
+        role: StaffRole,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/kitchen/order/12/bump")
+            .insert_header(("Authorization", "Bearer some-token"))
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    fn bumped(
+        order_id: i64,
+        cook_id: Option<i64>,
+        now: OffsetDateTime,
+    ) -> Result<CookOutcome, OperationError> {
+        Ok(CookOutcome::Done(Box::new(Order {
+            order_id,
+            cook_id: cook_id.or(Some(7)),
+            status: OrderStatus::Ready,
+            ready_at: Some(now),
+            ..Order::new(3, 5, 10)
+        })))
+    }
+
+    #[actix_web::test]
+    /// given: a cooking Order of the cook calling.
+    /// when: bumping it.
+    /// then: it is ready.
+    async fn test_success() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_bump()
+            .withf(|order_id, cook_id, _| *order_id == 12 && *cook_id == Some(4))
+            .once()
+            .returning(bumped);
+        let resp = call(order_repo, StaffRole::Cook).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let response_body: CookOrderData = test::read_body_json(resp).await;
+        assert_eq!(response_body.status, "ready");
+        assert_eq!(response_body.cook_id, Some(4));
+        assert!(response_body.ready_at.is_some());
+    }
+
+    #[actix_web::test]
+    /// given: a manager access token.
+    /// when: bumping an Order of another cook.
+    /// then: it is bumped on their behalf.
+    async fn test_manager() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_bump()
+            .withf(|_, cook_id, _| cook_id.is_none())
+            .once()
+            .returning(bumped);
+        let resp = call(order_repo, StaffRole::Manager).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let response_body: CookOrderData = test::read_body_json(resp).await;
+        assert_eq!(response_body.cook_id, Some(7));
+    }
+
+    #[actix_web::test]
+    /// given: an Order which is not cooking.
+    /// when: bumping it.
+    /// then: response status code is 404.
+    async fn test_not_found() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_bump()
+            .once()
+            .returning(|_, _, _| Ok(CookOutcome::NotFound));
+        let resp = call(order_repo, StaffRole::Cook).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: bumping an Order.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_bump()
+            .once()
+            .returning(|_, _, _| Err(OperationError::OtherError));
+        let resp = call(order_repo, StaffRole::Cook).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,146 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::{cook_response, KitchenFailure};
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    order_id: u32,
+}
+
+/// Claims an Order still to be served for the cook calling, unless another cook has it already.
+#[post("/order/{order_id}/claim")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, KitchenFailure> {
+    let identity = identity.require(StaffRole::Cook)?;
+    let order_id = path_params.into_inner().order_id as i64;
+
+    let outcome = order_repository
+        .assign_cook(
+            order_id,
+            Some(identity.0.staff_id),
+            true,
+            OffsetDateTime::now_utc(),
+        )
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+    cook_response(order_id, outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::{
+        db::{
+            order::{CookOutcome, Order},
+            staff::Staff,
+            OperationError,
+        },
+        kitchen::CookOrderData,
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(4, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        order_repo: crate::db::order::MockRepository,
+        role: StaffRole,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/kitchen/order/12/claim")
+            .insert_header(("Authorization", "Bearer some-token"))
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: claiming an Order.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_assign_cook().never();
+        let resp = call(order_repo, StaffRole::Waiter).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: an Order no cook has yet.
+    /// when: claiming it.
+    /// then: it is assigned to the cook calling.
+    async fn test_success() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_assign_cook()
+            .withf(|order_id, cook_id, claim, _| *order_id == 12 && *cook_id == Some(4) && *claim)
+            .once()
+            .returning(|order_id, cook_id, _, _| {
+                Ok(CookOutcome::Done(Box::new(Order {
+                    order_id,
+                    cook_id,
+                    ..Order::new(3, 5, 10)
+                })))
+            });
+        let resp = call(order_repo, StaffRole::Cook).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let response_body: CookOrderData = test::read_body_json(resp).await;
+        assert_eq!(response_body.cook_id, Some(4));
+        assert_eq!(response_body.status, "cooking");
+    }
+
+    #[actix_web::test]
+    /// given: an Order another cook has claimed already.
+    /// when: claiming it.
+    /// then: response status code is 409.
+    async fn test_taken() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_assign_cook()
+            .once()
+            .returning(|_, _, _, _| Ok(CookOutcome::Taken(7)));
+        let resp = call(order_repo, StaffRole::Cook).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: claiming an Order.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_assign_cook()
+            .once()
+            .returning(|_, _, _, _| Err(OperationError::OtherError));
+        let resp = call(order_repo, StaffRole::Cook).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,187 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, order::CookWorkload, staff::StaffRole},
+    order::BadRequestBody,
+    report::{business_day, date_from_str, today},
+};
+
+use super::KitchenFailure;
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    /// the business day to count the done Orders of, `YYYY-MM-DD`, today by default.
+    date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CookData {
+    staff_id: i64,
+    name: String,
+    on_shift: bool,
+    outstanding: i64,
+    portions: i64,
+    done: i64,
+    average_minutes: Option<i64>,
+}
+
+impl From<CookWorkload> for CookData {
+    fn from(workload: CookWorkload) -> Self {
+        Self {
+            staff_id: workload.staff_id,
+            name: workload.name,
+            on_shift: workload.on_shift,
+            outstanding: workload.outstanding,
+            portions: workload.portions,
+            done: workload.done,
+            average_minutes: workload.average_minutes,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    date: String,
+    cooks: Vec<CookData>,
+}
+
+/// Lists the workload of every cook: the Orders assigned to them which are still cooking or held,
+/// and those they got done on a business day along with how long they took on average.
+#[get("/cook")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    identity: StaffIdentity,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, KitchenFailure> {
+    identity.require(StaffRole::Cook)?;
+    let date = match query_params.into_inner().date {
+        Some(date) => date_from_str(&date).ok_or_else(|| {
+            KitchenFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("date must be formatted as YYYY-MM-DD"),
+            })
+        })?,
+        None => today(),
+    };
+    let (from, to) = business_day(date);
+
+    let workloads = order_repository
+        .list_cook_workloads(from, to, OffsetDateTime::now_utc())
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        date: date.to_string(),
+        cooks: workloads.into_iter().map(CookData::from).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{staff::Staff, OperationError};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        order_repo: crate::db::order::MockRepository,
+        role: StaffRole,
+        uri: &str,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(uri)
+            .insert_header(("Authorization", "Bearer some-token"))
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a date which is not a date.
+    /// when: getting the workload of the cooks.
+    /// then: response status code is 400.
+    async fn test_invalid_date() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_list_cook_workloads().never();
+        let resp = call(order_repo, StaffRole::Cook, "/kitchen/cook?date=yesterday").await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: two cooks, one of them off shift.
+    /// when: getting the workload of the cooks on a business day.
+    /// then: the workload of both is listed for that day.
+    async fn test_success() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_cook_workloads()
+            .withf(|from, to, _| (*from, *to) == business_day(date_from_str("2026-01-07").unwrap()))
+            .once()
+            .returning(|_, _, _| {
+                Ok(vec![
+                    CookWorkload {
+                        staff_id: 4,
+                        name: "Budi".to_string(),
+                        on_shift: true,
+                        outstanding: 3,
+                        portions: 5,
+                        done: 18,
+                        average_minutes: Some(11),
+                    },
+                    CookWorkload {
+                        staff_id: 7,
+                        name: "Sari".to_string(),
+                        on_shift: false,
+                        outstanding: 0,
+                        portions: 0,
+                        done: 0,
+                        average_minutes: None,
+                    },
+                ])
+            });
+        let resp = call(order_repo, StaffRole::Cook, "/kitchen/cook?date=2026-01-07").await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.date, "2026-01-07");
+        assert_eq!(response_body.cooks.len(), 2);
+        assert_eq!(response_body.cooks[0].outstanding, 3);
+        assert_eq!(response_body.cooks[0].average_minutes, Some(11));
+        assert!(!response_body.cooks[1].on_shift);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: getting the workload of the cooks.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_cook_workloads()
+            .once()
+            .returning(|_, _, _| Err(OperationError::OtherError));
+        let resp = call(order_repo, StaffRole::Cook, "/kitchen/cook").await;
+        assert!(resp.status().is_server_error());
+    }
+}
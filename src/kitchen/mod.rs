@@ -0,0 +1,363 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+
+use crate::{
+    auth::AuthFailure,
+    db::{
+        order::{CookOutcome, Order},
+        station::Station,
+        OperationError,
+    },
+    order::{BadRequestBody, InternalServerErrorBody},
+    receipt::sink,
+};
+
+pub mod capacity;
+pub mod cook_assign;
+pub mod cook_bump;
+pub mod cook_claim;
+pub mod cook_workload;
+pub mod printer;
+pub mod priority;
+pub mod queue;
+pub mod scheduler;
+pub mod station_capacity;
+pub mod station_create;
+pub mod station_list;
+pub mod station_printer;
+pub mod station_route;
+pub mod station_unroute;
+pub mod ticket;
+pub mod ticket_list;
+pub mod ticket_reprint;
+
+fn format_time(dt: OffsetDateTime) -> String {
+    dt.format(&Rfc3339).unwrap_or("---".to_string())
+}
+
+/// When an Order is due to be served, `cook_time` minutes after the kitchen started cooking it
+/// or sooner as its priority says, a held Order is counted as if it started now.
+pub fn due_at(order: &Order, now: OffsetDateTime) -> OffsetDateTime {
+    order.started_at.unwrap_or(now) + Duration::minutes(order.eta.unwrap_or(order.cook_time) as i64)
+}
+
+/// A single Order waiting in the kitchen.
+#[derive(Debug, Serialize, Deserialize)]
+struct QueueItem {
+    order_id: i64,
+    table_number: i32,
+    seat_number: Option<i32>,
+    course: Option<i32>,
+    priority: String,
+    cook_id: Option<i64>,
+    quantity: i32,
+    created_at: String,
+    due_at: String,
+    /// negative once the Order is overdue.
+    minutes_left: i64,
+    overdue: bool,
+}
+
+impl QueueItem {
+    fn new(order: &Order, now: OffsetDateTime) -> Self {
+        let due_at = due_at(order, now);
+        Self {
+            order_id: order.order_id,
+            table_number: order.table_number,
+            seat_number: order.seat_number,
+            course: order.course,
+            priority: order.priority.as_str().to_string(),
+            cook_id: order.cook_id,
+            quantity: order.quantity,
+            created_at: format_time(order.created_at),
+            due_at: format_time(due_at),
+            minutes_left: (due_at - now).whole_minutes(),
+            overdue: due_at < now,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MenuData {
+    id: i64,
+    name: String,
+}
+
+/// The Orders of a single Menu waiting in the kitchen, so they can be cooked together.
+#[derive(Debug, Serialize, Deserialize)]
+struct MenuQueue {
+    menu: MenuData,
+    /// how many portions are waiting.
+    quantity: i64,
+    /// how many of the Orders are overdue.
+    overdue: i64,
+    orders: Vec<QueueItem>,
+}
+
+/// Groups the waiting Orders by Menu, the group with the most urgent Order first.
+/// The Orders have to be sorted by their priority and due time already, they stay so within each group.
+fn group_by_menu(orders: &[Order], now: OffsetDateTime) -> Vec<MenuQueue> {
+    let mut groups: Vec<MenuQueue> = vec![];
+    for order in orders {
+        let item = QueueItem::new(order, now);
+        let index = match groups
+            .iter()
+            .position(|group| group.menu.id == order.menu_id as i64)
+        {
+            Some(index) => index,
+            None => {
+                groups.push(MenuQueue {
+                    menu: MenuData {
+                        id: order.menu_id as i64,
+                        name: order.name.clone().unwrap_or("".to_string()),
+                    },
+                    quantity: 0,
+                    overdue: 0,
+                    orders: vec![],
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[index];
+        group.quantity += item.quantity as i64;
+        group.overdue += item.overdue as i64;
+        group.orders.push(item);
+    }
+    groups
+}
+
+/// An Order handed to a cook.
+#[derive(Debug, Serialize, Deserialize)]
+struct CookOrderData {
+    order_id: i64,
+    table_number: i32,
+    menu_id: i32,
+    status: String,
+    cook_id: Option<i64>,
+    ready_at: Option<String>,
+}
+
+/// Responds with the Order handed to a cook, or with why it could not be.
+fn cook_response(order_id: i64, outcome: CookOutcome) -> Result<HttpResponse, KitchenFailure> {
+    match outcome {
+        CookOutcome::Done(order) => Ok(HttpResponse::Ok().json(CookOrderData {
+            order_id: order.order_id,
+            table_number: order.table_number,
+            menu_id: order.menu_id,
+            status: order.status.as_str().to_string(),
+            cook_id: order.cook_id,
+            ready_at: order.ready_at.map(format_time),
+        })),
+        CookOutcome::NotFound => Ok(HttpResponse::NotFound().body("".to_string())),
+        CookOutcome::Taken(cook_id) => Err(KitchenFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!("order {} is assigned to cook {}", order_id, cook_id),
+        })),
+        CookOutcome::NoCook => Err(KitchenFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!("there is no cook to take order {}", order_id),
+        })),
+    }
+}
+
+/// A kitchen station along with the Menus routed to it.
+#[derive(Debug, Serialize, Deserialize)]
+struct StationData {
+    station_id: i64,
+    name: String,
+    capacity: Option<i32>,
+    printer: Option<String>,
+    menu_ids: Vec<i64>,
+}
+
+impl From<Station> for StationData {
+    fn from(station: Station) -> Self {
+        Self {
+            station_id: station.station_id,
+            name: station.name,
+            capacity: station.capacity,
+            printer: station.printer,
+            menu_ids: station.menu_ids,
+        }
+    }
+}
+
+/// A station has to take at least one Order, None lifts its limit.
+fn validate_capacity(capacity: Option<i32>) -> Result<(), KitchenFailure> {
+    match capacity {
+        Some(c) if c < 1 => Err(KitchenFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: String::from("capacity must be at least 1"),
+        })),
+        _ => Ok(()),
+    }
+}
+
+/// A station prints on a printer on the network or into a file, None for the default kitchen printer.
+fn validate_printer(printer: Option<&str>) -> Result<(), KitchenFailure> {
+    match printer {
+        Some(p) if p.chars().count() > 300 || !sink::is_valid(p) => {
+            Err(KitchenFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("printer must be tcp://host:port or file:path"),
+            }))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RoutePathParams {
+    station_id: u32,
+    menu_id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RouteResponseBody {
+    station_id: i64,
+    menu_id: i64,
+}
+
+#[derive(Debug)]
+enum KitchenFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    /// the Order is someone else's or there is no one to take it.
+    Conflict(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for KitchenFailure {
+    fn from(e: AuthFailure) -> Self {
+        KitchenFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for KitchenFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to process kitchen request")
+    }
+}
+
+impl ResponseError for KitchenFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            KitchenFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            KitchenFailure::Auth(e) => e.status_code(),
+            KitchenFailure::Conflict(_) => StatusCode::CONFLICT,
+            KitchenFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            KitchenFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            KitchenFailure::Auth(e) => e.error_response(),
+            KitchenFailure::Conflict(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            KitchenFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+pub fn service() -> actix_web::Scope {
+    web::scope("/kitchen")
+        .service(queue::handler)
+        .service(station_list::handler)
+        .service(station_create::handler)
+        .service(station_capacity::handler)
+        .service(station_printer::handler)
+        .service(station_route::handler)
+        .service(station_unroute::handler)
+        .service(ticket_list::handler)
+        .service(ticket_reprint::handler)
+        .service(cook_assign::handler)
+        .service(cook_claim::handler)
+        .service(cook_bump::handler)
+        .service(cook_workload::handler)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::order::OrderPriority;
+
+    fn order(
+        order_id: i64,
+        menu_id: i32,
+        quantity: i32,
+        minutes_ago: i64,
+        cook_time: i32,
+    ) -> Order {
+        Order {
+            order_id,
+            quantity,
+            name: Some(format!("Menu {}", menu_id)),
+            created_at: now() - Duration::minutes(minutes_ago),
+            started_at: Some(now() - Duration::minutes(minutes_ago)),
+            ..Order::new(3, menu_id, cook_time)
+        }
+    }
+
+    fn now() -> OffsetDateTime {
+        OffsetDateTime::parse("2026-01-07T12:00:00Z", &Rfc3339).unwrap()
+    }
+
+    #[test]
+    fn test_queue_item() {
+        let item = QueueItem::new(&order(1, 5, 1, 20, 15), now());
+        assert_eq!(item.due_at, "2026-01-07T11:55:00Z");
+        assert_eq!(item.minutes_left, -5);
+        assert!(item.overdue);
+
+        let item = QueueItem::new(&order(2, 5, 1, 5, 15), now());
+        assert_eq!(item.minutes_left, 10);
+        assert!(!item.overdue);
+
+        // a rush Order is given less time than it takes:
+        let item = QueueItem::new(
+            &Order {
+                priority: OrderPriority::Rush,
+                eta: Some(10),
+                ..order(3, 5, 1, 5, 15)
+            },
+            now(),
+        );
+        assert_eq!(item.priority, "rush");
+        assert_eq!(item.minutes_left, 5);
+    }
+
+    #[test]
+    fn test_group_by_menu() {
+        // sorted by due time: 11:55, 11:58, 12:05, 12:10
+        let orders = vec![
+            order(1, 5, 1, 20, 15),
+            order(2, 9, 2, 10, 8),
+            order(3, 5, 3, 5, 10),
+            order(4, 9, 1, 0, 10),
+        ];
+        let groups = group_by_menu(&orders, now());
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].menu.id, 5);
+        assert_eq!(groups[0].menu.name, "Menu 5");
+        assert_eq!(groups[0].quantity, 4);
+        assert_eq!(groups[0].overdue, 1);
+        let order_ids: Vec<i64> = groups[0].orders.iter().map(|o| o.order_id).collect();
+        assert_eq!(order_ids, vec![1, 3]);
+        assert_eq!(groups[1].menu.id, 9);
+        assert_eq!(groups[1].quantity, 3);
+        assert_eq!(groups[1].overdue, 1);
+        assert!(group_by_menu(&[], now()).is_empty());
+    }
+}
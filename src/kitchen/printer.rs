@@ -0,0 +1,279 @@
+use std::{env, sync::Arc};
+
+use actix_web::web;
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    db::{self, OperationError},
+    receipt::{
+        receipt_width,
+        sink::{self, Sink},
+    },
+};
+
+use super::ticket::render_ticket;
+
+/// How many tickets are claimed at a time.
+const BATCH_SIZE: i64 = 20;
+
+/// How often the print queue is looked at.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How long a claimed ticket is left alone by the other server instances,
+/// one claimed by an instance which went down while printing is printed again after this.
+const LEASE: Duration = Duration::minutes(1);
+
+/// How the kitchen tickets are printed.
+#[derive(Debug, Clone)]
+pub struct PrintConfig {
+    /// where the tickets of stations without a printer of their own go, and those of Menus routed to no station.
+    pub printer: String,
+    /// how many times printing a ticket is attempted before giving up on it.
+    pub attempts: i32,
+    /// how long to wait after the first failed attempt, the wait grows with every further one.
+    pub retry: Duration,
+    pub width: usize,
+}
+
+impl PrintConfig {
+    /// Reads the config from `KITCHEN_PRINTER`, `KITCHEN_PRINT_ATTEMPTS` and `KITCHEN_PRINT_RETRY` in seconds,
+    /// or defer to predefined defaults. The tickets are as wide as the receipts.
+    pub fn from_env() -> Self {
+        Self {
+            printer: env::var("KITCHEN_PRINTER").unwrap_or("file:kitchen.escpos".to_string()),
+            attempts: env::var("KITCHEN_PRINT_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5)
+                .max(1),
+            retry: Duration::seconds(
+                env::var("KITCHEN_PRINT_RETRY")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(10),
+            ),
+            width: receipt_width(),
+        }
+    }
+}
+
+/// What a single round of printing did, the IDs of the tickets.
+pub struct PrintRound {
+    pub printed: Vec<i64>,
+    pub failed: Vec<i64>,
+}
+
+/// Prints the tickets which are due by `now` on the printers `open` gives for their targets.
+/// A ticket which fails is attempted again later, until it runs out of attempts.
+pub async fn tick(
+    ticket_repository: &dyn db::ticket::Repository,
+    config: &PrintConfig,
+    open: &dyn Fn(&str) -> Box<dyn Sink>,
+    now: OffsetDateTime,
+) -> Result<PrintRound, OperationError> {
+    let tickets = ticket_repository
+        .claim_due(now, now + LEASE, BATCH_SIZE)
+        .await?;
+    let mut round = PrintRound {
+        printed: vec![],
+        failed: vec![],
+    };
+    for ticket in tickets {
+        let printer = ticket.printer.clone().unwrap_or(config.printer.clone());
+        let sink = open(&printer);
+        let bytes = render_ticket(&ticket, config.width);
+        // printers are slow and the sink blocks, so it runs off the async workers:
+        let error = match web::block(move || sink.send(&bytes)).await {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(e) => Some(e.to_string()),
+        };
+        match error {
+            None => {
+                ticket_repository
+                    .mark_printed(ticket.ticket_id, now)
+                    .await?;
+                round.printed.push(ticket.ticket_id);
+            }
+            Some(error) => {
+                log::warn!(
+                    "failed to print ticket {} on {}: {}",
+                    ticket.ticket_id,
+                    printer,
+                    error
+                );
+                let retry_at = match ticket.attempts < config.attempts {
+                    true => Some(now + config.retry * ticket.attempts),
+                    false => None,
+                };
+                ticket_repository
+                    .mark_failed(
+                        ticket.ticket_id,
+                        format!("{}: {}", printer, error),
+                        retry_at,
+                    )
+                    .await?;
+                round.failed.push(ticket.ticket_id);
+            }
+        }
+    }
+    Ok(round)
+}
+
+/// Prints the queued kitchen tickets for as long as the server runs.
+/// Safe to run on every server instance at once, a ticket is only ever claimed by one of them at a time.
+pub async fn run(ticket_repository: Arc<dyn db::ticket::Repository>, config: PrintConfig) {
+    loop {
+        actix_web::rt::time::sleep(POLL_INTERVAL).await;
+        match tick(
+            ticket_repository.as_ref(),
+            &config,
+            &sink::open,
+            OffsetDateTime::now_utc(),
+        )
+        .await
+        {
+            Ok(round) if !round.printed.is_empty() => {
+                log::info!("{} kitchen tickets printed", round.printed.len())
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("failed to print the kitchen tickets: {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, sync::Mutex};
+
+    use super::*;
+    use crate::{db::ticket::Ticket, receipt::sink::MockSink};
+
+    fn config() -> PrintConfig {
+        PrintConfig {
+            printer: "file:kitchen.escpos".to_string(),
+            attempts: 3,
+            retry: Duration::seconds(10),
+            width: 32,
+        }
+    }
+
+    /// Opens printers which all succeed or all fail, and keeps the targets they were opened for.
+    fn printers(works: bool, opened: Arc<Mutex<Vec<String>>>) -> impl Fn(&str) -> Box<dyn Sink> {
+        move |printer: &str| {
+            opened.lock().unwrap().push(printer.to_string());
+            let mut sink = MockSink::new();
+            sink.expect_send()
+                .withf(|bytes| bytes.starts_with(&[0x1b, b'@']))
+                .once()
+                .returning(move |_| match works {
+                    true => Ok(()),
+                    false => Err(io::Error::new(io::ErrorKind::TimedOut, "timed out")),
+                });
+            Box::new(sink)
+        }
+    }
+
+    #[actix_web::test]
+    /// given: due tickets of a station with a printer and of a Menu routed to no station.
+    /// when: printing the queued tickets.
+    /// then: each goes to its printer and is marked as printed.
+    async fn test_tick_prints() {
+        let mut ticket_repo = crate::db::ticket::MockRepository::new();
+        ticket_repo
+            .expect_claim_due()
+            .withf(|now, lease_until, limit| *lease_until == *now + LEASE && *limit == BATCH_SIZE)
+            .once()
+            .returning(|_, _, _| {
+                Ok(vec![
+                    Ticket {
+                        station_id: Some(1),
+                        printer: Some("tcp://10.0.0.21:9100".to_string()),
+                        attempts: 1,
+                        ..Ticket::new(1, 40, 3)
+                    },
+                    Ticket {
+                        attempts: 1,
+                        ..Ticket::new(2, 41, 3)
+                    },
+                ])
+            });
+        ticket_repo
+            .expect_mark_printed()
+            .times(2)
+            .returning(|_, _| Ok(()));
+        ticket_repo.expect_mark_failed().never();
+
+        let opened = Arc::new(Mutex::new(vec![]));
+        let open = printers(true, opened.clone());
+        let round = tick(&ticket_repo, &config(), &open, OffsetDateTime::now_utc())
+            .await
+            .unwrap();
+        assert_eq!(round.printed, vec![1, 2]);
+        assert!(round.failed.is_empty());
+        assert_eq!(
+            *opened.lock().unwrap(),
+            vec!["tcp://10.0.0.21:9100", "file:kitchen.escpos"]
+        );
+    }
+
+    #[actix_web::test]
+    /// given: a printer which is offline.
+    /// when: printing the queued tickets.
+    /// then: a ticket with attempts left is retried later, one without is given up on.
+    async fn test_tick_retries() {
+        let now = OffsetDateTime::now_utc();
+        let mut ticket_repo = crate::db::ticket::MockRepository::new();
+        ticket_repo.expect_claim_due().once().returning(|_, _, _| {
+            Ok(vec![
+                Ticket {
+                    attempts: 2,
+                    ..Ticket::new(1, 40, 3)
+                },
+                Ticket {
+                    attempts: 3,
+                    ..Ticket::new(2, 41, 3)
+                },
+            ])
+        });
+        ticket_repo.expect_mark_printed().never();
+        ticket_repo
+            .expect_mark_failed()
+            .withf(move |ticket_id, error, retry_at| {
+                *ticket_id == 1
+                    && error.starts_with("file:kitchen.escpos")
+                    && *retry_at == Some(now + Duration::seconds(20))
+            })
+            .once()
+            .returning(|_, _, _| Ok(()));
+        ticket_repo
+            .expect_mark_failed()
+            .withf(|ticket_id, _, retry_at| *ticket_id == 2 && retry_at.is_none())
+            .once()
+            .returning(|_, _, _| Ok(()));
+
+        let open = printers(false, Arc::new(Mutex::new(vec![])));
+        let round = tick(&ticket_repo, &config(), &open, now).await.unwrap();
+        assert!(round.printed.is_empty());
+        assert_eq!(round.failed, vec![1, 2]);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: printing the queued tickets.
+    /// then: the failure is returned so the next round tries again.
+    async fn test_tick_failure() {
+        let mut ticket_repo = crate::db::ticket::MockRepository::new();
+        ticket_repo
+            .expect_claim_due()
+            .once()
+            .returning(|_, _, _| Err(OperationError::OtherError));
+
+        let open = printers(true, Arc::new(Mutex::new(vec![])));
+        assert!(
+            tick(&ticket_repo, &config(), &open, OffsetDateTime::now_utc())
+                .await
+                .is_err()
+        );
+    }
+}
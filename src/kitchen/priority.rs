@@ -0,0 +1,80 @@
+use std::env;
+
+use crate::db::order::OrderPriority;
+
+/// How much sooner the kitchen gets the rush and VIP Orders out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriorityConfig {
+    /// the share of the cook time a rush Order is given, in percent.
+    pub rush: i32,
+    /// the share of the cook time a VIP Order is given, in percent.
+    pub vip: i32,
+    /// how many minutes every VIP Order is given whatever it is, in place of the share of its cook time.
+    pub vip_eta: Option<i32>,
+}
+
+impl Default for PriorityConfig {
+    fn default() -> Self {
+        Self {
+            rush: 75,
+            vip: 50,
+            vip_eta: None,
+        }
+    }
+}
+
+impl PriorityConfig {
+    /// Reads the config from `KITCHEN_RUSH_PERCENT`, `KITCHEN_VIP_PERCENT` and `KITCHEN_VIP_ETA` in minutes,
+    /// or defer to predefined defaults.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let percent = |name: &str, default: i32| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|v: &i32| (1..=100).contains(v))
+                .unwrap_or(default)
+        };
+        Self {
+            rush: percent("KITCHEN_RUSH_PERCENT", default.rush),
+            vip: percent("KITCHEN_VIP_PERCENT", default.vip),
+            vip_eta: env::var("KITCHEN_VIP_ETA")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|v: &i32| *v > 0),
+        }
+    }
+
+    /// How many minutes the kitchen is given for an Order of the priority which takes `cook_time` minutes,
+    /// None for normal Orders which are given their cook time.
+    pub fn eta(&self, priority: OrderPriority, cook_time: i32) -> Option<i32> {
+        let share = |percent: i32| (cook_time.saturating_mul(percent) / 100).max(1);
+        match priority {
+            OrderPriority::Normal => None,
+            OrderPriority::Rush => Some(share(self.rush)),
+            OrderPriority::Vip => Some(self.vip_eta.unwrap_or_else(|| share(self.vip))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eta() {
+        let config = PriorityConfig::default();
+        assert_eq!(config.eta(OrderPriority::Normal, 20), None);
+        assert_eq!(config.eta(OrderPriority::Rush, 20), Some(15));
+        assert_eq!(config.eta(OrderPriority::Vip, 20), Some(10));
+        // never due the moment it starts:
+        assert_eq!(config.eta(OrderPriority::Vip, 1), Some(1));
+
+        let config = PriorityConfig {
+            vip_eta: Some(5),
+            ..config
+        };
+        assert_eq!(config.eta(OrderPriority::Vip, 20), Some(5));
+        assert_eq!(config.eta(OrderPriority::Rush, 20), Some(15));
+    }
+}
@@ -0,0 +1,195 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::{format_time, group_by_menu, KitchenFailure, MenuQueue};
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    section_id: Option<u32>,
+    station_id: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    now: String,
+    /// how many Orders are waiting and how many of them are overdue.
+    orders: i64,
+    overdue: i64,
+    menus: Vec<MenuQueue>,
+}
+
+/// Lists the Orders waiting in the kitchen across every Table, VIP then rush Orders first, soonest due first
+/// among those, and grouped by Menu.
+/// Pass `section_id` to only see the Tables of a section, and `station_id` to only see the Menus routed to a station.
+#[get("/queue")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    identity: StaffIdentity,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, KitchenFailure> {
+    identity.require(StaffRole::Cook)?;
+    let query_params = query_params.into_inner();
+    let section_id = query_params.section_id.map(|v| v as i64);
+    let station_id = query_params.station_id.map(|v| v as i64);
+
+    let orders = order_repository
+        .list_kitchen_queue(section_id, station_id)
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+
+    let now = OffsetDateTime::now_utc();
+    let menus = group_by_menu(&orders, now);
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        now: format_time(now),
+        orders: orders.len() as i64,
+        overdue: menus.iter().map(|m| m.overdue).sum(),
+        menus,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use time::Duration;
+    use web::Data;
+
+    use super::*;
+    use crate::db::{order::Order, staff::Staff, OperationError};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        order_repo: crate::db::order::MockRepository,
+        role: StaffRole,
+        uri: &str,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(uri)
+            .insert_header(("Authorization", "Bearer some-token"))
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: getting the kitchen queue.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let order_repo = crate::db::order::MockRepository::new();
+        let resp = call(order_repo, StaffRole::Waiter, "/kitchen/queue").await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: Orders waiting on Tables of a section, one of them overdue.
+    /// when: getting the kitchen queue of that section.
+    /// then: they are grouped by Menu and the overdue one is flagged.
+    async fn test_success() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_kitchen_queue()
+            .withf(|section_id, station_id| *section_id == Some(2) && station_id.is_none())
+            .once()
+            .returning(|_, _| {
+                let now = OffsetDateTime::now_utc();
+                Ok(vec![
+                    Order {
+                        order_id: 1,
+                        name: Some("Nasi Goreng".to_string()),
+                        created_at: now - Duration::minutes(20),
+                        started_at: Some(now - Duration::minutes(20)),
+                        ..Order::new(3, 5, 15)
+                    },
+                    Order {
+                        order_id: 2,
+                        name: Some("Mie Ayam".to_string()),
+                        created_at: now - Duration::minutes(2),
+                        started_at: Some(now - Duration::minutes(2)),
+                        ..Order::new(4, 9, 10)
+                    },
+                    Order {
+                        order_id: 3,
+                        quantity: 2,
+                        name: Some("Nasi Goreng".to_string()),
+                        created_at: now,
+                        started_at: Some(now),
+                        ..Order::new(4, 5, 12)
+                    },
+                ])
+            });
+
+        let resp = call(order_repo, StaffRole::Cook, "/kitchen/queue?section_id=2").await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.orders, 3);
+        assert_eq!(response_body.overdue, 1);
+        assert_eq!(response_body.menus.len(), 2);
+        assert_eq!(response_body.menus[0].menu.name, "Nasi Goreng");
+        assert_eq!(response_body.menus[0].quantity, 3);
+        assert!(response_body.menus[0].orders[0].overdue);
+        assert!(!response_body.menus[0].orders[1].overdue);
+        assert_eq!(response_body.menus[1].orders[0].table_number, 4);
+    }
+
+    #[actix_web::test]
+    /// given: Orders waiting on the grill station.
+    /// when: getting the kitchen queue of that station.
+    /// then: only the Orders of the Menus routed to it are listed.
+    async fn test_station() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_kitchen_queue()
+            .withf(|section_id, station_id| section_id.is_none() && *station_id == Some(1))
+            .once()
+            .returning(|_, _| {
+                Ok(vec![Order {
+                    order_id: 1,
+                    name: Some("Yakitori".to_string()),
+                    ..Order::new(3, 5, 15)
+                }])
+            });
+
+        let resp = call(order_repo, StaffRole::Cook, "/kitchen/queue?station_id=1").await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.orders, 1);
+        assert_eq!(response_body.menus[0].menu.name, "Yakitori");
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: getting the kitchen queue.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_kitchen_queue()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let resp = call(order_repo, StaffRole::Manager, "/kitchen/queue").await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,145 @@
+use std::{env, sync::Arc, time::Duration};
+
+use time::OffsetDateTime;
+
+use crate::db::{self, order::Order, OperationError};
+
+use super::capacity::CapacityConfig;
+
+/// How many Orders are marked as ready within a single transaction.
+const BATCH_SIZE: i64 = 100;
+
+/// Reads how often the due Orders are marked as ready from `SCHEDULER_INTERVAL` in seconds,
+/// or defer to predefined default.
+pub fn interval_from_env() -> Duration {
+    let seconds = match env::var("SCHEDULER_INTERVAL").ok() {
+        Some(v) => v.parse().unwrap_or(10),
+        None => 10,
+    };
+    Duration::from_secs(seconds.max(1))
+}
+
+/// What a single round of the scheduler did.
+pub struct Tick {
+    pub ready: Vec<Order>,
+    pub released: Vec<Order>,
+}
+
+/// Marks the cooking Orders which are due by `now` as ready, batch after batch,
+/// then starts cooking the held Orders which fit the room this made in the kitchen.
+/// Their status changes are announced by the repository.
+pub async fn tick(
+    order_repository: &dyn db::order::Repository,
+    now: OffsetDateTime,
+    capacity: Option<i64>,
+) -> Result<Tick, OperationError> {
+    let mut ready = vec![];
+    loop {
+        let orders = order_repository.mark_ready(now, BATCH_SIZE).await?;
+        let done = (orders.len() as i64) < BATCH_SIZE;
+        ready.extend(orders);
+        if done {
+            break;
+        }
+    }
+    let released = order_repository.release_held(now, capacity).await?;
+    Ok(Tick { ready, released })
+}
+
+/// Marks the due Orders as ready and releases the held ones every `interval` for as long as the server runs.
+/// Safe to run on every server instance at once, an Order is only ever marked or released by one of them.
+pub async fn run(
+    order_repository: Arc<dyn db::order::Repository>,
+    capacity_config: CapacityConfig,
+    interval: Duration,
+) {
+    loop {
+        actix_web::rt::time::sleep(interval).await;
+        match tick(
+            order_repository.as_ref(),
+            OffsetDateTime::now_utc(),
+            capacity_config.capacity,
+        )
+        .await
+        {
+            Ok(tick) => {
+                if !tick.ready.is_empty() {
+                    log::info!("{} orders are ready", tick.ready.len())
+                }
+                if !tick.released.is_empty() {
+                    log::info!("{} held orders started cooking", tick.released.len())
+                }
+            }
+            Err(e) => log::error!("failed to update the kitchen orders: {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::db::order::OrderStatus;
+
+    #[actix_web::test]
+    /// given: more due Orders than fit a single batch.
+    /// when: marking the due Orders as ready.
+    /// then: batches are marked until one comes back short, then the held Orders are released.
+    async fn test_tick_batches() {
+        let left = Arc::new(Mutex::new(BATCH_SIZE + 3));
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_mark_ready()
+            .withf(|_, limit| *limit == BATCH_SIZE)
+            .times(2)
+            .returning(move |now, limit| {
+                let mut left = left.lock().unwrap();
+                let count = (*left).min(limit);
+                *left -= count;
+                Ok((0..count)
+                    .map(|i| Order {
+                        order_id: i + 1,
+                        status: OrderStatus::Ready,
+                        ready_at: Some(now),
+                        ..Order::new(3, 5, 10)
+                    })
+                    .collect())
+            });
+        order_repo
+            .expect_release_held()
+            .withf(|_, capacity| *capacity == Some(20))
+            .once()
+            .returning(|now, _| {
+                Ok(vec![Order {
+                    order_id: 500,
+                    started_at: Some(now),
+                    ..Order::new(4, 8, 10)
+                }])
+            });
+
+        let tick = tick(&order_repo, OffsetDateTime::now_utc(), Some(20))
+            .await
+            .unwrap();
+        assert_eq!(tick.ready.len() as i64, BATCH_SIZE + 3);
+        assert_eq!(tick.released.len(), 1);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: marking the due Orders as ready.
+    /// then: the failure is returned so the next tick tries again.
+    async fn test_tick_failure() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_mark_ready()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+
+        order_repo.expect_release_held().never();
+
+        assert!(tick(&order_repo, OffsetDateTime::now_utc(), None)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use actix_web::{put, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::{validate_capacity, KitchenFailure};
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    station_id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    /// null lifts the limit.
+    capacity: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ResponseBody {
+    station_id: i64,
+    capacity: Option<i32>,
+}
+
+/// Limits how many outstanding Orders a kitchen station takes, manager only.
+/// New Orders beyond it are handled as configured for the whole kitchen.
+#[put("/station/{station_id}/capacity")]
+async fn handler(
+    station_repository: web::Data<dyn db::station::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, KitchenFailure> {
+    identity.require(StaffRole::Manager)?;
+    let capacity = request_body.into_inner().capacity;
+    validate_capacity(capacity)?;
+
+    let result_data = station_repository
+        .set_capacity(path_params.station_id as i64, capacity)
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+
+    match result_data {
+        Some(station_id) => Ok(HttpResponse::Ok().json(ResponseBody {
+            station_id,
+            capacity,
+        })),
+        None => Ok(HttpResponse::NotFound().body("".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{staff::Staff, OperationError};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        station_repo: crate::db::station::MockRepository,
+        role: StaffRole,
+        body: serde_json::Value,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_station_repo: Arc<dyn db::station::Repository> = Arc::new(station_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_station_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/kitchen/station/2/capacity")
+            .insert_header(("Authorization", "Bearer some-token"))
+            .set_json(body)
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a cook instead of a manager.
+    /// when: limiting a station.
+    /// then: response status code is 403.
+    async fn test_not_manager() {
+        let station_repo = crate::db::station::MockRepository::new();
+        let resp = call(
+            station_repo,
+            StaffRole::Cook,
+            serde_json::json!({"capacity": 5}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a negative capacity.
+    /// when: limiting a station.
+    /// then: response status code is 400.
+    async fn test_invalid_capacity() {
+        let station_repo = crate::db::station::MockRepository::new();
+        let resp = call(
+            station_repo,
+            StaffRole::Manager,
+            serde_json::json!({"capacity": -1}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a station which does not exist.
+    /// when: limiting it.
+    /// then: response status code is 404.
+    async fn test_not_found() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_set_capacity()
+            .once()
+            .returning(|_, _| Ok(None));
+        let resp = call(
+            station_repo,
+            StaffRole::Manager,
+            serde_json::json!({"capacity": 5}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: a limited station.
+    /// when: lifting its limit.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_set_capacity()
+            .withf(|station_id, capacity| *station_id == 2 && capacity.is_none())
+            .once()
+            .returning(|station_id, _| Ok(Some(station_id)));
+        let resp = call(
+            station_repo,
+            StaffRole::Manager,
+            serde_json::json!({"capacity": null}),
+        )
+        .await;
+        assert!(resp.status().is_success());
+        let response_body: ResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.station_id, 2);
+        assert!(response_body.capacity.is_none());
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: limiting a station.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_set_capacity()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let resp = call(
+            station_repo,
+            StaffRole::Manager,
+            serde_json::json!({"capacity": 5}),
+        )
+        .await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,202 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole, station::Station},
+    order::BadRequestBody,
+};
+
+use super::{validate_capacity, validate_printer, KitchenFailure, StationData};
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    name: String,
+    capacity: Option<i32>,
+    printer: Option<String>,
+}
+
+/// The input data to add a kitchen station.
+struct Input {
+    name: String,
+    capacity: Option<i32>,
+    printer: Option<String>,
+}
+
+impl Input {
+    fn new(rb: RequestBody) -> Self {
+        Self {
+            name: rb.name.trim().to_string(),
+            capacity: rb.capacity,
+            printer: rb.printer.map(|v| v.trim().to_string()),
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, KitchenFailure> {
+        if self.name.is_empty() || self.name.chars().count() > 100 {
+            return Err(KitchenFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("name must be 1 to 100 characters long"),
+            }));
+        }
+        validate_capacity(self.capacity)?;
+        validate_printer(self.printer.as_deref())?;
+        Ok(self)
+    }
+}
+
+/// Adds a kitchen station, manager only. Menus are routed to it afterwards.
+#[post("/station")]
+async fn handler(
+    station_repository: web::Data<dyn db::station::Repository>,
+    identity: StaffIdentity,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, KitchenFailure> {
+    identity.require(StaffRole::Manager)?;
+    let input = Input::new(request_body.into_inner()).validate()?;
+
+    let station = station_repository
+        .create_station(Station {
+            capacity: input.capacity,
+            printer: input.printer,
+            ..Station::new(input.name)
+        })
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Created().json(StationData::from(station)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{staff::Staff, OperationError};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        station_repo: crate::db::station::MockRepository,
+        role: StaffRole,
+        body: serde_json::Value,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_station_repo: Arc<dyn db::station::Repository> = Arc::new(station_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_station_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/kitchen/station")
+            .insert_header(("Authorization", "Bearer some-token"))
+            .set_json(body)
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a cook instead of a manager.
+    /// when: adding a kitchen station.
+    /// then: response status code is 403.
+    async fn test_not_manager() {
+        let station_repo = crate::db::station::MockRepository::new();
+        let resp = call(
+            station_repo,
+            StaffRole::Cook,
+            serde_json::json!({"name": "Fryer"}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a blank station name.
+    /// when: adding a kitchen station.
+    /// then: response status code is 400.
+    async fn test_invalid_name() {
+        let station_repo = crate::db::station::MockRepository::new();
+        let resp = call(
+            station_repo,
+            StaffRole::Manager,
+            serde_json::json!({"name": "  "}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a station which takes no Orders at all.
+    /// when: adding a kitchen station.
+    /// then: response status code is 400.
+    async fn test_invalid_capacity() {
+        let station_repo = crate::db::station::MockRepository::new();
+        let resp = call(
+            station_repo,
+            StaffRole::Manager,
+            serde_json::json!({"name": "Fryer", "capacity": 0}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a manager and a station name.
+    /// when: adding a kitchen station.
+    /// then: the station is stored without any Menu routed to it.
+    async fn test_success() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_create_station()
+            .withf(|data| data.name == "Fryer" && data.capacity == Some(6))
+            .once()
+            .returning(|data| {
+                Ok(Station {
+                    station_id: 5,
+                    ..data
+                })
+            });
+        let resp = call(
+            station_repo,
+            StaffRole::Manager,
+            serde_json::json!({"name": " Fryer ", "capacity": 6}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let response_body: StationData = test::read_body_json(resp).await;
+        assert_eq!(response_body.station_id, 5);
+        assert!(response_body.menu_ids.is_empty());
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: adding a kitchen station.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_create_station()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let resp = call(
+            station_repo,
+            StaffRole::Manager,
+            serde_json::json!({"name": "Fryer"}),
+        )
+        .await;
+        assert!(resp.status().is_server_error());
+    }
+}
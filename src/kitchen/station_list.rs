@@ -0,0 +1,123 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{auth::StaffIdentity, db};
+
+use super::{KitchenFailure, StationData};
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    stations: Vec<StationData>,
+}
+
+/// Lists the kitchen stations along with the Menus routed to them.
+#[get("/station")]
+async fn handler(
+    station_repository: web::Data<dyn db::station::Repository>,
+    _identity: StaffIdentity,
+) -> Result<HttpResponse, KitchenFailure> {
+    let stations = station_repository
+        .list_stations()
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        stations: stations.into_iter().map(StationData::from).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        staff::{Staff, StaffRole},
+        station::Station,
+        OperationError,
+    };
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Waiter))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        station_repo: crate::db::station::MockRepository,
+        token: Option<&str>,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_station_repo: Arc<dyn db::station::Repository> = Arc::new(station_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_station_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let mut req = test::TestRequest::get().uri("/kitchen/station");
+        if let Some(token) = token {
+            req = req.insert_header(("Authorization", format!("Bearer {}", token)));
+        }
+        test::call_service(&app, req.to_request()).await
+    }
+
+    #[actix_web::test]
+    /// given: no staff access token.
+    /// when: listing the kitchen stations.
+    /// then: response status code is 401.
+    async fn test_anonymous() {
+        let station_repo = crate::db::station::MockRepository::new();
+        let resp = call(station_repo, None).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    /// given: stations with Menus routed to them.
+    /// when: listing the kitchen stations.
+    /// then: every station is listed along with its Menus.
+    async fn test_success() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo.expect_list_stations().once().returning(|| {
+            Ok(vec![
+                Station {
+                    station_id: 1,
+                    menu_ids: vec![4, 5],
+                    ..Station::new("Grill".to_string())
+                },
+                Station {
+                    station_id: 4,
+                    ..Station::new("Drinks".to_string())
+                },
+            ])
+        });
+
+        let resp = call(station_repo, Some("waiter-token")).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.stations.len(), 2);
+        assert_eq!(response_body.stations[0].name, "Grill");
+        assert_eq!(response_body.stations[0].menu_ids, vec![4, 5]);
+        assert!(response_body.stations[1].menu_ids.is_empty());
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: listing the kitchen stations.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_list_stations()
+            .once()
+            .returning(|| Err(OperationError::OtherError));
+        let resp = call(station_repo, Some("waiter-token")).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,164 @@
+use actix_web::{put, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::{validate_printer, KitchenFailure};
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    station_id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    /// `tcp://host:port` or `file:path`, null for the default kitchen printer.
+    printer: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ResponseBody {
+    station_id: i64,
+    printer: Option<String>,
+}
+
+/// Sends the tickets of a kitchen station to a printer of its own, manager only.
+/// The tickets already queued go to the new printer as well.
+#[put("/station/{station_id}/printer")]
+async fn handler(
+    station_repository: web::Data<dyn db::station::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, KitchenFailure> {
+    identity.require(StaffRole::Manager)?;
+    let printer = request_body
+        .into_inner()
+        .printer
+        .map(|v| v.trim().to_string());
+    validate_printer(printer.as_deref())?;
+
+    let result_data = station_repository
+        .set_printer(path_params.station_id as i64, printer.clone())
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+
+    match result_data {
+        Some(station_id) => Ok(HttpResponse::Ok().json(ResponseBody {
+            station_id,
+            printer,
+        })),
+        None => Ok(HttpResponse::NotFound().body("".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{staff::Staff, OperationError};
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Manager))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        station_repo: crate::db::station::MockRepository,
+        body: serde_json::Value,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_station_repo: Arc<dyn db::station::Repository> = Arc::new(station_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_station_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/kitchen/station/1/printer")
+            .insert_header(("Authorization", "Bearer some-token"))
+            .set_json(body)
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a printer which is neither on the network nor a file.
+    /// when: setting the printer of a station.
+    /// then: response status code is 400.
+    async fn test_invalid_printer() {
+        let station_repo = crate::db::station::MockRepository::new();
+        let resp = call(station_repo, serde_json::json!({"printer": "lpt1"})).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a station which does not exist.
+    /// when: setting its printer.
+    /// then: response status code is 404.
+    async fn test_not_found() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_set_printer()
+            .once()
+            .returning(|_, _| Ok(None));
+        let resp = call(
+            station_repo,
+            serde_json::json!({"printer": "tcp://10.0.0.21:9100"}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: a printer on the network.
+    /// when: setting the printer of a station.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_set_printer()
+            .withf(|station_id, printer| {
+                *station_id == 1 && printer.as_deref() == Some("tcp://10.0.0.21:9100")
+            })
+            .once()
+            .returning(|station_id, _| Ok(Some(station_id)));
+        let resp = call(
+            station_repo,
+            serde_json::json!({"printer": " tcp://10.0.0.21:9100 "}),
+        )
+        .await;
+        assert!(resp.status().is_success());
+        let response_body: ResponseBody = test::read_body_json(resp).await;
+        assert_eq!(
+            response_body.printer.as_deref(),
+            Some("tcp://10.0.0.21:9100")
+        );
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: setting the printer of a station.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_set_printer()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let resp = call(station_repo, serde_json::json!({"printer": null})).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,129 @@
+use actix_web::{put, web, HttpResponse};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::{KitchenFailure, RoutePathParams, RouteResponseBody};
+
+/// Routes a Menu to a kitchen station, manager only. A Menu may be routed to several stations,
+/// e.g. a bento needs both the grill and the cold station.
+#[put("/station/{station_id}/menu/{menu_id}")]
+async fn handler(
+    station_repository: web::Data<dyn db::station::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<RoutePathParams>,
+) -> Result<HttpResponse, KitchenFailure> {
+    identity.require(StaffRole::Manager)?;
+    let path_params = path_params.into_inner();
+    let station_id = path_params.station_id as i64;
+
+    let result_data = station_repository
+        .route_menu(station_id, path_params.menu_id as i64)
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+
+    match result_data {
+        Some(menu_id) => Ok(HttpResponse::Ok().json(RouteResponseBody {
+            station_id,
+            menu_id,
+        })),
+        None => Ok(HttpResponse::NotFound().body("".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{staff::Staff, OperationError};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        station_repo: crate::db::station::MockRepository,
+        role: StaffRole,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_station_repo: Arc<dyn db::station::Repository> = Arc::new(station_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_station_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/kitchen/station/1/menu/5")
+            .insert_header(("Authorization", "Bearer some-token"))
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a cook instead of a manager.
+    /// when: routing a Menu to a station.
+    /// then: response status code is 403.
+    async fn test_not_manager() {
+        let station_repo = crate::db::station::MockRepository::new();
+        let resp = call(station_repo, StaffRole::Cook).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a station or a Menu which does not exist.
+    /// when: routing a Menu to a station.
+    /// then: response status code is 404.
+    async fn test_not_found() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_route_menu()
+            .once()
+            .returning(|_, _| Ok(None));
+        let resp = call(station_repo, StaffRole::Manager).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: a manager, an existing station and Menu.
+    /// when: routing a Menu to a station.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_route_menu()
+            .withf(|station_id, menu_id| *station_id == 1 && *menu_id == 5)
+            .once()
+            .returning(|_, menu_id| Ok(Some(menu_id)));
+        let resp = call(station_repo, StaffRole::Manager).await;
+        assert!(resp.status().is_success());
+        let response_body: RouteResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.station_id, 1);
+        assert_eq!(response_body.menu_id, 5);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: routing a Menu to a station.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_route_menu()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let resp = call(station_repo, StaffRole::Manager).await;
+        assert!(resp.status().is_server_error());
+    }
+}
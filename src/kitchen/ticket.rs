@@ -0,0 +1,190 @@
+use time::format_description;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    db::{order::OrderPriority, ticket::Ticket},
+    pricing::local_time,
+    receipt::{render::render_lines, truncate, Emphasis, ReceiptLine},
+};
+
+/// Breaks the text into lines of at most `columns` columns, between words where there are spaces,
+/// wide characters (e.g. Japanese) take two columns.
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for c in text.chars() {
+        if line.width() + c.width().unwrap_or(0) > columns {
+            match line.rfind(' ') {
+                Some(i) if c != ' ' => {
+                    let rest = line[i + 1..].to_string();
+                    lines.push(line[..i].trim_end().to_string());
+                    line = rest;
+                }
+                _ => {
+                    lines.push(line.trim_end().to_string());
+                    line = String::new();
+                }
+            }
+            if c == ' ' {
+                continue;
+            }
+        }
+        line.push(c);
+    }
+    lines.push(line.trim_end().to_string());
+    lines
+}
+
+/// Lays a kitchen ticket out in lines of `width` columns, the Table and the dish are printed large
+/// so they are read at a glance from across the kitchen.
+pub fn layout_ticket(ticket: &Ticket, width: usize) -> Vec<ReceiptLine> {
+    let double = |text: String| ReceiptLine {
+        emphasis: Emphasis::Double,
+        ..ReceiptLine::left(text)
+    };
+    let separator = || ReceiptLine::left("-".repeat(width));
+
+    let station = ticket.station_name.clone().unwrap_or("KITCHEN".to_string());
+    let mut lines = vec![ReceiptLine::center(
+        truncate(&station, width / 2),
+        Emphasis::Double,
+    )];
+    match ticket.priority {
+        OrderPriority::Normal => {}
+        priority => lines.push(ReceiptLine::center(
+            format!("** {} **", priority.as_str().to_uppercase()),
+            Emphasis::Double,
+        )),
+    }
+    if ticket.reprint {
+        lines.push(ReceiptLine::center(
+            "** REPRINT **".to_string(),
+            Emphasis::Bold,
+        ));
+    }
+    let table = match ticket.seat_number {
+        Some(seat) => format!("Table {} Seat {}", ticket.table_number, seat),
+        None => format!("Table {}", ticket.table_number),
+    };
+    lines.push(double(truncate(&table, width / 2)));
+    if let Some(course) = ticket.course {
+        lines.push(ReceiptLine {
+            emphasis: Emphasis::Bold,
+            ..ReceiptLine::left(format!("Course {}", course))
+        });
+    }
+
+    let queued_at = format_description::parse("[hour]:[minute]")
+        .ok()
+        .and_then(|format| local_time(ticket.queued_at).format(&format).ok())
+        .unwrap_or("--:--".to_string());
+    let order = format!("#{}", ticket.order_id);
+    let gap = width.saturating_sub(order.width() + queued_at.width());
+    lines.push(ReceiptLine::left(format!(
+        "{}{}{}",
+        order,
+        " ".repeat(gap),
+        queued_at
+    )));
+    lines.push(separator());
+
+    let dish = format!(
+        "{} x {}",
+        ticket.quantity,
+        ticket.menu_name.clone().unwrap_or("".to_string())
+    );
+    lines.extend(wrap(&dish, width / 2).into_iter().map(double));
+    for modifier in &ticket.modifiers {
+        lines.extend(
+            wrap(modifier, width.saturating_sub(4))
+                .into_iter()
+                .map(|text| ReceiptLine::left(format!("  + {}", text))),
+        );
+    }
+    if let Some(note) = &ticket.note {
+        lines.extend(
+            wrap(note, width.saturating_sub(4))
+                .into_iter()
+                .map(|text| ReceiptLine {
+                    emphasis: Emphasis::Bold,
+                    ..ReceiptLine::left(format!("  ! {}", text))
+                }),
+        );
+    }
+    lines.push(separator());
+    lines
+}
+
+/// Renders a kitchen ticket as an ESC/POS byte stream for the station printer.
+pub fn render_ticket(ticket: &Ticket, width: usize) -> Vec<u8> {
+    render_lines(layout_ticket(ticket, width))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+    use super::*;
+
+    fn ticket() -> Ticket {
+        Ticket {
+            station_id: Some(1),
+            station_name: Some("Grill".to_string()),
+            seat_number: Some(2),
+            quantity: 2,
+            menu_name: Some("焼き鳥".to_string()),
+            modifiers: vec!["no onion".to_string(), "sauce on the side".to_string()],
+            note: Some("guest is allergic to sesame".to_string()),
+            course: Some(2),
+            queued_at: OffsetDateTime::parse("2026-01-07T12:34:00Z", &Rfc3339).unwrap(),
+            ..Ticket::new(1, 42, 5)
+        }
+    }
+
+    #[test]
+    fn test_layout_ticket() {
+        let lines = layout_ticket(&ticket(), 32);
+        let texts: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts[0], "Grill");
+        assert_eq!(lines[0].emphasis, Emphasis::Double);
+        assert_eq!(texts[1], "Table 5 Seat 2");
+        assert_eq!(texts[2], "Course 2");
+        assert!(texts[3].starts_with("#42 "));
+        assert!(texts.contains(&"2 x 焼き鳥"));
+        assert!(texts.contains(&"  + no onion"));
+        assert!(texts.contains(&"  ! guest is allergic to sesame"));
+        assert!(!texts.contains(&"** REPRINT **"));
+        assert!(!texts.contains(&"** RUSH **"));
+        assert!(lines.iter().all(|line| match line.emphasis {
+            Emphasis::Double => line.text.width() <= 16,
+            _ => line.text.width() <= 32,
+        }));
+
+        let reprint = Ticket {
+            reprint: true,
+            station_name: None,
+            priority: OrderPriority::Rush,
+            ..ticket()
+        };
+        let lines = layout_ticket(&reprint, 32);
+        assert_eq!(lines[0].text, "KITCHEN");
+        assert_eq!(lines[1].text, "** RUSH **");
+        assert_eq!(lines[2].text, "** REPRINT **");
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("sauce on the side", 10), vec!["sauce on", "the side"]);
+        // two columns each:
+        assert_eq!(wrap("焼き鳥丼", 5), vec!["焼き", "鳥丼"]);
+        assert_eq!(wrap("", 5), vec![""]);
+    }
+
+    #[test]
+    fn test_render_ticket() {
+        let bytes = render_ticket(&ticket(), 32);
+        assert!(bytes.starts_with(&[0x1b, b'@']));
+        assert!(bytes.ends_with(&[0x1d, b'V', 66, 0]));
+        assert!(bytes.windows(8).any(|w| w == b"no onion"));
+    }
+}
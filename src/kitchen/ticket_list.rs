@@ -0,0 +1,179 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{
+        self,
+        staff::StaffRole,
+        ticket::{Ticket, TicketStatus},
+    },
+    order::BadRequestBody,
+};
+
+use super::{format_time, KitchenFailure};
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    status: Option<String>,
+    limit: Option<u32>,
+}
+
+/// A kitchen ticket in the print queue.
+#[derive(Debug, Serialize, Deserialize)]
+struct TicketData {
+    ticket_id: i64,
+    order_id: i64,
+    table_number: i32,
+    station_id: Option<i64>,
+    station_name: Option<String>,
+    /// the station printer, null for the default kitchen printer.
+    printer: Option<String>,
+    status: String,
+    reprint: bool,
+    attempts: i32,
+    last_error: Option<String>,
+    queued_at: String,
+    printed_at: Option<String>,
+}
+
+impl From<Ticket> for TicketData {
+    fn from(ticket: Ticket) -> Self {
+        Self {
+            ticket_id: ticket.ticket_id,
+            order_id: ticket.order_id,
+            table_number: ticket.table_number,
+            station_id: ticket.station_id,
+            station_name: ticket.station_name,
+            printer: ticket.printer,
+            status: ticket.status.as_str().to_string(),
+            reprint: ticket.reprint,
+            attempts: ticket.attempts,
+            last_error: ticket.last_error,
+            queued_at: format_time(ticket.queued_at),
+            printed_at: ticket.printed_at.map(format_time),
+        }
+    }
+}
+
+/// Lists the kitchen tickets latest first, pass `status=failed` to see those which never came out of a printer.
+#[get("/ticket")]
+async fn handler(
+    ticket_repository: web::Data<dyn db::ticket::Repository>,
+    identity: StaffIdentity,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, KitchenFailure> {
+    identity.require(StaffRole::Cook)?;
+    let query_params = query_params.into_inner();
+    let status = match query_params.status {
+        Some(v) => Some(TicketStatus::parse(&v).ok_or_else(|| {
+            KitchenFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("status must be one of pending, printed or failed"),
+            })
+        })?),
+        None => None,
+    };
+    let limit = query_params.limit.unwrap_or(50).clamp(1, 200) as i64;
+
+    let tickets = ticket_repository
+        .list_tickets(status, limit)
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(
+        tickets
+            .into_iter()
+            .map(TicketData::from)
+            .collect::<Vec<TicketData>>(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{staff::Staff, OperationError};
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Cook))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        ticket_repo: crate::db::ticket::MockRepository,
+        uri: &str,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_ticket_repo: Arc<dyn db::ticket::Repository> = Arc::new(ticket_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_ticket_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(uri)
+            .insert_header(("Authorization", "Bearer some-token"))
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: an unknown ticket status.
+    /// when: listing the kitchen tickets.
+    /// then: response status code is 400.
+    async fn test_invalid_status() {
+        let ticket_repo = crate::db::ticket::MockRepository::new();
+        let resp = call(ticket_repo, "/kitchen/ticket?status=lost").await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a ticket which failed to print.
+    /// when: listing the failed kitchen tickets.
+    /// then: it is listed along with why it failed.
+    async fn test_success() {
+        let mut ticket_repo = crate::db::ticket::MockRepository::new();
+        ticket_repo
+            .expect_list_tickets()
+            .withf(|status, limit| *status == Some(TicketStatus::Failed) && *limit == 50)
+            .once()
+            .returning(|status, _| {
+                Ok(vec![Ticket {
+                    status: status.unwrap(),
+                    attempts: 5,
+                    last_error: Some("tcp://10.0.0.21:9100: timed out".to_string()),
+                    ..Ticket::new(9, 40, 3)
+                }])
+            });
+        let resp = call(ticket_repo, "/kitchen/ticket?status=failed").await;
+        assert!(resp.status().is_success());
+        let response_body: Vec<TicketData> = test::read_body_json(resp).await;
+        assert_eq!(response_body.len(), 1);
+        assert_eq!(response_body[0].status, "failed");
+        assert_eq!(response_body[0].attempts, 5);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: listing the kitchen tickets.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut ticket_repo = crate::db::ticket::MockRepository::new();
+        ticket_repo
+            .expect_list_tickets()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let resp = call(ticket_repo, "/kitchen/ticket").await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,155 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::KitchenFailure;
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    order_id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    station_id: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    order_id: i64,
+    ticket_ids: Vec<i64>,
+}
+
+/// Prints the tickets of an Order once more, marked as a reprint, e.g. after a ticket got lost or a printer jammed.
+/// Pass `station_id` to only reprint the ticket of that station.
+#[post("/order/{order_id}/reprint")]
+async fn handler(
+    ticket_repository: web::Data<dyn db::ticket::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, KitchenFailure> {
+    identity.require(StaffRole::Cook)?;
+    let order_id = path_params.into_inner().order_id as i64;
+    let station_id = query_params.into_inner().station_id.map(|v| v as i64);
+
+    let ticket_ids = ticket_repository
+        .reprint(order_id, station_id, OffsetDateTime::now_utc())
+        .await
+        .map_err(KitchenFailure::InternalServerError)?;
+
+    match ticket_ids.is_empty() {
+        true => Ok(HttpResponse::NotFound().body("".to_string())),
+        // queued, the printer gets to it shortly:
+        false => Ok(HttpResponse::Accepted().json(SuccessResponseBody {
+            order_id,
+            ticket_ids,
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{staff::Staff, OperationError};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        ticket_repo: crate::db::ticket::MockRepository,
+        role: StaffRole,
+        uri: &str,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_ticket_repo: Arc<dyn db::ticket::Repository> = Arc::new(ticket_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_ticket_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/kitchen").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .insert_header(("Authorization", "Bearer some-token"))
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a waiter instead of a cook.
+    /// when: reprinting the tickets of an Order.
+    /// then: response status code is 403.
+    async fn test_not_cook() {
+        let ticket_repo = crate::db::ticket::MockRepository::new();
+        let resp = call(ticket_repo, StaffRole::Waiter, "/kitchen/order/40/reprint").await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: an Order which is not routed to the station.
+    /// when: reprinting its ticket of the station.
+    /// then: response status code is 404.
+    async fn test_not_found() {
+        let mut ticket_repo = crate::db::ticket::MockRepository::new();
+        ticket_repo
+            .expect_reprint()
+            .withf(|order_id, station_id, _| *order_id == 40 && *station_id == Some(2))
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        let resp = call(
+            ticket_repo,
+            StaffRole::Cook,
+            "/kitchen/order/40/reprint?station_id=2",
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: an Order routed to two stations.
+    /// when: reprinting its tickets.
+    /// then: a ticket is queued for each station.
+    async fn test_success() {
+        let mut ticket_repo = crate::db::ticket::MockRepository::new();
+        ticket_repo
+            .expect_reprint()
+            .withf(|order_id, station_id, _| *order_id == 40 && station_id.is_none())
+            .once()
+            .returning(|_, _, _| Ok(vec![71, 72]));
+        let resp = call(ticket_repo, StaffRole::Cook, "/kitchen/order/40/reprint").await;
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.ticket_ids, vec![71, 72]);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: reprinting the tickets of an Order.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut ticket_repo = crate::db::ticket::MockRepository::new();
+        ticket_repo
+            .expect_reprint()
+            .once()
+            .returning(|_, _, _| Err(OperationError::OtherError));
+        let resp = call(ticket_repo, StaffRole::Cook, "/kitchen/order/40/reprint").await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -1,2 +1,18 @@
+pub mod adjustment;
+pub mod auth;
+pub mod bill;
 pub mod db;
+pub mod discount;
+pub mod event;
+pub mod kitchen;
 pub mod order;
+pub mod payment;
+pub mod pricing;
+pub mod receipt;
+pub mod report;
+pub mod reservation;
+pub mod section;
+pub mod session;
+pub mod tax;
+pub mod tip;
+pub mod waitlist;
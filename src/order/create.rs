@@ -1,14 +1,27 @@
 use std::{env, fmt};
 
 use actix_web::{
-    body::BoxBody, http::StatusCode, post, web, HttpResponse, HttpResponseBuilder, ResponseError,
+    body::BoxBody,
+    http::{header::RETRY_AFTER, StatusCode},
+    post, web, HttpResponse, HttpResponseBuilder, ResponseError,
 };
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    db::{self, menu::Menu, order::Order, OperationError},
+    auth::{AuthFailure, OrderingCredential},
+    db::{
+        self,
+        menu::Menu,
+        order::{Order, OrderPriority, OrderStatus},
+        OperationError,
+    },
+    kitchen::{
+        capacity::{admit, Admission, CapacityConfig},
+        priority::PriorityConfig,
+    },
     order::InternalServerErrorBody,
+    pricing,
 };
 
 use super::{BadRequestBody, MenuData, OrderData};
@@ -46,6 +59,15 @@ impl CookTimeBounds {
 #[derive(Serialize, Deserialize)]
 pub struct RequestBody {
     menu_id: u32,
+    quantity: Option<u32>,
+    seat_number: Option<u32>,
+    /// how the guests want it changed, e.g. `no onion`.
+    modifiers: Option<Vec<String>>,
+    note: Option<String>,
+    /// the course to serve it with, it waits until the course is fired.
+    course: Option<u32>,
+    /// one of `normal` (the default), `rush` or `vip`.
+    priority: Option<String>,
 }
 
 struct CookTime {
@@ -71,6 +93,13 @@ impl CookTime {
 struct Input {
     table_number: u32,
     menu_id: u32,
+    quantity: u32,
+    seat_number: Option<u32>,
+    modifiers: Vec<String>,
+    note: Option<String>,
+    course: Option<u32>,
+    /// None when it is none of the known priorities.
+    priority: Option<OrderPriority>,
     cook_time: u16,
 }
 
@@ -90,6 +119,23 @@ impl Input {
             table_number: path_params.table_number,
             cook_time: cook_time.get_random(),
             menu_id: rb.menu_id,
+            quantity: rb.quantity.unwrap_or(1),
+            seat_number: rb.seat_number,
+            modifiers: rb
+                .modifiers
+                .unwrap_or_default()
+                .iter()
+                .map(|m| m.trim().to_string())
+                .collect(),
+            note: rb
+                .note
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty()),
+            course: rb.course,
+            priority: rb
+                .priority
+                .as_deref()
+                .map_or(Some(OrderPriority::Normal), OrderPriority::parse),
         }
     }
 
@@ -107,7 +153,48 @@ impl Input {
                 message: String::from("table_number must be in range of 1 to 100"),
             }));
         }
-        return Ok(self);
+        if self.quantity < 1 || self.quantity > 20 {
+            return Err(CreateFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("quantity must be in range of 1 to 20"),
+            }));
+        }
+        if matches!(self.seat_number, Some(seat) if !(1..=20).contains(&seat)) {
+            return Err(CreateFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("seat_number must be in range of 1 to 20"),
+            }));
+        }
+        if self.modifiers.len() > 10
+            || self
+                .modifiers
+                .iter()
+                .any(|m| m.is_empty() || m.chars().count() > 50)
+        {
+            return Err(CreateFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("up to 10 modifiers of 1 to 50 characters are allowed"),
+            }));
+        }
+        if matches!(&self.note, Some(note) if note.chars().count() > 300) {
+            return Err(CreateFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("note must be at most 300 characters long"),
+            }));
+        }
+        if matches!(self.course, Some(course) if !(1..=9).contains(&course)) {
+            return Err(CreateFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("course must be in range of 1 to 9"),
+            }));
+        }
+        if self.priority.is_none() {
+            return Err(CreateFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("priority must be one of normal, rush or vip"),
+            }));
+        }
+        Ok(self)
     }
 }
 
@@ -119,6 +206,10 @@ struct PathParams {
 #[derive(Debug)]
 enum CreateFailure {
     InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    NoActiveSession(BadRequestBody),
+    /// the kitchen is at capacity, try again after this many seconds.
+    Overloaded(u64),
     InternalServerError(OperationError),
 }
 
@@ -132,6 +223,9 @@ impl ResponseError for CreateFailure {
     fn status_code(&self) -> actix_web::http::StatusCode {
         match self {
             CreateFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            CreateFailure::Auth(e) => e.status_code(),
+            CreateFailure::NoActiveSession(_) => StatusCode::CONFLICT,
+            CreateFailure::Overloaded(_) => StatusCode::SERVICE_UNAVAILABLE,
             CreateFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -139,6 +233,16 @@ impl ResponseError for CreateFailure {
     fn error_response(&self) -> HttpResponse<BoxBody> {
         match self {
             CreateFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            CreateFailure::Auth(e) => e.error_response(),
+            CreateFailure::NoActiveSession(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            CreateFailure::Overloaded(retry_after) => HttpResponseBuilder::new(self.status_code())
+                .insert_header((RETRY_AFTER, retry_after.to_string()))
+                .json(BadRequestBody {
+                    error: true,
+                    message: String::from("the kitchen is at capacity, please try again later"),
+                }),
             CreateFailure::InternalServerError(e) => {
                 log::error!("{:?}", e);
                 HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
@@ -162,44 +266,179 @@ impl SuccessResponseBody {
             order: OrderData {
                 order_id: order.order_id,
                 table_number: order.table_number,
+                session_id: order.session_id,
                 cook_time: order.cook_time,
+                seat_number: order.seat_number,
+                quantity: order.quantity,
+                unit_price: order.unit_price,
                 menu: MenuData {
                     id: menu.id,
                     name: menu.name,
                 },
+                modifiers: order.modifiers,
+                note: order.note,
+                course: order.course,
+                status: order.status.as_str().to_string(),
+                priority: order.priority.as_str().to_string(),
+                eta: order.eta,
+                cook_id: order.cook_id,
                 created_at: OrderData::format_time(order.created_at),
+                ready_at: None,
+                voided_at: None,
+                void_reason: None,
             },
         }
     }
 }
 
+/// Places an Order on a Table, once the kitchen is at capacity the Order is rejected,
+/// takes longer or is held until there is room, depending on the overload policy.
+/// An Order for a course which is not fired yet waits for it instead, out of the kitchen.
 #[post("/order")]
+#[allow(clippy::too_many_arguments)]
 async fn handler(
     order_repository: web::Data<dyn db::order::Repository>,
     menu_repository: web::Data<dyn db::menu::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    price_rule_repository: web::Data<dyn db::price_rule::Repository>,
+    station_repository: web::Data<dyn db::station::Repository>,
+    capacity_config: web::Data<CapacityConfig>,
+    priority_config: web::Data<PriorityConfig>,
+    credential: OrderingCredential,
     path_params: web::Path<PathParams>,
     request_body: web::Json<RequestBody>,
 ) -> Result<HttpResponse, CreateFailure> {
     let json_request = request_body.into_inner();
     let cook_time = CookTime::new();
     let input = Input::new(json_request, path_params.into_inner(), cook_time).validate()?;
+    credential
+        .allows_table(input.table_number as i32)
+        .map_err(CreateFailure::Auth)?;
+    // guests order at normal priority, only staff may rush an Order, like changing its priority later on:
+    if matches!(credential, OrderingCredential::Guest(_))
+        && input.priority != Some(OrderPriority::Normal)
+    {
+        return Err(CreateFailure::Auth(AuthFailure::Forbidden(
+            BadRequestBody {
+                error: true,
+                message: String::from("only staff may set the priority of an order"),
+            },
+        )));
+    }
 
-    let order_entity = db::order::Order::new(
-        input.table_number as i32,
-        input.menu_id as i32,
-        input.cook_time as i32,
-    );
-    let order_result = match order_repository.create_order(order_entity).await {
-        Ok(order_data) => order_data,
+    // orders always belong to the guests currently seated on the table:
+    let session = match session_repository
+        .get_active_session(input.table_number as i32)
+        .await
+    {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            return Err(CreateFailure::NoActiveSession(BadRequestBody {
+                error: true,
+                message: format!("table {} has no open session", input.table_number),
+            }))
+        }
         Err(e) => {
             log::error!("{:?}", e);
             return Err(CreateFailure::InternalServerError(e));
         }
     };
-    match menu_repository.get_by_id(order_result.menu_id as i64).await {
-        Ok(menu) => {
+
+    let menu = match menu_repository.get_by_id(input.menu_id as i64).await {
+        Ok(menu) => menu,
+        Err(e) => {
+            log::error!("{:?}", e);
+            return Err(CreateFailure::InternalServerError(e));
+        }
+    };
+
+    let rules = match price_rule_repository
+        .list_applicable(Some(input.table_number as i32))
+        .await
+    {
+        Ok(rules) => rules,
+        Err(e) => {
+            log::error!("{:?}", e);
+            return Err(CreateFailure::InternalServerError(e));
+        }
+    };
+
+    let mut order_entity = db::order::Order {
+        session_id: Some(session.session_id),
+        seat_number: input.seat_number.map(|v| v as i32),
+        quantity: input.quantity as i32,
+        modifiers: input.modifiers,
+        note: input.note,
+        course: input.course.map(|v| v as i32),
+        priority: input.priority.unwrap_or(OrderPriority::Normal),
+        ..db::order::Order::new(
+            input.table_number as i32,
+            input.menu_id as i32,
+            input.cook_time as i32,
+        )
+    };
+    // the price in effect when ordering is copied into the order,
+    // so later menu price or price rule changes don't alter the bill:
+    order_entity.unit_price =
+        pricing::effective_price(&menu, &rules, pricing::local_time(order_entity.created_at)).price;
+
+    // an Order for a course stays out of the kitchen until its course is fired, unless it already is:
+    let waiting = match input.course {
+        Some(course) => match order_repository
+            .list_fired_courses(session.session_id)
+            .await
+        {
+            Ok(fired) => !fired.contains(&(course as i32)),
+            Err(e) => {
+                log::error!("{:?}", e);
+                return Err(CreateFailure::InternalServerError(e));
+            }
+        },
+        None => false,
+    };
+    if waiting {
+        order_entity.status = OrderStatus::Waiting;
+        order_entity.started_at = None;
+    } else {
+        // the whole kitchen only has to be counted when it has a capacity:
+        let outstanding = match capacity_config.capacity {
+            Some(_) => match order_repository.count_outstanding().await {
+                Ok(outstanding) => outstanding,
+                Err(e) => {
+                    log::error!("{:?}", e);
+                    return Err(CreateFailure::InternalServerError(e));
+                }
+            },
+            None => 0,
+        };
+        let stations = match station_repository.list_loads(input.menu_id as i64).await {
+            Ok(stations) => stations,
+            Err(e) => {
+                log::error!("{:?}", e);
+                return Err(CreateFailure::InternalServerError(e));
+            }
+        };
+        match admit(&capacity_config, outstanding, &stations) {
+            Admission::Accept => {}
+            Admission::Extend(minutes) => order_entity.cook_time += minutes,
+            Admission::Hold => {
+                order_entity.status = OrderStatus::Held;
+                order_entity.started_at = None;
+            }
+            Admission::Reject(retry_after) => return Err(CreateFailure::Overloaded(retry_after)),
+        }
+    }
+    // rush and VIP Orders are given less time than they take, counted from when they start cooking:
+    order_entity.eta = priority_config.eta(order_entity.priority, order_entity.cook_time);
+
+    match order_repository.create_order(order_entity).await {
+        Ok(order_result) => {
+            let held = order_result.status == OrderStatus::Held;
             let response_body = SuccessResponseBody::new(order_result, menu);
-            Ok(HttpResponse::Ok().json(response_body))
+            match held {
+                true => Ok(HttpResponse::Accepted().json(response_body)),
+                false => Ok(HttpResponse::Ok().json(response_body)),
+            }
         }
         Err(e) => {
             log::error!("{:?}", e);
@@ -217,37 +456,150 @@ mod tests {
     use web::Data;
 
     use super::*;
+    use crate::{
+        auth::guest::GuestTokenSigner,
+        db::{
+            price_rule::{AdjustmentKind, PriceRule},
+            session::Session,
+            staff::{Staff, StaffRole},
+            station::StationLoad,
+        },
+        kitchen::capacity::OverloadPolicy,
+    };
 
-    #[actix_web::test]
-    /// given: zero table_id.
-    /// when: creating new order.
-    /// then: response status code is 400.
-    async fn test_invalid_table_id() {
-        let order_repo = crate::db::order::MockRepository::new();
-        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(2, StaffRole::Waiter))));
+        Arc::new(staff_repo)
+    }
+
+    fn guest_token_signer() -> GuestTokenSigner {
+        GuestTokenSigner::new(b"sukab".to_vec(), time::Duration::minutes(30))
+    }
 
-        let menu_repo = crate::db::menu::MockRepository::new();
-        let arc_menu_repo: Arc<dyn db::menu::Repository> = Arc::new(menu_repo);
+    struct Mocks {
+        order_repo: crate::db::order::MockRepository,
+        menu_repo: crate::db::menu::MockRepository,
+        session_repo: crate::db::session::MockRepository,
+        price_rule_repo: crate::db::price_rule::MockRepository,
+        station_repo: crate::db::station::MockRepository,
+        capacity_config: CapacityConfig,
+    }
+
+    /// nothing is expected of the order, menu and session repositories,
+    /// no price rule is in effect and the kitchen has no capacity set.
+    fn mocks() -> Mocks {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo
+            .expect_list_applicable()
+            .returning(|_| Ok(vec![]));
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo.expect_list_loads().returning(|_| Ok(vec![]));
+        Mocks {
+            order_repo: crate::db::order::MockRepository::new(),
+            menu_repo: crate::db::menu::MockRepository::new(),
+            session_repo: crate::db::session::MockRepository::new(),
+            price_rule_repo,
+            station_repo,
+            capacity_config: CapacityConfig::default(),
+        }
+    }
+
+    /// a session open on the table, 7 being its ID.
+    fn open_session() -> crate::db::session::MockRepository {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        session_repo
+    }
+
+    /// an order of a single portion of the menu, with nothing else said about it.
+    fn request_body(menu_id: u32) -> RequestBody {
+        RequestBody {
+            menu_id,
+            quantity: None,
+            seat_number: None,
+            modifiers: None,
+            note: None,
+            course: None,
+            priority: None,
+        }
+    }
+
+    /// places the order on the table with the given `Authorization` header, or anonymously.
+    async fn call(
+        mocks: Mocks,
+        authorization: Option<String>,
+        table_number: i32,
+        body: RequestBody,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(mocks.order_repo);
+        let arc_menu_repo: Arc<dyn db::menu::Repository> = Arc::new(mocks.menu_repo);
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(mocks.session_repo);
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> =
+            Arc::new(mocks.price_rule_repo);
+        let arc_station_repo: Arc<dyn db::station::Repository> = Arc::new(mocks.station_repo);
 
         let app = test::init_service(
             App::new()
                 .app_data(Data::from(arc_order_repo))
                 .app_data(Data::from(arc_menu_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(arc_price_rule_repo))
+                .app_data(Data::from(arc_station_repo))
+                .app_data(Data::new(mocks.capacity_config))
+                .app_data(Data::new(PriorityConfig::default()))
+                .app_data(Data::from(staff_repo()))
+                .app_data(Data::new(guest_token_signer()))
                 .service(web::scope("/table/{table_number}").service(handler)),
         )
         .await;
 
-        let table_number = 0;
-
-        let req = test::TestRequest::post()
+        let mut req = test::TestRequest::post()
             .uri(format!("/table/{}/order", table_number).as_str())
-            .set_json(RequestBody { menu_id: 5 })
-            .to_request();
+            .set_json(body);
+        if let Some(authorization) = authorization {
+            req = req.insert_header(("Authorization", authorization));
+        }
+        test::call_service(&app, req.to_request()).await
+    }
+
+    fn waiter() -> Option<String> {
+        Some("Bearer waiter-token".to_string())
+    }
+
+    /// a guest token issued for the table.
+    fn guest(table_number: i32) -> Option<String> {
+        let (token, _) = guest_token_signer().issue(table_number, time::OffsetDateTime::now_utc());
+        Some(format!("Guest {}", token))
+    }
 
-        let resp = test::call_service(&app, req).await;
+    #[actix_web::test]
+    /// given: zero table_id.
+    /// when: creating new order.
+    /// then: response status code is 400.
+    async fn test_invalid_table_id() {
+        let resp = call(mocks(), waiter(), 0, request_body(5)).await;
         assert!(resp.status().is_client_error());
     }
 
+    #[actix_web::test]
+    /// given: a blank modifier.
+    /// when: creating new order.
+    /// then: response status code is 400.
+    async fn test_invalid_modifiers() {
+        let body = RequestBody {
+            modifiers: Some(vec!["no onion".to_string(), " ".to_string()]),
+            ..request_body(5)
+        };
+        let resp = call(mocks(), waiter(), 3, body).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[actix_web::test]
     /// given: all correct input request.
     /// when: creating new order.
@@ -256,10 +608,13 @@ mod tests {
         let expect_menu_name = "Nasi Goreng".to_string();
         let expect_order_id = 123;
 
-        let expect_order_id_cp = expect_order_id.clone();
+        let expect_order_id_cp = expect_order_id;
         let mut order_repo = crate::db::order::MockRepository::new();
         order_repo
             .expect_create_order()
+            .withf(|order| {
+                order.modifiers == vec!["no onion"] && order.note.as_deref() == Some("extra spicy")
+            })
             .once()
             .returning(move |order| {
                 Ok(Order {
@@ -268,42 +623,97 @@ mod tests {
                 })
             });
 
-        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
-
         let expect_menu_name_cp = expect_menu_name.clone();
         let mut menu_repo = crate::db::menu::MockRepository::new();
-        menu_repo
-            .expect_get_by_id()
-            .once()
-            .returning(move |_| Ok(Menu::new(1, expect_menu_name_cp.clone())));
-
-        let arc_menu_repo: Arc<dyn db::menu::Repository> = Arc::new(menu_repo);
-
-        let app = test::init_service(
-            App::new()
-                .app_data(Data::from(arc_order_repo))
-                .app_data(Data::from(arc_menu_repo))
-                .service(web::scope("/table/{table_number}").service(handler)),
-        )
-        .await;
+        menu_repo.expect_get_by_id().once().returning(move |_| {
+            Ok(Menu {
+                price: 32000,
+                ..Menu::new(1, expect_menu_name_cp.clone())
+            })
+        });
 
         let table_number = 3;
+        let body = RequestBody {
+            quantity: Some(2),
+            seat_number: Some(4),
+            modifiers: Some(vec![" no onion ".to_string()]),
+            note: Some("extra spicy".to_string()),
+            ..request_body(5)
+        };
+        let mocks = Mocks {
+            order_repo,
+            menu_repo,
+            session_repo: open_session(),
+            ..mocks()
+        };
+        let resp = call(mocks, waiter(), table_number, body).await;
 
-        let req = test::TestRequest::post()
-            .uri(format!("/table/{}/order", table_number).as_str())
-            .set_json(RequestBody { menu_id: 5 })
-            .to_request();
-
-        let resp = test::call_service(&app, req).await;
         assert!(resp.status().is_success());
         let response_body: SuccessResponseBody = test::read_body_json(resp).await;
         assert_eq!(response_body.order.table_number, table_number);
         assert_eq!(response_body.order.order_id, expect_order_id);
+        assert_eq!(response_body.order.session_id, Some(7));
         assert_eq!(response_body.order.menu.name, expect_menu_name);
+        assert_eq!(response_body.order.quantity, 2);
+        assert_eq!(response_body.order.seat_number, Some(4));
+        assert_eq!(response_body.order.modifiers, vec!["no onion"]);
+        assert_eq!(response_body.order.unit_price, 32000);
         assert_ne!(response_body.order.cook_time, 0);
         assert!(time::OffsetDateTime::parse(&response_body.order.created_at, &Rfc3339).is_ok());
     }
 
+    #[actix_web::test]
+    /// given: a price rule in effect all day on the table.
+    /// when: creating new order.
+    /// then: the adjusted price is snapshotted into the order.
+    async fn test_price_rule() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_create_order()
+            .withf(|order| order.unit_price == 28800)
+            .once()
+            .returning(|order| {
+                Ok(Order {
+                    order_id: 1,
+                    ..order
+                })
+            });
+
+        let mut menu_repo = crate::db::menu::MockRepository::new();
+        menu_repo.expect_get_by_id().once().returning(|_| {
+            Ok(Menu {
+                price: 32000,
+                ..Menu::new(9, "Nasi Goreng".to_string())
+            })
+        });
+
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo
+            .expect_list_applicable()
+            .withf(|table_number| *table_number == Some(3))
+            .once()
+            .returning(|_| {
+                Ok(vec![PriceRule {
+                    rule_id: 1,
+                    menu_id: Some(9),
+                    ..PriceRule::new("promo".to_string(), AdjustmentKind::Percent, -1000)
+                }])
+            });
+
+        let mocks = Mocks {
+            order_repo,
+            menu_repo,
+            session_repo: open_session(),
+            price_rule_repo,
+            ..mocks()
+        };
+        let resp = call(mocks, waiter(), 3, request_body(9)).await;
+
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.order.unit_price, 28800);
+    }
+
     #[actix_web::test]
     /// given: broken database connection.
     /// when: creating new order.
@@ -315,28 +725,361 @@ mod tests {
             .once()
             .returning(|_| Err(OperationError::OtherError));
 
-        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let mut menu_repo = crate::db::menu::MockRepository::new();
+        menu_repo
+            .expect_get_by_id()
+            .once()
+            .returning(|_| Ok(Menu::new(5, "焼き鳥".to_string())));
+
+        let mocks = Mocks {
+            order_repo,
+            menu_repo,
+            session_repo: open_session(),
+            ..mocks()
+        };
+        let resp = call(mocks, waiter(), 3, request_body(5)).await;
+        assert!(resp.status().is_server_error());
+    }
 
-        let menu_repo = crate::db::menu::MockRepository::new();
+    #[actix_web::test]
+    /// given: a table without an open session.
+    /// when: creating new order.
+    /// then: response status code is 409.
+    async fn test_no_active_session() {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|_| Ok(None));
 
-        let arc_menu_repo: Arc<dyn db::menu::Repository> = Arc::new(menu_repo);
+        let mocks = Mocks {
+            session_repo,
+            ..mocks()
+        };
+        let resp = call(mocks, waiter(), 3, request_body(5)).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
 
-        let app = test::init_service(
-            App::new()
-                .app_data(Data::from(arc_order_repo))
-                .app_data(Data::from(arc_menu_repo))
-                .service(web::scope("/table/{table_number}").service(handler)),
+    #[actix_web::test]
+    /// given: no credential at all.
+    /// when: creating new order.
+    /// then: response status code is 401.
+    async fn test_anonymous() {
+        let resp = call(mocks(), None, 3, request_body(5)).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    /// given: a guest token issued for another table.
+    /// when: creating new order.
+    /// then: response status code is 403.
+    async fn test_guest_token_other_table() {
+        let resp = call(mocks(), guest(4), 3, request_body(5)).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a guest token issued for the table.
+    /// when: creating new order at vip priority.
+    /// then: response status code is 403.
+    async fn test_guest_token_priority() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_create_order().never();
+
+        let body = RequestBody {
+            priority: Some("vip".to_string()),
+            ..request_body(5)
+        };
+        let mocks = Mocks {
+            order_repo,
+            ..mocks()
+        };
+        let resp = call(mocks, guest(3), 3, body).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a guest token issued for the table.
+    /// when: creating new order.
+    /// then: response status code is 200 without any staff credential.
+    async fn test_guest_token() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_create_order().once().returning(|order| {
+            Ok(Order {
+                order_id: 9,
+                ..order
+            })
+        });
+
+        let mut menu_repo = crate::db::menu::MockRepository::new();
+        menu_repo
+            .expect_get_by_id()
+            .once()
+            .returning(|_| Ok(Menu::new(5, "焼き鳥".to_string())));
+
+        let mocks = Mocks {
+            order_repo,
+            menu_repo,
+            session_repo: open_session(),
+            ..mocks()
+        };
+        let resp = call(mocks, guest(3), 3, request_body(5)).await;
+
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.order.order_id, 9);
+    }
+
+    /// places an Order for menu 5 on table 3 while the kitchen has 10 outstanding Orders
+    /// and the grill, where menu 5 is routed to, has 4 out of its capacity of 4.
+    async fn create_at_capacity(
+        order_repo: crate::db::order::MockRepository,
+        capacity_config: CapacityConfig,
+        course: Option<u32>,
+        priority: Option<&str>,
+    ) -> actix_web::dev::ServiceResponse {
+        let mut menu_repo = crate::db::menu::MockRepository::new();
+        menu_repo
+            .expect_get_by_id()
+            .returning(|_| Ok(Menu::new(5, "Yakitori".to_string())));
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        let mut station_repo = crate::db::station::MockRepository::new();
+        station_repo
+            .expect_list_loads()
+            .withf(|menu_id| *menu_id == 5)
+            .returning(|_| {
+                Ok(vec![StationLoad {
+                    station_id: 1,
+                    capacity: 4,
+                    outstanding: 4,
+                }])
+            });
+
+        let body = RequestBody {
+            course,
+            priority: priority.map(|p| p.to_string()),
+            ..request_body(5)
+        };
+        let mocks = Mocks {
+            order_repo,
+            menu_repo,
+            session_repo,
+            station_repo,
+            capacity_config,
+            ..mocks()
+        };
+        call(mocks, waiter(), 3, body).await
+    }
+
+    #[actix_web::test]
+    /// given: a kitchen at capacity which rejects new Orders.
+    /// when: creating new order.
+    /// then: response status code is 503 along with when to try again.
+    async fn test_over_capacity_reject() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_count_outstanding()
+            .once()
+            .returning(|| Ok(10));
+        order_repo.expect_create_order().never();
+
+        let resp = create_at_capacity(
+            order_repo,
+            CapacityConfig {
+                capacity: Some(20),
+                retry_after: 45,
+                ..CapacityConfig::default()
+            },
+            None,
+            None,
         )
         .await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(resp.headers().get(RETRY_AFTER).unwrap(), "45");
+    }
 
-        let table_number = 3;
+    #[actix_web::test]
+    /// given: a kitchen at capacity which takes longer.
+    /// when: creating new order.
+    /// then: the order is placed with a longer cook time.
+    async fn test_over_capacity_extend() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_create_order()
+            .withf(|order| order.cook_time >= 5 + 3 && order.status == OrderStatus::Cooking)
+            .once()
+            .returning(|order| {
+                Ok(Order {
+                    order_id: 1,
+                    ..order
+                })
+            });
 
-        let req = test::TestRequest::post()
-            .uri(format!("/table/{}/order", table_number).as_str())
-            .set_json(RequestBody { menu_id: 5 })
-            .to_request();
+        // the whole kitchen has no capacity, only the grill is full:
+        let resp = create_at_capacity(
+            order_repo,
+            CapacityConfig {
+                policy: OverloadPolicy::Extend,
+                delay: 3,
+                ..CapacityConfig::default()
+            },
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
 
-        let resp = test::call_service(&app, req).await;
-        assert!(resp.status().is_server_error());
+    #[actix_web::test]
+    /// given: a kitchen at capacity which holds new Orders.
+    /// when: creating new order.
+    /// then: the order is held without its cook time started, response status code is 202.
+    async fn test_over_capacity_hold() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_count_outstanding()
+            .once()
+            .returning(|| Ok(10));
+        order_repo
+            .expect_create_order()
+            .withf(|order| order.status == OrderStatus::Held && order.started_at.is_none())
+            .once()
+            .returning(|order| {
+                Ok(Order {
+                    order_id: 1,
+                    ..order
+                })
+            });
+
+        let resp = create_at_capacity(
+            order_repo,
+            CapacityConfig {
+                capacity: Some(10),
+                policy: OverloadPolicy::Hold,
+                ..CapacityConfig::default()
+            },
+            None,
+            None,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.order.status, "held");
+    }
+
+    #[actix_web::test]
+    /// given: a kitchen at capacity which rejects new Orders, and a course which is not fired yet.
+    /// when: creating new order for the course.
+    /// then: the Order waits for its course without being let into the kitchen.
+    async fn test_course_waiting() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_fired_courses()
+            .withf(|session_id| *session_id == 7)
+            .once()
+            .returning(|_| Ok(vec![1]));
+        order_repo.expect_count_outstanding().never();
+        order_repo
+            .expect_create_order()
+            .withf(|order| {
+                order.status == OrderStatus::Waiting
+                    && order.course == Some(2)
+                    && order.started_at.is_none()
+            })
+            .once()
+            .returning(Ok);
+
+        let resp = create_at_capacity(
+            order_repo,
+            CapacityConfig {
+                capacity: Some(10),
+                ..CapacityConfig::default()
+            },
+            Some(2),
+            None,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.order.status, "waiting");
+        assert_eq!(response_body.order.course, Some(2));
+    }
+
+    #[actix_web::test]
+    /// given: a kitchen at capacity which rejects new Orders, and a course which is fired already.
+    /// when: creating new order for the course.
+    /// then: the Order goes to the kitchen right away, so it is rejected.
+    async fn test_course_fired() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_fired_courses()
+            .once()
+            .returning(|_| Ok(vec![1]));
+        order_repo
+            .expect_count_outstanding()
+            .once()
+            .returning(|| Ok(10));
+        order_repo.expect_create_order().never();
+
+        let resp = create_at_capacity(
+            order_repo,
+            CapacityConfig {
+                capacity: Some(10),
+                ..CapacityConfig::default()
+            },
+            Some(1),
+            None,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[actix_web::test]
+    /// given: a kitchen at capacity which takes longer.
+    /// when: creating new VIP order.
+    /// then: the order is given half of its longer cook time.
+    async fn test_priority_vip() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_create_order()
+            .withf(|order| {
+                order.priority == OrderPriority::Vip
+                    && order.cook_time >= 5 + 3
+                    && order.eta == Some(order.cook_time / 2)
+            })
+            .once()
+            .returning(Ok);
+
+        let resp = create_at_capacity(
+            order_repo,
+            CapacityConfig {
+                policy: OverloadPolicy::Extend,
+                delay: 3,
+                ..CapacityConfig::default()
+            },
+            None,
+            Some("vip"),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.order.priority, "vip");
+        assert!(response_body.order.eta.is_some());
+    }
+
+    #[actix_web::test]
+    /// given: an unknown priority.
+    /// when: creating new order.
+    /// then: response status code is 400.
+    async fn test_invalid_priority() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_create_order().never();
+
+        let resp =
+            create_at_capacity(order_repo, CapacityConfig::default(), None, Some("urgent")).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 }
@@ -6,7 +6,8 @@ use actix_web::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    db::{self, OperationError},
+    auth::{AuthFailure, StaffIdentity},
+    db::{self, staff::StaffRole, OperationError},
     order::InternalServerErrorBody,
 };
 
@@ -34,7 +35,7 @@ impl Input {
                 message: String::from("table_number must be in range of 1 to 100"),
             }));
         }
-        return Ok(self);
+        Ok(self)
     }
 }
 
@@ -47,9 +48,18 @@ struct PathParams {
 #[derive(Debug)]
 enum DetailFailure {
     InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    /// the Order is voided or its bill is paid already.
+    Conflict(BadRequestBody),
     InternalServerError(OperationError),
 }
 
+impl From<AuthFailure> for DetailFailure {
+    fn from(e: AuthFailure) -> Self {
+        DetailFailure::Auth(e)
+    }
+}
+
 impl fmt::Display for DetailFailure {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "failed to get order detail")
@@ -60,6 +70,8 @@ impl ResponseError for DetailFailure {
     fn status_code(&self) -> actix_web::http::StatusCode {
         match self {
             DetailFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            DetailFailure::Auth(e) => e.status_code(),
+            DetailFailure::Conflict(_) => StatusCode::CONFLICT,
             DetailFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -67,6 +79,8 @@ impl ResponseError for DetailFailure {
     fn error_response(&self) -> HttpResponse<BoxBody> {
         match self {
             DetailFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            DetailFailure::Auth(e) => e.error_response(),
+            DetailFailure::Conflict(r) => HttpResponseBuilder::new(self.status_code()).json(r),
             DetailFailure::InternalServerError(e) => {
                 log::error!("{:?}", e);
                 HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
@@ -84,21 +98,39 @@ struct SuccessResponseBody {
     order_id: i64,
 }
 
+/// Deletes an Order keyed in by mistake, manager only.
+/// Only Orders which are not voided and whose bill has no payments yet can be deleted, paid bills are refunded instead.
 #[delete("/order/{order_id}")]
 async fn handler(
     order_repository: web::Data<dyn db::order::Repository>,
+    identity: StaffIdentity,
     path_params: web::Path<PathParams>,
 ) -> Result<HttpResponse, DetailFailure> {
+    identity.require(StaffRole::Manager)?;
     let input = Input::new(path_params.into_inner()).validate()?;
 
-    let result_data = order_repository
-        .delete_order(input.table_number as i32, input.order_id as i64)
+    let order = order_repository
+        .get_order_detail(input.table_number as i32, input.order_id as i64)
         .await
-        .map_err(|e| DetailFailure::InternalServerError(e))?;
+        .map_err(DetailFailure::InternalServerError)?;
+    if order.is_none() {
+        return Ok(HttpResponse::NotFound().body("".to_string()));
+    }
 
-    match result_data {
+    // the Order may have been voided or paid for meanwhile, then it is left as it is:
+    match order_repository
+        .delete_order(input.table_number as i32, input.order_id as i64)
+        .await
+        .map_err(DetailFailure::InternalServerError)?
+    {
         Some(order_id) => Ok(HttpResponse::Ok().json(SuccessResponseBody { order_id })),
-        None => Ok(HttpResponse::NotFound().body("".to_string())),
+        None => Err(DetailFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!(
+                "order {} is voided or its bill has payments already, refund instead",
+                input.order_id
+            ),
+        })),
     }
 }
 
@@ -110,95 +142,134 @@ mod tests {
     use web::Data;
 
     use super::*;
+    use crate::db::{order::Order, staff::Staff};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
 
-    #[actix_web::test]
-    /// given: zero table_id.
-    /// when: delete an order.
-    /// then: response status code is 400.
-    async fn test_invalid_table_id() {
-        let table_number = 0;
-        let order_id = 1;
-
-        let order_repo = crate::db::order::MockRepository::new();
+    async fn call(
+        order_repo: crate::db::order::MockRepository,
+        role: StaffRole,
+        table_number: u32,
+        order_id: u32,
+    ) -> actix_web::dev::ServiceResponse {
         let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
 
         let app = test::init_service(
             App::new()
                 .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(staff_repo(role)))
                 .service(web::scope("/table/{table_number}").service(handler)),
         )
         .await;
 
         let req = test::TestRequest::delete()
             .uri(format!("/table/{}/order/{}", table_number, order_id).as_str())
+            .insert_header(("Authorization", "Bearer manager-token"))
             .to_request();
+        test::call_service(&app, req).await
+    }
+
+    fn existing_order(order_repo: &mut crate::db::order::MockRepository) {
+        order_repo
+            .expect_get_order_detail()
+            .once()
+            .returning(|table_number, order_id| {
+                Ok(Some(Order {
+                    order_id,
+                    ..Order::new(table_number, 5, 10)
+                }))
+            });
+    }
 
-        let resp = test::call_service(&app, req).await;
+    #[actix_web::test]
+    /// given: zero table_id.
+    /// when: delete an order.
+    /// then: response status code is 400.
+    async fn test_invalid_table_id() {
+        let order_repo = crate::db::order::MockRepository::new();
+        let resp = call(order_repo, StaffRole::Manager, 0, 1).await;
         assert!(resp.status().is_client_error());
     }
 
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: delete an order.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_delete_order().never();
+        let resp = call(order_repo, StaffRole::Waiter, 3, 123).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
     #[actix_web::test]
     /// given: all request and inputs are valid.
     /// when: delete an order.
     /// then: response status code is 200.
     async fn test_success() {
         let expect_order_id = 123;
-        let table_number = 3;
 
         let mut order_repo = crate::db::order::MockRepository::new();
+        existing_order(&mut order_repo);
         order_repo
             .expect_delete_order()
             .once()
-            .returning(move |_, order_id| {
-                Ok(Some(order_id))
-            });
-
-        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+            .returning(move |_, order_id| Ok(Some(order_id)));
 
-        let app = test::init_service(
-            App::new()
-                .app_data(Data::from(arc_order_repo))
-                .service(web::scope("/table/{table_number}").service(handler)),
-        )
-        .await;
-
-        let req = test::TestRequest::delete()
-            .uri(format!("/table/{}/order/{}", table_number, expect_order_id).as_str())
-            .to_request();
-
-        let resp = test::call_service(&app, req).await;
+        let resp = call(order_repo, StaffRole::Manager, 3, expect_order_id as u32).await;
         assert!(resp.status().is_success());
         let response_body: SuccessResponseBody = test::read_body_json(resp).await;
         assert_eq!(response_body.order_id, expect_order_id);
     }
 
+    #[actix_web::test]
+    /// given: an Order which does not exist.
+    /// when: delete it.
+    /// then: response status code is 404.
+    async fn test_not_found() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_get_order_detail()
+            .once()
+            .returning(|_, _| Ok(None));
+        order_repo.expect_delete_order().never();
+        let resp = call(order_repo, StaffRole::Manager, 3, 123).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: an Order whose bill has payments already.
+    /// when: delete it.
+    /// then: response status code is 409.
+    async fn test_paid() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        existing_order(&mut order_repo);
+        order_repo
+            .expect_delete_order()
+            .once()
+            .returning(|_, _| Ok(None));
+        let resp = call(order_repo, StaffRole::Manager, 3, 123).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
     #[actix_web::test]
     /// given: failure when accessing the database.
     /// when: delete an order.
     /// then: response status code is 500.
     async fn test_database_failure() {
-        let table_number = 3;
-        let expect_order_id = 222;
-
         let mut order_repo = crate::db::order::MockRepository::new();
+        existing_order(&mut order_repo);
         order_repo
             .expect_delete_order()
             .once()
             .returning(|_, _| Err(OperationError::OtherError));
-        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
-
-        let app = test::init_service(
-            App::new()
-                .app_data(Data::from(arc_order_repo))
-                .service(web::scope("/table/{table_number}").service(handler)),
-        )
-        .await;
-
-        let req = test::TestRequest::delete()
-            .uri(format!("/table/{}/order/{}", table_number, expect_order_id).as_str())
-            .to_request();
-
-        let resp = test::call_service(&app, req).await;
+        let resp = call(order_repo, StaffRole::Manager, 3, 222).await;
         assert!(resp.status().is_server_error());
     }
 }
@@ -6,11 +6,12 @@ use actix_web::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    auth::{AuthFailure, OrderingCredential},
     db::{self, order::Order, OperationError},
     order::InternalServerErrorBody,
 };
 
-use super::{BadRequestBody, MenuData, OrderData};
+use super::{BadRequestBody, OrderData};
 
 /// The input data to get detail of an Order.
 struct Input {
@@ -34,7 +35,7 @@ impl Input {
                 message: String::from("table_number must be in range of 1 to 100"),
             }));
         }
-        return Ok(self);
+        Ok(self)
     }
 }
 
@@ -47,9 +48,16 @@ struct PathParams {
 #[derive(Debug)]
 enum DetailFailure {
     InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
     InternalServerError(OperationError),
 }
 
+impl From<AuthFailure> for DetailFailure {
+    fn from(e: AuthFailure) -> Self {
+        DetailFailure::Auth(e)
+    }
+}
+
 impl fmt::Display for DetailFailure {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "failed to get order detail")
@@ -60,6 +68,7 @@ impl ResponseError for DetailFailure {
     fn status_code(&self) -> actix_web::http::StatusCode {
         match self {
             DetailFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            DetailFailure::Auth(e) => e.status_code(),
             DetailFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -67,6 +76,7 @@ impl ResponseError for DetailFailure {
     fn error_response(&self) -> HttpResponse<BoxBody> {
         match self {
             DetailFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            DetailFailure::Auth(e) => e.error_response(),
             DetailFailure::InternalServerError(e) => {
                 log::error!("{:?}", e);
                 HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
@@ -87,31 +97,28 @@ struct SuccessResponseBody {
 impl SuccessResponseBody {
     fn new(order: Order) -> Self {
         Self {
-            order: OrderData {
-                order_id: order.order_id,
-                table_number: order.table_number,
-                cook_time: order.cook_time,
-                menu: MenuData {
-                    id: order.menu_id as i64,
-                    name: order.name.clone().unwrap_or("".to_string()),
-                },
-                created_at: OrderData::format_time(order.created_at),
-            },
+            order: OrderData::from_order(&order),
         }
     }
 }
 
+/// Gets an Order of a Table, guests only see their own Table and waiters the Tables of their sections.
 #[get("/order/{order_id}")]
 async fn handler(
     order_repository: web::Data<dyn db::order::Repository>,
+    section_repository: web::Data<dyn db::section::Repository>,
+    credential: OrderingCredential,
     path_params: web::Path<PathParams>,
 ) -> Result<HttpResponse, DetailFailure> {
     let input = Input::new(path_params.into_inner()).validate()?;
+    credential
+        .serves_table(section_repository.get_ref(), input.table_number as i32)
+        .await?;
 
     let result_data = order_repository
         .get_order_detail(input.table_number as i32, input.order_id as i64)
         .await
-        .map_err(|e| DetailFailure::InternalServerError(e))?;
+        .map_err(DetailFailure::InternalServerError)?;
 
     match result_data {
         Some(order) => Ok(HttpResponse::Ok().json(SuccessResponseBody::new(order))),
@@ -128,6 +135,27 @@ mod tests {
     use web::Data;
 
     use super::*;
+    use crate::db::staff::{Staff, StaffRole};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(4, role))));
+        Arc::new(staff_repo)
+    }
+
+    /// every Table is in section 2, the Staff member works the given sections.
+    fn section_repo(assigned: Vec<i64>) -> Arc<dyn db::section::Repository> {
+        let mut section_repo = crate::db::section::MockRepository::new();
+        section_repo
+            .expect_get_table_section()
+            .returning(|_| Ok(Some(2)));
+        section_repo
+            .expect_list_assigned_sections()
+            .returning(move |_, _| Ok(assigned.clone()));
+        Arc::new(section_repo)
+    }
 
     #[actix_web::test]
     /// given: zero table_id.
@@ -143,12 +171,15 @@ mod tests {
         let app = test::init_service(
             App::new()
                 .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(section_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
                 .service(web::scope("/table/{table_number}").service(handler)),
         )
         .await;
 
         let req = test::TestRequest::get()
             .uri(format!("/table/{}/order/{}", table_number, order_id).as_str())
+            .insert_header(("Authorization", "Bearer manager-token"))
             .to_request();
 
         let resp = test::call_service(&app, req).await;
@@ -173,10 +204,27 @@ mod tests {
                 let expect_order_data = Order {
                     order_id,
                     table_number,
+                    session_id: Some(1),
                     menu_id: 2,
                     cook_time: 3,
+                    seat_number: None,
+                    quantity: 1,
+                    unit_price: 35000,
                     name: Some(expect_menu_name_cp.clone()),
+                    modifiers: vec![],
+                    note: None,
+                    course: None,
+                    status: crate::db::order::OrderStatus::Cooking,
+                    priority: crate::db::order::OrderPriority::Normal,
+                    eta: None,
+                    cook_id: None,
                     created_at: OffsetDateTime::now_utc(),
+                    started_at: Some(OffsetDateTime::now_utc()),
+                    ready_at: None,
+                    voided_at: None,
+                    void_reason: None,
+                    void_note: None,
+                    voided_by: None,
                 };
                 Ok(Some(expect_order_data))
             });
@@ -186,12 +234,15 @@ mod tests {
         let app = test::init_service(
             App::new()
                 .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(section_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
                 .service(web::scope("/table/{table_number}").service(handler)),
         )
         .await;
 
         let req = test::TestRequest::get()
             .uri(format!("/table/{}/order/{}", table_number, expect_order_id).as_str())
+            .insert_header(("Authorization", "Bearer manager-token"))
             .to_request();
 
         let resp = test::call_service(&app, req).await;
@@ -221,15 +272,69 @@ mod tests {
         let app = test::init_service(
             App::new()
                 .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(section_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
                 .service(web::scope("/table/{table_number}").service(handler)),
         )
         .await;
 
         let req = test::TestRequest::get()
             .uri(format!("/table/{}/order/{}", table_number, expect_order_id).as_str())
+            .insert_header(("Authorization", "Bearer manager-token"))
             .to_request();
 
         let resp = test::call_service(&app, req).await;
         assert!(resp.status().is_server_error());
     }
+
+    #[actix_web::test]
+    /// given: no staff access token nor guest token.
+    /// when: get order detail.
+    /// then: response status code is 401.
+    async fn test_unauthorized() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_get_order_detail().never();
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(section_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/table/3/order/1").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    /// given: a waiter who does not work the section of the Table.
+    /// when: get order detail.
+    /// then: response status code is 403.
+    async fn test_waiter_other_section() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_get_order_detail().never();
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(section_repo(vec![1])))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/order/1")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
 }
@@ -0,0 +1,316 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, post, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::{AuthFailure, StaffIdentity},
+    db::{self, staff::StaffRole, OperationError},
+    order::{BadRequestBody, InternalServerErrorBody, OrderData},
+};
+
+/// The input data to fire a course of a Table.
+struct Input {
+    table_number: u32,
+    course: u32,
+}
+
+impl Input {
+    fn new(path_params: PathParams) -> Self {
+        Self {
+            table_number: path_params.table_number,
+            course: path_params.course,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, FireFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(FireFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        if self.course < 1 || self.course > 9 {
+            return Err(FireFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("course must be in range of 1 to 9"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    table_number: u32,
+    course: u32,
+}
+
+#[derive(Debug)]
+enum FireFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    NoActiveSession(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for FireFailure {
+    fn from(e: AuthFailure) -> Self {
+        FireFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for FireFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to fire course")
+    }
+}
+
+impl ResponseError for FireFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            FireFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            FireFailure::Auth(e) => e.status_code(),
+            FireFailure::NoActiveSession(_) => StatusCode::CONFLICT,
+            FireFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            FireFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            FireFailure::Auth(e) => e.error_response(),
+            FireFailure::NoActiveSession(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            FireFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    session_id: i64,
+    course: i32,
+    /// the Orders which went to the kitchen.
+    orders: Vec<OrderData>,
+}
+
+/// Fires a course of the Table, e.g. the mains once the starters are eaten, waiter only.
+/// Its waiting Orders go to the kitchen, their cook time starts now, and the Orders placed for it later
+/// go to the kitchen right away. Firing a course again sends along those which were waiting since.
+#[post("/course/{course}/fire")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, FireFailure> {
+    let identity = identity.require(StaffRole::Waiter)?;
+    let input = Input::new(path_params.into_inner()).validate()?;
+
+    let session = match session_repository
+        .get_active_session(input.table_number as i32)
+        .await
+        .map_err(FireFailure::InternalServerError)?
+    {
+        Some(session) => session,
+        None => {
+            return Err(FireFailure::NoActiveSession(BadRequestBody {
+                error: true,
+                message: format!("table {} has no open session", input.table_number),
+            }))
+        }
+    };
+
+    let orders = order_repository
+        .fire_course(
+            input.table_number as i32,
+            session.session_id,
+            input.course as i32,
+            identity.0.staff_id,
+            OffsetDateTime::now_utc(),
+        )
+        .await
+        .map_err(FireFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        session_id: session.session_id,
+        course: input.course as i32,
+        orders: orders.iter().map(OrderData::from_order).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        order::{Order, OrderStatus},
+        session::Session,
+        staff::Staff,
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(2, role))));
+        Arc::new(staff_repo)
+    }
+
+    fn session_repo(open: bool) -> Arc<dyn db::session::Repository> {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .returning(move |table_number| match open {
+                true => Ok(Some(Session::new(7, table_number))),
+                false => Ok(None),
+            });
+        Arc::new(session_repo)
+    }
+
+    async fn call(
+        order_repo: crate::db::order::MockRepository,
+        session_repo: Arc<dyn db::session::Repository>,
+        role: StaffRole,
+        uri: &str,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(session_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a course out of range.
+    /// when: firing it.
+    /// then: response status code is 400.
+    async fn test_invalid_course() {
+        let order_repo = crate::db::order::MockRepository::new();
+        let resp = call(
+            order_repo,
+            session_repo(true),
+            StaffRole::Waiter,
+            "/table/3/course/0/fire",
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a cook instead of a waiter.
+    /// when: firing a course.
+    /// then: response status code is 403.
+    async fn test_not_waiter() {
+        let order_repo = crate::db::order::MockRepository::new();
+        let resp = call(
+            order_repo,
+            session_repo(true),
+            StaffRole::Cook,
+            "/table/3/course/2/fire",
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a Table without an open session.
+    /// when: firing a course.
+    /// then: response status code is 409.
+    async fn test_no_active_session() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_fire_course().never();
+        let resp = call(
+            order_repo,
+            session_repo(false),
+            StaffRole::Waiter,
+            "/table/3/course/2/fire",
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: two Orders waiting for the mains.
+    /// when: firing the mains.
+    /// then: both go to the kitchen.
+    async fn test_success() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_fire_course()
+            .withf(|table_number, session_id, course, staff_id, _| {
+                *table_number == 3 && *session_id == 7 && *course == 2 && *staff_id == 2
+            })
+            .once()
+            .returning(|table_number, session_id, course, _, now| {
+                Ok((1..=2)
+                    .map(|i| Order {
+                        order_id: i,
+                        session_id: Some(session_id),
+                        course: Some(course),
+                        status: OrderStatus::Cooking,
+                        started_at: Some(now),
+                        ..Order::new(table_number, 5, 10)
+                    })
+                    .collect())
+            });
+        let resp = call(
+            order_repo,
+            session_repo(true),
+            StaffRole::Waiter,
+            "/table/3/course/2/fire",
+        )
+        .await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.session_id, 7);
+        assert_eq!(response_body.course, 2);
+        assert_eq!(response_body.orders.len(), 2);
+        assert_eq!(response_body.orders[0].status, "cooking");
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: firing a course.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_fire_course()
+            .once()
+            .returning(|_, _, _, _, _| Err(OperationError::OtherError));
+        let resp = call(
+            order_repo,
+            session_repo(true),
+            StaffRole::Waiter,
+            "/table/3/course/2/fire",
+        )
+        .await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,346 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, get, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::{AuthFailure, StaffIdentity},
+    db::{self, staff::StaffRole, OperationError},
+    order::InternalServerErrorBody,
+};
+
+use super::{BadRequestBody, OrderData};
+
+/// The input data to list Orders across the floor.
+struct Input {
+    section_id: Option<u32>,
+    page: i32,
+    limit: i32,
+}
+
+impl Input {
+    fn new(query_params: QueryParams) -> Self {
+        let page = query_params.page.unwrap_or(0) as i32;
+        let limit = query_params
+            .limit
+            .map(|v| if v == 0 { 1 } else { v })
+            .unwrap_or(20) as i32;
+        Self {
+            section_id: query_params.section_id,
+            page,
+            limit,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, FloorFailure> {
+        if self.limit > 100 {
+            return Err(FloorFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("limit must be at most 100"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    section_id: Option<u32>,
+    limit: Option<u32>,
+    page: Option<u32>,
+}
+
+#[derive(Debug)]
+enum FloorFailure {
+    InvalidInput(BadRequestBody),
+    Forbidden(BadRequestBody),
+    Auth(AuthFailure),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for FloorFailure {
+    fn from(e: AuthFailure) -> Self {
+        FloorFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for FloorFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to list floor orders")
+    }
+}
+
+impl ResponseError for FloorFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            FloorFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            FloorFailure::Forbidden(_) => StatusCode::FORBIDDEN,
+            FloorFailure::Auth(e) => e.status_code(),
+            FloorFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            FloorFailure::InvalidInput(r) | FloorFailure::Forbidden(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            FloorFailure::Auth(e) => e.error_response(),
+            FloorFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    orders: Vec<OrderData>,
+}
+
+#[get("")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    section_repository: web::Data<dyn db::section::Repository>,
+    identity: StaffIdentity,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, FloorFailure> {
+    let input = Input::new(query_params.into_inner()).validate()?;
+    let requested = input.section_id.map(|v| v as i64);
+
+    // waiters only get to see the Tables of the sections they work this shift:
+    let staff = identity.0;
+    let section_ids = match staff.role {
+        StaffRole::Waiter => {
+            let assigned = section_repository
+                .list_assigned_sections(staff.staff_id, OffsetDateTime::now_utc())
+                .await
+                .map_err(FloorFailure::InternalServerError)?;
+            match requested {
+                Some(section_id) if !assigned.contains(&section_id) => {
+                    return Err(FloorFailure::Forbidden(BadRequestBody {
+                        error: true,
+                        message: format!("you are not assigned to section {}", section_id),
+                    }))
+                }
+                Some(section_id) => Some(vec![section_id]),
+                None => Some(assigned),
+            }
+        }
+        _ => requested.map(|section_id| vec![section_id]),
+    };
+
+    match order_repository
+        .list_by_sections(section_ids, input.page as i64, input.limit as i64)
+        .await
+    {
+        Ok(orders) => Ok(HttpResponse::Ok().json(SuccessResponseBody {
+            orders: orders.iter().map(OrderData::from_order).collect(),
+        })),
+        Err(e) => Err(FloorFailure::InternalServerError(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{order::Order, staff::Staff};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(4, role))));
+        Arc::new(staff_repo)
+    }
+
+    #[actix_web::test]
+    /// given: no staff access token.
+    /// when: list Orders across the floor.
+    /// then: response status code is 401 and nothing is listed.
+    async fn test_unauthorized() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_list_by_sections().never();
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let section_repo = crate::db::section::MockRepository::new();
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/orders").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/orders").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    /// given: a waiter filtering by a section they do not work.
+    /// when: list Orders across the floor.
+    /// then: response status code is 403.
+    async fn test_waiter_other_section() {
+        let order_repo = crate::db::order::MockRepository::new();
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let mut section_repo = crate::db::section::MockRepository::new();
+        section_repo
+            .expect_list_assigned_sections()
+            .once()
+            .returning(|_, _| Ok(vec![1]));
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/orders").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/orders?section_id=2")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a waiter assigned to two sections.
+    /// when: list Orders across the floor.
+    /// then: only Orders within their sections are listed.
+    async fn test_waiter_sections() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_by_sections()
+            .withf(|section_ids, _, _| *section_ids == Some(vec![1, 3]))
+            .once()
+            .returning(|_, _, _| {
+                Ok(vec![Order {
+                    order_id: 10,
+                    session_id: Some(2),
+                    name: Some("Rendang".to_string()),
+                    ..Order::new(7, 10, 12)
+                }])
+            });
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let mut section_repo = crate::db::section::MockRepository::new();
+        section_repo
+            .expect_list_assigned_sections()
+            .once()
+            .returning(|_, _| Ok(vec![1, 3]));
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/orders").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/orders")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.orders.len(), 1);
+        assert_eq!(response_body.orders[0].table_number, 7);
+        assert_eq!(response_body.orders[0].menu.name, "Rendang");
+    }
+
+    #[actix_web::test]
+    /// given: a manager filtering by section.
+    /// when: list Orders across the floor.
+    /// then: Orders of the requested section are listed.
+    async fn test_section_filter() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_by_sections()
+            .withf(|section_ids, _, _| *section_ids == Some(vec![2]))
+            .once()
+            .returning(|_, _, _| Ok(vec![]));
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let section_repo = crate::db::section::MockRepository::new();
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/orders").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/orders?section_id=2")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: list Orders across the floor.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_by_sections()
+            .once()
+            .returning(|_, _, _| Err(OperationError::OtherError));
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let section_repo = crate::db::section::MockRepository::new();
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/orders").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/orders")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, post, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    auth::{guest::GuestTokenSigner, AuthFailure, StaffIdentity},
+    db::staff::StaffRole,
+    order::{BadRequestBody, OrderData},
+};
+
+/// The input data to issue a guest token for a Table.
+struct Input {
+    table_number: u32,
+}
+
+impl Input {
+    fn new(path_params: PathParams) -> Self {
+        Self {
+            table_number: path_params.table_number,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, GuestTokenFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(GuestTokenFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    table_number: u32,
+}
+
+#[derive(Debug)]
+enum GuestTokenFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+}
+
+impl From<AuthFailure> for GuestTokenFailure {
+    fn from(e: AuthFailure) -> Self {
+        GuestTokenFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for GuestTokenFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to issue guest token")
+    }
+}
+
+impl ResponseError for GuestTokenFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            GuestTokenFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            GuestTokenFailure::Auth(e) => e.status_code(),
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            GuestTokenFailure::InvalidInput(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            GuestTokenFailure::Auth(e) => e.error_response(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    table_number: i32,
+    token: String,
+    expires_at: String,
+}
+
+/// Issues a guest token for the Table, the waiter prints it as a QR code for the guests.
+#[post("/guest-token")]
+async fn handler(
+    signer: web::Data<GuestTokenSigner>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, GuestTokenFailure> {
+    identity.require(StaffRole::Waiter)?;
+    let input = Input::new(path_params.into_inner()).validate()?;
+
+    let (token, expires_at) = signer.issue(input.table_number as i32, OffsetDateTime::now_utc());
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        table_number: input.table_number as i32,
+        token,
+        expires_at: OrderData::format_time(expires_at),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{self, staff::Staff};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(2, role))));
+        Arc::new(staff_repo)
+    }
+
+    fn guest_token_signer() -> Data<GuestTokenSigner> {
+        Data::new(GuestTokenSigner::new(
+            b"sukab".to_vec(),
+            time::Duration::minutes(30),
+        ))
+    }
+
+    #[actix_web::test]
+    /// given: zero table_id.
+    /// when: issuing a guest token.
+    /// then: response status code is 400.
+    async fn test_invalid_table_id() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .app_data(guest_token_signer())
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/0/guest-token")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a cook access token.
+    /// when: issuing a guest token.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(staff_repo(StaffRole::Cook)))
+                .app_data(guest_token_signer())
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/guest-token")
+            .insert_header(("Authorization", "Bearer cook-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: issuing a guest token.
+    /// then: response contains a token which verifies for the table.
+    async fn test_success() {
+        let signer = guest_token_signer();
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .app_data(signer.clone())
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/guest-token")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.table_number, 3);
+        let claims = signer
+            .verify(&response_body.token, OffsetDateTime::now_utc())
+            .unwrap();
+        assert_eq!(claims.table_number, 3);
+    }
+}
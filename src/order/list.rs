@@ -4,18 +4,19 @@ use actix_web::{
     body::BoxBody, get, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
 };
 use serde::{Deserialize, Serialize};
-use time::format_description::well_known::Rfc3339;
 
 use crate::{
+    auth::{AuthFailure, OrderingCredential},
     db::{self, order::Order, OperationError},
     order::InternalServerErrorBody,
 };
 
-use super::{BadRequestBody, MenuData, OrderData};
+use super::{BadRequestBody, OrderData};
 
 /// The input data to list Orders.
 struct Input {
     table_number: u32,
+    session_id: Option<u32>,
     page: i32,
     limit: i32,
 }
@@ -30,6 +31,7 @@ impl Input {
             .unwrap_or(5) as i32;
         Self {
             table_number,
+            session_id: query_params.session_id,
             page,
             limit,
         }
@@ -43,7 +45,7 @@ impl Input {
                 message: String::from("table_number must be in range of 1 to 100"),
             }));
         }
-        return Ok(self);
+        Ok(self)
     }
 }
 
@@ -56,14 +58,22 @@ struct PathParams {
 struct QueryParams {
     limit: Option<u32>,
     page: Option<u32>,
+    session_id: Option<u32>,
 }
 
 #[derive(Debug)]
 enum ListFailure {
     InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
     InternalServerError(OperationError),
 }
 
+impl From<AuthFailure> for ListFailure {
+    fn from(e: AuthFailure) -> Self {
+        ListFailure::Auth(e)
+    }
+}
+
 impl fmt::Display for ListFailure {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "failed to list orders")
@@ -74,6 +84,7 @@ impl ResponseError for ListFailure {
     fn status_code(&self) -> actix_web::http::StatusCode {
         match self {
             ListFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            ListFailure::Auth(e) => e.status_code(),
             ListFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -81,6 +92,7 @@ impl ResponseError for ListFailure {
     fn error_response(&self) -> HttpResponse<BoxBody> {
         match self {
             ListFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            ListFailure::Auth(e) => e.error_response(),
             ListFailure::InternalServerError(e) => {
                 log::error!("{:?}", e);
                 HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
@@ -100,34 +112,43 @@ struct SuccessResponseBody {
 
 impl SuccessResponseBody {
     fn new(orders: Vec<Order>) -> Self {
-        let order_list: Vec<OrderData> = orders
-            .iter()
-            .map(|order| OrderData {
-                order_id: order.order_id,
-                table_number: order.table_number,
-                menu: MenuData {
-                    id: order.menu_id as i64,
-                    name: order.name.clone().unwrap_or("".to_string()),
-                },
-                cook_time: order.cook_time,
-                created_at: order.created_at.format(&Rfc3339).unwrap_or("".to_string()),
-            })
-            .collect();
+        let order_list: Vec<OrderData> = orders.iter().map(OrderData::from_order).collect();
         Self { orders: order_list }
     }
 }
 
+/// Lists the Orders of a Table, guests only see their own Table and waiters the Tables of their sections.
 #[get("/order")]
 async fn handler(
     order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    section_repository: web::Data<dyn db::section::Repository>,
+    credential: OrderingCredential,
     path_params: web::Path<PathParams>,
     query_params: web::Query<QueryParams>,
 ) -> Result<HttpResponse, ListFailure> {
     let input = Input::new(path_params.into_inner(), query_params.into_inner()).validate()?;
+    credential
+        .serves_table(section_repository.get_ref(), input.table_number as i32)
+        .await?;
+
+    // defaults to the guests currently seated, so older sessions don't leak into the list:
+    let session_id = match input.session_id {
+        Some(session_id) => session_id as i64,
+        None => match session_repository
+            .get_active_session(input.table_number as i32)
+            .await
+        {
+            Ok(Some(session)) => session.session_id,
+            Ok(None) => return Ok(HttpResponse::Ok().json(SuccessResponseBody::new(vec![]))),
+            Err(e) => return Err(ListFailure::InternalServerError(e)),
+        },
+    };
 
     match order_repository
-        .list_by_table(
+        .list_by_session(
             input.table_number as i32,
+            session_id,
             input.page as i64,
             input.limit as i64,
         )
@@ -147,6 +168,30 @@ mod tests {
     use web::Data;
 
     use super::*;
+    use crate::db::{
+        session::Session,
+        staff::{Staff, StaffRole},
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(4, role))));
+        Arc::new(staff_repo)
+    }
+
+    /// every Table is in section 2, the Staff member works the given sections.
+    fn section_repo(assigned: Vec<i64>) -> Arc<dyn db::section::Repository> {
+        let mut section_repo = crate::db::section::MockRepository::new();
+        section_repo
+            .expect_get_table_section()
+            .returning(|_| Ok(Some(2)));
+        section_repo
+            .expect_list_assigned_sections()
+            .returning(move |_, _| Ok(assigned.clone()));
+        Arc::new(section_repo)
+    }
 
     #[actix_web::test]
     /// given: zero table_id.
@@ -158,15 +203,22 @@ mod tests {
         let order_repo = crate::db::order::MockRepository::new();
         let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
 
+        let session_repo = crate::db::session::MockRepository::new();
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
         let app = test::init_service(
             App::new()
                 .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(section_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
                 .service(web::scope("/table/{table_number}").service(handler)),
         )
         .await;
 
         let req = test::TestRequest::get()
             .uri(format!("/table/{}/order", table_number).as_str())
+            .insert_header(("Authorization", "Bearer manager-token"))
             .to_request();
 
         let resp = test::call_service(&app, req).await;
@@ -184,32 +236,59 @@ mod tests {
         let table_number = 3;
 
         let mut order_repo = crate::db::order::MockRepository::new();
-        order_repo
-            .expect_list_by_table()
-            .once()
-            .returning(move |table_number, _, _| {
+        order_repo.expect_list_by_session().once().returning(
+            move |table_number, session_id, _, _| {
                 let expect_order_data = Order {
                     order_id: expect_order_id,
                     table_number,
+                    session_id: Some(session_id),
                     menu_id: 2,
                     cook_time: 3,
+                    seat_number: None,
+                    quantity: 1,
+                    unit_price: 35000,
                     name: Some(expect_menu_name_cp.clone()),
+                    modifiers: vec![],
+                    note: None,
+                    course: None,
+                    status: crate::db::order::OrderStatus::Cooking,
+                    priority: crate::db::order::OrderPriority::Normal,
+                    eta: None,
+                    cook_id: None,
                     created_at: OffsetDateTime::now_utc(),
+                    started_at: Some(OffsetDateTime::now_utc()),
+                    ready_at: None,
+                    voided_at: None,
+                    void_reason: None,
+                    void_note: None,
+                    voided_by: None,
                 };
                 Ok(vec![expect_order_data])
-            });
+            },
+        );
 
         let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
 
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
         let app = test::init_service(
             App::new()
                 .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(section_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
                 .service(web::scope("/table/{table_number}").service(handler)),
         )
         .await;
 
         let req = test::TestRequest::get()
             .uri(format!("/table/{}/order", table_number).as_str())
+            .insert_header(("Authorization", "Bearer manager-token"))
             .to_request();
 
         let resp = test::call_service(&app, req).await;
@@ -217,6 +296,7 @@ mod tests {
         let response_body: SuccessResponseBody = test::read_body_json(resp).await;
         assert_eq!(response_body.orders[0].table_number, table_number);
         assert_eq!(response_body.orders[0].order_id, expect_order_id);
+        assert_eq!(response_body.orders[0].session_id, Some(7));
         assert_eq!(response_body.orders[0].menu.name, expect_menu_name);
         assert_ne!(response_body.orders[0].cook_time, 0);
     }
@@ -230,23 +310,170 @@ mod tests {
 
         let mut order_repo = crate::db::order::MockRepository::new();
         order_repo
-            .expect_list_by_table()
+            .expect_list_by_session()
             .once()
-            .returning(|_, _, _| Err(OperationError::OtherError));
+            .returning(|_, _, _, _| Err(OperationError::OtherError));
         let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
 
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
         let app = test::init_service(
             App::new()
                 .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(section_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
                 .service(web::scope("/table/{table_number}").service(handler)),
         )
         .await;
 
         let req = test::TestRequest::get()
             .uri(format!("/table/{}/order", table_number).as_str())
+            .insert_header(("Authorization", "Bearer manager-token"))
             .to_request();
 
         let resp = test::call_service(&app, req).await;
         assert!(resp.status().is_server_error());
     }
+
+    #[actix_web::test]
+    /// given: a table without an open session.
+    /// when: list Orders in a Table.
+    /// then: response status code is 200 with no Orders.
+    async fn test_no_active_session() {
+        let order_repo = crate::db::order::MockRepository::new();
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|_| Ok(None));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(section_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/order")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert!(response_body.orders.is_empty());
+    }
+
+    #[actix_web::test]
+    /// given: an explicit session_id query parameter.
+    /// when: list Orders in a Table.
+    /// then: Orders of that session are listed without looking up the active session.
+    async fn test_explicit_session() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_by_session()
+            .withf(|table_number, session_id, _, _| *table_number == 3 && *session_id == 42)
+            .once()
+            .returning(|_, _, _, _| Ok(vec![]));
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let session_repo = crate::db::session::MockRepository::new();
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(section_repo(vec![])))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/order?session_id=42")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+    }
+
+    #[actix_web::test]
+    /// given: a waiter who does not work the section of the Table.
+    /// when: list Orders in a Table.
+    /// then: response status code is 403 and nothing is listed.
+    async fn test_waiter_other_section() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_list_by_session().never();
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let session_repo = crate::db::session::MockRepository::new();
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(section_repo(vec![1, 3])))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/order")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a waiter who works the section of the Table.
+    /// when: list Orders in a Table.
+    /// then: response status code is 200.
+    async fn test_waiter_own_section() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|_, _, _, _| Ok(vec![]));
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+
+        let session_repo = crate::db::session::MockRepository::new();
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(section_repo(vec![2])))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/order?session_id=42")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+    }
 }
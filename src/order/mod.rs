@@ -2,24 +2,73 @@ use actix_web::web;
 use serde::{Deserialize, Serialize};
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
+use crate::{adjustment, bill, db::order::Order, discount, payment, receipt, session};
+
 pub mod create;
 pub mod delete;
 pub mod detail;
+pub mod fire;
+pub mod floor;
+pub mod guest_token;
 pub mod list;
+pub mod priority;
 
 #[derive(Serialize, Deserialize)]
 struct OrderData {
     order_id: i64,
     table_number: i32,
+    session_id: Option<i64>,
     cook_time: i32,
+    seat_number: Option<i32>,
+    quantity: i32,
+    unit_price: i64,
     menu: MenuData,
+    modifiers: Vec<String>,
+    note: Option<String>,
+    course: Option<i32>,
+    status: String,
+    priority: String,
+    /// how many minutes the kitchen is given for it when it is not a normal one.
+    eta: Option<i32>,
+    cook_id: Option<i64>,
     created_at: String,
+    ready_at: Option<String>,
+    voided_at: Option<String>,
+    void_reason: Option<String>,
 }
 
 impl OrderData {
     fn format_time(dt: OffsetDateTime) -> String {
         dt.format(&Rfc3339).unwrap_or("---".to_string())
     }
+
+    /// builds the response data of an Order which has been joined with its Menu.
+    fn from_order(order: &Order) -> Self {
+        Self {
+            order_id: order.order_id,
+            table_number: order.table_number,
+            session_id: order.session_id,
+            cook_time: order.cook_time,
+            seat_number: order.seat_number,
+            quantity: order.quantity,
+            unit_price: order.unit_price,
+            menu: MenuData {
+                id: order.menu_id as i64,
+                name: order.name.clone().unwrap_or("".to_string()),
+            },
+            modifiers: order.modifiers.clone(),
+            note: order.note.clone(),
+            course: order.course,
+            status: order.status.as_str().to_string(),
+            priority: order.priority.as_str().to_string(),
+            eta: order.eta,
+            cook_id: order.cook_id,
+            created_at: OrderData::format_time(order.created_at),
+            ready_at: order.ready_at.map(OrderData::format_time),
+            voided_at: order.voided_at.map(OrderData::format_time),
+            void_reason: order.void_reason.map(|r| r.as_str().to_string()),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -29,15 +78,15 @@ struct MenuData {
 }
 
 #[derive(Serialize, Debug)]
-struct BadRequestBody {
-    error: bool,
-    message: String,
+pub struct BadRequestBody {
+    pub(crate) error: bool,
+    pub(crate) message: String,
 }
 
 #[derive(Serialize, Debug)]
-struct InternalServerErrorBody {
-    error: bool,
-    message: String,
+pub struct InternalServerErrorBody {
+    pub(crate) error: bool,
+    pub(crate) message: String,
 }
 
 pub fn service() -> actix_web::Scope {
@@ -45,5 +94,26 @@ pub fn service() -> actix_web::Scope {
         .service(detail::handler)
         .service(create::handler)
         .service(delete::handler)
+        .service(fire::handler)
+        .service(priority::handler)
         .service(list::handler)
+        .service(guest_token::handler)
+        .service(session::open::handler)
+        .service(session::detail::handler)
+        .service(session::close::handler)
+        .service(bill::detail::handler)
+        .service(bill::split::handler)
+        .service(discount::apply::handler)
+        .service(discount::redeem::handler)
+        .service(payment::create::handler)
+        .service(payment::list::handler)
+        .service(receipt::detail::handler)
+        .service(receipt::print::handler)
+        .service(adjustment::void::handler)
+        .service(adjustment::refund::handler)
+        .service(adjustment::refund_list::handler)
+}
+
+pub fn floor_service() -> actix_web::Scope {
+    web::scope("/orders").service(floor::handler)
 }
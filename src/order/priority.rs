@@ -0,0 +1,316 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, put, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{AuthFailure, StaffIdentity},
+    db::{
+        self,
+        order::{OrderPriority, OrderStatus},
+        staff::StaffRole,
+        OperationError,
+    },
+    kitchen::priority::PriorityConfig,
+    order::{BadRequestBody, InternalServerErrorBody, OrderData},
+};
+
+#[derive(Serialize, Deserialize)]
+struct RequestBody {
+    /// one of `normal`, `rush` or `vip`.
+    priority: String,
+}
+
+/// The input data to change the priority of an Order.
+struct Input {
+    table_number: u32,
+    order_id: u32,
+    /// None when it is none of the known priorities.
+    priority: Option<OrderPriority>,
+}
+
+impl Input {
+    fn new(rb: RequestBody, path_params: PathParams) -> Self {
+        Self {
+            table_number: path_params.table_number,
+            order_id: path_params.order_id,
+            priority: OrderPriority::parse(&rb.priority),
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, PriorityFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(PriorityFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        if self.priority.is_none() {
+            return Err(PriorityFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("priority must be one of normal, rush or vip"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    table_number: u32,
+    order_id: u32,
+}
+
+#[derive(Debug)]
+enum PriorityFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    /// the Order is served or voided already.
+    Conflict(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for PriorityFailure {
+    fn from(e: AuthFailure) -> Self {
+        PriorityFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for PriorityFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to change order priority")
+    }
+}
+
+impl ResponseError for PriorityFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            PriorityFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            PriorityFailure::Auth(e) => e.status_code(),
+            PriorityFailure::Conflict(_) => StatusCode::CONFLICT,
+            PriorityFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            PriorityFailure::InvalidInput(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            PriorityFailure::Auth(e) => e.error_response(),
+            PriorityFailure::Conflict(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            PriorityFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    order: OrderData,
+}
+
+/// Changes the priority of an Order still to be served, e.g. to rush it for guests in a hurry, waiter only.
+/// The kitchen is given the time of the new priority, counted from when it started cooking the Order.
+#[put("/order/{order_id}/priority")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    priority_config: web::Data<PriorityConfig>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, PriorityFailure> {
+    identity.require(StaffRole::Waiter)?;
+    let input = Input::new(request_body.into_inner(), path_params.into_inner()).validate()?;
+    let priority = input.priority.unwrap_or(OrderPriority::Normal);
+
+    let order = match order_repository
+        .get_order_detail(input.table_number as i32, input.order_id as i64)
+        .await
+        .map_err(PriorityFailure::InternalServerError)?
+    {
+        Some(order) => order,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+    let conflict = || {
+        PriorityFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!("order {} is served or voided already", input.order_id),
+        })
+    };
+    if order.status == OrderStatus::Ready || order.voided_at.is_some() {
+        return Err(conflict());
+    }
+
+    // the Order may have been served meanwhile, then it is left as it is:
+    match order_repository
+        .set_priority(
+            input.table_number as i32,
+            input.order_id as i64,
+            priority,
+            priority_config.eta(priority, order.cook_time),
+        )
+        .await
+        .map_err(PriorityFailure::InternalServerError)?
+    {
+        Some(order) => Ok(HttpResponse::Ok().json(SuccessResponseBody {
+            order: OrderData::from_order(&order),
+        })),
+        None => Err(conflict()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use time::OffsetDateTime;
+    use web::Data;
+
+    use super::*;
+    use crate::db::{order::Order, staff::Staff};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(2, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        order_repo: crate::db::order::MockRepository,
+        role: StaffRole,
+        priority: &str,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(order_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::new(PriorityConfig::default()))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/table/3/order/12/priority")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .set_json(RequestBody {
+                priority: priority.to_string(),
+            })
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: an unknown priority.
+    /// when: changing the priority of an Order.
+    /// then: response status code is 400.
+    async fn test_invalid_priority() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo.expect_set_priority().never();
+        let resp = call(order_repo, StaffRole::Waiter, "urgent").await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: an Order which is ready already.
+    /// when: rushing it.
+    /// then: response status code is 409.
+    async fn test_ready() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_get_order_detail()
+            .once()
+            .returning(|table_number, order_id| {
+                Ok(Some(Order {
+                    order_id,
+                    status: OrderStatus::Ready,
+                    ready_at: Some(OffsetDateTime::now_utc()),
+                    ..Order::new(table_number, 5, 10)
+                }))
+            });
+        order_repo.expect_set_priority().never();
+        let resp = call(order_repo, StaffRole::Waiter, "rush").await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: an Order which is cooking and takes 20 minutes.
+    /// when: rushing it.
+    /// then: the kitchen is given 15 minutes for it.
+    async fn test_success() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_get_order_detail()
+            .withf(|table_number, order_id| *table_number == 3 && *order_id == 12)
+            .once()
+            .returning(|table_number, order_id| {
+                Ok(Some(Order {
+                    order_id,
+                    ..Order::new(table_number, 5, 20)
+                }))
+            });
+        order_repo
+            .expect_set_priority()
+            .withf(|table_number, order_id, priority, eta| {
+                *table_number == 3
+                    && *order_id == 12
+                    && *priority == OrderPriority::Rush
+                    && *eta == Some(15)
+            })
+            .once()
+            .returning(|table_number, order_id, priority, eta| {
+                Ok(Some(Order {
+                    order_id,
+                    priority,
+                    eta,
+                    ..Order::new(table_number, 5, 20)
+                }))
+            });
+        let resp = call(order_repo, StaffRole::Waiter, "rush").await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.order.priority, "rush");
+        assert_eq!(response_body.order.eta, Some(15));
+    }
+
+    #[actix_web::test]
+    /// given: an Order which does not exist.
+    /// when: rushing it.
+    /// then: response status code is 404.
+    async fn test_not_found() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_get_order_detail()
+            .once()
+            .returning(|_, _| Ok(None));
+        let resp = call(order_repo, StaffRole::Waiter, "vip").await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: rushing an Order.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_get_order_detail()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let resp = call(order_repo, StaffRole::Waiter, "rush").await;
+        assert!(resp.status().is_server_error());
+    }
+}
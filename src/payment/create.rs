@@ -0,0 +1,605 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    bill::load_bill,
+    db::{
+        self,
+        payment::{Payment, PaymentMethod},
+        staff::StaffRole,
+    },
+    order::BadRequestBody,
+    tax::TaxConfig,
+};
+
+use super::{
+    gateway::{CaptureRequest, Gateway, GatewayError, RefundRequest},
+    PathParams, PaymentData, PaymentFailure, PaymentSummary,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    method: String,
+    amount: i64,
+    #[serde(default)]
+    tip: i64,
+}
+
+/// The input data to pay (a part of) the bill of a Table.
+struct Input {
+    table_number: u32,
+    method: Option<PaymentMethod>,
+    amount: i64,
+    tip: i64,
+}
+
+impl Input {
+    fn new(rb: RequestBody, path_params: PathParams) -> Self {
+        Self {
+            table_number: path_params.table_number,
+            method: PaymentMethod::parse(&rb.method),
+            amount: rb.amount,
+            tip: rb.tip,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, PaymentFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(PaymentFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        if self.method.is_none() {
+            return Err(PaymentFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("method must be one of cash, card or qris"),
+            }));
+        }
+        if self.amount < 1 {
+            return Err(PaymentFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("amount must be positive"),
+            }));
+        }
+        if self.tip < 0 {
+            return Err(PaymentFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("tip must not be negative"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    payment: PaymentData,
+    summary: PaymentSummary,
+    session_closed: bool,
+}
+
+/// Captures card and QRIS payments through the gateway, cash is taken at the counter.
+async fn capture(
+    gateway: &dyn Gateway,
+    method: PaymentMethod,
+    session_id: i64,
+    amount: i64,
+) -> Result<Option<String>, PaymentFailure> {
+    if method == PaymentMethod::Cash {
+        return Ok(None);
+    }
+    let request = CaptureRequest {
+        method,
+        session_id,
+        amount,
+    };
+    match gateway.capture(request).await {
+        Ok(capture) => Ok(Some(capture.reference)),
+        Err(GatewayError::Declined(reason)) => Err(PaymentFailure::Declined(BadRequestBody {
+            error: true,
+            message: format!("the payment was declined: {}", reason),
+        })),
+        Err(GatewayError::Unavailable(reason)) => {
+            log::error!("payment gateway is unavailable: {}", reason);
+            Err(PaymentFailure::GatewayUnavailable(BadRequestBody {
+                error: true,
+                message: String::from("the payment gateway is unavailable, please try again"),
+            }))
+        }
+    }
+}
+
+/// Gives a capture which could not be recorded back to the guest, so they don't pay without it counting.
+async fn release_capture(
+    gateway: &dyn Gateway,
+    method: PaymentMethod,
+    reference: Option<String>,
+    amount: i64,
+) {
+    let capture_reference = match reference {
+        Some(reference) => reference,
+        None => return,
+    };
+    let request = RefundRequest {
+        method,
+        capture_reference: capture_reference.clone(),
+        amount,
+    };
+    if let Err(e) = gateway.refund(request).await {
+        log::error!(
+            "failed to give back capture {} of {}, it has to be refunded by hand: {:?}",
+            capture_reference,
+            amount,
+            e
+        );
+    }
+}
+
+/// Pays (a part of) the bill of a Table, taken by a waiter or a manager.
+#[post("/payment")]
+#[allow(clippy::too_many_arguments)]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    payment_repository: web::Data<dyn db::payment::Repository>,
+    gateway: web::Data<dyn Gateway>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, PaymentFailure> {
+    let identity = identity.require(StaffRole::Waiter)?;
+    let input = Input::new(request_body.into_inner(), path_params.into_inner()).validate()?;
+    let method = input.method.unwrap_or(PaymentMethod::Cash);
+    let table_number = input.table_number as i32;
+
+    let bill = match load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        table_number,
+        None,
+    )
+    .await
+    .map_err(PaymentFailure::InternalServerError)?
+    {
+        Some(bill) => bill,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    let payments = payment_repository
+        .list_by_session(bill.session_id)
+        .await
+        .map_err(PaymentFailure::InternalServerError)?;
+    let summary = PaymentSummary::new(bill.total, &payments);
+    if summary.balance <= 0 {
+        return Err(PaymentFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!("the bill of table {} is already paid", table_number),
+        }));
+    }
+    if input.amount > summary.balance {
+        return Err(PaymentFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: format!("amount exceeds the balance due of {}", summary.balance),
+        }));
+    }
+
+    // the tip is captured along with the bill but doesn't count against it:
+    let reference = capture(
+        gateway.get_ref(),
+        method,
+        bill.session_id,
+        input.amount + input.tip,
+    )
+    .await?;
+
+    let payment = Payment {
+        tip: input.tip,
+        staff_id: Some(identity.0.staff_id),
+        ..Payment::new(
+            bill.session_id,
+            table_number,
+            method,
+            input.amount,
+            reference.clone(),
+        )
+    };
+    let payment = match payment_repository.record_payment(payment, bill.total).await {
+        Ok(Some(payment)) => payment,
+        Ok(None) => {
+            // another payment got in first, the guest gets the capture back:
+            release_capture(
+                gateway.get_ref(),
+                method,
+                reference,
+                input.amount + input.tip,
+            )
+            .await;
+            return Err(PaymentFailure::Conflict(BadRequestBody {
+                error: true,
+                message: String::from("the bill has been paid in the meantime"),
+            }));
+        }
+        Err(e) => {
+            release_capture(
+                gateway.get_ref(),
+                method,
+                reference,
+                input.amount + input.tip,
+            )
+            .await;
+            return Err(PaymentFailure::InternalServerError(e));
+        }
+    };
+
+    let summary = PaymentSummary {
+        paid: summary.paid + payment.amount,
+        balance: summary.balance - payment.amount,
+        ..summary
+    };
+    // fully paid guests are checked out right away, by the session which was paid,
+    // the table may be taken by the next guests already:
+    let session_closed = summary.balance == 0
+        && session_repository
+            .close_session(bill.session_id, TaxConfig::from_env())
+            .await
+            .map_err(PaymentFailure::InternalServerError)?
+            .is_some();
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        payment: payment.into(),
+        summary,
+        session_closed,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::{
+        db::{order::Order, session::Session, staff::Staff, OperationError},
+        payment::gateway::{Capture, MockGateway},
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    fn discount_repo() -> Arc<dyn db::discount::Repository> {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(discount_repo)
+    }
+
+    struct Mocks {
+        order_repo: crate::db::order::MockRepository,
+        session_repo: crate::db::session::MockRepository,
+        payment_repo: crate::db::payment::MockRepository,
+        gateway: MockGateway,
+    }
+
+    /// a table with an open session and a single order of 100.000 before service charge and tax.
+    fn mocks() -> Mocks {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .returning(|table_number, session_id| {
+                Ok(vec![Order {
+                    order_id: 1,
+                    session_id: Some(session_id),
+                    unit_price: 100_000,
+                    ..Order::new(table_number, 5, 10)
+                }])
+            });
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        Mocks {
+            order_repo,
+            session_repo,
+            payment_repo: crate::db::payment::MockRepository::new(),
+            gateway: MockGateway::new(),
+        }
+    }
+
+    async fn call(mocks: Mocks, body: serde_json::Value) -> actix_web::dev::ServiceResponse {
+        call_as(mocks, Some(StaffRole::Waiter), body).await
+    }
+
+    /// pays as a Staff member of the given role, or anonymously.
+    async fn call_as(
+        mocks: Mocks,
+        role: Option<StaffRole>,
+        body: serde_json::Value,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(mocks.order_repo);
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(mocks.session_repo);
+        let arc_payment_repo: Arc<dyn db::payment::Repository> = Arc::new(mocks.payment_repo);
+        let arc_gateway: Arc<dyn Gateway> = Arc::new(mocks.gateway);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(arc_payment_repo))
+                .app_data(Data::from(arc_gateway))
+                .app_data(Data::from(staff_repo(role.unwrap_or(StaffRole::Waiter))))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let mut req = test::TestRequest::post()
+            .uri("/table/3/payment")
+            .set_json(body);
+        if role.is_some() {
+            req = req.insert_header(("Authorization", "Bearer staff-token"));
+        }
+        test::call_service(&app, req.to_request()).await
+    }
+
+    #[actix_web::test]
+    /// given: no staff access token, as guests ordering by QR code have.
+    /// when: paying the bill.
+    /// then: response status code is 401 and nothing is recorded.
+    async fn test_unauthorized() {
+        let mut mocks = mocks();
+        mocks.payment_repo.expect_record_payment().never();
+        mocks.session_repo.expect_close_session().never();
+
+        let resp = call_as(
+            mocks,
+            None,
+            serde_json::json!({"method": "cash", "amount": 115_500}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    /// given: a cook access token.
+    /// when: paying the bill.
+    /// then: response status code is 403 and nothing is recorded.
+    async fn test_forbidden_role() {
+        let mut mocks = mocks();
+        mocks.payment_repo.expect_record_payment().never();
+        mocks.session_repo.expect_close_session().never();
+
+        let resp = call_as(
+            mocks,
+            Some(StaffRole::Cook),
+            serde_json::json!({"method": "cash", "amount": 115_500}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: an unknown payment method.
+    /// when: paying the bill.
+    /// then: response status code is 400.
+    async fn test_invalid_method() {
+        let resp = call(
+            mocks(),
+            serde_json::json!({"method": "cheque", "amount": 1000}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: an amount larger than the balance due.
+    /// when: paying the bill.
+    /// then: response status code is 400 and nothing is captured.
+    async fn test_overpay() {
+        let mut mocks = mocks();
+        mocks
+            .payment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|_| Ok(vec![]));
+        mocks.gateway.expect_capture().never();
+
+        let resp = call(
+            mocks,
+            serde_json::json!({"method": "card", "amount": 200_000}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a partially paid bill.
+    /// when: paying the rest in cash.
+    /// then: response status code is 200 and the session is closed.
+    async fn test_cash_closes_session() {
+        let mut mocks = mocks();
+        mocks
+            .payment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|session_id| {
+                Ok(vec![Payment::new(
+                    session_id,
+                    3,
+                    PaymentMethod::Cash,
+                    15_500,
+                    None,
+                )])
+            });
+        mocks
+            .payment_repo
+            .expect_record_payment()
+            .withf(|payment, _| payment.staff_id == Some(1))
+            .once()
+            .returning(|payment, _| {
+                Ok(Some(Payment {
+                    payment_id: 2,
+                    ..payment
+                }))
+            });
+        mocks.gateway.expect_capture().never();
+        mocks
+            .session_repo
+            .expect_close_session()
+            .withf(|session_id, _| *session_id == 7)
+            .once()
+            .returning(|session_id, _| Ok(Some(Session::new(session_id, 3))));
+
+        // 100.000 + 5% service + 10% tax = 115.500:
+        let resp = call(
+            mocks,
+            serde_json::json!({"method": "cash", "amount": 100_000}),
+        )
+        .await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.payment.payment_id, 2);
+        assert_eq!(response_body.summary.balance, 0);
+        assert!(response_body.session_closed);
+    }
+
+    #[actix_web::test]
+    /// given: a partial card payment with a tip.
+    /// when: paying the bill.
+    /// then: the tip is captured on top, the capture reference is recorded and the session stays open.
+    async fn test_partial_card() {
+        let mut mocks = mocks();
+        mocks
+            .payment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|_| Ok(vec![]));
+        mocks
+            .gateway
+            .expect_capture()
+            .withf(|request| request.amount == 55_000)
+            .once()
+            .returning(|_| {
+                Ok(Capture {
+                    reference: "ref-1".to_string(),
+                })
+            });
+        mocks
+            .payment_repo
+            .expect_record_payment()
+            .withf(|payment, _| payment.amount == 50_000 && payment.tip == 5_000)
+            .once()
+            .returning(|payment, _| Ok(Some(payment)));
+        mocks.session_repo.expect_close_session().never();
+
+        let resp = call(
+            mocks,
+            serde_json::json!({"method": "qris", "amount": 50_000, "tip": 5_000}),
+        )
+        .await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.payment.reference, Some("ref-1".to_string()));
+        assert_eq!(response_body.payment.tip, 5_000);
+        assert_eq!(response_body.summary.balance, 65_500);
+        assert!(!response_body.session_closed);
+    }
+
+    #[actix_web::test]
+    /// given: another payment settles the bill while the card is being captured.
+    /// when: paying the bill.
+    /// then: response status code is 409 and the capture is given back.
+    async fn test_paid_meanwhile() {
+        let mut mocks = mocks();
+        mocks
+            .payment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|_| Ok(vec![]));
+        mocks.gateway.expect_capture().once().returning(|_| {
+            Ok(Capture {
+                reference: "ref-1".to_string(),
+            })
+        });
+        mocks
+            .payment_repo
+            .expect_record_payment()
+            .once()
+            .returning(|_, _| Ok(None));
+        mocks
+            .gateway
+            .expect_refund()
+            .withf(|request| request.capture_reference == "ref-1" && request.amount == 55_000)
+            .once()
+            .returning(|_| {
+                Ok(Capture {
+                    reference: "refund-1".to_string(),
+                })
+            });
+
+        let resp = call(
+            mocks,
+            serde_json::json!({"method": "card", "amount": 50_000, "tip": 5_000}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: the gateway declines the card.
+    /// when: paying the bill.
+    /// then: response status code is 402 and nothing is recorded.
+    async fn test_declined() {
+        let mut mocks = mocks();
+        mocks
+            .payment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|_| Ok(vec![]));
+        mocks
+            .gateway
+            .expect_capture()
+            .once()
+            .returning(|_| Err(GatewayError::Declined("insufficient funds".to_string())));
+        mocks.payment_repo.expect_record_payment().never();
+
+        let resp = call(
+            mocks,
+            serde_json::json!({"method": "card", "amount": 50_000}),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: paying the bill.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut mocks = mocks();
+        mocks
+            .payment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+
+        let resp = call(
+            mocks,
+            serde_json::json!({"method": "cash", "amount": 50_000}),
+        )
+        .await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use mockall::automock;
+use time::OffsetDateTime;
+
+use crate::db::payment::PaymentMethod;
+
+#[automock]
+#[async_trait]
+/// Payment gateway abstraction, card and QRIS payments are captured through it.
+/// Use this trait as dependency to make the usecase function be easy testable via mocks.
+pub trait Gateway {
+    /// Capture the amount from the guest, returns the reference of the capture.
+    async fn capture(&self, request: CaptureRequest) -> Result<Capture, GatewayError>;
+    /// Give back (a part of) a capture to the guest, returns the reference of the refund.
+    async fn refund(&self, request: RefundRequest) -> Result<Capture, GatewayError>;
+}
+
+/// What the gateway is asked to capture.
+#[derive(Debug, Clone)]
+pub struct CaptureRequest {
+    pub method: PaymentMethod,
+    pub session_id: i64,
+    /// in minor units of the currency.
+    pub amount: i64,
+}
+
+/// What the gateway is asked to give back, against the reference of the original capture.
+#[derive(Debug, Clone)]
+pub struct RefundRequest {
+    pub method: PaymentMethod,
+    pub capture_reference: String,
+    /// in minor units of the currency.
+    pub amount: i64,
+}
+
+/// A successful capture.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    pub reference: String,
+}
+
+#[derive(Debug)]
+pub enum GatewayError {
+    /// the card or the QR payment was refused, the guest may try another way to pay.
+    Declined(String),
+    /// the gateway could not be reached, nothing has been captured.
+    Unavailable(String),
+}
+
+// Local implementation of the payment gateway which approves every capture and refund,
+// used for development until a real acquirer is integrated.
+pub struct FakeGateway;
+
+#[async_trait]
+impl Gateway for FakeGateway {
+    async fn capture(&self, request: CaptureRequest) -> Result<Capture, GatewayError> {
+        log::info!(
+            "fake gateway captures {} via {} for session {}",
+            request.amount,
+            request.method.as_str(),
+            request.session_id
+        );
+        Ok(Capture {
+            reference: format!(
+                "fake-{}-{}-{}",
+                request.method.as_str(),
+                request.session_id,
+                OffsetDateTime::now_utc().unix_timestamp_nanos()
+            ),
+        })
+    }
+
+    async fn refund(&self, request: RefundRequest) -> Result<Capture, GatewayError> {
+        log::info!(
+            "fake gateway refunds {} via {} of capture {}",
+            request.amount,
+            request.method.as_str(),
+            request.capture_reference
+        );
+        Ok(Capture {
+            reference: format!(
+                "fake-refund-{}-{}",
+                request.method.as_str(),
+                OffsetDateTime::now_utc().unix_timestamp_nanos()
+            ),
+        })
+    }
+}
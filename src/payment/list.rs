@@ -0,0 +1,235 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{bill::load_bill, db, order::BadRequestBody};
+
+use super::{PathParams, PaymentData, PaymentFailure, PaymentSummary};
+
+/// The input data to list the Payments of a Table.
+struct Input {
+    table_number: u32,
+    session_id: Option<u32>,
+}
+
+impl Input {
+    fn new(path_params: PathParams, query_params: QueryParams) -> Self {
+        Self {
+            table_number: path_params.table_number,
+            session_id: query_params.session_id,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, PaymentFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(PaymentFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    session_id: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    payments: Vec<PaymentData>,
+    summary: PaymentSummary,
+}
+
+#[get("/payment")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    payment_repository: web::Data<dyn db::payment::Repository>,
+    path_params: web::Path<PathParams>,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, PaymentFailure> {
+    let input = Input::new(path_params.into_inner(), query_params.into_inner()).validate()?;
+
+    let bill = match load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        input.table_number as i32,
+        input.session_id.map(|v| v as i64),
+    )
+    .await
+    .map_err(PaymentFailure::InternalServerError)?
+    {
+        Some(bill) => bill,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    let payments = payment_repository
+        .list_by_session(bill.session_id)
+        .await
+        .map_err(PaymentFailure::InternalServerError)?;
+    let summary = PaymentSummary::new(bill.total, &payments);
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        payments: payments.into_iter().map(PaymentData::from).collect(),
+        summary,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        order::Order,
+        payment::{Payment, PaymentMethod},
+        session::Session,
+        OperationError,
+    };
+
+    fn discount_repo() -> Arc<dyn db::discount::Repository> {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(discount_repo)
+    }
+
+    fn order_repo() -> Arc<dyn db::order::Repository> {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .returning(|table_number, session_id| {
+                Ok(vec![Order {
+                    session_id: Some(session_id),
+                    unit_price: 100_000,
+                    ..Order::new(table_number, 5, 10)
+                }])
+            });
+        Arc::new(order_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a table without an open session.
+    /// when: listing the payments of a table.
+    /// then: response status code is 404.
+    async fn test_no_active_session() {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|_| Ok(None));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+        let payment_repo = crate::db::payment::MockRepository::new();
+        let arc_payment_repo: Arc<dyn db::payment::Repository> = Arc::new(payment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(arc_payment_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/payment")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: a past session which has been paid in two parts.
+    /// when: listing the payments of a table.
+    /// then: response contains both payments and no balance.
+    async fn test_success() {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_session()
+            .returning(|table_number, session_id| Ok(Some(Session::new(session_id, table_number))));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+        let mut payment_repo = crate::db::payment::MockRepository::new();
+        payment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|session_id| {
+                Ok(vec![
+                    Payment::new(session_id, 3, PaymentMethod::Cash, 60_000, None),
+                    Payment::new(
+                        session_id,
+                        3,
+                        PaymentMethod::Card,
+                        55_500,
+                        Some("ref-1".to_string()),
+                    ),
+                ])
+            });
+        let arc_payment_repo: Arc<dyn db::payment::Repository> = Arc::new(payment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(arc_payment_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/payment?session_id=5")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.payments.len(), 2);
+        assert_eq!(response_body.payments[1].method, "card");
+        assert_eq!(response_body.summary.balance, 0);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: listing the payments of a table.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_session()
+            .returning(|table_number, session_id| Ok(Some(Session::new(session_id, table_number))));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+        let mut payment_repo = crate::db::payment::MockRepository::new();
+        payment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_payment_repo: Arc<dyn db::payment::Repository> = Arc::new(payment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(arc_payment_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/payment?session_id=5")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
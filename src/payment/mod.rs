@@ -0,0 +1,149 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+
+use crate::{
+    auth::AuthFailure,
+    db::{payment::Payment, OperationError},
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+pub mod create;
+pub mod gateway;
+pub mod list;
+
+#[derive(Serialize, Deserialize)]
+struct PaymentData {
+    payment_id: i64,
+    session_id: i64,
+    table_number: i32,
+    method: String,
+    amount: i64,
+    tip: i64,
+    reference: Option<String>,
+    created_at: String,
+}
+
+impl From<Payment> for PaymentData {
+    fn from(payment: Payment) -> Self {
+        Self {
+            payment_id: payment.payment_id,
+            session_id: payment.session_id,
+            table_number: payment.table_number,
+            method: payment.method.as_str().to_string(),
+            amount: payment.amount,
+            tip: payment.tip,
+            reference: payment.reference,
+            created_at: payment
+                .created_at
+                .format(&Rfc3339)
+                .unwrap_or("---".to_string()),
+        }
+    }
+}
+
+/// How much of the bill has been paid so far.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct PaymentSummary {
+    total: i64,
+    paid: i64,
+    balance: i64,
+}
+
+impl PaymentSummary {
+    fn new(total: i64, payments: &[Payment]) -> Self {
+        let paid = payments.iter().map(|p| p.amount).sum();
+        Self {
+            total,
+            paid,
+            balance: total - paid,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    table_number: u32,
+}
+
+#[derive(Debug)]
+enum PaymentFailure {
+    InvalidInput(BadRequestBody),
+    Conflict(BadRequestBody),
+    Declined(BadRequestBody),
+    GatewayUnavailable(BadRequestBody),
+    Auth(AuthFailure),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for PaymentFailure {
+    fn from(e: AuthFailure) -> Self {
+        PaymentFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for PaymentFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to process payment")
+    }
+}
+
+impl ResponseError for PaymentFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            PaymentFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            PaymentFailure::Conflict(_) => StatusCode::CONFLICT,
+            PaymentFailure::Declined(_) => StatusCode::PAYMENT_REQUIRED,
+            PaymentFailure::GatewayUnavailable(_) => StatusCode::BAD_GATEWAY,
+            PaymentFailure::Auth(e) => e.status_code(),
+            PaymentFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            PaymentFailure::InvalidInput(r)
+            | PaymentFailure::Conflict(r)
+            | PaymentFailure::Declined(r)
+            | PaymentFailure::GatewayUnavailable(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            PaymentFailure::Auth(e) => e.error_response(),
+            PaymentFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::payment::PaymentMethod;
+
+    #[test]
+    fn test_summary() {
+        let payments = vec![
+            Payment::new(7, 3, PaymentMethod::Cash, 50_000, None),
+            Payment::new(7, 3, PaymentMethod::Card, 20_000, Some("ref".to_string())),
+        ];
+        assert_eq!(
+            PaymentSummary::new(101_640, &payments),
+            PaymentSummary {
+                total: 101_640,
+                paid: 70_000,
+                balance: 31_640,
+            }
+        );
+        assert_eq!(PaymentSummary::new(0, &[]).balance, 0);
+    }
+}
@@ -0,0 +1,322 @@
+use std::{env, fmt};
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+
+use crate::{
+    auth::AuthFailure,
+    db::{
+        menu::Menu,
+        price_rule::{AdjustmentKind, PriceRule},
+        OperationError,
+    },
+    order::{BadRequestBody, InternalServerErrorBody},
+    tax::apply_rate,
+};
+
+pub mod preview;
+pub mod rule_create;
+pub mod rule_delete;
+pub mod rule_list;
+
+/// Reads the UTC offset of the outlet in hours from `OUTLET_UTC_OFFSET`, or defer to WIB (UTC+7).
+pub fn outlet_offset() -> UtcOffset {
+    let hours: f64 = match env::var("OUTLET_UTC_OFFSET").ok() {
+        Some(v) => v.parse().unwrap_or(7.0),
+        None => 7.0,
+    };
+    UtcOffset::from_whole_seconds((hours * 3600.0).round() as i32).unwrap_or(UtcOffset::UTC)
+}
+
+/// The wall clock time of the outlet at the given instant, price rule windows are in this time.
+pub fn local_time(at: OffsetDateTime) -> PrimitiveDateTime {
+    let local = at.to_offset(outlet_offset());
+    PrimitiveDateTime::new(local.date(), local.time())
+}
+
+/// The price of a Menu after the price rules in effect.
+#[derive(Debug, PartialEq)]
+pub struct Quote {
+    pub price: i64,
+    /// the rules which adjusted the price, in the order they were applied.
+    pub rule_ids: Vec<i64>,
+}
+
+/// Checks whether the rule covers the Menu at the local time.
+/// Zones are not checked here, the repository only lists the rules of the Table's section.
+fn rule_applies(rule: &PriceRule, menu: &Menu, local: PrimitiveDateTime) -> bool {
+    if rule.menu_id.is_some_and(|id| id != menu.id) {
+        return false;
+    }
+    if rule.category.is_some() && rule.category != menu.category {
+        return false;
+    }
+
+    let minute = local.hour() as i32 * 60 + local.minute() as i32;
+    let weekday = local.weekday();
+    // a window wrapping past midnight belongs to the day it opened on:
+    let opened_on = if rule.start_minute <= rule.end_minute {
+        (rule.start_minute..rule.end_minute)
+            .contains(&minute)
+            .then_some(weekday)
+    } else if minute >= rule.start_minute {
+        Some(weekday)
+    } else if minute < rule.end_minute {
+        Some(weekday.previous())
+    } else {
+        None
+    };
+
+    match opened_on {
+        Some(day) => rule.days.is_empty() || rule.days.contains(&(day.number_from_monday() as i32)),
+        None => false,
+    }
+}
+
+/// Applies the rules in effect on the Menu price one after another, in the order they were created.
+/// Percentages are taken from the running price, and the price never drops below zero.
+pub fn effective_price(menu: &Menu, rules: &[PriceRule], local: PrimitiveDateTime) -> Quote {
+    let mut price = menu.price;
+    let mut rule_ids = vec![];
+    for rule in rules.iter().filter(|r| rule_applies(r, menu, local)) {
+        price += match rule.kind {
+            AdjustmentKind::Percent => apply_rate(price, rule.value),
+            AdjustmentKind::Fixed => rule.value,
+        };
+        rule_ids.push(rule.rule_id);
+    }
+    Quote {
+        price: price.max(0),
+        rule_ids,
+    }
+}
+
+/// Formats minutes after midnight as `HH:MM`.
+fn format_minute(minute: i32) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+/// Parses `HH:MM` into minutes after midnight, `24:00` is the end of the day.
+fn parse_minute(v: &str) -> Option<i32> {
+    let (hour, minute) = v.split_once(':')?;
+    let (hour, minute): (i32, i32) = (hour.parse().ok()?, minute.parse().ok()?);
+    match (hour, minute) {
+        (24, 0) => Some(1440),
+        (0..=23, 0..=59) => Some(hour * 60 + minute),
+        _ => None,
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PriceRuleData {
+    rule_id: i64,
+    name: String,
+    menu_id: Option<i64>,
+    category: Option<String>,
+    section_id: Option<i64>,
+    days: Vec<i32>,
+    start_time: String,
+    end_time: String,
+    kind: String,
+    value: i64,
+}
+
+impl From<PriceRule> for PriceRuleData {
+    fn from(rule: PriceRule) -> Self {
+        Self {
+            rule_id: rule.rule_id,
+            name: rule.name,
+            menu_id: rule.menu_id,
+            category: rule.category,
+            section_id: rule.section_id,
+            days: rule.days,
+            start_time: format_minute(rule.start_minute),
+            end_time: format_minute(rule.end_minute),
+            kind: rule.kind.as_str().to_string(),
+            value: rule.value,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum PricingFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for PricingFailure {
+    fn from(e: AuthFailure) -> Self {
+        PricingFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for PricingFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to process pricing")
+    }
+}
+
+impl ResponseError for PricingFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            PricingFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            PricingFailure::Auth(e) => e.status_code(),
+            PricingFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            PricingFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            PricingFailure::Auth(e) => e.error_response(),
+            PricingFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+pub fn service() -> actix_web::Scope {
+    web::scope("/pricing")
+        .service(preview::handler)
+        .service(rule_list::handler)
+        .service(rule_create::handler)
+        .service(rule_delete::handler)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Month, Time};
+
+    use super::*;
+
+    /// local time on a day in January 2026, the 5th is a Monday.
+    fn local(day: u8, hour: u8, minute: u8) -> PrimitiveDateTime {
+        PrimitiveDateTime::new(
+            Date::from_calendar_date(2026, Month::January, day).unwrap(),
+            Time::from_hms(hour, minute, 0).unwrap(),
+        )
+    }
+
+    fn menu(id: i64, category: &str, price: i64) -> Menu {
+        Menu {
+            price,
+            category: Some(category.to_string()),
+            ..Menu::new(id, "menu".to_string())
+        }
+    }
+
+    fn happy_hour() -> PriceRule {
+        // 20% off rice on weekdays from 15:00 to 18:00:
+        PriceRule {
+            rule_id: 1,
+            category: Some("rice".to_string()),
+            days: vec![1, 2, 3, 4, 5],
+            start_minute: 900,
+            end_minute: 1080,
+            ..PriceRule::new("happy hour".to_string(), AdjustmentKind::Percent, -2000)
+        }
+    }
+
+    #[test]
+    fn test_parse_minute() {
+        assert_eq!(parse_minute("00:00"), Some(0));
+        assert_eq!(parse_minute("17:30"), Some(1050));
+        assert_eq!(parse_minute("24:00"), Some(1440));
+        assert_eq!(parse_minute("24:01"), None);
+        assert_eq!(parse_minute("7"), None);
+        assert_eq!(format_minute(1050), "17:30");
+    }
+
+    #[test]
+    fn test_happy_hour_window() {
+        let rules = [happy_hour()];
+        let quote = effective_price(&menu(9, "rice", 32000), &rules, local(7, 15, 0));
+        assert_eq!(
+            quote,
+            Quote {
+                price: 25600,
+                rule_ids: vec![1]
+            }
+        );
+        let quote = effective_price(&menu(9, "rice", 32000), &rules, local(7, 18, 0));
+        assert_eq!(quote.price, 32000);
+        // other categories and weekends pay the full price:
+        let quote = effective_price(&menu(5, "side", 35000), &rules, local(7, 16, 0));
+        assert_eq!(quote.price, 35000);
+        let quote = effective_price(&menu(9, "rice", 32000), &rules, local(10, 16, 0));
+        assert_eq!(quote.price, 32000);
+    }
+
+    #[test]
+    fn test_window_past_midnight() {
+        // late night on Fridays from 22:00 until 02:00:
+        let rules = [PriceRule {
+            rule_id: 2,
+            days: vec![5],
+            start_minute: 1320,
+            end_minute: 120,
+            ..PriceRule::new("late night".to_string(), AdjustmentKind::Fixed, -5000)
+        }];
+        let noodle = menu(2, "noodle", 45000);
+        assert_eq!(
+            effective_price(&noodle, &rules, local(9, 23, 0)).price,
+            40000
+        );
+        // past midnight it is Saturday, but the window opened on Friday:
+        assert_eq!(
+            effective_price(&noodle, &rules, local(10, 1, 30)).price,
+            40000
+        );
+        assert_eq!(
+            effective_price(&noodle, &rules, local(10, 23, 0)).price,
+            45000
+        );
+        assert_eq!(
+            effective_price(&noodle, &rules, local(9, 1, 30)).price,
+            45000
+        );
+    }
+
+    #[test]
+    fn test_rules_stack_in_order() {
+        let rules = [
+            happy_hour(),
+            PriceRule {
+                rule_id: 3,
+                section_id: Some(2),
+                ..PriceRule::new("terrace".to_string(), AdjustmentKind::Percent, 1000)
+            },
+            PriceRule {
+                rule_id: 4,
+                menu_id: Some(6),
+                ..PriceRule::new("free edamame".to_string(), AdjustmentKind::Fixed, -50000)
+            },
+        ];
+        let quote = effective_price(&menu(9, "rice", 32000), &rules, local(7, 16, 0));
+        assert_eq!(
+            quote,
+            Quote {
+                price: 28160,
+                rule_ids: vec![1, 3]
+            }
+        );
+        let quote = effective_price(&menu(6, "side", 18000), &rules, local(7, 16, 0));
+        assert_eq!(
+            quote,
+            Quote {
+                price: 0,
+                rule_ids: vec![3, 4]
+            }
+        );
+    }
+}
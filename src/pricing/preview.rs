@@ -0,0 +1,245 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{db, order::BadRequestBody};
+
+use super::{effective_price, local_time, PricingFailure};
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    at: Option<String>,
+    table_number: Option<u32>,
+}
+
+/// The input data to preview Menu prices.
+struct Input {
+    at: OffsetDateTime,
+    table_number: Option<u32>,
+}
+
+impl Input {
+    fn new(query_params: QueryParams) -> Result<Self, PricingFailure> {
+        let at = match query_params.at {
+            Some(v) => OffsetDateTime::parse(&v, &Rfc3339).map_err(|_| {
+                PricingFailure::InvalidInput(BadRequestBody {
+                    error: true,
+                    message: String::from("at must be an RFC3339 timestamp"),
+                })
+            })?,
+            None => OffsetDateTime::now_utc(),
+        };
+        Ok(Self {
+            at,
+            table_number: query_params.table_number,
+        })
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, PricingFailure> {
+        if self.table_number.is_some_and(|v| !(1..=100).contains(&v)) {
+            return Err(PricingFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct MenuPriceData {
+    menu_id: i64,
+    name: String,
+    category: Option<String>,
+    base_price: i64,
+    price: i64,
+    rule_ids: Vec<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    at: String,
+    table_number: Option<u32>,
+    menus: Vec<MenuPriceData>,
+}
+
+/// Previews the effective price of every Menu at a given time (now by default),
+/// pass `table_number` to include the surcharges of its zone.
+#[get("/preview")]
+async fn handler(
+    menu_repository: web::Data<dyn db::menu::Repository>,
+    price_rule_repository: web::Data<dyn db::price_rule::Repository>,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, PricingFailure> {
+    let input = Input::new(query_params.into_inner())?.validate()?;
+
+    let menus = menu_repository
+        .list_all()
+        .await
+        .map_err(PricingFailure::InternalServerError)?;
+    let rules = price_rule_repository
+        .list_applicable(input.table_number.map(|v| v as i32))
+        .await
+        .map_err(PricingFailure::InternalServerError)?;
+
+    let local = local_time(input.at);
+    let menus = menus
+        .into_iter()
+        .map(|menu| {
+            let quote = effective_price(&menu, &rules, local);
+            MenuPriceData {
+                menu_id: menu.id,
+                name: menu.name,
+                category: menu.category,
+                base_price: menu.price,
+                price: quote.price,
+                rule_ids: quote.rule_ids,
+            }
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        at: input.at.format(&Rfc3339).unwrap_or("---".to_string()),
+        table_number: input.table_number,
+        menus,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        menu::Menu,
+        price_rule::{AdjustmentKind, PriceRule},
+        OperationError,
+    };
+
+    fn menu_repo() -> Arc<dyn db::menu::Repository> {
+        let mut menu_repo = crate::db::menu::MockRepository::new();
+        menu_repo.expect_list_all().returning(|| {
+            Ok(vec![
+                Menu {
+                    price: 32000,
+                    category: Some("rice".to_string()),
+                    ..Menu::new(9, "Nasi Goreng".to_string())
+                },
+                Menu {
+                    price: 18000,
+                    category: Some("side".to_string()),
+                    ..Menu::new(6, "枝豆".to_string())
+                },
+            ])
+        });
+        Arc::new(menu_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a malformed time.
+    /// when: previewing prices.
+    /// then: response status code is 400.
+    async fn test_invalid_time() {
+        let price_rule_repo = crate::db::price_rule::MockRepository::new();
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(menu_repo()))
+                .app_data(Data::from(arc_price_rule_repo))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/pricing/preview?at=tomorrow")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a happy hour on rice and a zone surcharge on the table.
+    /// when: previewing prices within the happy hour.
+    /// then: each menu shows its effective price and the rules applied.
+    async fn test_success() {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo
+            .expect_list_applicable()
+            .withf(|table_number| *table_number == Some(12))
+            .once()
+            .returning(|_| {
+                Ok(vec![
+                    PriceRule {
+                        rule_id: 1,
+                        category: Some("rice".to_string()),
+                        start_minute: 900,
+                        end_minute: 1080,
+                        ..PriceRule::new("happy hour".to_string(), AdjustmentKind::Percent, -2000)
+                    },
+                    PriceRule {
+                        rule_id: 2,
+                        section_id: Some(2),
+                        ..PriceRule::new("terrace".to_string(), AdjustmentKind::Fixed, 2000)
+                    },
+                ])
+            });
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(menu_repo()))
+                .app_data(Data::from(arc_price_rule_repo))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        // 16:00 in UTC+7:
+        let req = test::TestRequest::get()
+            .uri("/pricing/preview?at=2026-01-07T16:00:00%2B07:00&table_number=12")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.menus.len(), 2);
+        assert_eq!(response_body.menus[0].base_price, 32000);
+        assert_eq!(response_body.menus[0].price, 27600);
+        assert_eq!(response_body.menus[0].rule_ids, vec![1, 2]);
+        assert_eq!(response_body.menus[1].price, 20000);
+        assert_eq!(response_body.menus[1].rule_ids, vec![2]);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: previewing prices.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo
+            .expect_list_applicable()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(menu_repo()))
+                .app_data(Data::from(arc_price_rule_repo))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/pricing/preview")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
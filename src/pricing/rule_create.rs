@@ -0,0 +1,283 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{
+        self,
+        price_rule::{AdjustmentKind, PriceRule},
+        staff::StaffRole,
+    },
+    order::BadRequestBody,
+};
+
+use super::{parse_minute, PriceRuleData, PricingFailure};
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    name: String,
+    kind: String,
+    value: i64,
+    menu_id: Option<u32>,
+    category: Option<String>,
+    section_id: Option<u32>,
+    days: Option<Vec<i32>>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+}
+
+fn invalid(message: &str) -> PricingFailure {
+    PricingFailure::InvalidInput(BadRequestBody {
+        error: true,
+        message: String::from(message),
+    })
+}
+
+/// The input data to create a price rule.
+struct Input {
+    rule: PriceRule,
+}
+
+impl Input {
+    fn new(rb: RequestBody) -> Result<Self, PricingFailure> {
+        let kind = AdjustmentKind::parse(&rb.kind)
+            .ok_or_else(|| invalid("kind must be one of percent or fixed"))?;
+        let start_minute = match rb.start_time {
+            Some(v) => parse_minute(&v).ok_or_else(|| invalid("start_time must be HH:MM"))?,
+            None => 0,
+        };
+        let end_minute = match rb.end_time {
+            Some(v) => parse_minute(&v).ok_or_else(|| invalid("end_time must be HH:MM"))?,
+            None => 1440,
+        };
+        let mut days = rb.days.unwrap_or_default();
+        days.sort_unstable();
+        days.dedup();
+
+        Ok(Self {
+            rule: PriceRule {
+                menu_id: rb.menu_id.map(|v| v as i64),
+                category: rb.category,
+                section_id: rb.section_id.map(|v| v as i64),
+                days,
+                start_minute,
+                end_minute,
+                ..PriceRule::new(rb.name.trim().to_string(), kind, rb.value)
+            },
+        })
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, PricingFailure> {
+        if self.rule.name.is_empty() || self.rule.name.len() > 100 {
+            return Err(invalid("name must be 1 to 100 characters"));
+        }
+        if self.rule.menu_id.is_some_and(|v| !(1..=10).contains(&v)) {
+            return Err(invalid("menu_id must be in range of 1 to 10"));
+        }
+        if self
+            .rule
+            .category
+            .as_ref()
+            .is_some_and(|v| v.is_empty() || v.len() > 50)
+        {
+            return Err(invalid("category must be 1 to 50 characters"));
+        }
+        if self.rule.days.iter().any(|d| !(1..=7).contains(d)) {
+            return Err(invalid("days must be in range of 1 (Monday) to 7 (Sunday)"));
+        }
+        if self.rule.start_minute == self.rule.end_minute {
+            return Err(invalid("start_time and end_time must differ"));
+        }
+        let valid_value = match self.rule.kind {
+            AdjustmentKind::Percent => (-10_000..=10_000).contains(&self.rule.value),
+            AdjustmentKind::Fixed => true,
+        };
+        if self.rule.value == 0 || !valid_value {
+            return Err(invalid(
+                "value must not be zero, and in range of -10000 to 10000 basis points for percent",
+            ));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    data: PriceRuleData,
+}
+
+/// Creates a price rule, manager only.
+/// Negative values lower the price (happy hour), positive values raise it (zone surcharge).
+#[post("/rule")]
+async fn handler(
+    price_rule_repository: web::Data<dyn db::price_rule::Repository>,
+    identity: StaffIdentity,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, PricingFailure> {
+    identity.require(StaffRole::Manager)?;
+    let input = Input::new(request_body.into_inner())?.validate()?;
+
+    let rule = price_rule_repository
+        .create_rule(input.rule)
+        .await
+        .map_err(PricingFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Created().json(SuccessResponseBody { data: rule.into() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{staff::Staff, OperationError};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: creating a price rule.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo.expect_create_rule().never();
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_price_rule_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/pricing/rule")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .set_json(serde_json::json!({"name": "happy hour", "kind": "percent", "value": -2000}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a day of week out of range.
+    /// when: creating a price rule.
+    /// then: response status code is 400.
+    async fn test_invalid_days() {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo.expect_create_rule().never();
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_price_rule_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/pricing/rule")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({
+                "name": "happy hour",
+                "kind": "percent",
+                "value": -2000,
+                "days": [0, 1],
+            }))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a happy hour on weekdays.
+    /// when: creating a price rule.
+    /// then: the rule is stored with its window in minutes.
+    async fn test_success() {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo
+            .expect_create_rule()
+            .withf(|data| {
+                data.start_minute == 900
+                    && data.end_minute == 1080
+                    && data.days == vec![1, 2, 3, 4, 5]
+                    && data.category == Some("rice".to_string())
+            })
+            .once()
+            .returning(|data| Ok(PriceRule { rule_id: 3, ..data }));
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_price_rule_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/pricing/rule")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({
+                "name": "happy hour",
+                "kind": "percent",
+                "value": -2000,
+                "category": "rice",
+                "days": [5, 4, 3, 2, 1, 1],
+                "start_time": "15:00",
+                "end_time": "18:00",
+            }))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.data.rule_id, 3);
+        assert_eq!(response_body.data.start_time, "15:00");
+        assert_eq!(response_body.data.end_time, "18:00");
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: creating a price rule.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo
+            .expect_create_rule()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_price_rule_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/pricing/rule")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(serde_json::json!({"name": "terrace", "kind": "percent", "value": 1000, "section_id": 2}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
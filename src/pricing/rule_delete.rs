@@ -0,0 +1,118 @@
+use actix_web::{delete, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::PricingFailure;
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    rule_id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    rule_id: i64,
+}
+
+/// Deactivates a price rule, manager only. Orders keep the price they were placed with.
+#[delete("/rule/{rule_id}")]
+async fn handler(
+    price_rule_repository: web::Data<dyn db::price_rule::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, PricingFailure> {
+    identity.require(StaffRole::Manager)?;
+    let rule_id = path_params.into_inner().rule_id as i64;
+
+    let deactivated = price_rule_repository
+        .deactivate_rule(rule_id)
+        .await
+        .map_err(PricingFailure::InternalServerError)?;
+
+    match deactivated {
+        true => Ok(HttpResponse::Ok().json(SuccessResponseBody { rule_id })),
+        false => Ok(HttpResponse::NotFound().body("".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::staff::Staff;
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Manager))));
+        Arc::new(staff_repo)
+    }
+
+    #[actix_web::test]
+    /// given: no active rule with the ID.
+    /// when: deactivating a price rule.
+    /// then: response status code is 404.
+    async fn test_not_found() {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo
+            .expect_deactivate_rule()
+            .once()
+            .returning(|_| Ok(false));
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_price_rule_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::delete()
+            .uri("/pricing/rule/9")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: an active rule.
+    /// when: deactivating a price rule.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo
+            .expect_deactivate_rule()
+            .withf(|rule_id| *rule_id == 2)
+            .once()
+            .returning(|_| Ok(true));
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_price_rule_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::delete()
+            .uri("/pricing/rule/2")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+    }
+}
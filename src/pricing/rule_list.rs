@@ -0,0 +1,119 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::{PriceRuleData, PricingFailure};
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    rules: Vec<PriceRuleData>,
+}
+
+/// Lists the active price rules, manager only.
+#[get("/rule")]
+async fn handler(
+    price_rule_repository: web::Data<dyn db::price_rule::Repository>,
+    identity: StaffIdentity,
+) -> Result<HttpResponse, PricingFailure> {
+    identity.require(StaffRole::Manager)?;
+
+    let rules = price_rule_repository
+        .list_active()
+        .await
+        .map_err(PricingFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        rules: rules.into_iter().map(PriceRuleData::from).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        price_rule::{AdjustmentKind, PriceRule},
+        staff::Staff,
+        OperationError,
+    };
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Manager))));
+        Arc::new(staff_repo)
+    }
+
+    #[actix_web::test]
+    /// given: an active price rule.
+    /// when: listing price rules.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo.expect_list_active().once().returning(|| {
+            Ok(vec![PriceRule {
+                rule_id: 1,
+                ..PriceRule::new("terrace".to_string(), AdjustmentKind::Percent, 1000)
+            }])
+        });
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_price_rule_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/pricing/rule")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.rules.len(), 1);
+        assert_eq!(response_body.rules[0].start_time, "00:00");
+        assert_eq!(response_body.rules[0].end_time, "24:00");
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: listing price rules.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut price_rule_repo = crate::db::price_rule::MockRepository::new();
+        price_rule_repo
+            .expect_list_active()
+            .once()
+            .returning(|| Err(OperationError::OtherError));
+        let arc_price_rule_repo: Arc<dyn db::price_rule::Repository> = Arc::new(price_rule_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_price_rule_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/pricing").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/pricing/rule")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,268 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{db, order::BadRequestBody};
+
+use super::{
+    load_receipt, receipt_width,
+    render::{render_escpos, render_html, render_text},
+    validate_table_number, PathParams, ReceiptFailure,
+};
+
+/// The media a receipt can be rendered to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Html,
+    EscPos,
+}
+
+/// The input data to render the receipt of a Table.
+struct Input {
+    table_number: u32,
+    session_id: Option<u32>,
+    format: Format,
+}
+
+impl Input {
+    fn new(path_params: PathParams, query_params: QueryParams) -> Result<Self, ReceiptFailure> {
+        let format = match query_params.format.as_deref() {
+            None | Some("text") => Format::Text,
+            Some("html") => Format::Html,
+            Some("escpos") => Format::EscPos,
+            Some(_) => {
+                return Err(ReceiptFailure::InvalidInput(BadRequestBody {
+                    error: true,
+                    message: String::from("format must be one of text, html or escpos"),
+                }))
+            }
+        };
+        Ok(Self {
+            table_number: path_params.table_number,
+            session_id: query_params.session_id,
+            format,
+        })
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, ReceiptFailure> {
+        validate_table_number(self.table_number)?;
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    session_id: Option<u32>,
+    format: Option<String>,
+}
+
+/// Renders the receipt of a Table session as text, HTML or an ESC/POS byte stream.
+#[get("/receipt")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    payment_repository: web::Data<dyn db::payment::Repository>,
+    path_params: web::Path<PathParams>,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, ReceiptFailure> {
+    let input = Input::new(path_params.into_inner(), query_params.into_inner())?.validate()?;
+
+    let receipt = match load_receipt(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        payment_repository.get_ref(),
+        input.table_number as i32,
+        input.session_id.map(|v| v as i64),
+    )
+    .await
+    .map_err(ReceiptFailure::InternalServerError)?
+    {
+        Some(receipt) => receipt,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    Ok(match input.format {
+        Format::Text => HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body(render_text(&receipt, receipt_width())),
+        Format::Html => HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .body(render_html(&receipt)),
+        Format::EscPos => HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .body(render_escpos(&receipt, receipt_width())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{order::Order, session::Session, OperationError};
+
+    fn order_repo() -> Arc<dyn db::order::Repository> {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .returning(|table_number, session_id| {
+                Ok(vec![Order {
+                    session_id: Some(session_id),
+                    unit_price: 35_000,
+                    name: Some("焼き鳥".to_string()),
+                    ..Order::new(table_number, 5, 10)
+                }])
+            });
+        Arc::new(order_repo)
+    }
+
+    fn discount_repo() -> Arc<dyn db::discount::Repository> {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(discount_repo)
+    }
+
+    fn payment_repo() -> Arc<dyn db::payment::Repository> {
+        let mut payment_repo = crate::db::payment::MockRepository::new();
+        payment_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(payment_repo)
+    }
+
+    #[actix_web::test]
+    /// given: an unknown format.
+    /// when: rendering a receipt.
+    /// then: response status code is 400.
+    async fn test_invalid_format() {
+        let session_repo = crate::db::session::MockRepository::new();
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/receipt?format=pdf")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a past session.
+    /// when: rendering its receipt as HTML.
+    /// then: response is an HTML page with the menu name in its original script.
+    async fn test_html() {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_session()
+            .returning(|table_number, session_id| Ok(Some(Session::new(session_id, table_number))));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/receipt?session_id=5&format=html")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "text/html; charset=utf-8"
+        );
+        let body = test::read_body(resp).await;
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains("1 &times; 焼き鳥"));
+    }
+
+    #[actix_web::test]
+    /// given: a past session.
+    /// when: rendering its receipt as an ESC/POS byte stream.
+    /// then: response starts with the printer initialization.
+    async fn test_escpos() {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_session()
+            .returning(|table_number, session_id| Ok(Some(Session::new(session_id, table_number))));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/receipt?session_id=5&format=escpos")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        assert!(body.starts_with(&[0x1b, b'@']));
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: rendering a receipt.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_session()
+            .returning(|table_number, session_id| Ok(Some(Session::new(session_id, table_number))));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+        let mut payment_repo = crate::db::payment::MockRepository::new();
+        payment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_payment_repo: Arc<dyn db::payment::Repository> = Arc::new(payment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(arc_payment_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/table/3/receipt?session_id=5")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
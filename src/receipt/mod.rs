@@ -0,0 +1,408 @@
+use std::{env, fmt};
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::{format_description, OffsetDateTime};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    auth::AuthFailure,
+    bill::{load_bill, Bill},
+    db::{
+        self,
+        payment::{Payment, PaymentMethod},
+        OperationError,
+    },
+    order::{BadRequestBody, InternalServerErrorBody},
+    pricing::local_time,
+};
+
+pub mod detail;
+pub mod print;
+pub mod render;
+pub mod sink;
+
+/// Everything printed on a receipt, amounts come from the Bill so they match it to the last Rupiah.
+pub struct Receipt {
+    pub outlet_name: String,
+    pub bill: Bill,
+    pub payments: Vec<Payment>,
+    pub printed_at: OffsetDateTime,
+}
+
+impl Receipt {
+    /// Reads the outlet name from `OUTLET_NAME`, or defer to predefined default.
+    pub fn new(bill: Bill, payments: Vec<Payment>, printed_at: OffsetDateTime) -> Self {
+        Self {
+            outlet_name: env::var("OUTLET_NAME").unwrap_or("Sukab Restaurant".to_string()),
+            bill,
+            payments,
+            printed_at,
+        }
+    }
+
+    pub fn paid(&self) -> i64 {
+        self.payments.iter().map(|p| p.amount).sum()
+    }
+
+    /// What the guests gave on top of the bill.
+    pub fn tips(&self) -> i64 {
+        self.payments.iter().map(|p| p.tip).sum()
+    }
+}
+
+/// Reads how many columns fit on a receipt line from `RECEIPT_WIDTH`, or defer to 42 (80mm paper).
+pub fn receipt_width() -> usize {
+    match env::var("RECEIPT_WIDTH").ok() {
+        Some(v) => v.parse().unwrap_or(42).clamp(24, 64),
+        None => 42,
+    }
+}
+
+/// Formats an amount in minor units with dots between thousands, e.g. `-101.640`.
+pub fn format_amount(amount: i64) -> String {
+    let digits = amount.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push('.');
+        }
+        grouped.push(c);
+    }
+    match amount < 0 {
+        true => format!("-{}", grouped),
+        false => grouped,
+    }
+}
+
+fn method_label(method: PaymentMethod) -> &'static str {
+    match method {
+        PaymentMethod::Cash => "Cash",
+        PaymentMethod::Card => "Card",
+        PaymentMethod::Qris => "QRIS",
+    }
+}
+
+/// Cuts the text to fit the columns, wide characters (e.g. Japanese) take two columns.
+pub(crate) fn truncate(text: &str, columns: usize) -> String {
+    let mut used = 0;
+    text.chars()
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+            used <= columns
+        })
+        .collect()
+}
+
+/// Puts the text on the left and the amount on the right of a line.
+fn columns(left: &str, right: &str, width: usize) -> String {
+    let left = truncate(left, width.saturating_sub(right.width() + 1));
+    let gap = width.saturating_sub(left.width() + right.width());
+    format!("{}{}{}", left, " ".repeat(gap), right)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Emphasis {
+    Normal,
+    Bold,
+    /// double width and height, so only half the columns fit.
+    Double,
+}
+
+/// A line of the receipt before it is rendered for a medium.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiptLine {
+    pub text: String,
+    pub align: Align,
+    pub emphasis: Emphasis,
+}
+
+impl ReceiptLine {
+    pub fn left(text: String) -> Self {
+        Self {
+            text,
+            align: Align::Left,
+            emphasis: Emphasis::Normal,
+        }
+    }
+
+    pub fn center(text: String, emphasis: Emphasis) -> Self {
+        Self {
+            text,
+            align: Align::Center,
+            emphasis,
+        }
+    }
+}
+
+/// Lays the receipt out in lines of `width` columns, shared by the text and ESC/POS renderers.
+pub fn layout(receipt: &Receipt, width: usize) -> Vec<ReceiptLine> {
+    let bill = &receipt.bill;
+    let separator = || ReceiptLine::left("-".repeat(width));
+    let row =
+        |label: &str, amount: i64| ReceiptLine::left(columns(label, &format_amount(amount), width));
+
+    let printed_at = format_description::parse("[year]-[month]-[day] [hour]:[minute]")
+        .ok()
+        .and_then(|format| local_time(receipt.printed_at).format(&format).ok())
+        .unwrap_or("---".to_string());
+    let mut lines = vec![
+        ReceiptLine::center(truncate(&receipt.outlet_name, width / 2), Emphasis::Double),
+        ReceiptLine::left(columns(
+            &format!("Table {}", bill.table_number),
+            &format!("Session {}", bill.session_id),
+            width,
+        )),
+        ReceiptLine::left(printed_at),
+        separator(),
+    ];
+
+    for line in &bill.lines {
+        lines.push(row(
+            &format!("{} x {}", line.quantity, line.name),
+            line.amount,
+        ));
+        if line.quantity > 1 {
+            lines.push(ReceiptLine::left(format!(
+                "    @ {}",
+                format_amount(line.unit_price)
+            )));
+        }
+        if line.discount > 0 {
+            lines.push(row("    Discount", -line.discount));
+        }
+    }
+
+    let incl = match bill.tax_inclusive {
+        true => " (incl.)",
+        false => "",
+    };
+    lines.push(separator());
+    lines.push(row("Subtotal", bill.subtotal));
+    if bill.discount > 0 {
+        lines.push(row("Discount", -bill.discount));
+    }
+    lines.push(row(&format!("Service charge{}", incl), bill.service_charge));
+    lines.push(row(&format!("Tax{}", incl), bill.tax));
+    if bill.rounding != 0 {
+        lines.push(row("Rounding", bill.rounding));
+    }
+    lines.push(ReceiptLine {
+        emphasis: Emphasis::Bold,
+        ..row("TOTAL", bill.total)
+    });
+
+    if !receipt.payments.is_empty() {
+        lines.push(separator());
+        for payment in &receipt.payments {
+            let label = match &payment.reference {
+                Some(reference) => format!("{} ({})", method_label(payment.method), reference),
+                None => method_label(payment.method).to_string(),
+            };
+            lines.push(row(&label, payment.amount));
+        }
+        lines.push(row("Paid", receipt.paid()));
+        lines.push(row("Balance", bill.total - receipt.paid()));
+        if receipt.tips() != 0 {
+            lines.push(row("Tip", receipt.tips()));
+        }
+    }
+
+    lines.push(separator());
+    lines.push(ReceiptLine::center(
+        "Thank you, see you again!".to_string(),
+        Emphasis::Normal,
+    ));
+    lines
+}
+
+/// Builds the receipt of a Table session along with its Payments, defaults to the open session.
+/// Returns None when the Table has no open session.
+async fn load_receipt(
+    order_repository: &dyn db::order::Repository,
+    session_repository: &dyn db::session::Repository,
+    discount_repository: &dyn db::discount::Repository,
+    payment_repository: &dyn db::payment::Repository,
+    table_number: i32,
+    session_id: Option<i64>,
+) -> Result<Option<Receipt>, OperationError> {
+    let bill = match load_bill(
+        order_repository,
+        session_repository,
+        discount_repository,
+        table_number,
+        session_id,
+    )
+    .await?
+    {
+        Some(bill) => bill,
+        None => return Ok(None),
+    };
+    let payments = payment_repository.list_by_session(bill.session_id).await?;
+    Ok(Some(Receipt::new(
+        bill,
+        payments,
+        OffsetDateTime::now_utc(),
+    )))
+}
+
+/// performs simple request validation to make check some bounds.
+fn validate_table_number(table_number: u32) -> Result<(), ReceiptFailure> {
+    if !(1..=100).contains(&table_number) {
+        return Err(ReceiptFailure::InvalidInput(BadRequestBody {
+            error: true,
+            message: String::from("table_number must be in range of 1 to 100"),
+        }));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    table_number: u32,
+}
+
+#[derive(Debug)]
+enum ReceiptFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    PrinterUnavailable(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for ReceiptFailure {
+    fn from(e: AuthFailure) -> Self {
+        ReceiptFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for ReceiptFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to process receipt")
+    }
+}
+
+impl ResponseError for ReceiptFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            ReceiptFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            ReceiptFailure::Auth(e) => e.status_code(),
+            ReceiptFailure::PrinterUnavailable(_) => StatusCode::BAD_GATEWAY,
+            ReceiptFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            ReceiptFailure::InvalidInput(r) | ReceiptFailure::PrinterUnavailable(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            ReceiptFailure::Auth(e) => e.error_response(),
+            ReceiptFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use time::format_description::well_known::Rfc3339;
+
+    use super::*;
+    use crate::{
+        db::{
+            discount::{Discount, DiscountKind},
+            order::Order,
+        },
+        tax::{RoundingMode, TaxConfig, TaxMode},
+    };
+
+    /// a paid bill with a Japanese and an Indonesian menu.
+    pub(crate) fn receipt() -> Receipt {
+        let orders = vec![
+            Order {
+                order_id: 1,
+                quantity: 2,
+                unit_price: 35_000,
+                name: Some("焼き鳥".to_string()),
+                ..Order::new(3, 5, 10)
+            },
+            Order {
+                order_id: 2,
+                unit_price: 32_000,
+                name: Some("Nasi Goreng".to_string()),
+                ..Order::new(3, 9, 10)
+            },
+        ];
+        let discounts = vec![Discount::new(7, Some(2), DiscountKind::Percent, 1_000)];
+        let tax_config = TaxConfig {
+            service_charge: 500,
+            tax: 1_000,
+            mode: TaxMode::Exclusive,
+            rounding_unit: 100,
+            rounding_mode: RoundingMode::HalfUp,
+        };
+        let bill = Bill::compute(3, 7, &orders, &discounts, tax_config);
+        let payments = vec![Payment {
+            tip: 5_000,
+            ..Payment::new(7, 3, PaymentMethod::Cash, bill.total, None)
+        }];
+        Receipt {
+            outlet_name: "Sukab Restaurant".to_string(),
+            bill,
+            payments,
+            printed_at: OffsetDateTime::parse("2026-01-07T09:00:00Z", &Rfc3339).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(0), "0");
+        assert_eq!(format_amount(999), "999");
+        assert_eq!(format_amount(101_640), "101.640");
+        assert_eq!(format_amount(-1_234_567), "-1.234.567");
+    }
+
+    #[test]
+    fn test_columns_wide_characters() {
+        let line = columns("2 x 焼き鳥", "70.000", 24);
+        assert_eq!(line.width(), 24);
+        assert!(line.starts_with("2 x 焼き鳥 "));
+        // a name too long for the line is cut without splitting a wide character:
+        let line = columns("らーめんらーめんらーめん", "45.000", 20);
+        assert_eq!(line, "らーめんらー  45.000");
+        assert_eq!(line.width(), 20);
+    }
+
+    #[test]
+    fn test_layout() {
+        let lines = layout(&receipt(), 32);
+        assert!(lines
+            .iter()
+            .filter(|line| line.emphasis != Emphasis::Double)
+            .all(|line| line.text.width() <= 32));
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts[0], "Sukab Restaurant");
+        assert!(texts.contains(&"2026-01-07 16:00"));
+        assert!(texts.contains(&"    @ 35.000"));
+        assert!(texts.contains(&columns("    Discount", "-3.200", 32).as_str()));
+        assert!(texts.contains(&columns("Rounding", "-14", 32).as_str()));
+        assert!(texts.contains(&columns("TOTAL", "114.100", 32).as_str()));
+        assert!(texts.contains(&columns("Balance", "0", 32).as_str()));
+        assert!(texts.contains(&columns("Tip", "5.000", 32).as_str()));
+    }
+}
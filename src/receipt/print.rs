@@ -0,0 +1,247 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+    order::BadRequestBody,
+};
+
+use super::{
+    load_receipt, receipt_width, render::render_escpos, sink::Sink, validate_table_number,
+    PathParams, ReceiptFailure,
+};
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    session_id: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    session_id: i64,
+    printer: String,
+    bytes: usize,
+}
+
+/// Prints the receipt of a Table session on the receipt printer.
+#[post("/receipt/print")]
+#[allow(clippy::too_many_arguments)]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    payment_repository: web::Data<dyn db::payment::Repository>,
+    sink: web::Data<dyn Sink>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, ReceiptFailure> {
+    identity.require(StaffRole::Waiter)?;
+    let table_number = path_params.into_inner().table_number;
+    validate_table_number(table_number)?;
+
+    let receipt = match load_receipt(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        payment_repository.get_ref(),
+        table_number as i32,
+        query_params.into_inner().session_id.map(|v| v as i64),
+    )
+    .await
+    .map_err(ReceiptFailure::InternalServerError)?
+    {
+        Some(receipt) => receipt,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    let bytes = render_escpos(&receipt, receipt_width());
+    let length = bytes.len();
+    let printer = sink.describe();
+    // printers are slow and the sink blocks, so it runs off the async workers:
+    let sent = {
+        let sink = sink.clone();
+        web::block(move || sink.send(&bytes)).await
+    };
+    match sent {
+        Ok(Ok(())) => Ok(HttpResponse::Ok().json(SuccessResponseBody {
+            session_id: receipt.bill.session_id,
+            printer,
+            bytes: length,
+        })),
+        Ok(Err(e)) => {
+            log::error!("failed to print receipt on {}: {:?}", printer, e);
+            Err(ReceiptFailure::PrinterUnavailable(BadRequestBody {
+                error: true,
+                message: format!("the receipt printer is unavailable: {}", e),
+            }))
+        }
+        Err(e) => {
+            log::error!("failed to print receipt on {}: {:?}", printer, e);
+            Err(ReceiptFailure::PrinterUnavailable(BadRequestBody {
+                error: true,
+                message: String::from("the receipt printer is unavailable"),
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, sync::Arc};
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{order::Order, session::Session, staff::Staff};
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Waiter))));
+        Arc::new(staff_repo)
+    }
+
+    fn order_repo() -> Arc<dyn db::order::Repository> {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .returning(|table_number, session_id| {
+                Ok(vec![Order {
+                    session_id: Some(session_id),
+                    unit_price: 45_000,
+                    name: Some("らーめん".to_string()),
+                    ..Order::new(table_number, 2, 10)
+                }])
+            });
+        Arc::new(order_repo)
+    }
+
+    fn session_repo() -> Arc<dyn db::session::Repository> {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .returning(|table_number| Ok(Some(Session::new(7, table_number))));
+        Arc::new(session_repo)
+    }
+
+    fn discount_repo() -> Arc<dyn db::discount::Repository> {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(discount_repo)
+    }
+
+    fn payment_repo() -> Arc<dyn db::payment::Repository> {
+        let mut payment_repo = crate::db::payment::MockRepository::new();
+        payment_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(payment_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a working receipt printer.
+    /// when: printing the receipt of the open session.
+    /// then: the ESC/POS byte stream is sent to the printer.
+    async fn test_success() {
+        let mut sink = crate::receipt::sink::MockSink::new();
+        sink.expect_describe()
+            .returning(|| "tcp://printer:9100".to_string());
+        sink.expect_send()
+            .withf(|bytes| bytes.starts_with(&[0x1b, b'@']))
+            .once()
+            .returning(|_| Ok(()));
+        let arc_sink: Arc<dyn Sink> = Arc::new(sink);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_sink))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/receipt/print")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.session_id, 7);
+        assert_eq!(response_body.printer, "tcp://printer:9100");
+        assert!(response_body.bytes > 0);
+    }
+
+    #[actix_web::test]
+    /// given: a printer which is offline.
+    /// when: printing a receipt.
+    /// then: response status code is 502.
+    async fn test_printer_unavailable() {
+        let mut sink = crate::receipt::sink::MockSink::new();
+        sink.expect_describe()
+            .returning(|| "tcp://printer:9100".to_string());
+        sink.expect_send()
+            .once()
+            .returning(|_| Err(io::Error::new(io::ErrorKind::TimedOut, "timed out")));
+        let arc_sink: Arc<dyn Sink> = Arc::new(sink);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_sink))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/receipt/print")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[actix_web::test]
+    /// given: no access token.
+    /// when: printing a receipt.
+    /// then: response status code is 401.
+    async fn test_anonymous() {
+        let sink = crate::receipt::sink::MockSink::new();
+        let arc_sink: Arc<dyn Sink> = Arc::new(sink);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(order_repo()))
+                .app_data(Data::from(session_repo()))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(payment_repo()))
+                .app_data(Data::from(arc_sink))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/receipt/print")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,208 @@
+use encoding_rs::SHIFT_JIS;
+use unicode_width::UnicodeWidthStr;
+
+use super::{format_amount, layout, method_label, Align, Emphasis, Receipt, ReceiptLine};
+
+/// Renders the receipt as plain UTF-8 text, e.g. for e-mail or a terminal.
+pub fn render_text(receipt: &Receipt, width: usize) -> String {
+    layout(receipt, width)
+        .into_iter()
+        .map(|line| match line.align {
+            Align::Left => line.text,
+            Align::Center => {
+                let pad = width.saturating_sub(line.text.width()) / 2;
+                format!("{}{}", " ".repeat(pad), line.text)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+        + "\n"
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders the receipt as a standalone HTML page.
+pub fn render_html(receipt: &Receipt) -> String {
+    let bill = &receipt.bill;
+    let row = |label: String, amount: i64| {
+        format!(
+            "<tr><td>{}</td><td class=\"amount\">{}</td></tr>\n",
+            label,
+            format_amount(amount)
+        )
+    };
+
+    let mut items = String::new();
+    for line in &bill.lines {
+        items += &row(
+            format!("{} &times; {}", line.quantity, escape_html(&line.name)),
+            line.amount,
+        );
+        if line.discount > 0 {
+            items += &row("<small>Discount</small>".to_string(), -line.discount);
+        }
+    }
+
+    let incl = match bill.tax_inclusive {
+        true => " (incl.)",
+        false => "",
+    };
+    let mut totals = row("Subtotal".to_string(), bill.subtotal);
+    if bill.discount > 0 {
+        totals += &row("Discount".to_string(), -bill.discount);
+    }
+    totals += &row(format!("Service charge{}", incl), bill.service_charge);
+    totals += &row(format!("Tax{}", incl), bill.tax);
+    if bill.rounding != 0 {
+        totals += &row("Rounding".to_string(), bill.rounding);
+    }
+    totals += &row("<strong>TOTAL</strong>".to_string(), bill.total);
+
+    let mut payments = String::new();
+    for payment in &receipt.payments {
+        payments += &row(method_label(payment.method).to_string(), payment.amount);
+    }
+    if !receipt.payments.is_empty() {
+        payments += &row("Balance".to_string(), bill.total - receipt.paid());
+    }
+
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Receipt {session_id}</title>
+<style>body {{ font-family: monospace; max-width: 24em; }} td.amount {{ text-align: right; }} table {{ width: 100%; }}</style>
+</head>
+<body>
+<h1>{outlet}</h1>
+<p>Table {table_number} &middot; Session {session_id}</p>
+<table class=\"items\">
+{items}</table>
+<hr>
+<table class=\"totals\">
+{totals}</table>
+<hr>
+<table class=\"payments\">
+{payments}</table>
+<p>Thank you, see you again!</p>
+</body>
+</html>
+",
+        outlet = escape_html(&receipt.outlet_name),
+        table_number = bill.table_number,
+        session_id = bill.session_id,
+        items = items,
+        totals = totals,
+        payments = payments,
+    )
+}
+
+const ESC: u8 = 0x1b;
+const FS: u8 = 0x1c;
+const GS: u8 = 0x1d;
+const LF: u8 = 0x0a;
+
+/// Encodes the text for a printer in kanji mode, characters Shift_JIS lacks are printed as `?`.
+fn encode(text: &str) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut buf = [0u8; 4];
+    for c in text.chars() {
+        if c.is_ascii() {
+            bytes.push(c as u8);
+            continue;
+        }
+        let (encoded, _, had_errors) = SHIFT_JIS.encode(c.encode_utf8(&mut buf));
+        match had_errors {
+            true => bytes.push(b'?'),
+            false => bytes.extend_from_slice(&encoded),
+        }
+    }
+    bytes
+}
+
+/// Renders the receipt as an ESC/POS byte stream for thermal printers.
+/// Japanese menu names are printed in kanji mode with Shift_JIS encoding.
+pub fn render_escpos(receipt: &Receipt, width: usize) -> Vec<u8> {
+    render_lines(layout(receipt, width))
+}
+
+/// Renders laid out lines as an ESC/POS byte stream which ends with a paper cut,
+/// shared by the receipts and the kitchen tickets.
+pub fn render_lines(lines: Vec<ReceiptLine>) -> Vec<u8> {
+    // initialize, select Shift_JIS and enter kanji mode:
+    let mut bytes = vec![ESC, b'@', FS, b'C', 1, FS, b'&'];
+    for line in lines {
+        let align = match line.align {
+            Align::Left => 0,
+            Align::Center => 1,
+        };
+        let mode = match line.emphasis {
+            Emphasis::Normal => 0x00,
+            Emphasis::Bold => 0x08,
+            Emphasis::Double => 0x38,
+        };
+        // kanji characters have their own print mode command:
+        let kanji_mode = match line.emphasis {
+            Emphasis::Double => 0x0c,
+            _ => 0x00,
+        };
+        bytes.extend_from_slice(&[ESC, b'a', align, ESC, b'!', mode, FS, b'!', kanji_mode]);
+        bytes.extend(encode(&line.text));
+        bytes.push(LF);
+    }
+    // back to normal, feed the paper past the cutter and cut:
+    bytes.extend_from_slice(&[ESC, b'!', 0, FS, b'!', 0, ESC, b'd', 3, GS, b'V', 66, 0]);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receipt::tests::receipt;
+
+    #[test]
+    fn test_render_text() {
+        let text = render_text(&receipt(), 32);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "        Sukab Restaurant");
+        assert!(lines.iter().all(|line| line.width() <= 32));
+        assert!(text.contains("2 x 焼き鳥"));
+        assert!(text.contains("1 x Nasi Goreng"));
+        assert!(text.ends_with("Thank you, see you again!\n"));
+    }
+
+    #[test]
+    fn test_render_html() {
+        let mut receipt = receipt();
+        receipt.outlet_name = "Sukab <Resto>".to_string();
+        let html = render_html(&receipt);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<meta charset=\"utf-8\">"));
+        assert!(html.contains("<h1>Sukab &lt;Resto&gt;</h1>"));
+        assert!(html.contains("2 &times; 焼き鳥"));
+        assert!(html.contains("114.100"));
+    }
+
+    #[test]
+    fn test_render_escpos() {
+        let bytes = render_escpos(&receipt(), 32);
+        assert!(bytes.starts_with(&[ESC, b'@', FS, b'C', 1, FS, b'&']));
+        assert!(bytes.ends_with(&[GS, b'V', 66, 0]));
+        // 焼き鳥 in Shift_JIS:
+        let yakitori = [0x8f, 0xc4, 0x82, 0xab, 0x92, 0xb9];
+        assert!(bytes.windows(yakitori.len()).any(|w| w == yakitori));
+        assert!(bytes.windows(11).any(|w| w == b"Nasi Goreng"));
+    }
+
+    #[test]
+    fn test_encode_unsupported() {
+        assert_eq!(encode("Rp 1.000"), b"Rp 1.000".to_vec());
+        assert_eq!(encode("🍜"), b"?".to_vec());
+    }
+}
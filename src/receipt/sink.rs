@@ -0,0 +1,152 @@
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{self, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use mockall::automock;
+
+#[automock]
+/// Where printed receipts go, a printer on the network or a file standing in for one.
+pub trait Sink: Send + Sync {
+    /// Writes the whole byte stream, blocking until it is sent.
+    fn send(&self, bytes: &[u8]) -> io::Result<()>;
+    /// Describes the destination for the response and the logs.
+    fn describe(&self) -> String;
+}
+
+/// Appends receipts to a file, handy to test without a printer.
+pub struct FileSink {
+    path: String,
+}
+
+impl FileSink {
+    pub fn new(path: String) -> Self {
+        Self { path }
+    }
+}
+
+impl Sink for FileSink {
+    fn send(&self, bytes: &[u8]) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(bytes)
+    }
+
+    fn describe(&self) -> String {
+        format!("file:{}", self.path)
+    }
+}
+
+/// Sends receipts to a network printer, which usually listens on the raw port 9100.
+pub struct TcpSink {
+    address: String,
+    timeout: Duration,
+}
+
+impl TcpSink {
+    pub fn new(address: String, timeout: Duration) -> Self {
+        Self { address, timeout }
+    }
+}
+
+impl Sink for TcpSink {
+    fn send(&self, bytes: &[u8]) -> io::Result<()> {
+        let address =
+            self.address.to_socket_addrs()?.next().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "printer address not found")
+            })?;
+        let mut stream = TcpStream::connect_timeout(&address, self.timeout)?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        stream.write_all(bytes)?;
+        stream.flush()
+    }
+
+    fn describe(&self) -> String {
+        format!("tcp://{}", self.address)
+    }
+}
+
+/// Opens a printer, either `tcp://host:port` or `file:path`, a bare path is taken as a file.
+pub fn open(printer: &str) -> Box<dyn Sink> {
+    match printer.strip_prefix("tcp://") {
+        Some(address) => Box::new(TcpSink::new(address.to_string(), Duration::from_secs(5))),
+        None => Box::new(FileSink::new(
+            printer.strip_prefix("file:").unwrap_or(printer).to_string(),
+        )),
+    }
+}
+
+/// Whether the printer is spelled out as `tcp://host:port` or `file:path`.
+pub fn is_valid(printer: &str) -> bool {
+    match (
+        printer.strip_prefix("tcp://"),
+        printer.strip_prefix("file:"),
+    ) {
+        (Some(address), _) => {
+            matches!(address.rsplit_once(':'), Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok())
+        }
+        (_, Some(path)) => !path.trim().is_empty(),
+        _ => false,
+    }
+}
+
+/// Reads the printer from `RECEIPT_PRINTER`, either `tcp://host:port` or `file:path`,
+/// or defer to the `receipts.escpos` file in the working directory.
+pub fn from_env() -> Box<dyn Sink> {
+    open(&env::var("RECEIPT_PRINTER").unwrap_or("file:receipts.escpos".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, net::TcpListener, thread};
+
+    use super::*;
+
+    #[test]
+    fn test_file_sink_appends() {
+        let path = env::temp_dir().join(format!("sukab-receipt-{}.escpos", std::process::id()));
+        let sink = FileSink::new(path.to_string_lossy().to_string());
+
+        sink.send(b"first").unwrap();
+        sink.send(b"second").unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"firstsecond".to_vec());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(is_valid("tcp://10.0.0.21:9100"));
+        assert!(is_valid("file:kitchen.escpos"));
+        assert!(!is_valid("tcp://10.0.0.21"));
+        assert!(!is_valid("file: "));
+        assert!(!is_valid("lpt1"));
+    }
+
+    #[test]
+    fn test_tcp_sink() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let printer = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = vec![];
+            stream.read_to_end(&mut received).unwrap();
+            received
+        });
+
+        let sink = TcpSink::new(address.clone(), Duration::from_secs(1));
+        sink.send(&[0x1b, b'@', b'h', b'i']).unwrap();
+        drop(sink);
+
+        assert_eq!(printer.join().unwrap(), vec![0x1b, b'@', b'h', b'i']);
+        assert_eq!(
+            TcpSink::new(address.clone(), Duration::from_secs(1)).describe(),
+            format!("tcp://{}", address)
+        );
+    }
+}
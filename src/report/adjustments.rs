@@ -0,0 +1,170 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::{business_day, parse_date, AdjustmentReport, ReportFailure};
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    date: String,
+    #[serde(flatten)]
+    report: AdjustmentReport,
+}
+
+/// Sums up the voids and refunds of a business day by reason, manager only.
+#[get("/adjustments")]
+async fn handler(
+    adjustment_repository: web::Data<dyn db::adjustment::Repository>,
+    identity: StaffIdentity,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, ReportFailure> {
+    identity.require(StaffRole::Manager)?;
+    let date = parse_date(query_params.into_inner().date)?;
+    let (from, to) = business_day(date);
+
+    let totals = adjustment_repository
+        .summarize(from, to)
+        .await
+        .map_err(ReportFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        date: date.to_string(),
+        report: AdjustmentReport::new(totals),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        adjustment::{AdjustmentTotal, ReasonCode},
+        staff::Staff,
+        OperationError,
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: getting the adjustments report.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let adjustment_repo = crate::db::adjustment::MockRepository::new();
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_adjustment_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/report").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/report/adjustments?date=2026-01-07")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: voids and refunds on a business day.
+    /// when: getting the adjustments report of that day.
+    /// then: they are summed up by reason within the day.
+    async fn test_success() {
+        let mut adjustment_repo = crate::db::adjustment::MockRepository::new();
+        adjustment_repo
+            .expect_summarize()
+            .withf(|from, to| *to - *from == time::Duration::days(1))
+            .once()
+            .returning(|_, _| {
+                Ok(vec![
+                    AdjustmentTotal {
+                        kind: "void".to_string(),
+                        reason: ReasonCode::EntryError,
+                        count: 2,
+                        amount: 53_000,
+                    },
+                    AdjustmentTotal {
+                        kind: "refund".to_string(),
+                        reason: ReasonCode::Overcharge,
+                        count: 1,
+                        amount: 10_000,
+                    },
+                ])
+            });
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_adjustment_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/report").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/report/adjustments?date=2026-01-07")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.date, "2026-01-07");
+        assert_eq!(response_body.report.void_total, 53_000);
+        assert_eq!(response_body.report.refunds[0].reason, "overcharge");
+        assert_eq!(response_body.report.refund_total, 10_000);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: getting the adjustments report.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut adjustment_repo = crate::db::adjustment::MockRepository::new();
+        adjustment_repo
+            .expect_summarize()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let arc_adjustment_repo: Arc<dyn db::adjustment::Repository> = Arc::new(adjustment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_adjustment_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/report").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/report/adjustments")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
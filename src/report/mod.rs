@@ -0,0 +1,538 @@
+use std::{collections::HashMap, fmt};
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::{format_description, Date, OffsetDateTime, Time};
+
+use crate::{
+    auth::AuthFailure,
+    bill::Bill,
+    db::{
+        self, adjustment::AdjustmentTotal, discount::Discount, order::Order, report::MethodTotal,
+        session::Session, OperationError,
+    },
+    order::{BadRequestBody, InternalServerErrorBody},
+    pricing::{local_time, outlet_offset},
+    receipt::format_amount,
+    tax::TaxConfig,
+};
+
+pub mod adjustments;
+pub mod tips;
+pub mod z;
+
+/// Parses a `YYYY-MM-DD` business day.
+pub fn date_from_str(date: &str) -> Option<Date> {
+    let format = format_description::parse("[year]-[month]-[day]").ok()?;
+    Date::parse(date, &format).ok()
+}
+
+/// Today in the outlet time.
+pub fn today() -> Date {
+    local_time(OffsetDateTime::now_utc()).date()
+}
+
+/// Parses a business day out of the request, defaults to today in the outlet time.
+fn parse_date(date: Option<String>) -> Result<Date, ReportFailure> {
+    match date {
+        Some(date) => date_from_str(&date).ok_or_else(|| {
+            ReportFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("date must be formatted as YYYY-MM-DD"),
+            })
+        }),
+        None => Ok(today()),
+    }
+}
+
+/// The instants a business day starts and ends at, from midnight to midnight in the outlet time.
+pub fn business_day(date: Date) -> (OffsetDateTime, OffsetDateTime) {
+    let from = date
+        .with_time(Time::MIDNIGHT)
+        .assume_offset(outlet_offset());
+    (from, from + time::Duration::days(1))
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdjustmentTotalData {
+    pub reason: String,
+    pub count: i64,
+    pub amount: i64,
+}
+
+/// The voids and refunds of a business day by reason.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdjustmentReport {
+    pub voids: Vec<AdjustmentTotalData>,
+    pub void_total: i64,
+    pub refunds: Vec<AdjustmentTotalData>,
+    pub refund_total: i64,
+}
+
+impl AdjustmentReport {
+    pub fn new(totals: Vec<AdjustmentTotal>) -> Self {
+        let (voids, refunds): (Vec<AdjustmentTotal>, Vec<AdjustmentTotal>) =
+            totals.into_iter().partition(|total| total.kind == "void");
+        let data = |totals: Vec<AdjustmentTotal>| {
+            totals
+                .into_iter()
+                .map(|total| AdjustmentTotalData {
+                    reason: total.reason.as_str().to_string(),
+                    count: total.count,
+                    amount: total.amount,
+                })
+                .collect::<Vec<AdjustmentTotalData>>()
+        };
+        let voids = data(voids);
+        let refunds = data(refunds);
+        Self {
+            void_total: voids.iter().map(|v| v.amount).sum(),
+            voids,
+            refund_total: refunds.iter().map(|r| r.amount).sum(),
+            refunds,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct MethodTotalData {
+    pub method: String,
+    pub count: i64,
+    pub amount: i64,
+    pub tip: i64,
+}
+
+impl From<MethodTotal> for MethodTotalData {
+    fn from(total: MethodTotal) -> Self {
+        Self {
+            method: total.method.as_str().to_string(),
+            count: total.count,
+            amount: total.amount,
+            tip: total.tip,
+        }
+    }
+}
+
+/// The end-of-day report of a business day, every amount is in minor units of the currency.
+/// Sales are the bills of the Table sessions opened during the day,
+/// payments and refunds are the money which changed hands during the day.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ZReport {
+    pub date: String,
+    pub sessions: i64,
+    /// the sessions of the day which are not checked out yet, their bills may still change.
+    pub open_sessions: i64,
+    /// the Orders on the bills, voided ones are left out.
+    pub orders: i64,
+    pub gross_sales: i64,
+    pub discounts: i64,
+    pub net_sales: i64,
+    pub service_charge: i64,
+    pub tax: i64,
+    pub rounding: i64,
+    pub total: i64,
+    pub adjustments: AdjustmentReport,
+    pub payments: Vec<MethodTotalData>,
+    pub payment_total: i64,
+    pub refunds: Vec<MethodTotalData>,
+    pub refund_total: i64,
+    /// what was received minus what was given back.
+    pub net_payments: i64,
+    /// what was given on top of the bills, it belongs to the tip pool and not to the sales.
+    pub tip_total: i64,
+}
+
+impl ZReport {
+    pub fn new(
+        date: Date,
+        bills: &[Bill],
+        open_sessions: i64,
+        payments: Vec<MethodTotal>,
+        refunds: Vec<MethodTotal>,
+        adjustments: Vec<AdjustmentTotal>,
+    ) -> Self {
+        let sum = |amount: fn(&Bill) -> i64| bills.iter().map(amount).sum::<i64>();
+        let payments: Vec<MethodTotalData> = payments.into_iter().map(Into::into).collect();
+        let refunds: Vec<MethodTotalData> = refunds.into_iter().map(Into::into).collect();
+        let payment_total = payments.iter().map(|p| p.amount).sum();
+        let refund_total = refunds.iter().map(|r| r.amount).sum();
+        Self {
+            date: date.to_string(),
+            sessions: bills.len() as i64,
+            open_sessions,
+            orders: bills.iter().map(|bill| bill.lines.len() as i64).sum(),
+            gross_sales: sum(|bill| bill.subtotal),
+            discounts: sum(|bill| bill.discount),
+            net_sales: sum(|bill| bill.subtotal - bill.discount),
+            service_charge: sum(|bill| bill.service_charge),
+            tax: sum(|bill| bill.tax),
+            rounding: sum(|bill| bill.rounding),
+            total: sum(|bill| bill.total),
+            adjustments: AdjustmentReport::new(adjustments),
+            payment_total,
+            refunds,
+            refund_total,
+            net_payments: payment_total - refund_total,
+            tip_total: payments.iter().map(|p| p.tip).sum(),
+            payments,
+        }
+    }
+
+    /// Renders the report as plain text lines for the terminal or a printer.
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("Z-REPORT {}", self.date),
+            String::new(),
+            row("Sessions", self.sessions),
+            row("Still open", self.open_sessions),
+            row("Orders", self.orders),
+            String::new(),
+            amount_row("Gross sales", self.gross_sales),
+            amount_row("Discounts", -self.discounts),
+            amount_row("Net sales", self.net_sales),
+            amount_row("Service charge", self.service_charge),
+            amount_row("Tax", self.tax),
+            amount_row("Rounding", self.rounding),
+            amount_row("Total", self.total),
+            String::new(),
+            amount_row("Voids", self.adjustments.void_total),
+        ];
+        for void in &self.adjustments.voids {
+            lines.push(amount_row(
+                &format!("  {} x{}", void.reason, void.count),
+                void.amount,
+            ));
+        }
+        lines.push(amount_row("Refunds", self.adjustments.refund_total));
+        for refund in &self.adjustments.refunds {
+            lines.push(amount_row(
+                &format!("  {} x{}", refund.reason, refund.count),
+                refund.amount,
+            ));
+        }
+        lines.push(String::new());
+        lines.push(amount_row("Payments", self.payment_total));
+        for payment in &self.payments {
+            lines.push(amount_row(
+                &format!("  {} x{}", payment.method, payment.count),
+                payment.amount,
+            ));
+        }
+        lines.push(amount_row("Refunded", -self.refund_total));
+        for refund in &self.refunds {
+            lines.push(amount_row(
+                &format!("  {} x{}", refund.method, refund.count),
+                -refund.amount,
+            ));
+        }
+        lines.push(amount_row("Net payments", self.net_payments));
+        lines.push(amount_row("Tips", self.tip_total));
+        lines.join("\n") + "\n"
+    }
+}
+
+fn row(label: &str, value: i64) -> String {
+    format!("{:<28}{:>14}", label, value)
+}
+
+fn amount_row(label: &str, amount: i64) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    format!(
+        "{:<28}{:>14}",
+        label,
+        format!("{}{}", sign, format_amount(amount))
+    )
+}
+
+/// Builds the Bill of every Table session out of the Orders and Discounts of the day.
+/// Checked out sessions are billed with the tax configuration they were settled with,
+/// so past days don't change along with the configuration, open ones with `tax_config`.
+fn bills(
+    sessions: &[Session],
+    orders: Vec<Order>,
+    discounts: Vec<Discount>,
+    tax_config: TaxConfig,
+) -> Vec<Bill> {
+    let mut session_orders: HashMap<i64, Vec<Order>> = HashMap::new();
+    for order in orders {
+        if let Some(session_id) = order.session_id {
+            session_orders.entry(session_id).or_default().push(order);
+        }
+    }
+    let mut session_discounts: HashMap<i64, Vec<Discount>> = HashMap::new();
+    for discount in discounts {
+        session_discounts
+            .entry(discount.session_id)
+            .or_default()
+            .push(discount);
+    }
+    sessions
+        .iter()
+        .map(|session| {
+            Bill::compute(
+                session.table_number,
+                session.session_id,
+                session_orders
+                    .get(&session.session_id)
+                    .map_or(&[], |orders| orders.as_slice()),
+                session_discounts
+                    .get(&session.session_id)
+                    .map_or(&[], |discounts| discounts.as_slice()),
+                session.tax_config().unwrap_or(tax_config),
+            )
+        })
+        .collect()
+}
+
+/// Computes the end-of-day report of a business day out of the Orders and the payment records.
+pub async fn load_z_report(
+    report_repository: &dyn db::report::Repository,
+    adjustment_repository: &dyn db::adjustment::Repository,
+    date: Date,
+) -> Result<ZReport, OperationError> {
+    let (from, to) = business_day(date);
+    let sessions = report_repository.list_sessions(from, to).await?;
+    let orders = report_repository.list_orders(from, to).await?;
+    let discounts = report_repository.list_discounts(from, to).await?;
+    let payments = report_repository.payment_totals(from, to).await?;
+    let refunds = report_repository.refund_totals(from, to).await?;
+    let adjustments = adjustment_repository.summarize(from, to).await?;
+
+    let open_sessions = sessions.iter().filter(|s| s.closed_at.is_none()).count() as i64;
+    let bills = bills(&sessions, orders, discounts, TaxConfig::from_env());
+    Ok(ZReport::new(
+        date,
+        &bills,
+        open_sessions,
+        payments,
+        refunds,
+        adjustments,
+    ))
+}
+
+#[derive(Debug)]
+enum ReportFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for ReportFailure {
+    fn from(e: AuthFailure) -> Self {
+        ReportFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for ReportFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to build report")
+    }
+}
+
+impl ResponseError for ReportFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            ReportFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            ReportFailure::Auth(e) => e.status_code(),
+            ReportFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            ReportFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            ReportFailure::Auth(e) => e.error_response(),
+            ReportFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+pub fn service() -> actix_web::Scope {
+    web::scope("/report")
+        .service(adjustments::handler)
+        .service(tips::handler)
+        .service(z::handler)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Month;
+
+    use super::*;
+    use crate::{
+        db::{adjustment::ReasonCode, discount::DiscountKind, payment::PaymentMethod},
+        tax::{RoundingMode, TaxMode},
+    };
+
+    fn order(order_id: i64, session_id: i64, quantity: i32, unit_price: i64) -> Order {
+        Order {
+            order_id,
+            session_id: Some(session_id),
+            quantity,
+            unit_price,
+            ..Order::new(3, 1, 5)
+        }
+    }
+
+    fn tax_config() -> TaxConfig {
+        TaxConfig {
+            service_charge: 500,
+            tax: 1_000,
+            mode: TaxMode::Exclusive,
+            rounding_unit: 1,
+            rounding_mode: RoundingMode::HalfUp,
+        }
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            parse_date(Some("2026-01-07".to_string())).unwrap(),
+            Date::from_calendar_date(2026, Month::January, 7).unwrap()
+        );
+        assert!(parse_date(Some("07/01/2026".to_string())).is_err());
+        assert!(parse_date(None).is_ok());
+    }
+
+    #[test]
+    fn test_business_day() {
+        let date = Date::from_calendar_date(2026, Month::January, 7).unwrap();
+        let (from, to) = business_day(date);
+        assert_eq!(to - from, time::Duration::days(1));
+        assert_eq!(from.offset(), outlet_offset());
+        assert_eq!(from.date(), date);
+        assert_eq!(from.time(), Time::MIDNIGHT);
+    }
+
+    #[test]
+    fn test_adjustment_report() {
+        let total = |kind: &str, reason, count, amount| AdjustmentTotal {
+            kind: kind.to_string(),
+            reason,
+            count,
+            amount,
+        };
+        let report = AdjustmentReport::new(vec![
+            total("void", ReasonCode::EntryError, 2, 53_000),
+            total("void", ReasonCode::GuestCancelled, 1, 18_000),
+            total("refund", ReasonCode::QualityComplaint, 1, 34_650),
+        ]);
+        assert_eq!(report.voids.len(), 2);
+        assert_eq!(report.voids[0].reason, "entry_error");
+        assert_eq!(report.void_total, 71_000);
+        assert_eq!(report.refunds.len(), 1);
+        assert_eq!(report.refund_total, 34_650);
+    }
+
+    #[test]
+    fn test_bills() {
+        let sessions = vec![Session::new(7, 3), Session::new(8, 4)];
+        let voided = Order {
+            voided_at: Some(OffsetDateTime::now_utc()),
+            ..order(3, 7, 1, 18_000)
+        };
+        let orders = vec![
+            order(1, 7, 2, 25_000),
+            order(2, 8, 1, 30_000),
+            voided,
+            order(4, 9, 1, 99_000),
+        ];
+        let discounts = vec![Discount::new(8, None, DiscountKind::Fixed, 10_000)];
+
+        let bills = bills(&sessions, orders, discounts, tax_config());
+        assert_eq!(bills.len(), 2);
+        assert_eq!(bills[0].session_id, 7);
+        assert_eq!(bills[0].subtotal, 50_000);
+        assert_eq!(bills[0].voided.len(), 1);
+        assert_eq!(bills[1].table_number, 4);
+        assert_eq!(bills[1].discount, 10_000);
+        assert_eq!(bills[1].total, 23_100);
+    }
+
+    #[test]
+    fn test_bills_settled_config() {
+        // checked out at 10% service charge and 11% tax, the outlet charges 5% and 10% by now:
+        let settled = Session {
+            closed_at: Some(OffsetDateTime::now_utc()),
+            service_charge_rate: Some(1_000),
+            tax_rate: Some(1_100),
+            tax_mode: Some("exclusive".to_string()),
+            rounding_unit: Some(1),
+            rounding_mode: Some("half_up".to_string()),
+            ..Session::new(7, 3)
+        };
+        let sessions = vec![settled, Session::new(8, 4)];
+        let orders = vec![order(1, 7, 1, 100_000), order(2, 8, 1, 100_000)];
+
+        let bills = bills(&sessions, orders, vec![], tax_config());
+        assert_eq!(bills[0].service_charge, 10_000);
+        assert_eq!(bills[0].tax, 12_100);
+        assert_eq!(bills[0].total, 122_100);
+        assert_eq!(bills[1].service_charge, 5_000);
+        assert_eq!(bills[1].total, 115_500);
+    }
+
+    #[test]
+    fn test_z_report() {
+        let sessions = vec![Session::new(7, 3), Session::new(8, 4)];
+        let orders = vec![order(1, 7, 2, 25_000), order(2, 8, 1, 30_000)];
+        let discounts = vec![Discount::new(8, None, DiscountKind::Fixed, 10_000)];
+        let bills = bills(&sessions, orders, discounts, tax_config());
+        let total = |method, count, amount| MethodTotal {
+            method,
+            count,
+            amount,
+            tip: 0,
+        };
+
+        let report = ZReport::new(
+            Date::from_calendar_date(2026, Month::January, 7).unwrap(),
+            &bills,
+            1,
+            vec![
+                total(PaymentMethod::Cash, 1, 57_750),
+                MethodTotal {
+                    tip: 2_000,
+                    ..total(PaymentMethod::Card, 1, 23_100)
+                },
+            ],
+            vec![total(PaymentMethod::Card, 1, 5_000)],
+            vec![AdjustmentTotal {
+                kind: "refund".to_string(),
+                reason: ReasonCode::Overcharge,
+                count: 1,
+                amount: 5_000,
+            }],
+        );
+        assert_eq!(report.date, "2026-01-07");
+        assert_eq!(report.sessions, 2);
+        assert_eq!(report.open_sessions, 1);
+        assert_eq!(report.orders, 2);
+        assert_eq!(report.gross_sales, 80_000);
+        assert_eq!(report.discounts, 10_000);
+        assert_eq!(report.net_sales, 70_000);
+        assert_eq!(report.service_charge, 3_500);
+        assert_eq!(report.tax, 7_350);
+        assert_eq!(report.total, 80_850);
+        assert_eq!(report.payments[0].method, "cash");
+        assert_eq!(report.payment_total, 80_850);
+        assert_eq!(report.refund_total, 5_000);
+        assert_eq!(report.net_payments, 75_850);
+        assert_eq!(report.tip_total, 2_000);
+        assert_eq!(report.adjustments.refund_total, 5_000);
+
+        let text = report.to_text();
+        assert!(text.starts_with("Z-REPORT 2026-01-07\n"));
+        assert!(text.contains("Net payments"));
+        assert!(text.lines().all(|line| line.len() <= 42));
+    }
+}
@@ -0,0 +1,262 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, report::ShiftWork, staff::StaffRole},
+    order::BadRequestBody,
+    tip::{TipPoolBasis, TipPoolRules, Work},
+};
+
+use super::{business_day, parse_date, ReportFailure};
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    date: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+impl QueryParams {
+    /// The shift the tips are pooled for, either `from`..`to` or a whole business day.
+    fn window(self) -> Result<(OffsetDateTime, OffsetDateTime), ReportFailure> {
+        let invalid = |message: &str| {
+            ReportFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from(message),
+            })
+        };
+        match (self.from, self.to) {
+            (Some(from), Some(to)) => {
+                let from = OffsetDateTime::parse(&from, &Rfc3339)
+                    .map_err(|_| invalid("from must be formatted as RFC3339"))?;
+                let to = OffsetDateTime::parse(&to, &Rfc3339)
+                    .map_err(|_| invalid("to must be formatted as RFC3339"))?;
+                if from >= to {
+                    return Err(invalid("from must be before to"));
+                }
+                Ok((from, to))
+            }
+            (None, None) => Ok(business_day(parse_date(self.date)?)),
+            _ => Err(invalid("from and to must be given together")),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ShareData {
+    staff_id: i64,
+    name: String,
+    role: String,
+    minutes: i64,
+    points: i64,
+    amount: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    from: String,
+    to: String,
+    basis: TipPoolBasis,
+    tips: i64,
+    shares: Vec<ShareData>,
+    /// what is left in the pool when nobody on the shift earns a share.
+    undistributed: i64,
+}
+
+/// Shares the tips received during a shift across the Staff who worked it, manager only.
+#[get("/tips")]
+async fn handler(
+    report_repository: web::Data<dyn db::report::Repository>,
+    identity: StaffIdentity,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, ReportFailure> {
+    identity.require(StaffRole::Manager)?;
+    let (from, to) = query_params.into_inner().window()?;
+
+    let payments = report_repository
+        .payment_totals(from, to)
+        .await
+        .map_err(ReportFailure::InternalServerError)?;
+    let staff: Vec<ShiftWork> = report_repository
+        .list_shift_work(from, to)
+        .await
+        .map_err(ReportFailure::InternalServerError)?;
+
+    let tips: i64 = payments.iter().map(|p| p.tip).sum();
+    let rules = TipPoolRules::from_env();
+    let work: Vec<Work> = staff
+        .iter()
+        .map(|s| Work {
+            staff_id: s.staff_id,
+            role: s.role,
+            minutes: s.minutes,
+        })
+        .collect();
+    let shares: Vec<ShareData> = staff
+        .into_iter()
+        .zip(rules.distribute(tips, &work))
+        .map(|(s, share)| ShareData {
+            staff_id: s.staff_id,
+            name: s.name,
+            role: s.role.as_str().to_string(),
+            minutes: s.minutes,
+            points: rules.points(s.role),
+            amount: share.amount,
+        })
+        .collect();
+
+    let format = |at: OffsetDateTime| at.format(&Rfc3339).unwrap_or("---".to_string());
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        from: format(from),
+        to: format(to),
+        basis: rules.basis,
+        undistributed: tips - shares.iter().map(|s| s.amount).sum::<i64>(),
+        tips,
+        shares,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{payment::PaymentMethod, report::MethodTotal, staff::Staff, OperationError};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    async fn call(
+        report_repo: crate::db::report::MockRepository,
+        role: StaffRole,
+        uri: &str,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_report_repo: Arc<dyn db::report::Repository> = Arc::new(report_repo);
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_report_repo))
+                .app_data(Data::from(staff_repo(role)))
+                .service(web::scope("/report").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(uri)
+            .insert_header(("Authorization", "Bearer some-token"))
+            .to_request();
+        test::call_service(&app, req).await
+    }
+
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: getting the tip pool.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let report_repo = crate::db::report::MockRepository::new();
+        let resp = call(report_repo, StaffRole::Waiter, "/report/tips").await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a shift without its end.
+    /// when: getting the tip pool.
+    /// then: response status code is 400.
+    async fn test_invalid_window() {
+        let report_repo = crate::db::report::MockRepository::new();
+        let resp = call(
+            report_repo,
+            StaffRole::Manager,
+            "/report/tips?from=2026-01-07T10:00:00%2B07:00",
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: tips received during a shift worked by a waiter, a cook and a manager.
+    /// when: getting the tip pool of that shift.
+    /// then: the tips are shared by the points of their role and the minutes they worked.
+    async fn test_success() {
+        let mut report_repo = crate::db::report::MockRepository::new();
+        report_repo
+            .expect_payment_totals()
+            .withf(|from, to| *to - *from == time::Duration::hours(8))
+            .once()
+            .returning(|_, _| {
+                Ok(vec![
+                    MethodTotal {
+                        method: PaymentMethod::Cash,
+                        count: 3,
+                        amount: 300_000,
+                        tip: 20_000,
+                    },
+                    MethodTotal {
+                        method: PaymentMethod::Card,
+                        count: 1,
+                        amount: 100_000,
+                        tip: 10_001,
+                    },
+                ])
+            });
+        report_repo
+            .expect_list_shift_work()
+            .once()
+            .returning(|_, _| {
+                let work = |staff_id, role, minutes| ShiftWork {
+                    staff_id,
+                    name: format!("Staff {}", staff_id),
+                    role,
+                    minutes,
+                };
+                Ok(vec![
+                    work(1, StaffRole::Waiter, 480),
+                    work(2, StaffRole::Cook, 240),
+                    work(3, StaffRole::Manager, 480),
+                ])
+            });
+
+        let resp = call(
+            report_repo,
+            StaffRole::Manager,
+            "/report/tips?from=2026-01-07T10:00:00%2B07:00&to=2026-01-07T18:00:00%2B07:00",
+        )
+        .await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.tips, 30_001);
+        assert_eq!(response_body.basis, TipPoolBasis::Hours);
+        let amounts: Vec<i64> = response_body.shares.iter().map(|s| s.amount).collect();
+        assert_eq!(amounts, vec![20_001, 10_000, 0]);
+        assert_eq!(response_body.shares[1].role, "cook");
+        assert_eq!(response_body.undistributed, 0);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: getting the tip pool.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut report_repo = crate::db::report::MockRepository::new();
+        report_repo
+            .expect_payment_totals()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let resp = call(
+            report_repo,
+            StaffRole::Manager,
+            "/report/tips?date=2026-01-07",
+        )
+        .await;
+        assert!(resp.status().is_server_error());
+    }
+}
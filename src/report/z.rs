@@ -0,0 +1,225 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+};
+
+use super::{load_z_report, parse_date, ReportFailure};
+
+#[derive(Serialize, Deserialize)]
+struct QueryParams {
+    date: Option<String>,
+}
+
+/// The end-of-day report of a business day, manager only.
+#[get("/z")]
+async fn handler(
+    report_repository: web::Data<dyn db::report::Repository>,
+    adjustment_repository: web::Data<dyn db::adjustment::Repository>,
+    identity: StaffIdentity,
+    query_params: web::Query<QueryParams>,
+) -> Result<HttpResponse, ReportFailure> {
+    identity.require(StaffRole::Manager)?;
+    let date = parse_date(query_params.into_inner().date)?;
+
+    let report = load_z_report(
+        report_repository.get_ref(),
+        adjustment_repository.get_ref(),
+        date,
+    )
+    .await
+    .map_err(ReportFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::{
+        db::{
+            order::Order, payment::PaymentMethod, report::MethodTotal, session::Session,
+            staff::Staff, OperationError,
+        },
+        report::ZReport,
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    fn adjustment_repo() -> Arc<dyn db::adjustment::Repository> {
+        let mut adjustment_repo = crate::db::adjustment::MockRepository::new();
+        adjustment_repo
+            .expect_summarize()
+            .returning(|_, _| Ok(vec![]));
+        Arc::new(adjustment_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a waiter access token.
+    /// when: getting the Z-report.
+    /// then: response status code is 403.
+    async fn test_forbidden_role() {
+        let report_repo = crate::db::report::MockRepository::new();
+        let arc_report_repo: Arc<dyn db::report::Repository> = Arc::new(report_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_report_repo))
+                .app_data(Data::from(adjustment_repo()))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/report").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/report/z?date=2026-01-07")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: a malformed date.
+    /// when: getting the Z-report.
+    /// then: response status code is 400.
+    async fn test_invalid_date() {
+        let report_repo = crate::db::report::MockRepository::new();
+        let arc_report_repo: Arc<dyn db::report::Repository> = Arc::new(report_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_report_repo))
+                .app_data(Data::from(adjustment_repo()))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/report").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/report/z?date=07-01-2026")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a business day with a paid session.
+    /// when: getting the Z-report of that day.
+    /// then: its sales and payments are summed up.
+    async fn test_success() {
+        let mut report_repo = crate::db::report::MockRepository::new();
+        report_repo
+            .expect_list_sessions()
+            .withf(|from, to| *to - *from == time::Duration::days(1))
+            .once()
+            .returning(|_, _| {
+                Ok(vec![Session {
+                    closed_at: Some(time::OffsetDateTime::now_utc()),
+                    ..Session::new(7, 3)
+                }])
+            });
+        report_repo.expect_list_orders().once().returning(|_, _| {
+            Ok(vec![Order {
+                order_id: 1,
+                session_id: Some(7),
+                quantity: 2,
+                unit_price: 25_000,
+                ..Order::new(3, 1, 5)
+            }])
+        });
+        report_repo
+            .expect_list_discounts()
+            .once()
+            .returning(|_, _| Ok(vec![]));
+        report_repo
+            .expect_payment_totals()
+            .once()
+            .returning(|_, _| {
+                Ok(vec![MethodTotal {
+                    method: PaymentMethod::Qris,
+                    count: 1,
+                    amount: 57_750,
+                    tip: 5_000,
+                }])
+            });
+        report_repo
+            .expect_refund_totals()
+            .once()
+            .returning(|_, _| Ok(vec![]));
+        let arc_report_repo: Arc<dyn db::report::Repository> = Arc::new(report_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_report_repo))
+                .app_data(Data::from(adjustment_repo()))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/report").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/report/z?date=2026-01-07")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: ZReport = test::read_body_json(resp).await;
+        assert_eq!(response_body.date, "2026-01-07");
+        assert_eq!(response_body.sessions, 1);
+        assert_eq!(response_body.open_sessions, 0);
+        assert_eq!(response_body.orders, 1);
+        assert_eq!(response_body.gross_sales, 50_000);
+        assert_eq!(response_body.total, 57_750);
+        assert_eq!(response_body.payments[0].method, "qris");
+        assert_eq!(response_body.net_payments, 57_750);
+        assert_eq!(response_body.tip_total, 5_000);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: getting the Z-report.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut report_repo = crate::db::report::MockRepository::new();
+        report_repo
+            .expect_list_sessions()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let arc_report_repo: Arc<dyn db::report::Repository> = Arc::new(report_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_report_repo))
+                .app_data(Data::from(adjustment_repo()))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/report").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/report/z")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
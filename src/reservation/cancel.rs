@@ -0,0 +1,163 @@
+use actix_web::{delete, web, HttpResponse};
+
+use crate::{db, order::BadRequestBody};
+
+use super::{ensure_booked, PathParams, ReservationFailure, SuccessResponseBody};
+
+#[delete("/{reservation_id}")]
+async fn handler(
+    reservation_repository: web::Data<dyn db::reservation::Repository>,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, ReservationFailure> {
+    let reservation_id = path_params.reservation_id as i64;
+
+    let reservation = match reservation_repository
+        .get_reservation(reservation_id)
+        .await
+        .map_err(ReservationFailure::InternalServerError)?
+    {
+        Some(reservation) => reservation,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+    ensure_booked(&reservation)?;
+
+    match reservation_repository
+        .cancel_reservation(reservation_id)
+        .await
+        .map_err(ReservationFailure::InternalServerError)?
+    {
+        Some(reservation) => Ok(HttpResponse::Ok().json(SuccessResponseBody::new(reservation))),
+        // the reservation got seated or cancelled in between:
+        None => Err(ReservationFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!("reservation {} can no longer be cancelled", reservation_id),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use time::{Duration, OffsetDateTime};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        reservation::{Reservation, ReservationStatus},
+        OperationError,
+    };
+
+    fn booked(reservation_id: i64) -> Reservation {
+        let from = OffsetDateTime::now_utc() + Duration::hours(2);
+        Reservation {
+            reservation_id,
+            ..Reservation::new(
+                12,
+                4,
+                "Sukab".to_string(),
+                "+62 812 0000 0000".to_string(),
+                from,
+                from + Duration::hours(2),
+            )
+        }
+    }
+
+    #[actix_web::test]
+    /// given: a reservation which has been seated.
+    /// when: cancel the reservation.
+    /// then: response status code is 409.
+    async fn test_already_seated() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|reservation_id| {
+                Ok(Some(Reservation {
+                    status: ReservationStatus::Seated,
+                    ..booked(reservation_id)
+                }))
+            });
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::delete()
+            .uri("/reservation/9")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: a booked reservation.
+    /// when: cancel the reservation.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|reservation_id| Ok(Some(booked(reservation_id))));
+        reservation_repo
+            .expect_cancel_reservation()
+            .once()
+            .returning(|reservation_id| {
+                Ok(Some(Reservation {
+                    status: ReservationStatus::Cancelled,
+                    ..booked(reservation_id)
+                }))
+            });
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::delete()
+            .uri("/reservation/9")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.reservation.status, "cancelled");
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: cancel the reservation.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::delete()
+            .uri("/reservation/9")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,255 @@
+use actix_web::{post, web, HttpResponse};
+
+use crate::db;
+
+use super::{
+    assign_table, storing_failure, Input, RequestBody, ReservationFailure, SuccessResponseBody,
+};
+
+#[post("")]
+async fn handler(
+    reservation_repository: web::Data<dyn db::reservation::Repository>,
+    table_repository: web::Data<dyn db::table::Repository>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, ReservationFailure> {
+    let input = Input::new(request_body.into_inner())?.validate()?;
+
+    let table_number = assign_table(
+        table_repository.get_ref(),
+        reservation_repository.get_ref(),
+        &input,
+        0,
+    )
+    .await?;
+
+    let reservation = reservation_repository
+        .create_reservation(input.into_reservation(table_number))
+        .await
+        .map_err(storing_failure(table_number))?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody::new(reservation)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{reservation::Reservation, table::DiningTable, OperationError};
+
+    fn request_body(party_size: u32, table_number: Option<u32>) -> RequestBody {
+        let reserved_at = OffsetDateTime::now_utc() + Duration::days(1);
+        RequestBody {
+            party_size,
+            reserved_at: reserved_at.format(&Rfc3339).unwrap(),
+            contact_name: "Sukab".to_string(),
+            contact_phone: "+62 812 0000 0000".to_string(),
+            table_number,
+        }
+    }
+
+    #[actix_web::test]
+    /// given: zero party_size.
+    /// when: creating new reservation.
+    /// then: response status code is 400.
+    async fn test_invalid_party_size() {
+        let reservation_repo = crate::db::reservation::MockRepository::new();
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+        let table_repo = crate::db::table::MockRepository::new();
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/reservation")
+            .set_json(request_body(0, None))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: no requested table and free tables are available.
+    /// when: creating new reservation.
+    /// then: the smallest fitting table is assigned.
+    async fn test_success() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_create_reservation()
+            .once()
+            .returning(|reservation| {
+                Ok(Reservation {
+                    reservation_id: 5,
+                    ..reservation
+                })
+            });
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let mut table_repo = crate::db::table::MockRepository::new();
+        table_repo
+            .expect_list_available()
+            .once()
+            .returning(|_, _, _, _| {
+                Ok(vec![
+                    DiningTable {
+                        table_number: 21,
+                        capacity: 4,
+                    },
+                    DiningTable {
+                        table_number: 81,
+                        capacity: 8,
+                    },
+                ])
+            });
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/reservation")
+            .set_json(request_body(3, None))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.reservation.reservation_id, 5);
+        assert_eq!(response_body.reservation.table_number, 21);
+        assert_eq!(response_body.reservation.status, "booked");
+    }
+
+    #[actix_web::test]
+    /// given: the requested table is already booked at that time.
+    /// when: creating new reservation.
+    /// then: response status code is 409.
+    async fn test_conflict() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo.expect_list_conflicts().once().returning(
+            |table_number, from, until, _| {
+                Ok(vec![Reservation::new(
+                    table_number,
+                    2,
+                    "Alina".to_string(),
+                    "+62 812 1111 1111".to_string(),
+                    from,
+                    until,
+                )])
+            },
+        );
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let mut table_repo = crate::db::table::MockRepository::new();
+        table_repo
+            .expect_get_table()
+            .once()
+            .returning(|table_number| {
+                Ok(Some(DiningTable {
+                    table_number,
+                    capacity: 4,
+                }))
+            });
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/reservation")
+            .set_json(request_body(2, Some(21)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: the requested table is too small for the party.
+    /// when: creating new reservation.
+    /// then: response status code is 400.
+    async fn test_over_capacity() {
+        let reservation_repo = crate::db::reservation::MockRepository::new();
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let mut table_repo = crate::db::table::MockRepository::new();
+        table_repo
+            .expect_get_table()
+            .once()
+            .returning(|table_number| {
+                Ok(Some(DiningTable {
+                    table_number,
+                    capacity: 2,
+                }))
+            });
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/reservation")
+            .set_json(request_body(6, Some(3)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: creating new reservation.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let reservation_repo = crate::db::reservation::MockRepository::new();
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let mut table_repo = crate::db::table::MockRepository::new();
+        table_repo
+            .expect_list_available()
+            .once()
+            .returning(|_, _, _, _| Err(OperationError::OtherError));
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/reservation")
+            .set_json(request_body(2, None))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
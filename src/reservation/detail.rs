@@ -0,0 +1,124 @@
+use actix_web::{get, web, HttpResponse};
+
+use crate::db;
+
+use super::{PathParams, ReservationFailure, SuccessResponseBody};
+
+#[get("/{reservation_id}")]
+async fn handler(
+    reservation_repository: web::Data<dyn db::reservation::Repository>,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, ReservationFailure> {
+    let result_data = reservation_repository
+        .get_reservation(path_params.reservation_id as i64)
+        .await
+        .map_err(ReservationFailure::InternalServerError)?;
+
+    match result_data {
+        Some(reservation) => Ok(HttpResponse::Ok().json(SuccessResponseBody::new(reservation))),
+        None => Ok(HttpResponse::NotFound().body("".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use time::{Duration, OffsetDateTime};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{reservation::Reservation, OperationError};
+
+    #[actix_web::test]
+    /// given: an unknown reservation_id.
+    /// when: get reservation detail.
+    /// then: response status code is 404.
+    async fn test_not_found() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|_| Ok(None));
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/reservation/9").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: an existing reservation.
+    /// when: get reservation detail.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|reservation_id| {
+                let from = OffsetDateTime::now_utc() + Duration::hours(2);
+                Ok(Some(Reservation {
+                    reservation_id,
+                    ..Reservation::new(
+                        12,
+                        4,
+                        "Sukab".to_string(),
+                        "+62 812 0000 0000".to_string(),
+                        from,
+                        from + Duration::hours(2),
+                    )
+                }))
+            });
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/reservation/9").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.reservation.reservation_id, 9);
+        assert_eq!(response_body.reservation.table_number, 12);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: get reservation detail.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/reservation/9").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
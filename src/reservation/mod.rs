@@ -0,0 +1,324 @@
+use std::{env, fmt};
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+use tokio_postgres::error::SqlState;
+
+use crate::{
+    db::{
+        self,
+        reservation::{Reservation, ReservationStatus},
+        OperationError,
+    },
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+pub mod cancel;
+pub mod create;
+pub mod detail;
+pub mod seat;
+pub mod update;
+
+/// How long a reservation holds its table, in minutes.
+/// Read from `RESERVATION_DURATION`, or defer to predefined default.
+fn reservation_duration() -> Duration {
+    const DEFAULT_DURATION: i64 = 120;
+    let minutes = match env::var("RESERVATION_DURATION").ok() {
+        Some(v) => v.parse().unwrap_or(DEFAULT_DURATION),
+        None => DEFAULT_DURATION,
+    };
+    Duration::minutes(minutes)
+}
+
+/// How far from its `reserved_from` a reservation may be seated, either way, in minutes.
+/// Read from `RESERVATION_SEAT_WINDOW`, or defer to predefined default.
+pub(crate) fn seat_window() -> Duration {
+    const DEFAULT_WINDOW: i64 = 30;
+    let minutes = match env::var("RESERVATION_SEAT_WINDOW").ok() {
+        Some(v) => v.parse().unwrap_or(DEFAULT_WINDOW),
+        None => DEFAULT_WINDOW,
+    };
+    Duration::minutes(minutes)
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReservationData {
+    reservation_id: i64,
+    table_number: i32,
+    party_size: i32,
+    contact_name: String,
+    contact_phone: String,
+    reserved_from: String,
+    reserved_until: String,
+    status: String,
+    session_id: Option<i64>,
+}
+
+impl ReservationData {
+    fn format_time(dt: OffsetDateTime) -> String {
+        dt.format(&Rfc3339).unwrap_or("---".to_string())
+    }
+}
+
+impl From<Reservation> for ReservationData {
+    fn from(reservation: Reservation) -> Self {
+        Self {
+            reservation_id: reservation.reservation_id,
+            table_number: reservation.table_number,
+            party_size: reservation.party_size,
+            contact_name: reservation.contact_name,
+            contact_phone: reservation.contact_phone,
+            reserved_from: ReservationData::format_time(reservation.reserved_from),
+            reserved_until: ReservationData::format_time(reservation.reserved_until),
+            status: reservation.status.as_str().to_string(),
+            session_id: reservation.session_id,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    reservation: ReservationData,
+}
+
+impl SuccessResponseBody {
+    fn new(reservation: Reservation) -> Self {
+        Self {
+            reservation: reservation.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    reservation_id: u32,
+}
+
+/// The booking details sent by the User, shared by create and modify.
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    party_size: u32,
+    reserved_at: String,
+    contact_name: String,
+    contact_phone: String,
+    table_number: Option<u32>,
+}
+
+/// The validated booking details of a Reservation.
+struct Input {
+    party_size: u32,
+    reserved_from: OffsetDateTime,
+    reserved_until: OffsetDateTime,
+    contact_name: String,
+    contact_phone: String,
+    table_number: Option<u32>,
+}
+
+impl Input {
+    fn new(rb: RequestBody) -> Result<Self, ReservationFailure> {
+        let reserved_from = OffsetDateTime::parse(&rb.reserved_at, &Rfc3339).map_err(|_| {
+            ReservationFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("reserved_at must be a RFC3339 timestamp"),
+            })
+        })?;
+        Ok(Self {
+            party_size: rb.party_size,
+            reserved_from,
+            reserved_until: reserved_from + reservation_duration(),
+            contact_name: rb.contact_name.trim().to_string(),
+            contact_phone: rb.contact_phone.trim().to_string(),
+            table_number: rb.table_number,
+        })
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, ReservationFailure> {
+        let invalid = |message: &str| {
+            Err(ReservationFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: message.to_string(),
+            }))
+        };
+        if self.party_size < 1 || self.party_size > 20 {
+            return invalid("party_size must be in range of 1 to 20");
+        }
+        if self.contact_name.is_empty() || self.contact_name.chars().count() > 300 {
+            return invalid("contact_name must be between 1 and 300 characters");
+        }
+        if self.contact_phone.is_empty() || self.contact_phone.chars().count() > 50 {
+            return invalid("contact_phone must be between 1 and 50 characters");
+        }
+        if let Some(table_number) = self.table_number {
+            if !(1..=100).contains(&table_number) {
+                return invalid("table_number must be in range of 1 to 100");
+            }
+        }
+        if self.reserved_from <= OffsetDateTime::now_utc() {
+            return invalid("reserved_at must be in the future");
+        }
+        Ok(self)
+    }
+
+    fn into_reservation(self, table_number: i32) -> Reservation {
+        Reservation::new(
+            table_number,
+            self.party_size as i32,
+            self.contact_name,
+            self.contact_phone,
+            self.reserved_from,
+            self.reserved_until,
+        )
+    }
+}
+
+/// Picks the Table for a booking:
+/// a requested Table must fit the party and be free for the whole slot,
+/// otherwise the smallest free Table that fits the party is assigned.
+async fn assign_table(
+    table_repository: &dyn db::table::Repository,
+    reservation_repository: &dyn db::reservation::Repository,
+    input: &Input,
+    exclude_id: i64,
+) -> Result<i32, ReservationFailure> {
+    let party_size = input.party_size as i32;
+    match input.table_number {
+        Some(table_number) => {
+            let table_number = table_number as i32;
+            let table = table_repository
+                .get_table(table_number)
+                .await
+                .map_err(ReservationFailure::InternalServerError)?
+                .ok_or_else(|| {
+                    ReservationFailure::InvalidInput(BadRequestBody {
+                        error: true,
+                        message: format!("table {} does not exist", table_number),
+                    })
+                })?;
+            if table.capacity < party_size {
+                return Err(ReservationFailure::InvalidInput(BadRequestBody {
+                    error: true,
+                    message: format!(
+                        "table {} only seats {} guests",
+                        table_number, table.capacity
+                    ),
+                }));
+            }
+            let conflicts = reservation_repository
+                .list_conflicts(
+                    table_number,
+                    input.reserved_from,
+                    input.reserved_until,
+                    exclude_id,
+                )
+                .await
+                .map_err(ReservationFailure::InternalServerError)?;
+            if !conflicts.is_empty() {
+                return Err(ReservationFailure::Conflict(BadRequestBody {
+                    error: true,
+                    message: format!("table {} is already booked at that time", table_number),
+                }));
+            }
+            Ok(table_number)
+        }
+        None => table_repository
+            .list_available(
+                party_size,
+                input.reserved_from,
+                input.reserved_until,
+                exclude_id,
+            )
+            .await
+            .map_err(ReservationFailure::InternalServerError)?
+            .first()
+            .map(|table| table.table_number)
+            .ok_or_else(|| {
+                ReservationFailure::Conflict(BadRequestBody {
+                    error: true,
+                    message: format!("no table is available for a party of {}", party_size),
+                })
+            }),
+    }
+}
+
+/// Turns a failure storing a booking of a Table into a response,
+/// a booking that overlapped another one racing past `assign_table` is a conflict rather than a server error.
+fn storing_failure(table_number: i32) -> impl FnOnce(OperationError) -> ReservationFailure {
+    move |e| match &e {
+        OperationError::FailedToCreate(error) | OperationError::FailedToUpdate(error)
+            if error.code() == Some(&SqlState::EXCLUSION_VIOLATION) =>
+        {
+            ReservationFailure::Conflict(BadRequestBody {
+                error: true,
+                message: format!("table {} is already booked at that time", table_number),
+            })
+        }
+        _ => ReservationFailure::InternalServerError(e),
+    }
+}
+
+/// Ensures the Reservation can still be changed, only booked Reservations can.
+fn ensure_booked(reservation: &Reservation) -> Result<(), ReservationFailure> {
+    match reservation.status {
+        ReservationStatus::Booked => Ok(()),
+        status => Err(ReservationFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!(
+                "reservation {} is already {}",
+                reservation.reservation_id,
+                status.as_str()
+            ),
+        })),
+    }
+}
+
+#[derive(Debug)]
+enum ReservationFailure {
+    InvalidInput(BadRequestBody),
+    Conflict(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl fmt::Display for ReservationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to process reservation")
+    }
+}
+
+impl ResponseError for ReservationFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            ReservationFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            ReservationFailure::Conflict(_) => StatusCode::CONFLICT,
+            ReservationFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            ReservationFailure::InvalidInput(r) | ReservationFailure::Conflict(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            ReservationFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+pub fn service() -> actix_web::Scope {
+    web::scope("/reservation")
+        .service(create::handler)
+        .service(detail::handler)
+        .service(update::handler)
+        .service(cancel::handler)
+        .service(seat::handler)
+}
@@ -0,0 +1,239 @@
+use actix_web::{post, web, HttpResponse};
+use time::OffsetDateTime;
+
+use crate::{
+    db::{self, session::SeatOutcome},
+    order::BadRequestBody,
+};
+
+use super::{ensure_booked, seat_window, PathParams, ReservationFailure, SuccessResponseBody};
+
+#[post("/{reservation_id}/seat")]
+async fn handler(
+    reservation_repository: web::Data<dyn db::reservation::Repository>,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, ReservationFailure> {
+    let reservation_id = path_params.reservation_id as i64;
+
+    let reservation = match reservation_repository
+        .get_reservation(reservation_id)
+        .await
+        .map_err(ReservationFailure::InternalServerError)?
+    {
+        Some(reservation) => reservation,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+    ensure_booked(&reservation)?;
+
+    // the table may be promised to someone else outside of the reserved time:
+    let window = seat_window();
+    let now = OffsetDateTime::now_utc();
+    if now < reservation.reserved_from - window || now > reservation.reserved_from + window {
+        return Err(ReservationFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!(
+                "reservation {} can only be seated within {} minutes of its reserved time",
+                reservation_id,
+                window.whole_minutes()
+            ),
+        }));
+    }
+
+    // the previous guests must have checked out before the party can be seated,
+    // the session is opened along with seating the reservation or not at all:
+    match reservation_repository
+        .seat_reservation(reservation_id)
+        .await
+        .map_err(ReservationFailure::InternalServerError)?
+    {
+        SeatOutcome::Seated(reservation) => {
+            Ok(HttpResponse::Ok().json(SuccessResponseBody::new(reservation)))
+        }
+        SeatOutcome::Occupied => Err(ReservationFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!(
+                "table {} is still occupied by another session",
+                reservation.table_number
+            ),
+        })),
+        SeatOutcome::Gone => Err(ReservationFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!("reservation {} can no longer be seated", reservation_id),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use time::{Duration, OffsetDateTime};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        reservation::{Reservation, ReservationStatus},
+        OperationError,
+    };
+
+    fn booked(reservation_id: i64) -> Reservation {
+        booked_at(
+            reservation_id,
+            OffsetDateTime::now_utc() + Duration::minutes(10),
+        )
+    }
+
+    fn booked_at(reservation_id: i64, from: OffsetDateTime) -> Reservation {
+        Reservation {
+            reservation_id,
+            ..Reservation::new(
+                12,
+                4,
+                "Sukab".to_string(),
+                "+62 812 0000 0000".to_string(),
+                from,
+                from + Duration::hours(2),
+            )
+        }
+    }
+
+    #[actix_web::test]
+    /// given: the reserved table is still occupied.
+    /// when: seat a reservation.
+    /// then: response status code is 409.
+    async fn test_table_occupied() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|reservation_id| Ok(Some(booked(reservation_id))));
+        reservation_repo
+            .expect_seat_reservation()
+            .once()
+            .returning(|_| Ok(SeatOutcome::Occupied));
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/reservation/9/seat")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: the reservation is hours away or long past.
+    /// when: seat a reservation.
+    /// then: response status code is 409 and no session is opened.
+    async fn test_outside_window() {
+        for offset in [Duration::hours(3), Duration::hours(-3)] {
+            let mut reservation_repo = crate::db::reservation::MockRepository::new();
+            reservation_repo
+                .expect_get_reservation()
+                .once()
+                .returning(move |reservation_id| {
+                    Ok(Some(booked_at(
+                        reservation_id,
+                        OffsetDateTime::now_utc() + offset,
+                    )))
+                });
+            reservation_repo.expect_seat_reservation().never();
+            let arc_reservation_repo: Arc<dyn db::reservation::Repository> =
+                Arc::new(reservation_repo);
+
+            let app = test::init_service(
+                App::new()
+                    .app_data(Data::from(arc_reservation_repo))
+                    .service(web::scope("/reservation").service(handler)),
+            )
+            .await;
+
+            let req = test::TestRequest::post()
+                .uri("/reservation/9/seat")
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::CONFLICT);
+        }
+    }
+
+    #[actix_web::test]
+    /// given: a booked reservation and a free table.
+    /// when: seat a reservation.
+    /// then: a session is opened and the reservation is seated into it.
+    async fn test_success() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|reservation_id| Ok(Some(booked(reservation_id))));
+        reservation_repo
+            .expect_seat_reservation()
+            .withf(|reservation_id| *reservation_id == 9)
+            .once()
+            .returning(|reservation_id| {
+                Ok(SeatOutcome::Seated(Reservation {
+                    status: ReservationStatus::Seated,
+                    session_id: Some(77),
+                    ..booked(reservation_id)
+                }))
+            });
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/reservation/9/seat")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.reservation.status, "seated");
+        assert_eq!(response_body.reservation.session_id, Some(77));
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: seat a reservation.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|reservation_id| Ok(Some(booked(reservation_id))));
+        reservation_repo
+            .expect_seat_reservation()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/reservation/9/seat")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,212 @@
+use actix_web::{put, web, HttpResponse};
+
+use crate::{
+    db::{self, reservation::Reservation},
+    order::BadRequestBody,
+};
+
+use super::{
+    assign_table, ensure_booked, storing_failure, Input, PathParams, RequestBody,
+    ReservationFailure, SuccessResponseBody,
+};
+
+#[put("/{reservation_id}")]
+async fn handler(
+    reservation_repository: web::Data<dyn db::reservation::Repository>,
+    table_repository: web::Data<dyn db::table::Repository>,
+    path_params: web::Path<PathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, ReservationFailure> {
+    let reservation_id = path_params.reservation_id as i64;
+    let input = Input::new(request_body.into_inner())?.validate()?;
+
+    let current = match reservation_repository
+        .get_reservation(reservation_id)
+        .await
+        .map_err(ReservationFailure::InternalServerError)?
+    {
+        Some(reservation) => reservation,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+    ensure_booked(&current)?;
+
+    // the reservation must not conflict with its own current slot:
+    let table_number = assign_table(
+        table_repository.get_ref(),
+        reservation_repository.get_ref(),
+        &input,
+        reservation_id,
+    )
+    .await?;
+
+    let modified = Reservation {
+        reservation_id,
+        created_at: current.created_at,
+        ..input.into_reservation(table_number)
+    };
+    match reservation_repository
+        .update_reservation(modified)
+        .await
+        .map_err(storing_failure(table_number))?
+    {
+        Some(reservation) => Ok(HttpResponse::Ok().json(SuccessResponseBody::new(reservation))),
+        // the reservation got seated or cancelled in between:
+        None => Err(ReservationFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!("reservation {} can no longer be modified", reservation_id),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{table::DiningTable, OperationError};
+
+    fn booked(reservation_id: i64) -> Reservation {
+        let from = OffsetDateTime::now_utc() + Duration::hours(2);
+        Reservation {
+            reservation_id,
+            ..Reservation::new(
+                12,
+                4,
+                "Sukab".to_string(),
+                "+62 812 0000 0000".to_string(),
+                from,
+                from + Duration::hours(2),
+            )
+        }
+    }
+
+    fn request_body(party_size: u32, table_number: Option<u32>) -> RequestBody {
+        let reserved_at = OffsetDateTime::now_utc() + Duration::days(1);
+        RequestBody {
+            party_size,
+            reserved_at: reserved_at.format(&Rfc3339).unwrap(),
+            contact_name: "Sukab".to_string(),
+            contact_phone: "+62 812 0000 0000".to_string(),
+            table_number,
+        }
+    }
+
+    #[actix_web::test]
+    /// given: a reserved_at which is not a timestamp.
+    /// when: modify a reservation.
+    /// then: response status code is 400.
+    async fn test_invalid_reserved_at() {
+        let reservation_repo = crate::db::reservation::MockRepository::new();
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+        let table_repo = crate::db::table::MockRepository::new();
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/reservation/9")
+            .set_json(RequestBody {
+                reserved_at: "tomorrow at seven".to_string(),
+                ..request_body(2, None)
+            })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a booked reservation moved to another free table.
+    /// when: modify a reservation.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|reservation_id| Ok(Some(booked(reservation_id))));
+        reservation_repo
+            .expect_list_conflicts()
+            .withf(|table_number, _, _, exclude_id| *table_number == 40 && *exclude_id == 9)
+            .once()
+            .returning(|_, _, _, _| Ok(vec![]));
+        reservation_repo
+            .expect_update_reservation()
+            .once()
+            .returning(|reservation| Ok(Some(reservation)));
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+
+        let mut table_repo = crate::db::table::MockRepository::new();
+        table_repo
+            .expect_get_table()
+            .once()
+            .returning(|table_number| {
+                Ok(Some(DiningTable {
+                    table_number,
+                    capacity: 6,
+                }))
+            });
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/reservation/9")
+            .set_json(request_body(5, Some(40)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.reservation.reservation_id, 9);
+        assert_eq!(response_body.reservation.table_number, 40);
+        assert_eq!(response_body.reservation.party_size, 5);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: modify a reservation.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_get_reservation()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_reservation_repo: Arc<dyn db::reservation::Repository> = Arc::new(reservation_repo);
+        let table_repo = crate::db::table::MockRepository::new();
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_reservation_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/reservation").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/reservation/9")
+            .set_json(request_body(2, None))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,234 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, section::Assignment, staff::StaffRole},
+    order::BadRequestBody,
+};
+
+use super::{SectionFailure, SectionPathParams};
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    staff_id: u32,
+    starts_at: String,
+    ends_at: String,
+}
+
+/// The input data to assign a Staff member to a section for a shift.
+struct Input {
+    section_id: u32,
+    staff_id: u32,
+    starts_at: OffsetDateTime,
+    ends_at: OffsetDateTime,
+}
+
+impl Input {
+    fn new(path_params: SectionPathParams, rb: RequestBody) -> Result<Self, SectionFailure> {
+        let parse = |v: &str, field: &str| {
+            OffsetDateTime::parse(v, &Rfc3339).map_err(|_| {
+                SectionFailure::InvalidInput(BadRequestBody {
+                    error: true,
+                    message: format!("{} must be a RFC3339 timestamp", field),
+                })
+            })
+        };
+        Ok(Self {
+            section_id: path_params.section_id,
+            staff_id: rb.staff_id,
+            starts_at: parse(&rb.starts_at, "starts_at")?,
+            ends_at: parse(&rb.ends_at, "ends_at")?,
+        })
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, SectionFailure> {
+        if self.ends_at <= self.starts_at {
+            return Err(SectionFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("ends_at must be after starts_at"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct AssignmentData {
+    assignment_id: i64,
+    section_id: i64,
+    staff_id: i64,
+    starts_at: String,
+    ends_at: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    assignment: AssignmentData,
+}
+
+impl SuccessResponseBody {
+    fn new(assignment: Assignment) -> Self {
+        let format_time = |dt: OffsetDateTime| dt.format(&Rfc3339).unwrap_or("---".to_string());
+        Self {
+            assignment: AssignmentData {
+                assignment_id: assignment.assignment_id,
+                section_id: assignment.section_id,
+                staff_id: assignment.staff_id,
+                starts_at: format_time(assignment.starts_at),
+                ends_at: format_time(assignment.ends_at),
+            },
+        }
+    }
+}
+
+#[post("/{section_id}/assignment")]
+async fn handler(
+    section_repository: web::Data<dyn db::section::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<SectionPathParams>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, SectionFailure> {
+    identity.require(StaffRole::Manager)?;
+    let input = Input::new(path_params.into_inner(), request_body.into_inner())?.validate()?;
+
+    let assignment = section_repository
+        .create_assignment(Assignment::new(
+            input.section_id as i64,
+            input.staff_id as i64,
+            input.starts_at,
+            input.ends_at,
+        ))
+        .await
+        .map_err(SectionFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody::new(assignment)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use time::Duration;
+    use web::Data;
+
+    use super::*;
+    use crate::db::{staff::Staff, OperationError};
+
+    fn staff_repo() -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(|_| Ok(Some(Staff::new(1, StaffRole::Manager))));
+        Arc::new(staff_repo)
+    }
+
+    fn request_body(shift_hours: i64) -> RequestBody {
+        let starts_at = OffsetDateTime::now_utc();
+        RequestBody {
+            staff_id: 4,
+            starts_at: starts_at.format(&Rfc3339).unwrap(),
+            ends_at: (starts_at + Duration::hours(shift_hours))
+                .format(&Rfc3339)
+                .unwrap(),
+        }
+    }
+
+    #[actix_web::test]
+    /// given: a shift which ends before it starts.
+    /// when: assigning a staff member to a section.
+    /// then: response status code is 400.
+    async fn test_invalid_shift() {
+        let section_repo = crate::db::section::MockRepository::new();
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/section").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/section/2/assignment")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(request_body(-4))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: a valid shift.
+    /// when: assigning a staff member to a section.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut section_repo = crate::db::section::MockRepository::new();
+        section_repo
+            .expect_create_assignment()
+            .once()
+            .returning(|assignment| {
+                Ok(Assignment {
+                    assignment_id: 8,
+                    ..assignment
+                })
+            });
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/section").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/section/2/assignment")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(request_body(8))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.assignment.assignment_id, 8);
+        assert_eq!(response_body.assignment.section_id, 2);
+        assert_eq!(response_body.assignment.staff_id, 4);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: assigning a staff member to a section.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut section_repo = crate::db::section::MockRepository::new();
+        section_repo
+            .expect_create_assignment()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo()))
+                .service(web::scope("/section").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/section/2/assignment")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .set_json(request_body(8))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,200 @@
+use actix_web::{put, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::StaffIdentity,
+    db::{self, staff::StaffRole},
+    order::BadRequestBody,
+};
+
+use super::SectionFailure;
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    section_id: u32,
+    table_number: u32,
+}
+
+/// The input data to move a Table into a section.
+struct Input {
+    section_id: u32,
+    table_number: u32,
+}
+
+impl Input {
+    fn new(path_params: PathParams) -> Self {
+        Self {
+            section_id: path_params.section_id,
+            table_number: path_params.table_number,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, SectionFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(SectionFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    section_id: i64,
+    table_number: i32,
+}
+
+#[put("/{section_id}/table/{table_number}")]
+async fn handler(
+    section_repository: web::Data<dyn db::section::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, SectionFailure> {
+    identity.require(StaffRole::Manager)?;
+    let input = Input::new(path_params.into_inner()).validate()?;
+
+    let result_data = section_repository
+        .assign_table(input.section_id as i64, input.table_number as i32)
+        .await
+        .map_err(SectionFailure::InternalServerError)?;
+
+    match result_data {
+        Some(table_number) => Ok(HttpResponse::Ok().json(SuccessResponseBody {
+            section_id: input.section_id as i64,
+            table_number,
+        })),
+        None => Ok(HttpResponse::NotFound().body("".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{staff::Staff, OperationError};
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    #[actix_web::test]
+    /// given: a waiter instead of a manager.
+    /// when: moving a Table into a section.
+    /// then: response status code is 403.
+    async fn test_not_manager() {
+        let section_repo = crate::db::section::MockRepository::new();
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Waiter)))
+                .service(web::scope("/section").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/section/2/table/61")
+            .insert_header(("Authorization", "Bearer waiter-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    /// given: no staff access token.
+    /// when: moving a Table into a section.
+    /// then: response status code is 401.
+    async fn test_anonymous() {
+        let section_repo = crate::db::section::MockRepository::new();
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/section").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/section/2/table/61")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    /// given: a manager and an existing section.
+    /// when: moving a Table into a section.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut section_repo = crate::db::section::MockRepository::new();
+        section_repo
+            .expect_assign_table()
+            .withf(|section_id, table_number| *section_id == 2 && *table_number == 61)
+            .once()
+            .returning(|_, table_number| Ok(Some(table_number)));
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/section").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/section/2/table/61")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.table_number, 61);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: moving a Table into a section.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut section_repo = crate::db::section::MockRepository::new();
+        section_repo
+            .expect_assign_table()
+            .once()
+            .returning(|_, _| Err(OperationError::OtherError));
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_section_repo))
+                .app_data(Data::from(staff_repo(StaffRole::Manager)))
+                .service(web::scope("/section").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::put()
+            .uri("/section/2/table/61")
+            .insert_header(("Authorization", "Bearer manager-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
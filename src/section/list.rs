@@ -0,0 +1,109 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::db::{self, section::Section};
+
+use super::SectionFailure;
+
+#[derive(Serialize, Deserialize)]
+struct SectionData {
+    section_id: i64,
+    name: String,
+    table_numbers: Vec<i32>,
+}
+
+impl From<Section> for SectionData {
+    fn from(section: Section) -> Self {
+        Self {
+            section_id: section.section_id,
+            name: section.name,
+            table_numbers: section.table_numbers,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    sections: Vec<SectionData>,
+}
+
+#[get("")]
+async fn handler(
+    section_repository: web::Data<dyn db::section::Repository>,
+) -> Result<HttpResponse, SectionFailure> {
+    let sections = section_repository
+        .list_sections()
+        .await
+        .map_err(SectionFailure::InternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        sections: sections.into_iter().map(SectionData::from).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::OperationError;
+
+    #[actix_web::test]
+    /// given: sections with tables in them.
+    /// when: list floor sections.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut section_repo = crate::db::section::MockRepository::new();
+        section_repo.expect_list_sections().once().returning(|| {
+            Ok(vec![Section {
+                section_id: 2,
+                name: "Terrace".to_string(),
+                table_numbers: vec![61, 62],
+            }])
+        });
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_section_repo))
+                .service(web::scope("/section").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/section").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.sections[0].name, "Terrace");
+        assert_eq!(response_body.sections[0].table_numbers, vec![61, 62]);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: list floor sections.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut section_repo = crate::db::section::MockRepository::new();
+        section_repo
+            .expect_list_sections()
+            .once()
+            .returning(|| Err(OperationError::OtherError));
+        let arc_section_repo: Arc<dyn db::section::Repository> = Arc::new(section_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_section_repo))
+                .service(web::scope("/section").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/section").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::AuthFailure,
+    db::OperationError,
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+pub mod assign_staff;
+pub mod assign_table;
+pub mod list;
+
+#[derive(Serialize, Deserialize)]
+struct SectionPathParams {
+    section_id: u32,
+}
+
+#[derive(Debug)]
+enum SectionFailure {
+    InvalidInput(BadRequestBody),
+    Auth(AuthFailure),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for SectionFailure {
+    fn from(e: AuthFailure) -> Self {
+        SectionFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for SectionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to process floor section")
+    }
+}
+
+impl ResponseError for SectionFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            SectionFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            SectionFailure::Auth(e) => e.status_code(),
+            SectionFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            SectionFailure::InvalidInput(r) => HttpResponseBuilder::new(self.status_code()).json(r),
+            SectionFailure::Auth(e) => e.error_response(),
+            SectionFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+pub fn service() -> actix_web::Scope {
+    web::scope("/section")
+        .service(list::handler)
+        .service(assign_table::handler)
+        .service(assign_staff::handler)
+}
@@ -0,0 +1,372 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, delete, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{AuthFailure, StaffIdentity},
+    bill::load_bill,
+    db::{self, staff::StaffRole, OperationError},
+    order::{BadRequestBody, InternalServerErrorBody},
+    tax::TaxConfig,
+};
+
+use super::SessionData;
+
+/// The input data to close the active session of a Table on checkout.
+struct Input {
+    table_number: u32,
+}
+
+impl Input {
+    fn new(path_params: PathParams) -> Self {
+        Self {
+            table_number: path_params.table_number,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, CloseFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(CloseFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    table_number: u32,
+}
+
+#[derive(Debug)]
+enum CloseFailure {
+    InvalidInput(BadRequestBody),
+    Conflict(BadRequestBody),
+    Auth(AuthFailure),
+    InternalServerError(OperationError),
+}
+
+impl From<AuthFailure> for CloseFailure {
+    fn from(e: AuthFailure) -> Self {
+        CloseFailure::Auth(e)
+    }
+}
+
+impl fmt::Display for CloseFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to close table session")
+    }
+}
+
+impl ResponseError for CloseFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            CloseFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            CloseFailure::Conflict(_) => StatusCode::CONFLICT,
+            CloseFailure::Auth(e) => e.status_code(),
+            CloseFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            CloseFailure::InvalidInput(r) | CloseFailure::Conflict(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            CloseFailure::Auth(e) => e.error_response(),
+            CloseFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    session: SessionData,
+}
+
+/// Checks the guests out of a Table, once their bill is paid unless a manager closes it anyway.
+#[delete("/session")]
+async fn handler(
+    order_repository: web::Data<dyn db::order::Repository>,
+    session_repository: web::Data<dyn db::session::Repository>,
+    discount_repository: web::Data<dyn db::discount::Repository>,
+    payment_repository: web::Data<dyn db::payment::Repository>,
+    identity: StaffIdentity,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, CloseFailure> {
+    let identity = identity.require(StaffRole::Waiter)?;
+    let input = Input::new(path_params.into_inner()).validate()?;
+    let table_number = input.table_number as i32;
+
+    let bill = match load_bill(
+        order_repository.get_ref(),
+        session_repository.get_ref(),
+        discount_repository.get_ref(),
+        table_number,
+        None,
+    )
+    .await
+    .map_err(CloseFailure::InternalServerError)?
+    {
+        Some(bill) => bill,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    // a closed session can no longer be paid, so what is due has to be collected first:
+    let paid: i64 = payment_repository
+        .list_by_session(bill.session_id)
+        .await
+        .map_err(CloseFailure::InternalServerError)?
+        .iter()
+        .map(|payment| payment.amount)
+        .sum();
+    let balance = bill.total - paid;
+    if balance > 0 && identity.0.role != StaffRole::Manager {
+        return Err(CloseFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!(
+                "table {} still has {} to pay, only a manager may close it anyway",
+                table_number, balance
+            ),
+        }));
+    }
+
+    let result_data = session_repository
+        .close_session(bill.session_id, TaxConfig::from_env())
+        .await
+        .map_err(CloseFailure::InternalServerError)?;
+
+    match result_data {
+        Some(session) => Ok(HttpResponse::Ok().json(SuccessResponseBody {
+            session: session.into(),
+        })),
+        None => Ok(HttpResponse::NotFound().body("".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        order::Order,
+        payment::{Payment, PaymentMethod},
+        session::Session,
+        staff::Staff,
+    };
+
+    fn staff_repo(role: StaffRole) -> Arc<dyn db::staff::Repository> {
+        let mut staff_repo = crate::db::staff::MockRepository::new();
+        staff_repo
+            .expect_get_by_token()
+            .returning(move |_| Ok(Some(Staff::new(1, role))));
+        Arc::new(staff_repo)
+    }
+
+    fn discount_repo() -> Arc<dyn db::discount::Repository> {
+        let mut discount_repo = crate::db::discount::MockRepository::new();
+        discount_repo
+            .expect_list_by_session()
+            .returning(|_| Ok(vec![]));
+        Arc::new(discount_repo)
+    }
+
+    struct Mocks {
+        order_repo: crate::db::order::MockRepository,
+        session_repo: crate::db::session::MockRepository,
+        payment_repo: crate::db::payment::MockRepository,
+    }
+
+    /// a table with an open session and a single order of 100.000 before service charge and tax,
+    /// of which `paid` has been paid.
+    fn mocks(paid: i64) -> Mocks {
+        let mut order_repo = crate::db::order::MockRepository::new();
+        order_repo
+            .expect_list_all_by_session()
+            .returning(|table_number, session_id| {
+                Ok(vec![Order {
+                    order_id: 1,
+                    session_id: Some(session_id),
+                    unit_price: 100_000,
+                    ..Order::new(table_number, 5, 10)
+                }])
+            });
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_get_active_session()
+            .returning(|table_number| Ok(Some(Session::new(11, table_number))));
+        let mut payment_repo = crate::db::payment::MockRepository::new();
+        payment_repo
+            .expect_list_by_session()
+            .returning(move |session_id| {
+                Ok(vec![Payment::new(
+                    session_id,
+                    3,
+                    PaymentMethod::Cash,
+                    paid,
+                    None,
+                )])
+            });
+        Mocks {
+            order_repo,
+            session_repo,
+            payment_repo,
+        }
+    }
+
+    /// closes the session of the Table as a Staff member of the given role, or anonymously.
+    async fn call(
+        mocks: Mocks,
+        role: Option<StaffRole>,
+        table_number: u32,
+    ) -> actix_web::dev::ServiceResponse {
+        let arc_order_repo: Arc<dyn db::order::Repository> = Arc::new(mocks.order_repo);
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(mocks.session_repo);
+        let arc_payment_repo: Arc<dyn db::payment::Repository> = Arc::new(mocks.payment_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_order_repo))
+                .app_data(Data::from(arc_session_repo))
+                .app_data(Data::from(discount_repo()))
+                .app_data(Data::from(arc_payment_repo))
+                .app_data(Data::from(staff_repo(role.unwrap_or(StaffRole::Waiter))))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let mut req =
+            test::TestRequest::delete().uri(format!("/table/{}/session", table_number).as_str());
+        if role.is_some() {
+            req = req.insert_header(("Authorization", "Bearer staff-token"));
+        }
+        test::call_service(&app, req.to_request()).await
+    }
+
+    #[actix_web::test]
+    /// given: zero table_id.
+    /// when: close the active table session.
+    /// then: response status code is 400.
+    async fn test_invalid_table_id() {
+        let resp = call(mocks(0), Some(StaffRole::Waiter), 0).await;
+        assert!(resp.status().is_client_error());
+    }
+
+    #[actix_web::test]
+    /// given: no staff access token.
+    /// when: close the active table session.
+    /// then: response status code is 401 and the session stays open.
+    async fn test_unauthorized() {
+        let mut mocks = mocks(115_500);
+        mocks.session_repo.expect_close_session().never();
+
+        let resp = call(mocks, None, 3).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    /// given: a table whose bill is paid in full.
+    /// when: a waiter closes the active table session.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut mocks = mocks(115_500);
+        mocks
+            .session_repo
+            .expect_close_session()
+            .withf(|session_id, _| *session_id == 11)
+            .once()
+            .returning(|session_id, _| {
+                Ok(Some(Session {
+                    closed_at: Some(time::OffsetDateTime::now_utc()),
+                    ..Session::new(session_id, 3)
+                }))
+            });
+
+        let resp = call(mocks, Some(StaffRole::Waiter), 3).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.session.session_id, 11);
+        assert_eq!(response_body.session.table_number, 3);
+        assert!(response_body.session.closed_at.is_some());
+    }
+
+    #[actix_web::test]
+    /// given: a table with money still due on its bill.
+    /// when: a waiter closes the active table session.
+    /// then: response status code is 409 and the session stays open.
+    async fn test_balance_due() {
+        let mut mocks = mocks(100_000);
+        mocks.session_repo.expect_close_session().never();
+
+        let resp = call(mocks, Some(StaffRole::Waiter), 3).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: a table with money still due on its bill.
+    /// when: a manager closes the active table session.
+    /// then: the session is closed anyway.
+    async fn test_balance_due_manager() {
+        let mut mocks = mocks(100_000);
+        mocks
+            .session_repo
+            .expect_close_session()
+            .once()
+            .returning(|session_id, _| Ok(Some(Session::new(session_id, 3))));
+
+        let resp = call(mocks, Some(StaffRole::Manager), 3).await;
+        assert!(resp.status().is_success());
+    }
+
+    #[actix_web::test]
+    /// given: a table without an open session.
+    /// when: close the active table session.
+    /// then: response status code is 404.
+    async fn test_not_found() {
+        let mut mocks = mocks(0);
+        mocks.session_repo = crate::db::session::MockRepository::new();
+        mocks
+            .session_repo
+            .expect_get_active_session()
+            .once()
+            .returning(|_| Ok(None));
+        mocks.session_repo.expect_close_session().never();
+
+        let resp = call(mocks, Some(StaffRole::Waiter), 3).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: close the active table session.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut mocks = mocks(0);
+        mocks.payment_repo = crate::db::payment::MockRepository::new();
+        mocks
+            .payment_repo
+            .expect_list_by_session()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+
+        let resp = call(mocks, Some(StaffRole::Waiter), 3).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::db::session::Session;
+
+pub mod close;
+pub mod detail;
+pub mod open;
+
+#[derive(Serialize, Deserialize)]
+struct SessionData {
+    session_id: i64,
+    table_number: i32,
+    opened_at: String,
+    closed_at: Option<String>,
+}
+
+impl SessionData {
+    fn format_time(dt: OffsetDateTime) -> String {
+        dt.format(&Rfc3339).unwrap_or("---".to_string())
+    }
+}
+
+impl From<Session> for SessionData {
+    fn from(session: Session) -> Self {
+        Self {
+            session_id: session.session_id,
+            table_number: session.table_number,
+            opened_at: SessionData::format_time(session.opened_at),
+            closed_at: session.closed_at.map(SessionData::format_time),
+        }
+    }
+}
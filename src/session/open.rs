@@ -0,0 +1,230 @@
+use std::fmt;
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, post, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{self, OperationError},
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+use super::SessionData;
+
+/// The input data to seat guests on a Table.
+struct Input {
+    table_number: u32,
+}
+
+impl Input {
+    fn new(path_params: PathParams) -> Self {
+        Self {
+            table_number: path_params.table_number,
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, OpenFailure> {
+        if self.table_number < 1 || self.table_number > 100 {
+            return Err(OpenFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: String::from("table_number must be in range of 1 to 100"),
+            }));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    table_number: u32,
+}
+
+#[derive(Debug)]
+enum OpenFailure {
+    InvalidInput(BadRequestBody),
+    AlreadyOpen(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl fmt::Display for OpenFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to open table session")
+    }
+}
+
+impl ResponseError for OpenFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            OpenFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            OpenFailure::AlreadyOpen(_) => StatusCode::CONFLICT,
+            OpenFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            OpenFailure::InvalidInput(r) | OpenFailure::AlreadyOpen(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            OpenFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    session: SessionData,
+}
+
+#[post("/session")]
+async fn handler(
+    session_repository: web::Data<dyn db::session::Repository>,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, OpenFailure> {
+    let input = Input::new(path_params.into_inner()).validate()?;
+
+    let result_data = session_repository
+        .open_session(input.table_number as i32)
+        .await
+        .map_err(OpenFailure::InternalServerError)?;
+
+    match result_data {
+        Some(session) => Ok(HttpResponse::Ok().json(SuccessResponseBody {
+            session: session.into(),
+        })),
+        None => Err(OpenFailure::AlreadyOpen(BadRequestBody {
+            error: true,
+            message: format!("table {} already has an open session", input.table_number),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::session::Session;
+
+    #[actix_web::test]
+    /// given: zero table_id.
+    /// when: open a table session.
+    /// then: response status code is 400.
+    async fn test_invalid_table_id() {
+        let session_repo = crate::db::session::MockRepository::new();
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_session_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/0/session")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_client_error());
+    }
+
+    #[actix_web::test]
+    /// given: a table without an open session.
+    /// when: open a table session.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let table_number = 3;
+
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_open_session()
+            .once()
+            .returning(|table_number| Ok(Some(Session::new(11, table_number))));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_session_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri(format!("/table/{}/session", table_number).as_str())
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.session.session_id, 11);
+        assert_eq!(response_body.session.table_number, table_number);
+        assert!(response_body.session.closed_at.is_none());
+    }
+
+    #[actix_web::test]
+    /// given: a table which already has an open session.
+    /// when: open a table session.
+    /// then: response status code is 409.
+    async fn test_already_open() {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_open_session()
+            .once()
+            .returning(|_| Ok(None));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_session_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/session")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: open a table session.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut session_repo = crate::db::session::MockRepository::new();
+        session_repo
+            .expect_open_session()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_session_repo: Arc<dyn db::session::Repository> = Arc::new(session_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_session_repo))
+                .service(web::scope("/table/{table_number}").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/table/3/session")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
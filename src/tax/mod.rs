@@ -0,0 +1,234 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+
+/// Takes the basis points of an amount, rounding half up to the nearest minor unit.
+pub fn apply_rate(amount: i64, basis_points: i64) -> i64 {
+    (amount * basis_points + 5_000).div_euclid(10_000)
+}
+
+/// Divides rounding half up to the nearest minor unit, the divisor has to be positive.
+fn divide(amount: i64, divisor: i64) -> i64 {
+    (2 * amount + divisor).div_euclid(2 * divisor)
+}
+
+/// Whether the Menu prices already include the service charge and the tax.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxMode {
+    /// the service charge and the tax are added on top of the prices, e.g. PB1 in Indonesia.
+    Exclusive,
+    /// the prices already include them, they are only broken out of the total.
+    Inclusive,
+}
+
+impl TaxMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Exclusive => "exclusive",
+            Self::Inclusive => "inclusive",
+        }
+    }
+
+    pub fn parse(v: &str) -> Option<Self> {
+        match v {
+            "exclusive" => Some(Self::Exclusive),
+            "inclusive" => Some(Self::Inclusive),
+            _ => None,
+        }
+    }
+}
+
+/// How the total of a bill is rounded to the rounding unit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    HalfUp,
+    Down,
+    Up,
+}
+
+impl RoundingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HalfUp => "half_up",
+            Self::Down => "down",
+            Self::Up => "up",
+        }
+    }
+
+    pub fn parse(v: &str) -> Option<Self> {
+        match v {
+            "half_up" => Some(Self::HalfUp),
+            "down" => Some(Self::Down),
+            "up" => Some(Self::Up),
+            _ => None,
+        }
+    }
+}
+
+/// The tax configuration of the outlet, rates are in basis points (1% = 100).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TaxConfig {
+    pub service_charge: i64,
+    pub tax: i64,
+    pub mode: TaxMode,
+    /// the smallest amount the total is rounded to, in minor units (e.g. 100 to round to Rp100).
+    pub rounding_unit: i64,
+    pub rounding_mode: RoundingMode,
+}
+
+/// The service charge and tax of an amount, every amount is in minor units of the currency.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Assessment {
+    pub service_charge: i64,
+    pub tax: i64,
+    /// what the total was rounded by, negative when rounded down.
+    pub rounding: i64,
+    pub total: i64,
+}
+
+impl TaxConfig {
+    /// Reads the configuration from `SERVICE_CHARGE_RATE` and `TAX_RATE` (in percent), `TAX_MODE`,
+    /// `ROUNDING_UNIT` and `ROUNDING_MODE`, or defer to predefined default.
+    pub fn from_env() -> Self {
+        let mode = env::var("TAX_MODE")
+            .ok()
+            .and_then(|v| TaxMode::parse(&v))
+            .unwrap_or(TaxMode::Exclusive);
+        let rounding_mode = env::var("ROUNDING_MODE")
+            .ok()
+            .and_then(|v| RoundingMode::parse(&v))
+            .unwrap_or(RoundingMode::HalfUp);
+        let rounding_unit = match env::var("ROUNDING_UNIT").ok() {
+            Some(v) => v.parse().unwrap_or(1).max(1),
+            None => 1,
+        };
+        Self {
+            service_charge: Self::percent_from_env("SERVICE_CHARGE_RATE", 5.0),
+            tax: Self::percent_from_env("TAX_RATE", 10.0),
+            mode,
+            rounding_unit,
+            rounding_mode,
+        }
+    }
+
+    fn percent_from_env(key: &str, default: f64) -> i64 {
+        let percent = match env::var(key).ok() {
+            Some(v) => v.parse().unwrap_or(default),
+            None => default,
+        };
+        (percent * 100.0).round() as i64
+    }
+
+    /// Rounds the amount to the rounding unit.
+    pub fn round(&self, amount: i64) -> i64 {
+        let unit = self.rounding_unit.max(1);
+        match self.rounding_mode {
+            RoundingMode::HalfUp => divide(amount, unit) * unit,
+            RoundingMode::Down => amount.div_euclid(unit) * unit,
+            RoundingMode::Up => -(-amount).div_euclid(unit) * unit,
+        }
+    }
+
+    /// Assesses the service charge and the tax of the amount after discounts.
+    /// The tax is taken from the amount plus the service charge. In the inclusive mode
+    /// both are broken out of the amount instead, so the total stays the amount.
+    pub fn assess(&self, amount: i64) -> Assessment {
+        let (service_charge, tax, total) = match self.mode {
+            TaxMode::Exclusive => {
+                let service_charge = apply_rate(amount, self.service_charge);
+                let tax = apply_rate(amount + service_charge, self.tax);
+                (service_charge, tax, amount + service_charge + tax)
+            }
+            TaxMode::Inclusive => {
+                let tax = amount - divide(amount * 10_000, 10_000 + self.tax);
+                let before_tax = amount - tax;
+                let service_charge =
+                    before_tax - divide(before_tax * 10_000, 10_000 + self.service_charge);
+                (service_charge, tax, amount)
+            }
+        };
+        let rounded = self.round(total);
+        Assessment {
+            service_charge,
+            tax,
+            rounding: rounded - total,
+            total: rounded,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mode: TaxMode, rounding_unit: i64, rounding_mode: RoundingMode) -> TaxConfig {
+        TaxConfig {
+            service_charge: 500,
+            tax: 1_000,
+            mode,
+            rounding_unit,
+            rounding_mode,
+        }
+    }
+
+    #[test]
+    fn test_apply_rate_rounds_half_up() {
+        assert_eq!(apply_rate(10_000, 1_000), 1_000);
+        assert_eq!(apply_rate(5, 1_000), 1);
+        assert_eq!(apply_rate(4, 1_000), 0);
+        assert_eq!(apply_rate(0, 1_000), 0);
+        assert_eq!(apply_rate(35_000, -2_000), -7_000);
+    }
+
+    #[test]
+    fn test_round() {
+        let half_up = config(TaxMode::Exclusive, 100, RoundingMode::HalfUp);
+        assert_eq!(half_up.round(101_640), 101_600);
+        assert_eq!(half_up.round(101_650), 101_700);
+        let down = config(TaxMode::Exclusive, 100, RoundingMode::Down);
+        assert_eq!(down.round(101_699), 101_600);
+        let up = config(TaxMode::Exclusive, 100, RoundingMode::Up);
+        assert_eq!(up.round(101_601), 101_700);
+        assert_eq!(up.round(101_600), 101_600);
+        let none = config(TaxMode::Exclusive, 1, RoundingMode::HalfUp);
+        assert_eq!(none.round(101_641), 101_641);
+    }
+
+    #[test]
+    fn test_assess_exclusive() {
+        let assessment = config(TaxMode::Exclusive, 1, RoundingMode::HalfUp).assess(88_000);
+        assert_eq!(
+            assessment,
+            Assessment {
+                service_charge: 4_400,
+                tax: 9_240,
+                rounding: 0,
+                total: 101_640,
+            }
+        );
+
+        let assessment = config(TaxMode::Exclusive, 100, RoundingMode::Down).assess(88_000);
+        assert_eq!(assessment.rounding, -40);
+        assert_eq!(assessment.total, 101_600);
+    }
+
+    #[test]
+    fn test_assess_inclusive() {
+        let assessment = config(TaxMode::Inclusive, 1, RoundingMode::HalfUp).assess(115_500);
+        assert_eq!(
+            assessment,
+            Assessment {
+                service_charge: 5_000,
+                tax: 10_500,
+                rounding: 0,
+                total: 115_500,
+            }
+        );
+
+        let assessment = config(TaxMode::Inclusive, 100, RoundingMode::HalfUp).assess(115_549);
+        assert_eq!(assessment.total, 115_500);
+        assert_eq!(assessment.rounding, -49);
+    }
+}
@@ -0,0 +1,203 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::staff::StaffRole;
+
+/// What a Staff member's share of the tip pool is weighed by, on top of the points of their role.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TipPoolBasis {
+    /// the minutes worked within the shift, a half shift gets half the share.
+    Hours,
+    /// everyone on the shift gets the same share.
+    Equal,
+}
+
+/// The rules the tip pool is shared by, e.g. waiters get two points and cooks one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TipPoolRules {
+    pub waiter: i64,
+    pub cook: i64,
+    pub manager: i64,
+    pub basis: TipPoolBasis,
+}
+
+/// How long a Staff member worked within the shift.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Work {
+    pub staff_id: i64,
+    pub role: StaffRole,
+    pub minutes: i64,
+}
+
+/// The share of the tip pool a Staff member gets, in minor units of the currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Share {
+    pub staff_id: i64,
+    pub weight: i64,
+    pub amount: i64,
+}
+
+impl TipPoolRules {
+    /// Reads the points per role from `TIP_POOL_POINTS` (e.g. `waiter:2,cook:1,manager:0`)
+    /// and the basis from `TIP_POOL_BASIS`, or defer to predefined default.
+    pub fn from_env() -> Self {
+        let basis = match env::var("TIP_POOL_BASIS").ok().as_deref() {
+            Some("equal") => TipPoolBasis::Equal,
+            _ => TipPoolBasis::Hours,
+        };
+        let points = env::var("TIP_POOL_POINTS").unwrap_or_default();
+        Self::parse(&points, basis)
+    }
+
+    /// Parses `role:points` pairs, roles left out keep their default points.
+    fn parse(points: &str, basis: TipPoolBasis) -> Self {
+        let mut rules = Self {
+            waiter: 1,
+            cook: 1,
+            manager: 0,
+            basis,
+        };
+        for pair in points.split(',') {
+            let (role, value) = match pair.split_once(':') {
+                Some((role, value)) => (role.trim(), value.trim()),
+                None => continue,
+            };
+            let value: i64 = match value.parse() {
+                Ok(value) if value >= 0 => value,
+                _ => continue,
+            };
+            match StaffRole::parse(role) {
+                Some(StaffRole::Waiter) => rules.waiter = value,
+                Some(StaffRole::Cook) => rules.cook = value,
+                Some(StaffRole::Manager) => rules.manager = value,
+                None => {}
+            }
+        }
+        rules
+    }
+
+    pub fn points(&self, role: StaffRole) -> i64 {
+        match role {
+            StaffRole::Waiter => self.waiter,
+            StaffRole::Cook => self.cook,
+            StaffRole::Manager => self.manager,
+        }
+    }
+
+    fn weight(&self, work: &Work) -> i64 {
+        let points = self.points(work.role);
+        match self.basis {
+            TipPoolBasis::Hours => points * work.minutes.max(0),
+            TipPoolBasis::Equal => points,
+        }
+    }
+
+    /// Shares the tips across the Staff who worked the shift in proportion to their weight.
+    /// Whole minor units are handed out by the largest remainder, so the shares add up to the tips
+    /// unless nobody on the shift has any weight, in which case nothing is shared.
+    pub fn distribute(&self, tips: i64, work: &[Work]) -> Vec<Share> {
+        let weights: Vec<i64> = work.iter().map(|w| self.weight(w)).collect();
+        let total_weight: i64 = weights.iter().sum();
+        if total_weight == 0 || tips <= 0 {
+            return work
+                .iter()
+                .zip(&weights)
+                .map(|(w, weight)| Share {
+                    staff_id: w.staff_id,
+                    weight: *weight,
+                    amount: 0,
+                })
+                .collect();
+        }
+
+        let mut shares: Vec<Share> = work
+            .iter()
+            .zip(&weights)
+            .map(|(w, weight)| Share {
+                staff_id: w.staff_id,
+                weight: *weight,
+                amount: tips * weight / total_weight,
+            })
+            .collect();
+        let left = tips - shares.iter().map(|s| s.amount).sum::<i64>();
+
+        // the largest remainders get a minor unit each, ties go to whoever is listed first:
+        let mut order: Vec<usize> = (0..shares.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(tips * weights[i] % total_weight));
+        for &i in order.iter().take(left as usize) {
+            shares[i].amount += 1;
+        }
+        shares
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work(staff_id: i64, role: StaffRole, minutes: i64) -> Work {
+        Work {
+            staff_id,
+            role,
+            minutes,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let rules = TipPoolRules::parse("waiter:3, manager:1,cook:x,chef:2", TipPoolBasis::Equal);
+        assert_eq!(
+            rules,
+            TipPoolRules {
+                waiter: 3,
+                cook: 1,
+                manager: 1,
+                basis: TipPoolBasis::Equal,
+            }
+        );
+        assert_eq!(TipPoolRules::parse("", TipPoolBasis::Hours).manager, 0);
+    }
+
+    #[test]
+    fn test_distribute_by_hours() {
+        let rules = TipPoolRules::parse("waiter:2,cook:1", TipPoolBasis::Hours);
+        let shares = rules.distribute(
+            100_000,
+            &[
+                work(1, StaffRole::Waiter, 480),
+                work(2, StaffRole::Waiter, 240),
+                work(3, StaffRole::Cook, 480),
+                work(4, StaffRole::Manager, 480),
+            ],
+        );
+        let amounts: Vec<i64> = shares.iter().map(|s| s.amount).collect();
+        assert_eq!(amounts, vec![50_000, 25_000, 25_000, 0]);
+        assert_eq!(shares[0].weight, 960);
+    }
+
+    #[test]
+    fn test_distribute_remainder() {
+        let rules = TipPoolRules::parse("", TipPoolBasis::Equal);
+        let shares = rules.distribute(
+            10_000,
+            &[
+                work(1, StaffRole::Waiter, 60),
+                work(2, StaffRole::Cook, 60),
+                work(3, StaffRole::Waiter, 60),
+            ],
+        );
+        let amounts: Vec<i64> = shares.iter().map(|s| s.amount).collect();
+        assert_eq!(amounts, vec![3_334, 3_333, 3_333]);
+        assert_eq!(amounts.iter().sum::<i64>(), 10_000);
+    }
+
+    #[test]
+    fn test_distribute_nobody_weighted() {
+        let rules = TipPoolRules::parse("", TipPoolBasis::Hours);
+        let shares = rules.distribute(10_000, &[work(4, StaffRole::Manager, 480)]);
+        assert_eq!(shares[0].amount, 0);
+        assert!(rules.distribute(10_000, &[]).is_empty());
+    }
+}
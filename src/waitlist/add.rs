@@ -0,0 +1,216 @@
+use actix_web::{post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{self, waitlist::WaitlistEntry},
+    order::BadRequestBody,
+};
+
+use super::{load_estimates, SuccessResponseBody, WaitlistEntryData, WaitlistFailure};
+
+#[derive(Serialize, Deserialize)]
+pub struct RequestBody {
+    party_size: u32,
+    contact_name: String,
+    contact_phone: String,
+}
+
+/// The input data to put a walk-in party on the waitlist.
+struct Input {
+    party_size: u32,
+    contact_name: String,
+    contact_phone: String,
+}
+
+impl Input {
+    fn new(rb: RequestBody) -> Self {
+        Self {
+            party_size: rb.party_size,
+            contact_name: rb.contact_name.trim().to_string(),
+            contact_phone: rb.contact_phone.trim().to_string(),
+        }
+    }
+
+    /// performs simple request validation to make check some bounds.
+    fn validate(self) -> Result<Self, WaitlistFailure> {
+        let invalid = |message: &str| {
+            Err(WaitlistFailure::InvalidInput(BadRequestBody {
+                error: true,
+                message: message.to_string(),
+            }))
+        };
+        if self.party_size < 1 || self.party_size > 20 {
+            return invalid("party_size must be in range of 1 to 20");
+        }
+        if self.contact_name.is_empty() || self.contact_name.chars().count() > 300 {
+            return invalid("contact_name must be between 1 and 300 characters");
+        }
+        if self.contact_phone.is_empty() || self.contact_phone.chars().count() > 50 {
+            return invalid("contact_phone must be between 1 and 50 characters");
+        }
+        Ok(self)
+    }
+}
+
+#[post("")]
+async fn handler(
+    waitlist_repository: web::Data<dyn db::waitlist::Repository>,
+    table_repository: web::Data<dyn db::table::Repository>,
+    request_body: web::Json<RequestBody>,
+) -> Result<HttpResponse, WaitlistFailure> {
+    let input = Input::new(request_body.into_inner()).validate()?;
+
+    let entry = waitlist_repository
+        .add_entry(WaitlistEntry::new(
+            input.party_size as i32,
+            input.contact_name,
+            input.contact_phone,
+        ))
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?;
+
+    // the party is quoted against everyone already in the queue:
+    let estimated_wait = load_estimates(waitlist_repository.get_ref(), table_repository.get_ref())
+        .await?
+        .into_iter()
+        .find(|(e, _)| e.entry_id == entry.entry_id)
+        .and_then(|(_, estimate)| estimate);
+
+    Ok(HttpResponse::Ok().json(SuccessResponseBody {
+        entry: WaitlistEntryData::new(entry, estimated_wait),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use time::{Duration, OffsetDateTime};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{table::TableStatus, OperationError};
+
+    #[actix_web::test]
+    /// given: an empty contact_name.
+    /// when: adding a party to the waitlist.
+    /// then: response status code is 400.
+    async fn test_invalid_contact_name() {
+        let waitlist_repo = crate::db::waitlist::MockRepository::new();
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+        let table_repo = crate::db::table::MockRepository::new();
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/waitlist")
+            .set_json(RequestBody {
+                party_size: 2,
+                contact_name: " ".to_string(),
+                contact_phone: "0812".to_string(),
+            })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    /// given: the only suitable table has been occupied for 45 minutes.
+    /// when: adding a party to the waitlist.
+    /// then: the party is quoted the remaining dwell time of that table.
+    async fn test_success() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo.expect_add_entry().once().returning(|entry| {
+            Ok(WaitlistEntry {
+                entry_id: 4,
+                ..entry
+            })
+        });
+        waitlist_repo.expect_list_pending().once().returning(|| {
+            Ok(vec![WaitlistEntry {
+                entry_id: 4,
+                ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+            }])
+        });
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let mut table_repo = crate::db::table::MockRepository::new();
+        table_repo.expect_list_with_status().once().returning(|_| {
+            Ok(vec![TableStatus {
+                table_number: 1,
+                capacity: 2,
+                occupied_since: Some(OffsetDateTime::now_utc() - Duration::minutes(45)),
+            }])
+        });
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/waitlist")
+            .set_json(RequestBody {
+                party_size: 2,
+                contact_name: "Sukab".to_string(),
+                contact_phone: "0812".to_string(),
+            })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.entry.entry_id, 4);
+        assert_eq!(response_body.entry.status, "waiting");
+        let estimate = response_body.entry.estimated_wait_minutes.unwrap();
+        assert!((14..=15).contains(&estimate));
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: adding a party to the waitlist.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo
+            .expect_add_entry()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+        let table_repo = crate::db::table::MockRepository::new();
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/waitlist")
+            .set_json(RequestBody {
+                party_size: 2,
+                contact_name: "Sukab".to_string(),
+                contact_phone: "0812".to_string(),
+            })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,112 @@
+use actix_web::{get, web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::db;
+
+use super::{load_estimates, WaitlistEntryData, WaitlistFailure};
+
+#[derive(Serialize, Deserialize)]
+struct ListResponseBody {
+    entries: Vec<WaitlistEntryData>,
+}
+
+#[get("")]
+async fn handler(
+    waitlist_repository: web::Data<dyn db::waitlist::Repository>,
+    table_repository: web::Data<dyn db::table::Repository>,
+) -> Result<HttpResponse, WaitlistFailure> {
+    let entries = load_estimates(waitlist_repository.get_ref(), table_repository.get_ref())
+        .await?
+        .into_iter()
+        .map(|(entry, estimate)| WaitlistEntryData::new(entry, estimate))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ListResponseBody { entries }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{table::TableStatus, waitlist::WaitlistEntry, OperationError};
+
+    #[actix_web::test]
+    /// given: two parties waiting and one free table.
+    /// when: listing the waitlist.
+    /// then: the first party has no wait and the second has to wait.
+    async fn test_success() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo.expect_list_pending().once().returning(|| {
+            Ok(vec![
+                WaitlistEntry {
+                    entry_id: 1,
+                    ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+                },
+                WaitlistEntry {
+                    entry_id: 2,
+                    ..WaitlistEntry::new(2, "Alina".to_string(), "0813".to_string())
+                },
+            ])
+        });
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let mut table_repo = crate::db::table::MockRepository::new();
+        table_repo.expect_list_with_status().once().returning(|_| {
+            Ok(vec![TableStatus {
+                table_number: 1,
+                capacity: 2,
+                occupied_since: None,
+            }])
+        });
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/waitlist").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: ListResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.entries.len(), 2);
+        assert_eq!(response_body.entries[0].estimated_wait_minutes, Some(0));
+        assert_eq!(response_body.entries[1].estimated_wait_minutes, Some(60));
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: listing the waitlist.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo
+            .expect_list_pending()
+            .once()
+            .returning(|| Err(OperationError::OtherError));
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+        let table_repo = crate::db::table::MockRepository::new();
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .app_data(Data::from(arc_table_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/waitlist").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,389 @@
+use std::{env, fmt};
+
+use actix_web::{
+    body::BoxBody, http::StatusCode, web, HttpResponse, HttpResponseBuilder, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+
+use crate::{
+    db::{
+        self,
+        table::TableStatus,
+        waitlist::{WaitlistEntry, WaitlistStatus},
+        OperationError,
+    },
+    order::{BadRequestBody, InternalServerErrorBody},
+};
+
+pub mod add;
+pub mod list;
+pub mod next;
+pub mod no_show;
+pub mod seat;
+
+/// How long guests typically stay on a Table, in minutes.
+/// Read from `TABLE_DWELL_TIME`, or defer to predefined default.
+fn dwell_time() -> Duration {
+    const DEFAULT_DWELL_TIME: i64 = 60;
+    let minutes = match env::var("TABLE_DWELL_TIME").ok() {
+        Some(v) => v.parse().unwrap_or(DEFAULT_DWELL_TIME),
+        None => DEFAULT_DWELL_TIME,
+    };
+    Duration::minutes(minutes)
+}
+
+#[derive(Serialize, Deserialize)]
+struct WaitlistEntryData {
+    entry_id: i64,
+    party_size: i32,
+    contact_name: String,
+    contact_phone: String,
+    status: String,
+    table_number: Option<i32>,
+    session_id: Option<i64>,
+    created_at: String,
+    estimated_wait_minutes: Option<i64>,
+}
+
+impl WaitlistEntryData {
+    fn new(entry: WaitlistEntry, estimated_wait: Option<Duration>) -> Self {
+        Self {
+            entry_id: entry.entry_id,
+            party_size: entry.party_size,
+            contact_name: entry.contact_name,
+            contact_phone: entry.contact_phone,
+            status: entry.status.as_str().to_string(),
+            table_number: entry.table_number,
+            session_id: entry.session_id,
+            created_at: entry
+                .created_at
+                .format(&Rfc3339)
+                .unwrap_or("---".to_string()),
+            estimated_wait_minutes: estimated_wait.map(|d| d.whole_minutes()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SuccessResponseBody {
+    entry: WaitlistEntryData,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PathParams {
+    entry_id: u32,
+}
+
+/// Tables promised to parties which have been called but not seated yet.
+fn held_tables(entries: &[WaitlistEntry]) -> Vec<i32> {
+    entries
+        .iter()
+        .filter(|e| e.status == WaitlistStatus::Called)
+        .filter_map(|e| e.table_number)
+        .collect()
+}
+
+/// Estimates how long the party at `position` (0 is the head of the queue) waits
+/// for one of the suitable `tables`, assuming each seating lasts `dwell` long.
+/// Returns None when no Table can ever seat the party.
+fn estimate_wait(
+    position: usize,
+    tables: &[&TableStatus],
+    dwell: Duration,
+    now: OffsetDateTime,
+) -> Option<Duration> {
+    if tables.is_empty() {
+        return None;
+    }
+
+    // when each Table frees up next, a free Table is available right away:
+    let mut free_at: Vec<Duration> = tables
+        .iter()
+        .map(|t| match t.occupied_since {
+            Some(since) => (dwell - (now - since)).max(Duration::ZERO),
+            None => Duration::ZERO,
+        })
+        .collect();
+    free_at.sort();
+
+    // parties ahead take the earliest Tables, after that every Table frees up once per dwell time:
+    let rounds = (position / free_at.len()) as i32;
+    Some(free_at[position % free_at.len()] + dwell * rounds)
+}
+
+/// Pairs every pending entry with its estimated wait, called and seated parties wait no more.
+fn estimate_all(
+    entries: Vec<WaitlistEntry>,
+    tables: &[TableStatus],
+    dwell: Duration,
+    now: OffsetDateTime,
+) -> Vec<(WaitlistEntry, Option<Duration>)> {
+    let held = held_tables(&entries);
+    // a held Table is as good as occupied by a party which just sat down:
+    let tables: Vec<TableStatus> = tables
+        .iter()
+        .map(|t| TableStatus {
+            occupied_since: match held.contains(&t.table_number) {
+                true => Some(t.occupied_since.unwrap_or(now)),
+                false => t.occupied_since,
+            },
+            ..t.clone()
+        })
+        .collect();
+
+    let waiting: Vec<(i64, i32)> = entries
+        .iter()
+        .filter(|e| e.status == WaitlistStatus::Waiting)
+        .map(|e| (e.entry_id, e.party_size))
+        .collect();
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            if entry.status != WaitlistStatus::Waiting {
+                return (entry, Some(Duration::ZERO));
+            }
+            let suitable: Vec<&TableStatus> = tables
+                .iter()
+                .filter(|t| t.capacity >= entry.party_size)
+                .collect();
+            let largest = suitable.iter().map(|t| t.capacity).max().unwrap_or(0);
+            // only parties ahead which compete for the same Tables delay this one:
+            let position = waiting
+                .iter()
+                .take_while(|(entry_id, _)| *entry_id != entry.entry_id)
+                .filter(|(_, party_size)| *party_size <= largest)
+                .count();
+            let estimate = estimate_wait(position, &suitable, dwell, now);
+            (entry, estimate)
+        })
+        .collect()
+}
+
+/// Picks the longest waiting party which fits a free Table,
+/// returns the entry ID along with the smallest free Table which seats them.
+/// The `reserved` Tables are about to be taken by their Reservation, so they are not free.
+fn pick_next(
+    entries: &[WaitlistEntry],
+    tables: &[TableStatus],
+    reserved: &[i32],
+) -> Option<(i64, i32)> {
+    let held = held_tables(entries);
+    let free: Vec<&TableStatus> = tables
+        .iter()
+        .filter(|t| {
+            t.occupied_since.is_none()
+                && !held.contains(&t.table_number)
+                && !reserved.contains(&t.table_number)
+        })
+        .collect();
+
+    entries
+        .iter()
+        .filter(|e| e.status == WaitlistStatus::Waiting)
+        .find_map(|entry| {
+            free.iter()
+                .filter(|t| t.capacity >= entry.party_size)
+                .min_by_key(|t| (t.capacity, t.table_number))
+                .map(|t| (entry.entry_id, t.table_number))
+        })
+}
+
+/// Loads the pending parties along with their estimated wait.
+async fn load_estimates(
+    waitlist_repository: &dyn db::waitlist::Repository,
+    table_repository: &dyn db::table::Repository,
+) -> Result<Vec<(WaitlistEntry, Option<Duration>)>, WaitlistFailure> {
+    let entries = waitlist_repository
+        .list_pending()
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?;
+    let tables = table_repository
+        .list_with_status(1)
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?;
+    Ok(estimate_all(
+        entries,
+        &tables,
+        dwell_time(),
+        OffsetDateTime::now_utc(),
+    ))
+}
+
+/// Ensures the party is still pending, seated and no-show parties are final.
+fn ensure_pending(entry: &WaitlistEntry) -> Result<(), WaitlistFailure> {
+    match entry.status {
+        WaitlistStatus::Waiting | WaitlistStatus::Called => Ok(()),
+        status => Err(WaitlistFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!(
+                "waitlist entry {} is already {}",
+                entry.entry_id,
+                status.as_str()
+            ),
+        })),
+    }
+}
+
+#[derive(Debug)]
+enum WaitlistFailure {
+    InvalidInput(BadRequestBody),
+    Conflict(BadRequestBody),
+    InternalServerError(OperationError),
+}
+
+impl fmt::Display for WaitlistFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to process waitlist")
+    }
+}
+
+impl ResponseError for WaitlistFailure {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            WaitlistFailure::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            WaitlistFailure::Conflict(_) => StatusCode::CONFLICT,
+            WaitlistFailure::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self {
+            WaitlistFailure::InvalidInput(r) | WaitlistFailure::Conflict(r) => {
+                HttpResponseBuilder::new(self.status_code()).json(r)
+            }
+            WaitlistFailure::InternalServerError(e) => {
+                log::error!("{:?}", e);
+                HttpResponseBuilder::new(self.status_code()).json(InternalServerErrorBody {
+                    error: true,
+                    message: "An unknown server error has occurred, please try again later."
+                        .to_string(),
+                })
+            }
+        }
+    }
+}
+
+pub fn service() -> actix_web::Scope {
+    web::scope("/waitlist")
+        .service(add::handler)
+        .service(list::handler)
+        .service(next::handler)
+        .service(seat::handler)
+        .service(no_show::handler)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(table_number: i32, capacity: i32, occupied_for: Option<i64>) -> TableStatus {
+        TableStatus {
+            table_number,
+            capacity,
+            occupied_since: occupied_for
+                .map(|minutes| OffsetDateTime::now_utc() - Duration::minutes(minutes)),
+        }
+    }
+
+    fn entry(entry_id: i64, party_size: i32) -> WaitlistEntry {
+        WaitlistEntry {
+            entry_id,
+            ..WaitlistEntry::new(party_size, "Sukab".to_string(), "0812".to_string())
+        }
+    }
+
+    #[test]
+    /// given: a free suitable table.
+    /// when: estimating the wait of the head of the queue.
+    /// then: there is no wait.
+    fn test_estimate_free_table() {
+        let tables = [table(1, 2, None), table(2, 2, Some(10))];
+        let suitable: Vec<&TableStatus> = tables.iter().collect();
+        let estimate = estimate_wait(
+            0,
+            &suitable,
+            Duration::minutes(60),
+            OffsetDateTime::now_utc(),
+        );
+        assert_eq!(estimate, Some(Duration::ZERO));
+    }
+
+    #[test]
+    /// given: all suitable tables are occupied.
+    /// when: estimating the wait of parties in the queue.
+    /// then: parties get the earliest freed tables, then wait a full dwell time per round.
+    fn test_estimate_occupied_tables() {
+        let now = OffsetDateTime::now_utc();
+        let tables = [
+            TableStatus {
+                occupied_since: Some(now - Duration::minutes(50)),
+                ..table(1, 4, None)
+            },
+            TableStatus {
+                occupied_since: Some(now - Duration::minutes(20)),
+                ..table(2, 4, None)
+            },
+        ];
+        let suitable: Vec<&TableStatus> = tables.iter().collect();
+        let dwell = Duration::minutes(60);
+        assert_eq!(
+            estimate_wait(0, &suitable, dwell, now),
+            Some(Duration::minutes(10))
+        );
+        assert_eq!(
+            estimate_wait(1, &suitable, dwell, now),
+            Some(Duration::minutes(40))
+        );
+        assert_eq!(
+            estimate_wait(2, &suitable, dwell, now),
+            Some(Duration::minutes(70))
+        );
+    }
+
+    #[test]
+    /// given: no table is big enough.
+    /// when: estimating the wait.
+    /// then: there is no estimate.
+    fn test_estimate_no_suitable_table() {
+        let estimate = estimate_wait(0, &[], Duration::minutes(60), OffsetDateTime::now_utc());
+        assert_eq!(estimate, None);
+    }
+
+    #[test]
+    /// given: the head of the queue does not fit the only free table.
+    /// when: picking the next party.
+    /// then: the next party which fits is called to the smallest fitting table.
+    fn test_pick_next_skips_party_too_big() {
+        let entries = [entry(1, 6), entry(2, 2), entry(3, 2)];
+        let tables = [table(5, 4, None), table(3, 2, None), table(9, 8, Some(5))];
+        assert_eq!(pick_next(&entries, &tables, &[]), Some((2, 3)));
+    }
+
+    #[test]
+    /// given: the only free table is held for a called party.
+    /// when: picking the next party.
+    /// then: nobody is called.
+    fn test_pick_next_held_table() {
+        let called = WaitlistEntry {
+            status: WaitlistStatus::Called,
+            table_number: Some(3),
+            ..entry(1, 2)
+        };
+        let entries = [called, entry(2, 2)];
+        let tables = [table(3, 2, None)];
+        assert_eq!(pick_next(&entries, &tables, &[]), None);
+    }
+
+    #[test]
+    /// given: the smallest fitting free table is about to be taken by its reservation.
+    /// when: picking the next party.
+    /// then: the party is called to the next fitting table.
+    fn test_pick_next_reserved_table() {
+        let entries = [entry(1, 2)];
+        let tables = [table(3, 2, None), table(5, 4, None)];
+        assert_eq!(pick_next(&entries, &tables, &[3]), Some((1, 5)));
+        assert_eq!(pick_next(&entries, &tables, &[3, 5]), None);
+    }
+}
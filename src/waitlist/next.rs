@@ -0,0 +1,259 @@
+use actix_web::{post, web, HttpResponse};
+use time::OffsetDateTime;
+
+use crate::{db, order::BadRequestBody, reservation::seat_window};
+
+use super::{pick_next, SuccessResponseBody, WaitlistEntryData, WaitlistFailure};
+
+#[post("/next")]
+async fn handler(
+    waitlist_repository: web::Data<dyn db::waitlist::Repository>,
+    table_repository: web::Data<dyn db::table::Repository>,
+    reservation_repository: web::Data<dyn db::reservation::Repository>,
+) -> Result<HttpResponse, WaitlistFailure> {
+    let entries = waitlist_repository
+        .list_pending()
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?;
+    let tables = table_repository
+        .list_with_status(1)
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?;
+    // a table whose party may be seated any moment now is not free for walk-ins:
+    let now = OffsetDateTime::now_utc();
+    let window = seat_window();
+    let reserved: Vec<i32> = reservation_repository
+        .list_starting(now - window, now + window)
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?
+        .iter()
+        .map(|reservation| reservation.table_number)
+        .collect();
+
+    let (entry_id, table_number) = match pick_next(&entries, &tables, &reserved) {
+        Some(next) => next,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+
+    match waitlist_repository
+        .call_entry(entry_id, table_number)
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?
+    {
+        Some(entry) => Ok(HttpResponse::Ok().json(SuccessResponseBody {
+            entry: WaitlistEntryData::new(entry, None),
+        })),
+        // another staff called or dismissed the party, or called someone else to the table, in between:
+        None => Err(WaitlistFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!(
+                "waitlist entry {} is no longer waiting or table {} has been taken meanwhile",
+                entry_id, table_number
+            ),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        reservation::Reservation,
+        table::TableStatus,
+        waitlist::{WaitlistEntry, WaitlistStatus},
+        OperationError,
+    };
+
+    /// Reservation repository whose booked reservations about to start are on the `reserved` tables.
+    fn reservation_repo(reserved: Vec<i32>) -> Arc<dyn db::reservation::Repository> {
+        let mut reservation_repo = crate::db::reservation::MockRepository::new();
+        reservation_repo
+            .expect_list_starting()
+            .returning(move |from, until| {
+                Ok(reserved
+                    .iter()
+                    .map(|table_number| {
+                        Reservation::new(
+                            *table_number,
+                            2,
+                            "Alina".to_string(),
+                            "+62 812 1111 1111".to_string(),
+                            from,
+                            until,
+                        )
+                    })
+                    .collect())
+            });
+        Arc::new(reservation_repo)
+    }
+
+    #[actix_web::test]
+    /// given: no table is free.
+    /// when: calling the next party.
+    /// then: response status code is 404.
+    async fn test_no_free_table() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo.expect_list_pending().once().returning(|| {
+            Ok(vec![WaitlistEntry {
+                entry_id: 1,
+                ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+            }])
+        });
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let mut table_repo = crate::db::table::MockRepository::new();
+        table_repo.expect_list_with_status().once().returning(|_| {
+            Ok(vec![TableStatus {
+                table_number: 1,
+                capacity: 2,
+                occupied_since: Some(time::OffsetDateTime::now_utc()),
+            }])
+        });
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+        let arc_reservation_repo = reservation_repo(vec![]);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .app_data(Data::from(arc_table_repo))
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post().uri("/waitlist/next").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: a waiting party and a free table.
+    /// when: calling the next party.
+    /// then: the party is called to the free table.
+    async fn test_success() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo.expect_list_pending().once().returning(|| {
+            Ok(vec![WaitlistEntry {
+                entry_id: 1,
+                ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+            }])
+        });
+        waitlist_repo
+            .expect_call_entry()
+            .withf(|entry_id, table_number| *entry_id == 1 && *table_number == 8)
+            .once()
+            .returning(|entry_id, table_number| {
+                Ok(Some(WaitlistEntry {
+                    entry_id,
+                    status: WaitlistStatus::Called,
+                    table_number: Some(table_number),
+                    ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+                }))
+            });
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let mut table_repo = crate::db::table::MockRepository::new();
+        table_repo.expect_list_with_status().once().returning(|_| {
+            Ok(vec![TableStatus {
+                table_number: 8,
+                capacity: 2,
+                occupied_since: None,
+            }])
+        });
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+        let arc_reservation_repo = reservation_repo(vec![]);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .app_data(Data::from(arc_table_repo))
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post().uri("/waitlist/next").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.entry.status, "called");
+        assert_eq!(response_body.entry.table_number, Some(8));
+    }
+
+    #[actix_web::test]
+    /// given: the only free table is reserved for a party arriving soon.
+    /// when: calling the next party.
+    /// then: response status code is 404 and nobody is called.
+    async fn test_reserved_table() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo.expect_list_pending().once().returning(|| {
+            Ok(vec![WaitlistEntry {
+                entry_id: 1,
+                ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+            }])
+        });
+        waitlist_repo.expect_call_entry().never();
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let mut table_repo = crate::db::table::MockRepository::new();
+        table_repo.expect_list_with_status().once().returning(|_| {
+            Ok(vec![TableStatus {
+                table_number: 8,
+                capacity: 2,
+                occupied_since: None,
+            }])
+        });
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .app_data(Data::from(arc_table_repo))
+                .app_data(Data::from(reservation_repo(vec![8])))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post().uri("/waitlist/next").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: calling the next party.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo
+            .expect_list_pending()
+            .once()
+            .returning(|| Err(OperationError::OtherError));
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+        let table_repo = crate::db::table::MockRepository::new();
+        let arc_table_repo: Arc<dyn db::table::Repository> = Arc::new(table_repo);
+        let arc_reservation_repo = reservation_repo(vec![]);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .app_data(Data::from(arc_table_repo))
+                .app_data(Data::from(arc_reservation_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post().uri("/waitlist/next").to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,157 @@
+use actix_web::{post, web, HttpResponse};
+
+use crate::{db, order::BadRequestBody};
+
+use super::{ensure_pending, PathParams, SuccessResponseBody, WaitlistEntryData, WaitlistFailure};
+
+#[post("/{entry_id}/no-show")]
+async fn handler(
+    waitlist_repository: web::Data<dyn db::waitlist::Repository>,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, WaitlistFailure> {
+    let entry_id = path_params.entry_id as i64;
+
+    let entry = match waitlist_repository
+        .get_entry(entry_id)
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?
+    {
+        Some(entry) => entry,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+    ensure_pending(&entry)?;
+
+    match waitlist_repository
+        .mark_no_show(entry_id)
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?
+    {
+        Some(entry) => Ok(HttpResponse::Ok().json(SuccessResponseBody {
+            entry: WaitlistEntryData::new(entry, None),
+        })),
+        None => Err(WaitlistFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!("waitlist entry {} is no longer pending", entry_id),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        waitlist::{WaitlistEntry, WaitlistStatus},
+        OperationError,
+    };
+
+    #[actix_web::test]
+    /// given: a party which has been seated.
+    /// when: marking the party as no-show.
+    /// then: response status code is 409.
+    async fn test_already_seated() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo
+            .expect_get_entry()
+            .once()
+            .returning(|entry_id| {
+                Ok(Some(WaitlistEntry {
+                    entry_id,
+                    status: WaitlistStatus::Seated,
+                    ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+                }))
+            });
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/waitlist/3/no-show")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: a called party which did not show up.
+    /// when: marking the party as no-show.
+    /// then: response status code is 200.
+    async fn test_success() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo
+            .expect_get_entry()
+            .once()
+            .returning(|entry_id| {
+                Ok(Some(WaitlistEntry {
+                    entry_id,
+                    status: WaitlistStatus::Called,
+                    table_number: Some(8),
+                    ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+                }))
+            });
+        waitlist_repo
+            .expect_mark_no_show()
+            .once()
+            .returning(|entry_id| {
+                Ok(Some(WaitlistEntry {
+                    entry_id,
+                    status: WaitlistStatus::NoShow,
+                    ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+                }))
+            });
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/waitlist/3/no-show")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.entry.status, "no_show");
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: marking the party as no-show.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo
+            .expect_get_entry()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/waitlist/3/no-show")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}
@@ -0,0 +1,210 @@
+use actix_web::{post, web, HttpResponse};
+
+use crate::{
+    db::{self, session::SeatOutcome},
+    order::BadRequestBody,
+};
+
+use super::{ensure_pending, PathParams, SuccessResponseBody, WaitlistEntryData, WaitlistFailure};
+
+#[post("/{entry_id}/seat")]
+async fn handler(
+    waitlist_repository: web::Data<dyn db::waitlist::Repository>,
+    path_params: web::Path<PathParams>,
+) -> Result<HttpResponse, WaitlistFailure> {
+    let entry_id = path_params.entry_id as i64;
+
+    let entry = match waitlist_repository
+        .get_entry(entry_id)
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?
+    {
+        Some(entry) => entry,
+        None => return Ok(HttpResponse::NotFound().body("".to_string())),
+    };
+    ensure_pending(&entry)?;
+
+    // only a called party has a Table held for them:
+    let table_number = entry.table_number.ok_or_else(|| {
+        WaitlistFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!("waitlist entry {} has not been called yet", entry_id),
+        })
+    })?;
+
+    // the session is opened along with seating the party or not at all:
+    match waitlist_repository
+        .seat_entry(entry_id)
+        .await
+        .map_err(WaitlistFailure::InternalServerError)?
+    {
+        SeatOutcome::Seated(entry) => Ok(HttpResponse::Ok().json(SuccessResponseBody {
+            entry: WaitlistEntryData::new(entry, None),
+        })),
+        SeatOutcome::Occupied => Err(WaitlistFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!(
+                "table {} is still occupied by another session",
+                table_number
+            ),
+        })),
+        SeatOutcome::Gone => Err(WaitlistFailure::Conflict(BadRequestBody {
+            error: true,
+            message: format!("waitlist entry {} can no longer be seated", entry_id),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use web::Data;
+
+    use super::*;
+    use crate::db::{
+        waitlist::{WaitlistEntry, WaitlistStatus},
+        OperationError,
+    };
+
+    fn called(entry_id: i64) -> WaitlistEntry {
+        WaitlistEntry {
+            entry_id,
+            status: WaitlistStatus::Called,
+            table_number: Some(8),
+            ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+        }
+    }
+
+    #[actix_web::test]
+    /// given: a party which is still waiting to be called.
+    /// when: seating the party.
+    /// then: response status code is 409.
+    async fn test_not_called() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo
+            .expect_get_entry()
+            .once()
+            .returning(|entry_id| {
+                Ok(Some(WaitlistEntry {
+                    entry_id,
+                    ..WaitlistEntry::new(2, "Sukab".to_string(), "0812".to_string())
+                }))
+            });
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/waitlist/3/seat")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: a called party.
+    /// when: seating the party.
+    /// then: a session is opened on the held table.
+    async fn test_success() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo
+            .expect_get_entry()
+            .once()
+            .returning(|entry_id| Ok(Some(called(entry_id))));
+        waitlist_repo
+            .expect_seat_entry()
+            .withf(|entry_id| *entry_id == 3)
+            .once()
+            .returning(|entry_id| {
+                Ok(SeatOutcome::Seated(WaitlistEntry {
+                    status: WaitlistStatus::Seated,
+                    session_id: Some(31),
+                    ..called(entry_id)
+                }))
+            });
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/waitlist/3/seat")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let response_body: SuccessResponseBody = test::read_body_json(resp).await;
+        assert_eq!(response_body.entry.status, "seated");
+        assert_eq!(response_body.entry.session_id, Some(31));
+    }
+
+    #[actix_web::test]
+    /// given: a called party whose table is still occupied.
+    /// when: seating the party.
+    /// then: response status code is 409.
+    async fn test_table_occupied() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo
+            .expect_get_entry()
+            .once()
+            .returning(|entry_id| Ok(Some(called(entry_id))));
+        waitlist_repo
+            .expect_seat_entry()
+            .once()
+            .returning(|_| Ok(SeatOutcome::Occupied));
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/waitlist/3/seat")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    /// given: failure when accessing the database.
+    /// when: seating the party.
+    /// then: response status code is 500.
+    async fn test_database_failure() {
+        let mut waitlist_repo = crate::db::waitlist::MockRepository::new();
+        waitlist_repo
+            .expect_get_entry()
+            .once()
+            .returning(|_| Err(OperationError::OtherError));
+        let arc_waitlist_repo: Arc<dyn db::waitlist::Repository> = Arc::new(waitlist_repo);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::from(arc_waitlist_repo))
+                .service(web::scope("/waitlist").service(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/waitlist/3/seat")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_server_error());
+    }
+}